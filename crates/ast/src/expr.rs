@@ -15,6 +15,9 @@ pub enum Expr {
     /// Literal value
     Literal(Literal),
 
+    /// The unit value: ()
+    Unit { id: NodeId, span: Span },
+
     /// Variable reference
     Variable {
         id: NodeId,
@@ -57,6 +60,13 @@ pub enum Expr {
         span: Span,
     },
 
+    /// Await of an asynchronous computation: `await expr`
+    Await {
+        id: NodeId,
+        expr: Box<Expr>,
+        span: Span,
+    },
+
     /// Function call
     Call {
         id: NodeId,
@@ -96,6 +106,13 @@ pub enum Expr {
         span: Span,
     },
 
+    /// String with interpolated expressions: "hi ${name}"
+    StringInterp {
+        id: NodeId,
+        parts: Vec<StringPart>,
+        span: Span,
+    },
+
     /// List literal
     List {
         id: NodeId,
@@ -103,6 +120,13 @@ pub enum Expr {
         span: Span,
     },
 
+    /// Tuple literal, e.g. `(1, "a")`
+    Tuple {
+        id: NodeId,
+        elements: Vec<Expr>,
+        span: Span,
+    },
+
     /// Map/dict literal
     Map {
         id: NodeId,
@@ -126,10 +150,12 @@ pub enum Expr {
         span: Span,
     },
 
-    /// Assignment expression: target = value
+    /// Assignment expression: target = value, or target op= value
     Assign {
         id: NodeId,
         target: Box<Expr>,
+        /// Operator of a compound assignment such as `+=`
+        op: Option<BinaryOp>,
         value: Box<Expr>,
         span: Span,
     },
@@ -141,6 +167,36 @@ pub enum Expr {
         member: String,
         span: Span,
     },
+
+    /// Integer range: `start..end`, or `start..=end` to include `end`
+    Range {
+        id: NodeId,
+        start: Box<Expr>,
+        end: Box<Expr>,
+        inclusive: bool,
+        span: Span,
+    },
+
+    /// Slice of a list or string: `array[start..end]`, or
+    /// `array[start..=end]` to include `end`
+    Slice {
+        id: NodeId,
+        array: Box<Expr>,
+        start: Box<Expr>,
+        end: Box<Expr>,
+        inclusive: bool,
+        span: Span,
+    },
+}
+
+/// A piece of an interpolated string
+#[derive(Debug, Clone)]
+pub enum StringPart {
+    /// Text copied into the string as is
+    Literal(String),
+
+    /// An expression whose value is spliced into the string
+    Expr(Expr),
 }
 
 /// A function parameter
@@ -150,6 +206,8 @@ pub struct Parameter {
     pub name: String,
     pub type_annotation: Option<TypeAnnotation>,
     pub default_value: Option<Box<Expr>>,
+    /// The parameter's name
+    pub span: Span,
 }
 
 /// A match arm
@@ -165,22 +223,57 @@ impl Expr {
     pub fn span(&self) -> &Span {
         match self {
             Expr::Literal(lit) => lit.span(),
+            Expr::Unit { span, .. } => span,
             Expr::Variable { span, .. } => span,
             Expr::Binary { span, .. } => span,
             Expr::Comparison { span, .. } => span,
             Expr::Logical { span, .. } => span,
             Expr::Unary { span, .. } => span,
+            Expr::Await { span, .. } => span,
             Expr::Call { span, .. } => span,
             Expr::Pipeline { span, .. } => span,
             Expr::Lambda { span, .. } => span,
             Expr::If { span, .. } => span,
             Expr::Block { span, .. } => span,
+            Expr::StringInterp { span, .. } => span,
             Expr::List { span, .. } => span,
+            Expr::Tuple { span, .. } => span,
             Expr::Map { span, .. } => span,
             Expr::Match { span, .. } => span,
             Expr::Index { span, .. } => span,
             Expr::Assign { span, .. } => span,
             Expr::MemberAccess { span, .. } => span,
+            Expr::Range { span, .. } => span,
+            Expr::Slice { span, .. } => span,
+        }
+    }
+
+    /// Get the node id of this expression (literals carry none)
+    pub fn id(&self) -> Option<NodeId> {
+        match self {
+            Expr::Literal(_) => None,
+            Expr::Unit { id, .. } => Some(*id),
+            Expr::Variable { id, .. } => Some(*id),
+            Expr::Binary { id, .. } => Some(*id),
+            Expr::Comparison { id, .. } => Some(*id),
+            Expr::Logical { id, .. } => Some(*id),
+            Expr::Unary { id, .. } => Some(*id),
+            Expr::Await { id, .. } => Some(*id),
+            Expr::Call { id, .. } => Some(*id),
+            Expr::Pipeline { id, .. } => Some(*id),
+            Expr::Lambda { id, .. } => Some(*id),
+            Expr::If { id, .. } => Some(*id),
+            Expr::Block { id, .. } => Some(*id),
+            Expr::StringInterp { id, .. } => Some(*id),
+            Expr::List { id, .. } => Some(*id),
+            Expr::Tuple { id, .. } => Some(*id),
+            Expr::Map { id, .. } => Some(*id),
+            Expr::Match { id, .. } => Some(*id),
+            Expr::Index { id, .. } => Some(*id),
+            Expr::Assign { id, .. } => Some(*id),
+            Expr::MemberAccess { id, .. } => Some(*id),
+            Expr::Range { id, .. } => Some(*id),
+            Expr::Slice { id, .. } => Some(*id),
         }
     }
 }
@@ -237,6 +330,7 @@ impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Expr::Literal(lit) => write!(f, "{}", lit),
+            Expr::Unit { .. } => write!(f, "()"),
             Expr::Variable { name, .. } => write!(f, "{}", name),
             Expr::Binary {
                 left, op, right, ..
@@ -297,12 +391,38 @@ impl fmt::Display for Expr {
                 }
                 write!(f, "]")
             }
-            Expr::Assign { target, value, .. } => {
-                write!(f, "({} = {})", target, value)
+            Expr::Tuple { elements, .. } => {
+                write!(f, "(")?;
+                for (i, elem) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                if elements.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
             }
+            Expr::Assign {
+                target, op, value, ..
+            } => match op {
+                Some(op) => write!(f, "({} {}= {})", target, op.symbol(), value),
+                None => write!(f, "({} = {})", target, value),
+            },
             Expr::MemberAccess { object, member, .. } => {
                 write!(f, "{}.{}", object, member)
             }
+            Expr::StringInterp { parts, .. } => {
+                write!(f, "\"")?;
+                for part in parts {
+                    match part {
+                        StringPart::Literal(text) => write!(f, "{}", text)?,
+                        StringPart::Expr(expr) => write!(f, "${{{}}}", expr)?,
+                    }
+                }
+                write!(f, "\"")
+            }
             _ => write!(f, "(expression)"),
         }
     }
@@ -5,12 +5,14 @@ pub mod op;
 pub mod pattern;
 pub mod stmt;
 pub mod types;
+pub mod visit;
 
-pub use expr::{Expr, Literal, MatchArm, Parameter};
+pub use expr::{Expr, Literal, MatchArm, Parameter, StringPart};
 pub use op::{BinaryOp, ComparisonOp, LogicalOp, UnaryOp};
 pub use pattern::Pattern;
-pub use stmt::Stmt;
-pub use types::{Type, TypeAnnotation};
+pub use stmt::{ImportedSymbol, Stmt};
+pub use types::{Type, TypeAnnotation, TypeParam};
+pub use visit::Visitor;
 
 /// A unique identifier for AST nodes
 pub type NodeId = usize;
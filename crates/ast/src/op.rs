@@ -23,6 +23,21 @@ pub enum BinaryOp {
 
     /// Concatenation (++)
     Concat,
+
+    /// Bitwise and (&)
+    BitAnd,
+
+    /// Bitwise or (|)
+    BitOr,
+
+    /// Bitwise exclusive or (^)
+    BitXor,
+
+    /// Left shift (<<)
+    ShiftLeft,
+
+    /// Right shift (>>)
+    ShiftRight,
 }
 
 impl BinaryOp {
@@ -33,9 +48,21 @@ impl BinaryOp {
             Self::Mul | Self::Div | Self::Mod => 13,
             Self::Add | Self::Sub => 12,
             Self::Concat => 11,
+            Self::ShiftLeft | Self::ShiftRight => 10,
+            Self::BitAnd => 9,
+            Self::BitXor => 8,
+            Self::BitOr => 7,
         }
     }
 
+    /// Check if this operator does numeric arithmetic
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(
+            self,
+            Self::Add | Self::Sub | Self::Mul | Self::Div | Self::Mod | Self::Pow
+        )
+    }
+
     /// Check if this operator is left-associative
     pub fn is_left_associative(&self) -> bool {
         match self {
@@ -54,6 +81,11 @@ impl BinaryOp {
             Self::Mod => "%",
             Self::Pow => "**",
             Self::Concat => "++",
+            Self::BitAnd => "&",
+            Self::BitOr => "|",
+            Self::BitXor => "^",
+            Self::ShiftLeft => "<<",
+            Self::ShiftRight => ">>",
         }
     }
 }
@@ -167,6 +199,9 @@ mod tests {
     fn test_precedence() {
         assert!(BinaryOp::Mul.precedence() > BinaryOp::Add.precedence());
         assert!(BinaryOp::Pow.precedence() > BinaryOp::Mul.precedence());
+        assert!(BinaryOp::Add.precedence() > BinaryOp::ShiftLeft.precedence());
+        assert!(BinaryOp::BitAnd.precedence() > BinaryOp::BitXor.precedence());
+        assert!(BinaryOp::BitXor.precedence() > BinaryOp::BitOr.precedence());
     }
 
     #[test]
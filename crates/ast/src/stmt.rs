@@ -1,7 +1,7 @@
 //! Statement nodes
 
 use crate::expr::{Expr, Parameter};
-use crate::{NodeId, Pattern, TypeAnnotation};
+use crate::{NodeId, Pattern, TypeAnnotation, TypeParam};
 use nevermind_common::Span;
 
 /// A statement
@@ -27,6 +27,8 @@ pub enum Stmt {
     /// Function declaration
     Function {
         id: NodeId,
+        /// Declared `async fn`
+        is_async: bool,
         name: String,
         params: Vec<Parameter>,
         return_type: Option<TypeAnnotation>,
@@ -38,7 +40,7 @@ pub enum Stmt {
     TypeAlias {
         id: NodeId,
         name: String,
-        type_params: Vec<String>,
+        type_params: Vec<TypeParam>,
         definition: TypeAnnotation,
         span: Span,
     },
@@ -84,6 +86,20 @@ pub enum Stmt {
         span: Span,
     },
 
+    /// Try statement, with a `catch` clause, a `finally` clause or both
+    Try {
+        id: NodeId,
+        body: Vec<Stmt>,
+        /// Name the caught error's message is bound to in `catch_body`
+        catch_var: Option<String>,
+        catch_body: Option<Vec<Stmt>>,
+        finally_body: Option<Vec<Stmt>>,
+        span: Span,
+    },
+
+    /// Raise statement
+    Raise { id: NodeId, value: Expr, span: Span },
+
     /// Break statement
     Break { id: NodeId, span: Span },
 
@@ -97,7 +113,9 @@ pub enum Stmt {
     Import {
         id: NodeId,
         module: String,
-        symbols: Option<Vec<String>>,
+        symbols: Option<Vec<ImportedSymbol>>,
+        /// `as` name of a namespace import
+        alias: Option<String>,
         span: Span,
     },
 
@@ -119,12 +137,41 @@ pub struct MatchArm {
     pub body: Expr,
 }
 
+/// A symbol named by `from "module" import name` or `... import name as alias`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedSymbol {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl ImportedSymbol {
+    /// Import a symbol under its own name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            alias: None,
+        }
+    }
+
+    /// The name the symbol is bound to in the importing module
+    pub fn binding(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// The name a `use "module"` import binds: its alias, or else the last
+/// segment of the module path
+pub fn namespace_binding<'a>(module: &'a str, alias: Option<&'a str>) -> &'a str {
+    alias.unwrap_or_else(|| module.split('/').next_back().unwrap_or(module))
+}
+
 /// A class member
 #[derive(Debug, Clone)]
 pub enum ClassMember {
     Field {
+        is_mutable: bool,
         name: String,
-        type_annotation: TypeAnnotation,
+        type_annotation: Option<TypeAnnotation>,
         default_value: Option<Expr>,
     },
     Method {
@@ -148,6 +195,8 @@ impl Stmt {
             Stmt::For { span, .. } => span,
             Stmt::Match { span, .. } => span,
             Stmt::Return { span, .. } => span,
+            Stmt::Try { span, .. } => span,
+            Stmt::Raise { span, .. } => span,
             Stmt::Break { span, .. } => span,
             Stmt::Continue { span, .. } => span,
             Stmt::ExprStmt { span, .. } => span,
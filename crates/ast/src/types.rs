@@ -23,6 +23,13 @@ impl TypeAnnotation {
     }
 }
 
+/// A type parameter of a type alias, with the type used when it is omitted
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeParam {
+    pub name: String,
+    pub default: Option<TypeAnnotation>,
+}
+
 /// Types in Nevermind
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
@@ -0,0 +1,346 @@
+//! Recursive traversal of the AST
+//!
+//! A pass implements [`Visitor`] and overrides only the nodes it cares
+//! about. The default methods descend into every child through the `walk_*`
+//! functions, which an override calls to keep descending.
+
+use crate::expr::{Expr, StringPart};
+use crate::stmt::{ClassMember, Stmt};
+use crate::{Parameter, Pattern};
+
+/// A read-only pass over statements, expressions and patterns
+pub trait Visitor {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    /// A list of statements that runs in order, such as a program or the
+    /// body of a block, loop or branch
+    fn visit_block(&mut self, stmts: &[Stmt]) {
+        walk_stmts(self, stmts);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        walk_pattern(self, pattern);
+    }
+}
+
+/// Visit every statement of a list in order
+pub fn walk_stmts<V: Visitor + ?Sized>(visitor: &mut V, stmts: &[Stmt]) {
+    for stmt in stmts {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+/// Visit the children of a statement
+pub fn walk_stmt<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+    match stmt {
+        Stmt::Export { stmt, .. } => visitor.visit_stmt(stmt),
+        Stmt::Let { value, .. } => visitor.visit_expr(value),
+        Stmt::Function { params, body, .. } => {
+            walk_params(visitor, params);
+            visitor.visit_expr(body);
+        }
+        Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(then_branch);
+            if let Some(else_branch) = else_branch {
+                visitor.visit_block(else_branch);
+            }
+        }
+        Stmt::While {
+            condition, body, ..
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(body);
+        }
+        Stmt::For {
+            variable,
+            iter,
+            body,
+            ..
+        } => {
+            visitor.visit_pattern(variable);
+            visitor.visit_expr(iter);
+            visitor.visit_block(body);
+        }
+        Stmt::Match {
+            scrutinee, arms, ..
+        } => {
+            visitor.visit_expr(scrutinee);
+            for arm in arms {
+                visitor.visit_pattern(&arm.pattern);
+                if let Some(guard) = &arm.guard {
+                    visitor.visit_expr(guard);
+                }
+                visitor.visit_expr(&arm.body);
+            }
+        }
+        Stmt::Return { value, .. } => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+        }
+        Stmt::Try {
+            body,
+            catch_body,
+            finally_body,
+            ..
+        } => {
+            visitor.visit_block(body);
+            if let Some(catch_body) = catch_body {
+                visitor.visit_block(catch_body);
+            }
+            if let Some(finally_body) = finally_body {
+                visitor.visit_block(finally_body);
+            }
+        }
+        Stmt::Raise { value, .. } => visitor.visit_expr(value),
+        Stmt::ExprStmt { expr, .. } => visitor.visit_expr(expr),
+        Stmt::Class { members, .. } => {
+            for member in members {
+                match member {
+                    ClassMember::Field { default_value, .. } => {
+                        if let Some(value) = default_value {
+                            visitor.visit_expr(value);
+                        }
+                    }
+                    ClassMember::Method { params, body, .. } => {
+                        walk_params(visitor, params);
+                        visitor.visit_expr(body);
+                    }
+                }
+            }
+        }
+        Stmt::TypeAlias { .. }
+        | Stmt::Break { .. }
+        | Stmt::Continue { .. }
+        | Stmt::Import { .. } => {}
+    }
+}
+
+/// Visit the children of an expression
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Literal(_) | Expr::Unit { .. } | Expr::Variable { .. } => {}
+        Expr::Binary { left, right, .. }
+        | Expr::Comparison { left, right, .. }
+        | Expr::Logical { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        Expr::Unary { expr, .. } | Expr::Await { expr, .. } => visitor.visit_expr(expr),
+        Expr::Call { callee, args, .. } => {
+            visitor.visit_expr(callee);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        Expr::Pipeline { stages, .. } => {
+            for stage in stages {
+                visitor.visit_expr(stage);
+            }
+        }
+        Expr::Lambda { params, body, .. } => {
+            walk_params(visitor, params);
+            visitor.visit_expr(body);
+        }
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_expr(then_branch);
+            visitor.visit_expr(else_branch);
+        }
+        Expr::Block { statements, .. } => visitor.visit_block(statements),
+        Expr::StringInterp { parts, .. } => {
+            for part in parts {
+                if let StringPart::Expr(expr) = part {
+                    visitor.visit_expr(expr);
+                }
+            }
+        }
+        Expr::List { elements, .. } | Expr::Tuple { elements, .. } => {
+            for element in elements {
+                visitor.visit_expr(element);
+            }
+        }
+        Expr::Map { entries, .. } => {
+            for (key, value) in entries {
+                visitor.visit_expr(key);
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            visitor.visit_expr(scrutinee);
+            for arm in arms {
+                visitor.visit_pattern(&arm.pattern);
+                if let Some(guard) = &arm.guard {
+                    visitor.visit_expr(guard);
+                }
+                visitor.visit_expr(&arm.body);
+            }
+        }
+        Expr::Index { array, index, .. } => {
+            visitor.visit_expr(array);
+            visitor.visit_expr(index);
+        }
+        Expr::Assign { target, value, .. } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        }
+        Expr::MemberAccess { object, .. } => visitor.visit_expr(object),
+        Expr::Range { start, end, .. } => {
+            visitor.visit_expr(start);
+            visitor.visit_expr(end);
+        }
+        Expr::Slice {
+            array, start, end, ..
+        } => {
+            visitor.visit_expr(array);
+            visitor.visit_expr(start);
+            visitor.visit_expr(end);
+        }
+    }
+}
+
+/// Visit the sub-patterns of a pattern
+pub fn walk_pattern<V: Visitor + ?Sized>(visitor: &mut V, pattern: &Pattern) {
+    match pattern {
+        Pattern::Wildcard { .. } | Pattern::Variable { .. } | Pattern::Literal { .. } => {}
+        Pattern::Or { patterns, .. }
+        | Pattern::Tuple { patterns, .. }
+        | Pattern::List { patterns, .. }
+        | Pattern::Constructor { args: patterns, .. } => {
+            for pattern in patterns {
+                visitor.visit_pattern(pattern);
+            }
+        }
+        Pattern::ListCons { head, tail, .. } => {
+            visitor.visit_pattern(head);
+            visitor.visit_pattern(tail);
+        }
+        Pattern::Struct { fields, .. } => {
+            for field in fields {
+                visitor.visit_pattern(&field.pattern);
+            }
+        }
+        Pattern::Range { start, end, .. } => {
+            visitor.visit_pattern(start);
+            visitor.visit_pattern(end);
+        }
+    }
+}
+
+/// Visit the default values of a parameter list
+fn walk_params<V: Visitor + ?Sized>(visitor: &mut V, params: &[Parameter]) {
+    for default in params.iter().filter_map(|p| p.default_value.as_ref()) {
+        visitor.visit_expr(default);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BinaryOp, Literal};
+    use nevermind_common::Span;
+
+    struct LiteralCounter(usize);
+
+    impl Visitor for LiteralCounter {
+        fn visit_expr(&mut self, expr: &Expr) {
+            if let Expr::Literal(_) = expr {
+                self.0 += 1;
+            }
+            walk_expr(self, expr);
+        }
+    }
+
+    fn int(value: i64) -> Expr {
+        Expr::Literal(Literal::Integer(value, Span::dummy()))
+    }
+
+    #[test]
+    fn test_visitor_counts_nested_literals() {
+        // fn f(n = 1) do n + g(2, [3]) end
+        // let x = if true then 4 else x end
+        let call = Expr::Call {
+            id: 1,
+            callee: Box::new(Expr::Variable {
+                id: 2,
+                name: "g".to_string(),
+                span: Span::dummy(),
+            }),
+            args: vec![
+                int(2),
+                Expr::List {
+                    id: 3,
+                    elements: vec![int(3)],
+                    span: Span::dummy(),
+                },
+            ],
+            span: Span::dummy(),
+        };
+        let function = Stmt::Function {
+            id: 4,
+            is_async: false,
+            name: "f".to_string(),
+            params: vec![Parameter {
+                id: 5,
+                name: "n".to_string(),
+                type_annotation: None,
+                default_value: Some(Box::new(int(1))),
+                span: Span::dummy(),
+            }],
+            return_type: None,
+            body: Expr::Binary {
+                id: 6,
+                left: Box::new(Expr::Variable {
+                    id: 7,
+                    name: "n".to_string(),
+                    span: Span::dummy(),
+                }),
+                op: BinaryOp::Add,
+                right: Box::new(call),
+                span: Span::dummy(),
+            },
+            span: Span::dummy(),
+        };
+        let binding = Stmt::Let {
+            id: 8,
+            is_mutable: false,
+            name: "x".to_string(),
+            type_annotation: None,
+            value: Expr::If {
+                id: 9,
+                condition: Box::new(Expr::Literal(Literal::Boolean(true, Span::dummy()))),
+                then_branch: Box::new(int(4)),
+                else_branch: Box::new(Expr::Variable {
+                    id: 10,
+                    name: "x".to_string(),
+                    span: Span::dummy(),
+                }),
+                span: Span::dummy(),
+            },
+            span: Span::dummy(),
+        };
+
+        let mut counter = LiteralCounter(0);
+        walk_stmts(&mut counter, &[function, binding]);
+        assert_eq!(counter.0, 5);
+    }
+}
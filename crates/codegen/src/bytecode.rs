@@ -0,0 +1,969 @@
+//! Stack-based bytecode
+//!
+//! [`BytecodeEmitter`] lowers MIR into a [`BytecodeChunk`]: a flat list of
+//! [`Instruction`]s and the pool of constants they push. A chunk can be
+//! printed with [`BytecodeChunk::disassemble`] or run with [`interpret`].
+//!
+//! The instruction set covers scalars, variables, functions and control
+//! flow. Collections, classes, patterns and imports are only compiled to
+//! Python, and lowering them here fails with [`EmitError::UnsupportedNode`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use thiserror::Error;
+
+use super::emit::{EmitError, Result};
+use super::CodeEmitter;
+use nevermind_mir::{
+    BinOp, Literal, MirBlock, MirExpr, MirExprStmt, MirFunction, MirProgram, MirStmt, UnaryOp,
+};
+
+/// A single stack machine instruction
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    /// Push the constant at this index of the pool
+    PushConst(usize),
+
+    /// Push the value of a variable
+    Load(String),
+
+    /// Pop a value into a variable
+    Store(String),
+
+    /// Pop the right then the left operand and push the result
+    BinOp(BinOp),
+
+    /// Pop the operand and push the result
+    UnaryOp(UnaryOp),
+
+    /// Call a function with this many arguments, which sit above the callee
+    /// on the stack, and push its result
+    Call(usize),
+
+    /// Continue at the instruction with this index
+    Jump(usize),
+
+    /// Pop a condition and continue at this index when it's false
+    JumpIfFalse(usize),
+
+    /// Discard the value on top of the stack
+    Pop,
+
+    /// Pop a value and return it from the running chunk
+    Return,
+}
+
+/// A value on the stack or in the constant pool
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    Null,
+    Function(Rc<Function>),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "Int",
+            Value::Float(_) => "Float",
+            Value::Bool(_) => "Bool",
+            Value::String(_) => "String",
+            Value::Null => "Null",
+            Value::Function(_) => "Function",
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Int(a), Value::Float(b)) | (Value::Float(b), Value::Int(a)) => *a as f64 == *b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Null, Value::Null) => true,
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{:?}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Null => write!(f, "null"),
+            Value::Function(function) => write!(f, "<fn {}>", function.name),
+        }
+    }
+}
+
+/// A compiled function or lambda, stored in the constant pool
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<String>,
+    pub chunk: BytecodeChunk,
+}
+
+/// A chunk of generated bytecode
+#[derive(Debug, Clone, Default)]
+pub struct BytecodeChunk {
+    pub instructions: Vec<Instruction>,
+    pub constants: Vec<Value>,
+}
+
+impl BytecodeChunk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an instruction, returning its index
+    pub fn push(&mut self, instruction: Instruction) -> usize {
+        self.instructions.push(instruction);
+        self.instructions.len() - 1
+    }
+
+    /// Add a value to the constant pool, returning its index
+    pub fn add_constant(&mut self, value: Value) -> usize {
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+
+    /// Point the jump at `index` to the next instruction to be pushed
+    fn patch_jump(&mut self, index: usize) {
+        let target = self.instructions.len();
+        match &mut self.instructions[index] {
+            Instruction::Jump(to) | Instruction::JumpIfFalse(to) => *to = target,
+            other => unreachable!("patching a non-jump instruction {:?}", other),
+        }
+    }
+
+    /// One instruction per line, with constants shown beside the
+    /// instructions that push them, followed by the chunks of any functions
+    pub fn disassemble(&self) -> String {
+        let mut out = String::new();
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            let line = match instruction {
+                Instruction::PushConst(constant) => {
+                    format!("PushConst {} ({})", constant, self.constants[*constant])
+                }
+                Instruction::Load(name) => format!("Load {}", name),
+                Instruction::Store(name) => format!("Store {}", name),
+                Instruction::BinOp(op) => format!("BinOp {:?}", op),
+                Instruction::UnaryOp(op) => format!("UnaryOp {:?}", op),
+                Instruction::Call(argc) => format!("Call {}", argc),
+                Instruction::Jump(to) => format!("Jump {:04}", to),
+                Instruction::JumpIfFalse(to) => format!("JumpIfFalse {:04}", to),
+                Instruction::Pop => "Pop".to_string(),
+                Instruction::Return => "Return".to_string(),
+            };
+            out.push_str(&format!("{:04} {}\n", index, line));
+        }
+
+        for constant in &self.constants {
+            if let Value::Function(function) = constant {
+                out.push_str(&format!(
+                    "\nfn {}({}):\n",
+                    function.name,
+                    function.params.join(", ")
+                ));
+                out.push_str(&function.chunk.disassemble());
+            }
+        }
+        out
+    }
+}
+
+/// The innermost loop being lowered: where `continue` goes, and the `break`
+/// jumps to patch once its end is known
+struct Loop {
+    start: usize,
+    breaks: Vec<usize>,
+}
+
+/// Lowers MIR to [`BytecodeChunk`]s
+#[derive(Default)]
+pub struct BytecodeEmitter {
+    chunk: BytecodeChunk,
+    loops: Vec<Loop>,
+}
+
+impl BytecodeEmitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `emit` against a fresh chunk and return it, leaving the chunk
+    /// being emitted before untouched
+    fn in_new_chunk(
+        &mut self,
+        emit: impl FnOnce(&mut Self) -> Result<()>,
+    ) -> Result<BytecodeChunk> {
+        let outer = std::mem::take(&mut self.chunk);
+        let outer_loops = std::mem::take(&mut self.loops);
+        let result = emit(self);
+        self.loops = outer_loops;
+        let chunk = std::mem::replace(&mut self.chunk, outer);
+        result.map(|()| chunk)
+    }
+
+    fn push(&mut self, instruction: Instruction) -> usize {
+        self.chunk.push(instruction)
+    }
+
+    fn push_const(&mut self, value: Value) {
+        let constant = self.chunk.add_constant(value);
+        self.push(Instruction::PushConst(constant));
+    }
+
+    /// Compile a function body and push the function
+    fn push_function(&mut self, name: &str, params: Vec<String>, body: &MirBlock) -> Result<()> {
+        let chunk = self.in_new_chunk(|emitter| {
+            emitter.lower_block(&body.statements, body.expr.as_deref())?;
+            emitter.push(Instruction::Return);
+            Ok(())
+        })?;
+        self.push_const(Value::Function(Rc::new(Function {
+            name: name.to_string(),
+            params,
+            chunk,
+        })));
+        Ok(())
+    }
+
+    fn lower_block(&mut self, statements: &[MirExprStmt], expr: Option<&MirExpr>) -> Result<()> {
+        for stmt in statements {
+            self.lower_expr_stmt(stmt)?;
+        }
+        match expr {
+            Some(expr) => self.lower_expr(expr),
+            None => {
+                self.push_const(Value::Null);
+                Ok(())
+            }
+        }
+    }
+
+    fn lower_if<T>(
+        &mut self,
+        condition: &MirExpr,
+        then_body: &[T],
+        else_body: Option<&[T]>,
+        lower: fn(&mut Self, &T) -> Result<()>,
+    ) -> Result<()> {
+        self.lower_expr(condition)?;
+        let to_else = self.push(Instruction::JumpIfFalse(0));
+        for stmt in then_body {
+            lower(self, stmt)?;
+        }
+        match else_body {
+            Some(else_body) => {
+                let to_end = self.push(Instruction::Jump(0));
+                self.chunk.patch_jump(to_else);
+                for stmt in else_body {
+                    lower(self, stmt)?;
+                }
+                self.chunk.patch_jump(to_end);
+            }
+            None => self.chunk.patch_jump(to_else),
+        }
+        Ok(())
+    }
+
+    fn lower_while<T>(
+        &mut self,
+        condition: &MirExpr,
+        body: &[T],
+        lower: fn(&mut Self, &T) -> Result<()>,
+    ) -> Result<()> {
+        let start = self.chunk.instructions.len();
+        self.lower_expr(condition)?;
+        let to_end = self.push(Instruction::JumpIfFalse(0));
+        self.loops.push(Loop {
+            start,
+            breaks: Vec::new(),
+        });
+        for stmt in body {
+            lower(self, stmt)?;
+        }
+        self.push(Instruction::Jump(start));
+        self.chunk.patch_jump(to_end);
+        if let Some(finished) = self.loops.pop() {
+            for jump in finished.breaks {
+                self.chunk.patch_jump(jump);
+            }
+        }
+        Ok(())
+    }
+
+    fn lower_return(&mut self, value: Option<&MirExpr>) -> Result<()> {
+        match value {
+            Some(value) => self.lower_expr(value)?,
+            None => self.push_const(Value::Null),
+        }
+        self.push(Instruction::Return);
+        Ok(())
+    }
+
+    fn lower_break(&mut self) -> Result<()> {
+        let jump = self.push(Instruction::Jump(0));
+        self.loops
+            .last_mut()
+            .ok_or_else(|| EmitError::EmitError("break outside of a loop".to_string()))?
+            .breaks
+            .push(jump);
+        Ok(())
+    }
+
+    fn lower_continue(&mut self) -> Result<()> {
+        let start = self
+            .loops
+            .last()
+            .ok_or_else(|| EmitError::EmitError("continue outside of a loop".to_string()))?
+            .start;
+        self.push(Instruction::Jump(start));
+        Ok(())
+    }
+
+    fn lower_stmt(&mut self, stmt: &MirStmt) -> Result<()> {
+        match stmt {
+            MirStmt::Function { is_async: true, .. } => return Err(unsupported("async function")),
+            MirStmt::Function {
+                name, params, body, ..
+            } => {
+                let params = params.iter().map(|p| p.name.clone()).collect();
+                self.push_function(name, params, body)?;
+                self.push(Instruction::Store(name.clone()));
+            }
+            MirStmt::Let { name, value, .. } => {
+                self.lower_expr(value)?;
+                self.push(Instruction::Store(name.clone()));
+            }
+            MirStmt::Expr(expr) => {
+                self.lower_expr(expr)?;
+                self.push(Instruction::Pop);
+            }
+            MirStmt::If {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => self.lower_if(condition, then_body, else_body.as_deref(), Self::lower_stmt)?,
+            MirStmt::While {
+                condition, body, ..
+            } => self.lower_while(condition, body, Self::lower_stmt)?,
+            MirStmt::Return { value, .. } => self.lower_return(value.as_ref())?,
+            MirStmt::Break { .. } => self.lower_break()?,
+            MirStmt::Continue { .. } => self.lower_continue()?,
+            MirStmt::Class { .. } => return Err(unsupported("class definition")),
+            MirStmt::For { .. } => return Err(unsupported("for loop")),
+            MirStmt::Match { .. } => return Err(unsupported("match statement")),
+            MirStmt::Import { .. } => return Err(unsupported("import")),
+            MirStmt::Try { .. } => return Err(unsupported("try statement")),
+            MirStmt::Raise { .. } => return Err(unsupported("raise statement")),
+        }
+        Ok(())
+    }
+
+    fn lower_expr_stmt(&mut self, stmt: &MirExprStmt) -> Result<()> {
+        match stmt {
+            MirExprStmt::Let { name, value, .. } => {
+                self.lower_expr(value)?;
+                self.push(Instruction::Store(name.clone()));
+            }
+            MirExprStmt::Assign {
+                target, op, value, ..
+            } => {
+                if let Some(op) = op {
+                    self.push(Instruction::Load(target.clone()));
+                    self.lower_expr(value)?;
+                    self.push(Instruction::BinOp(*op));
+                } else {
+                    self.lower_expr(value)?;
+                }
+                self.push(Instruction::Store(target.clone()));
+            }
+            MirExprStmt::Expr(expr) => {
+                self.lower_expr(expr)?;
+                self.push(Instruction::Pop);
+            }
+            MirExprStmt::Return { value, .. } => self.lower_return(value.as_deref())?,
+            MirExprStmt::If {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => self.lower_if(
+                condition,
+                then_body,
+                else_body.as_deref(),
+                Self::lower_expr_stmt,
+            )?,
+            MirExprStmt::While {
+                condition, body, ..
+            } => self.lower_while(condition, body, Self::lower_expr_stmt)?,
+            MirExprStmt::Break { .. } => self.lower_break()?,
+            MirExprStmt::Continue { .. } => self.lower_continue()?,
+            MirExprStmt::IndexAssign { .. } => return Err(unsupported("index assignment")),
+            MirExprStmt::FieldAssign { .. } => return Err(unsupported("field assignment")),
+            MirExprStmt::For { .. } => return Err(unsupported("for loop")),
+            MirExprStmt::Try { .. } => return Err(unsupported("try statement")),
+            MirExprStmt::Raise { .. } => return Err(unsupported("raise statement")),
+        }
+        Ok(())
+    }
+
+    fn lower_expr(&mut self, expr: &MirExpr) -> Result<()> {
+        match expr {
+            MirExpr::Literal { value, .. } => self.push_const(literal_value(value)),
+            MirExpr::Variable { name, .. } => {
+                self.push(Instruction::Load(name.clone()));
+            }
+
+            // The right side only runs when the left doesn't settle the result
+            MirExpr::Binary {
+                op: BinOp::And,
+                left,
+                right,
+                ..
+            } => {
+                self.lower_expr(left)?;
+                let to_false = self.push(Instruction::JumpIfFalse(0));
+                self.lower_expr(right)?;
+                let to_end = self.push(Instruction::Jump(0));
+                self.chunk.patch_jump(to_false);
+                self.push_const(Value::Bool(false));
+                self.chunk.patch_jump(to_end);
+            }
+            MirExpr::Binary {
+                op: BinOp::Or,
+                left,
+                right,
+                ..
+            } => {
+                self.lower_expr(left)?;
+                let to_right = self.push(Instruction::JumpIfFalse(0));
+                self.push_const(Value::Bool(true));
+                let to_end = self.push(Instruction::Jump(0));
+                self.chunk.patch_jump(to_right);
+                self.lower_expr(right)?;
+                self.chunk.patch_jump(to_end);
+            }
+
+            MirExpr::Binary {
+                op, left, right, ..
+            } => {
+                self.lower_expr(left)?;
+                self.lower_expr(right)?;
+                self.push(Instruction::BinOp(*op));
+            }
+            MirExpr::Unary { op, operand, .. } => {
+                self.lower_expr(operand)?;
+                self.push(Instruction::UnaryOp(*op));
+            }
+            MirExpr::Call { callee, args, .. } => {
+                self.lower_expr(callee)?;
+                for arg in args {
+                    self.lower_expr(arg)?;
+                }
+                self.push(Instruction::Call(args.len()));
+            }
+            MirExpr::Block {
+                statements, expr, ..
+            } => self.lower_block(statements, expr.as_deref())?,
+            MirExpr::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.lower_expr(condition)?;
+                let to_else = self.push(Instruction::JumpIfFalse(0));
+                self.lower_expr(then_branch)?;
+                let to_end = self.push(Instruction::Jump(0));
+                self.chunk.patch_jump(to_else);
+                self.lower_expr(else_branch)?;
+                self.chunk.patch_jump(to_end);
+            }
+            MirExpr::Lambda { params, body, .. } => {
+                let chunk = self.in_new_chunk(|emitter| {
+                    emitter.lower_expr(body)?;
+                    emitter.push(Instruction::Return);
+                    Ok(())
+                })?;
+                self.push_const(Value::Function(Rc::new(Function {
+                    name: "<lambda>".to_string(),
+                    params: params.clone(),
+                    chunk,
+                })));
+            }
+            MirExpr::StringInterp { .. } => return Err(unsupported("string interpolation")),
+            MirExpr::List { .. } => return Err(unsupported("list literal")),
+            MirExpr::Tuple { .. } => return Err(unsupported("tuple literal")),
+            MirExpr::Map { .. } => return Err(unsupported("map literal")),
+            MirExpr::FieldAccess { .. } => return Err(unsupported("field access")),
+            MirExpr::Index { .. } => return Err(unsupported("index expression")),
+            MirExpr::Range { .. } => return Err(unsupported("range")),
+            MirExpr::Slice { .. } => return Err(unsupported("slice")),
+            MirExpr::Await { .. } => return Err(unsupported("await expression")),
+        }
+        Ok(())
+    }
+}
+
+impl CodeEmitter for BytecodeEmitter {
+    type Chunk = BytecodeChunk;
+
+    /// The program's statements in order, returning null at the end
+    fn emit_program(&mut self, program: &MirProgram) -> Result<BytecodeChunk> {
+        self.in_new_chunk(|emitter| {
+            for stmt in &program.statements {
+                emitter.lower_stmt(stmt)?;
+            }
+            emitter.push_const(Value::Null);
+            emitter.push(Instruction::Return);
+            Ok(())
+        })
+    }
+
+    /// The function's body, returning the value of its final expression
+    fn emit_function(&mut self, func: &MirFunction) -> Result<BytecodeChunk> {
+        self.in_new_chunk(|emitter| {
+            emitter.lower_block(&func.body.statements, func.body.expr.as_deref())?;
+            emitter.push(Instruction::Return);
+            Ok(())
+        })
+    }
+
+    /// Instructions that leave the expression's value on the stack
+    fn emit_expr(&mut self, expr: &MirExpr) -> Result<BytecodeChunk> {
+        self.in_new_chunk(|emitter| emitter.lower_expr(expr))
+    }
+}
+
+fn unsupported(node: &str) -> EmitError {
+    EmitError::UnsupportedNode(format!("{} in bytecode", node))
+}
+
+fn literal_value(literal: &Literal) -> Value {
+    match literal {
+        Literal::Int(n) => Value::Int(*n),
+        Literal::Float(n) => Value::Float(*n),
+        Literal::String(s) => Value::String(s.clone()),
+        Literal::Char(c) => Value::String(c.to_string()),
+        Literal::Bool(b) => Value::Bool(*b),
+        Literal::Null => Value::Null,
+    }
+}
+
+/// Error while running bytecode
+#[derive(Debug, Error, PartialEq)]
+pub enum RuntimeError {
+    #[error("undefined variable: {0}")]
+    UndefinedVariable(String),
+
+    #[error("{0} is not a function")]
+    NotCallable(String),
+
+    #[error("{name} takes {expected} argument(s) but {given} were given")]
+    WrongArgumentCount {
+        name: String,
+        expected: usize,
+        given: usize,
+    },
+
+    #[error("unsupported operand types for {op:?}: {left} and {right}")]
+    OperandTypes {
+        op: String,
+        left: &'static str,
+        right: &'static str,
+    },
+
+    #[error("division by zero")]
+    DivisionByZero,
+
+    #[error("integer overflow")]
+    Overflow,
+
+    #[error("stack underflow")]
+    StackUnderflow,
+}
+
+/// Run a chunk with no globals defined, returning the value it returns or
+/// the value left on top of the stack
+pub fn interpret(chunk: &BytecodeChunk) -> std::result::Result<Value, RuntimeError> {
+    Interpreter::new().run(chunk)
+}
+
+/// A stack machine for [`BytecodeChunk`]s
+///
+/// Top-level stores go to globals that stay defined between runs. Inside a
+/// function every store is local, and a function sees its own locals and
+/// the globals but not the locals of the function that made it.
+#[derive(Debug, Default)]
+pub struct Interpreter {
+    globals: HashMap<String, Value>,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current value of a global
+    pub fn global(&self, name: &str) -> Option<&Value> {
+        self.globals.get(name)
+    }
+
+    pub fn run(&mut self, chunk: &BytecodeChunk) -> std::result::Result<Value, RuntimeError> {
+        self.execute(chunk, None)
+    }
+
+    fn execute(
+        &mut self,
+        chunk: &BytecodeChunk,
+        mut locals: Option<HashMap<String, Value>>,
+    ) -> std::result::Result<Value, RuntimeError> {
+        let mut stack = Vec::new();
+        let mut pc = 0;
+
+        while let Some(instruction) = chunk.instructions.get(pc) {
+            pc += 1;
+            match instruction {
+                Instruction::PushConst(constant) => stack.push(chunk.constants[*constant].clone()),
+                Instruction::Load(name) => {
+                    let value = locals
+                        .as_ref()
+                        .and_then(|locals| locals.get(name))
+                        .or_else(|| self.globals.get(name))
+                        .cloned()
+                        .ok_or_else(|| RuntimeError::UndefinedVariable(name.clone()))?;
+                    stack.push(value);
+                }
+                Instruction::Store(name) => {
+                    let value = pop(&mut stack)?;
+                    match &mut locals {
+                        Some(locals) => locals.insert(name.clone(), value),
+                        None => self.globals.insert(name.clone(), value),
+                    };
+                }
+                Instruction::BinOp(op) => {
+                    let right = pop(&mut stack)?;
+                    let left = pop(&mut stack)?;
+                    stack.push(binary(*op, left, right)?);
+                }
+                Instruction::UnaryOp(op) => {
+                    let operand = pop(&mut stack)?;
+                    stack.push(unary(*op, operand)?);
+                }
+                Instruction::Call(argc) => {
+                    let first_arg = stack
+                        .len()
+                        .checked_sub(*argc)
+                        .ok_or(RuntimeError::StackUnderflow)?;
+                    let args = stack.split_off(first_arg);
+                    let function = match pop(&mut stack)? {
+                        Value::Function(function) => function,
+                        other => return Err(RuntimeError::NotCallable(other.to_string())),
+                    };
+                    if args.len() != function.params.len() {
+                        return Err(RuntimeError::WrongArgumentCount {
+                            name: function.name.clone(),
+                            expected: function.params.len(),
+                            given: args.len(),
+                        });
+                    }
+                    let frame = function.params.iter().cloned().zip(args).collect();
+                    stack.push(self.execute(&function.chunk, Some(frame))?);
+                }
+                Instruction::Jump(to) => pc = *to,
+                Instruction::JumpIfFalse(to) => match pop(&mut stack)? {
+                    Value::Bool(true) => {}
+                    Value::Bool(false) => pc = *to,
+                    other => {
+                        return Err(RuntimeError::OperandTypes {
+                            op: "condition".to_string(),
+                            left: other.type_name(),
+                            right: "Bool",
+                        })
+                    }
+                },
+                Instruction::Pop => {
+                    pop(&mut stack)?;
+                }
+                Instruction::Return => return pop(&mut stack),
+            }
+        }
+
+        Ok(stack.pop().unwrap_or(Value::Null))
+    }
+}
+
+fn pop(stack: &mut Vec<Value>) -> std::result::Result<Value, RuntimeError> {
+    stack.pop().ok_or(RuntimeError::StackUnderflow)
+}
+
+/// Apply a binary operator with the meaning the Python output gives it
+fn binary(op: BinOp, left: Value, right: Value) -> std::result::Result<Value, RuntimeError> {
+    let mismatch = || RuntimeError::OperandTypes {
+        op: format!("{:?}", op),
+        left: left.type_name(),
+        right: right.type_name(),
+    };
+
+    match (op, &left, &right) {
+        (BinOp::Eq, _, _) => Ok(Value::Bool(left == right)),
+        (BinOp::Ne, _, _) => Ok(Value::Bool(left != right)),
+        (BinOp::And, Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(*a && *b)),
+        (BinOp::Or, Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(*a || *b)),
+        (BinOp::Add | BinOp::Concat, Value::String(a), Value::String(b)) => {
+            Ok(Value::String(format!("{}{}", a, b)))
+        }
+        (BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge, Value::String(a), Value::String(b)) => {
+            Ok(Value::Bool(compare(op, a.cmp(b))))
+        }
+        (_, Value::Int(a), Value::Int(b)) => int_binary(op, *a, *b).ok_or_else(mismatch)?,
+        (_, Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+            float_binary(op, as_float(&left), as_float(&right)).ok_or_else(mismatch)?
+        }
+        _ => Err(mismatch()),
+    }
+}
+
+/// `None` when the operator doesn't apply to integers
+fn int_binary(op: BinOp, a: i64, b: i64) -> Option<std::result::Result<Value, RuntimeError>> {
+    let checked = |result: Option<i64>| Some(result.map(Value::Int).ok_or(RuntimeError::Overflow));
+    let nonzero = |b: i64| {
+        if b == 0 {
+            Err(RuntimeError::DivisionByZero)
+        } else {
+            Ok(b)
+        }
+    };
+
+    match op {
+        BinOp::Add => checked(a.checked_add(b)),
+        BinOp::Sub => checked(a.checked_sub(b)),
+        BinOp::Mul => checked(a.checked_mul(b)),
+        // Integer division and remainder round toward negative infinity
+        BinOp::Div => Some(nonzero(b).and_then(|b| {
+            floor_div(a, b)
+                .map(Value::Int)
+                .ok_or(RuntimeError::Overflow)
+        })),
+        BinOp::Mod => Some(nonzero(b).and_then(|b| {
+            floor_div(a, b)
+                .and_then(|q| a.checked_sub(q * b))
+                .map(Value::Int)
+                .ok_or(RuntimeError::Overflow)
+        })),
+        BinOp::Pow if b < 0 => Some(Ok(Value::Float((a as f64).powf(b as f64)))),
+        BinOp::Pow => checked(u32::try_from(b).ok().and_then(|b| a.checked_pow(b))),
+        BinOp::BitAnd => Some(Ok(Value::Int(a & b))),
+        BinOp::BitOr => Some(Ok(Value::Int(a | b))),
+        BinOp::BitXor => Some(Ok(Value::Int(a ^ b))),
+        BinOp::ShiftLeft => checked(u32::try_from(b).ok().and_then(|b| a.checked_shl(b))),
+        BinOp::ShiftRight => checked(u32::try_from(b).ok().and_then(|b| a.checked_shr(b))),
+        BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+            Some(Ok(Value::Bool(compare(op, a.cmp(&b)))))
+        }
+        BinOp::Concat | BinOp::Eq | BinOp::Ne | BinOp::And | BinOp::Or => None,
+    }
+}
+
+/// `None` when the operator doesn't apply to floats
+fn float_binary(op: BinOp, a: f64, b: f64) -> Option<std::result::Result<Value, RuntimeError>> {
+    let value = match op {
+        BinOp::Add => a + b,
+        BinOp::Sub => a - b,
+        BinOp::Mul => a * b,
+        BinOp::Div | BinOp::Mod if b == 0.0 => return Some(Err(RuntimeError::DivisionByZero)),
+        BinOp::Div => a / b,
+        BinOp::Mod => a - b * (a / b).floor(),
+        BinOp::Pow => a.powf(b),
+        BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+            return a
+                .partial_cmp(&b)
+                .map(|ordering| Ok(Value::Bool(compare(op, ordering))))
+                .or(Some(Ok(Value::Bool(false))))
+        }
+        _ => return None,
+    };
+    Some(Ok(Value::Float(value)))
+}
+
+fn floor_div(a: i64, b: i64) -> Option<i64> {
+    let quotient = a.checked_div(b)?;
+    if a % b != 0 && (a < 0) != (b < 0) {
+        Some(quotient - 1)
+    } else {
+        Some(quotient)
+    }
+}
+
+fn compare(op: BinOp, ordering: std::cmp::Ordering) -> bool {
+    match op {
+        BinOp::Lt => ordering.is_lt(),
+        BinOp::Le => ordering.is_le(),
+        BinOp::Gt => ordering.is_gt(),
+        _ => ordering.is_ge(),
+    }
+}
+
+fn as_float(value: &Value) -> f64 {
+    match value {
+        Value::Int(n) => *n as f64,
+        Value::Float(n) => *n,
+        _ => f64::NAN,
+    }
+}
+
+fn unary(op: UnaryOp, operand: Value) -> std::result::Result<Value, RuntimeError> {
+    match (op, &operand) {
+        (UnaryOp::Neg, Value::Int(n)) => n
+            .checked_neg()
+            .map(Value::Int)
+            .ok_or(RuntimeError::Overflow),
+        (UnaryOp::Neg, Value::Float(n)) => Ok(Value::Float(-n)),
+        (UnaryOp::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
+        (UnaryOp::BitNot, Value::Int(n)) => Ok(Value::Int(!n)),
+        _ => Err(RuntimeError::OperandTypes {
+            op: format!("{:?}", op),
+            left: operand.type_name(),
+            right: "nothing",
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nevermind_mir::Param;
+    use nevermind_type_checker::Type;
+
+    fn int(value: i64) -> MirExpr {
+        MirExpr::Literal {
+            value: Literal::Int(value),
+            ty: Type::Int,
+            id: 0,
+        }
+    }
+
+    fn var(name: &str) -> MirExpr {
+        MirExpr::Variable {
+            name: name.to_string(),
+            ty: Type::Int,
+            id: 0,
+        }
+    }
+
+    fn binary(op: BinOp, left: MirExpr, right: MirExpr) -> MirExpr {
+        MirExpr::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+            ty: Type::Int,
+            id: 0,
+        }
+    }
+
+    #[test]
+    fn test_addition_emits_two_pushes_and_a_binop() {
+        let chunk = BytecodeEmitter::new()
+            .emit_expr(&binary(BinOp::Add, int(1), int(2)))
+            .unwrap();
+        assert_eq!(
+            chunk.instructions,
+            vec![
+                Instruction::PushConst(0),
+                Instruction::PushConst(1),
+                Instruction::BinOp(BinOp::Add),
+            ]
+        );
+        assert_eq!(chunk.constants, vec![Value::Int(1), Value::Int(2)]);
+        assert_eq!(
+            chunk.disassemble(),
+            "0000 PushConst 0 (1)\n0001 PushConst 1 (2)\n0002 BinOp Add\n"
+        );
+    }
+
+    #[test]
+    fn test_addition_evaluates_to_three() {
+        let chunk = BytecodeEmitter::new()
+            .emit_expr(&binary(BinOp::Add, int(1), int(2)))
+            .unwrap();
+        assert_eq!(interpret(&chunk), Ok(Value::Int(3)));
+    }
+
+    #[test]
+    fn test_program_calls_a_recursive_function() {
+        // fn fact(n) do if n <= 1 then 1 else n * fact(n - 1) end
+        // let answer = fact(5)
+        let call = |arg: MirExpr| MirExpr::Call {
+            callee: Box::new(var("fact")),
+            args: vec![arg],
+            ty: Type::Int,
+            id: 0,
+        };
+        let body = MirExpr::If {
+            condition: Box::new(binary(BinOp::Le, var("n"), int(1))),
+            then_branch: Box::new(int(1)),
+            else_branch: Box::new(binary(
+                BinOp::Mul,
+                var("n"),
+                call(binary(BinOp::Sub, var("n"), int(1))),
+            )),
+            ty: Type::Int,
+            id: 0,
+        };
+        let program = MirProgram {
+            statements: vec![
+                MirStmt::Function {
+                    is_async: false,
+                    name: "fact".to_string(),
+                    params: vec![Param {
+                        name: "n".to_string(),
+                        ty: Type::Int,
+                        id: 0,
+                    }],
+                    body: MirBlock {
+                        statements: Vec::new(),
+                        expr: Some(Box::new(body)),
+                    },
+                    return_type: Type::Int,
+                    id: 0,
+                },
+                MirStmt::Let {
+                    name: "answer".to_string(),
+                    value: call(int(5)),
+                    ty: Type::Int,
+                    id: 0,
+                },
+            ],
+            ..MirProgram::default()
+        };
+
+        let chunk = BytecodeEmitter::new().emit_program(&program).unwrap();
+        let mut interpreter = Interpreter::new();
+        assert_eq!(interpreter.run(&chunk), Ok(Value::Null));
+        assert_eq!(interpreter.global("answer"), Some(&Value::Int(120)));
+    }
+
+    #[test]
+    fn test_integer_division_rounds_down() {
+        let chunk = BytecodeEmitter::new()
+            .emit_expr(&binary(BinOp::Div, int(-7), int(2)))
+            .unwrap();
+        assert_eq!(interpret(&chunk), Ok(Value::Int(-4)));
+    }
+}
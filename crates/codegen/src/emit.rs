@@ -1,4 +1,4 @@
-//! Bytecode emitter
+//! Emitter interfaces shared by the code generators
 
 use thiserror::Error;
 
@@ -14,14 +14,14 @@ pub enum EmitError {
 
 pub type Result<T> = std::result::Result<T, EmitError>;
 
-/// A chunk of generated bytecode
+/// A chunk of generated source text
 #[derive(Debug, Default)]
-pub struct BytecodeChunk {
+pub struct SourceChunk {
     pub code: String,
     pub labels: Vec<(String, usize)>,
 }
 
-impl BytecodeChunk {
+impl SourceChunk {
     pub fn new() -> Self {
         Self::default()
     }
@@ -31,16 +31,36 @@ impl BytecodeChunk {
         self.code.push('\n');
     }
 
-    pub fn extend(&mut self, other: &BytecodeChunk) {
+    pub fn extend(&mut self, other: &SourceChunk) {
         self.code.push_str(&other.code);
     }
 }
 
+/// One level of indentation in generated code
+pub const INDENT: &str = "    ";
+
+/// Line-by-line output for emitters that generate indented source text
+pub trait LineEmitter {
+    /// How many blocks deep the next emitted line is nested
+    fn indent_level(&self) -> usize;
+
+    /// Append `line` to `output`, indented to the current level
+    ///
+    /// Every indented line goes through here, so nested bodies always line up.
+    fn emit_line(&self, output: &mut SourceChunk, line: &str) {
+        output.add_line(&format!("{}{}", INDENT.repeat(self.indent_level()), line));
+    }
+}
+
 /// Code emitter interface
 pub trait CodeEmitter {
-    fn emit_program(&mut self, program: &nevermind_mir::MirProgram) -> Result<BytecodeChunk>;
+    /// What the emitter produces for each node, such as source text or
+    /// bytecode
+    type Chunk;
+
+    fn emit_program(&mut self, program: &nevermind_mir::MirProgram) -> Result<Self::Chunk>;
 
-    fn emit_function(&mut self, func: &nevermind_mir::MirFunction) -> Result<BytecodeChunk>;
+    fn emit_function(&mut self, func: &nevermind_mir::MirFunction) -> Result<Self::Chunk>;
 
-    fn emit_expr(&mut self, expr: &nevermind_mir::MirExpr) -> Result<BytecodeChunk>;
+    fn emit_expr(&mut self, expr: &nevermind_mir::MirExpr) -> Result<Self::Chunk>;
 }
@@ -1,24 +1,34 @@
-//! Python bytecode code generator for Nevermind
+//! Python code generator for Nevermind
 //!
-//! This module translates MIR to Python bytecode.
+//! This module translates MIR to Python source. Bytecode (`compile --emit
+//! pyc`) is left to Python's own compiler; the [`bytecode`] module lowers MIR
+//! to a small stack machine of its own instead.
 
+pub mod bytecode;
 pub mod emit;
 pub mod python;
 
-pub use emit::{BytecodeChunk, CodeEmitter};
-pub use python::{PythonGenerator, PythonModuleContext};
+pub use bytecode::{BytecodeChunk, BytecodeEmitter};
+pub use emit::{CodeEmitter, SourceChunk};
+pub use python::{PythonGenerator, PythonModuleContext, PythonTarget};
 
 use emit::Result;
+use nevermind_common::CompileOptions;
 use nevermind_mir::MirProgram;
 
-/// Generate Python bytecode from MIR program
-pub fn generate(program: &MirProgram) -> Result<String> {
-    PythonGenerator::new().generate(program)
+/// Generate Python source from a MIR program
+pub fn generate(program: &MirProgram, options: &CompileOptions) -> Result<String> {
+    PythonGenerator::new()
+        .with_target(options.target)
+        .generate(program)
 }
 
 pub fn generate_with_context(
     program: &MirProgram,
     module_context: PythonModuleContext,
+    options: &CompileOptions,
 ) -> Result<String> {
-    PythonGenerator::with_module_context(module_context).generate(program)
+    PythonGenerator::with_module_context(module_context)
+        .with_target(options.target)
+        .generate(program)
 }
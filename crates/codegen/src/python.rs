@@ -1,12 +1,18 @@
 //! Python code generator
 
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
-use super::emit::Result;
-use super::{BytecodeChunk, CodeEmitter};
+use super::emit::{LineEmitter, Result, SourceChunk};
+use super::CodeEmitter;
 use nevermind_mir::{
-    BinOp, Literal, MirExpr, MirExprStmt, MirFunction, MirProgram, MirStmt, UnaryOp,
+    namespace_binding, BinOp, Literal, MirExpr, MirExprStmt, MirFunction, MirMatchArm, MirProgram,
+    MirStmt, MirStringPart, UnaryOp,
 };
+use nevermind_type_checker::Type;
+
+pub use nevermind_common::PythonTarget;
 
 #[derive(Debug, Clone)]
 pub struct PythonModuleContext {
@@ -46,6 +52,17 @@ impl PythonModuleContext {
 pub struct PythonGenerator {
     pub indent_level: usize,
     module_context: Option<PythonModuleContext>,
+    target: PythonTarget,
+
+    /// Arities of top-level functions defined more than once, by name
+    overloads: HashMap<String, Vec<usize>>,
+
+    /// Names of top-level functions, which shadow builtins of the same name
+    defined_functions: HashSet<String>,
+
+    /// How many statement-form conditionals have been given a temporary
+    /// for their value
+    branch_values: usize,
 }
 
 impl PythonGenerator {
@@ -53,6 +70,10 @@ impl PythonGenerator {
         Self {
             indent_level: 0,
             module_context: None,
+            target: PythonTarget::default(),
+            overloads: HashMap::new(),
+            defined_functions: HashSet::new(),
+            branch_values: 0,
         }
     }
 
@@ -60,22 +81,24 @@ impl PythonGenerator {
         Self {
             indent_level: 0,
             module_context: Some(module_context),
+            target: PythonTarget::default(),
+            overloads: HashMap::new(),
+            defined_functions: HashSet::new(),
+            branch_values: 0,
         }
     }
 
+    /// Generate code for `target` instead of the default Python version
+    pub fn with_target(mut self, target: PythonTarget) -> Self {
+        self.target = target;
+        self
+    }
+
     pub fn generate(&mut self, program: &MirProgram) -> Result<String> {
         let chunk = self.emit_program(program)?;
         Ok(chunk.code)
     }
 
-    fn indent(&self) -> String {
-        "    ".repeat(self.indent_level)
-    }
-
-    fn output_line(&self, output: &mut BytecodeChunk, text: &str) {
-        output.add_line(&format!("{}{}", self.indent(), text));
-    }
-
     fn python_import_path(&self, module: &str) -> String {
         if let Some(module_context) = &self.module_context {
             if module_context.local_module_path(module).exists() {
@@ -91,15 +114,17 @@ impl PythonGenerator {
     fn emit_literal(&self, literal: &Literal) -> String {
         match literal {
             Literal::Int(v) => v.to_string(),
-            Literal::Float(v) => v.to_string(),
+            // Debug keeps the fractional part, so `5.0` stays a float
+            Literal::Float(v) => format!("{:?}", v),
             Literal::String(v) => {
                 // Check for string interpolation: {expr}
                 if v.contains('{') && v.contains('}') {
-                    format!("f\"{}\"", escape_string(v))
+                    format!("f{}", escape_python_string(&sanitize_interpolations(v)))
                 } else {
-                    format!("\"{}\"", escape_string(v))
+                    escape_python_string(v)
                 }
             }
+            Literal::Char(c) => char_literal(*c),
             Literal::Bool(v) => if *v { "True" } else { "False" }.to_string(),
             Literal::Null => "None".to_string(),
         }
@@ -113,6 +138,12 @@ impl PythonGenerator {
             BinOp::Div => "//",
             BinOp::Mod => "%",
             BinOp::Pow => "**",
+            BinOp::Concat => "+",
+            BinOp::BitAnd => "&",
+            BinOp::BitOr => "|",
+            BinOp::BitXor => "^",
+            BinOp::ShiftLeft => "<<",
+            BinOp::ShiftRight => ">>",
             BinOp::Eq => "==",
             BinOp::Ne => "!=",
             BinOp::Lt => "<",
@@ -124,21 +155,327 @@ impl PythonGenerator {
         }
     }
 
+    /// Python operator for `op`, picking true division when a float is involved
+    fn typed_binop(&self, op: BinOp, left: &Type, right: &Type) -> &'static str {
+        match op {
+            BinOp::Div if *left == Type::Float || *right == Type::Float => "/",
+            _ => self.map_binop(op),
+        }
+    }
+
+    /// Assignment operator text, e.g. `=` or `+=` for a compound assignment
+    fn assign_op(&self, op: Option<BinOp>, value: &MirExpr) -> String {
+        match op {
+            Some(op) => format!(
+                "{}=",
+                self.typed_binop(op, value.get_type(), value.get_type())
+            ),
+            None => "=".to_string(),
+        }
+    }
+
     fn map_unop(&self, op: UnaryOp) -> &'static str {
         match op {
             UnaryOp::Neg => "-",
             UnaryOp::Not => "not ",
+            UnaryOp::BitNot => "~",
+        }
+    }
+
+    /// Emit a function that forwards to the overload matching its argument count
+    fn emit_overload_dispatcher(
+        &mut self,
+        name: &str,
+        arities: &[usize],
+        output: &mut SourceChunk,
+    ) {
+        self.emit_line(output, &format!("def {}(*args):", sanitize_ident(name)));
+        self.indent_level += 1;
+        for arity in arities {
+            self.emit_line(output, &format!("if len(args) == {}:", arity));
+            self.indent_level += 1;
+            self.emit_line(
+                output,
+                &format!("return {}(*args)", overload_name(name, *arity)),
+            );
+            self.indent_level -= 1;
         }
+        self.emit_line(
+            output,
+            &format!(
+                "raise TypeError(\"no overload of '{}' takes %d argument(s)\" % len(args))",
+                name
+            ),
+        );
+        self.indent_level -= 1;
+        output.add_line("");
+    }
+
+    /// Emit an expression in statement position.
+    ///
+    /// Blocks (such as lowered assignments) are flattened into their
+    /// statements rather than rendered as a single expression line.
+    fn emit_stmt_expr(&mut self, expr: &MirExpr, output: &mut SourceChunk) -> Result<()> {
+        if let MirExpr::Block {
+            statements, expr, ..
+        } = expr
+        {
+            for stmt in statements {
+                self.emit_expr_stmt(stmt, output)?;
+            }
+            if let Some(e) = expr {
+                self.emit_stmt_expr(e, output)?;
+            }
+            return Ok(());
+        }
+
+        if let Some(line) = self.emit_assert(expr)? {
+            self.emit_line(output, &line);
+            return Ok(());
+        }
+
+        if runs_statements(expr) {
+            self.emit_value_expr(expr, output)?;
+            return Ok(());
+        }
+        let hoisted = self.hoist_statements(expr, output)?;
+        let chunk = self.emit_expr(&hoisted)?;
+        let code = chunk.code.trim();
+        if !code.is_empty() {
+            self.emit_line(output, code);
+        }
+        Ok(())
+    }
+
+    /// Emit an expression in value position and return its Python source.
+    ///
+    /// The statements of a block value are hoisted ahead of the use site; the
+    /// block evaluates to its final expression, or `None` when it has none
+    /// (an assignment, for instance, has the value `Unit`).
+    fn emit_value_expr(&mut self, expr: &MirExpr, output: &mut SourceChunk) -> Result<String> {
+        if let MirExpr::Block {
+            statements, expr, ..
+        } = expr
+        {
+            for stmt in statements {
+                self.emit_expr_stmt(stmt, output)?;
+            }
+            return match expr {
+                Some(e) => self.emit_value_expr(e, output),
+                None => Ok("None".to_string()),
+            };
+        }
+
+        if let Some(line) = self.emit_assert(expr)? {
+            self.emit_line(output, &line);
+            return Ok("None".to_string());
+        }
+
+        // A conditional whose branches run statements, such as a match arm
+        // with a block body, can't be a conditional expression; it becomes
+        // an `if`/`else` assigning its value in each branch instead
+        if let MirExpr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } = expr
+        {
+            if runs_statements(expr) {
+                let condition_code = self.emit_value_expr(condition, output)?;
+                let target = self.fresh_branch_value();
+                self.emit_line(output, &format!("if {}:", condition_code));
+                self.indent_level += 1;
+                self.emit_branch_value(then_branch, &target, output)?;
+                self.indent_level -= 1;
+                self.emit_line(output, "else:");
+                self.indent_level += 1;
+                self.emit_branch_value(else_branch, &target, output)?;
+                self.indent_level -= 1;
+                return Ok(target);
+            }
+        }
+
+        let hoisted = self.hoist_statements(expr, output)?;
+        Ok(self.emit_expr(&hoisted)?.code.trim().to_string())
+    }
+
+    /// Emit an operand that runs statements ahead of its use, returning the
+    /// temporary that holds its value, or hoist out of an operand that
+    /// doesn't
+    fn hoist_operand(
+        &mut self,
+        operand: &MirExpr,
+        output: &mut SourceChunk,
+    ) -> Result<Box<MirExpr>> {
+        if !runs_statements(operand) {
+            return self.hoist_statements(operand, output).map(Box::new);
+        }
+        let code = self.emit_value_expr(operand, output)?;
+        let name = if is_branch_value(&code) {
+            code
+        } else {
+            let target = self.fresh_branch_value();
+            self.emit_line(output, &format!("{} = {}", target, code));
+            target
+        };
+        Ok(Box::new(MirExpr::Variable {
+            name,
+            ty: operand.get_type().clone(),
+            id: operand.get_id(),
+        }))
+    }
+
+    /// A new temporary for the value of a statement-form conditional, so
+    /// that two in one expression don't overwrite each other
+    fn fresh_branch_value(&mut self) -> String {
+        self.branch_values += 1;
+        format!("{}_{}", BRANCH_VALUE, self.branch_values)
+    }
+
+    /// Rewrite `expr` so that none of its operands run statements: each
+    /// one that does is emitted ahead of it, and replaced by a temporary
+    /// holding its value.
+    ///
+    /// Lambda bodies are left alone, as they run later if at all. So is the
+    /// right side of `and` and `or`, which only runs when the left side
+    /// doesn't decide the result; such an operator becomes a conditional.
+    fn hoist_statements(&mut self, expr: &MirExpr, output: &mut SourceChunk) -> Result<MirExpr> {
+        if !has_statement_operands(expr) {
+            return Ok(expr.clone());
+        }
+
+        let mut expr = expr.clone();
+        match &mut expr {
+            MirExpr::Binary {
+                op: op @ (BinOp::And | BinOp::Or),
+                left,
+                right,
+                ty,
+                id,
+            } if contains_statements(right) => {
+                // The left side is both the condition and a possible result,
+                // so it is evaluated once into a temporary
+                let left_code = self.emit_value_expr(left, output)?;
+                let target = self.fresh_branch_value();
+                self.emit_line(output, &format!("{} = {}", target, left_code));
+                let left = Box::new(MirExpr::Variable {
+                    name: target,
+                    ty: left.get_type().clone(),
+                    id: left.get_id(),
+                });
+                let (then_branch, else_branch) = if *op == BinOp::And {
+                    (right.clone(), left.clone())
+                } else {
+                    (left.clone(), right.clone())
+                };
+                let conditional = MirExpr::If {
+                    condition: left,
+                    then_branch,
+                    else_branch,
+                    ty: ty.clone(),
+                    id: *id,
+                };
+                return Ok(*self.hoist_operand(&conditional, output)?);
+            }
+            MirExpr::Binary { left, right, .. }
+            | MirExpr::Index {
+                array: left,
+                index: right,
+                ..
+            }
+            | MirExpr::Range {
+                start: left,
+                end: right,
+                ..
+            } => {
+                *left = self.hoist_operand(left, output)?;
+                *right = self.hoist_operand(right, output)?;
+            }
+            MirExpr::Unary { operand: inner, .. }
+            | MirExpr::Await { expr: inner, .. }
+            | MirExpr::FieldAccess { object: inner, .. } => {
+                *inner = self.hoist_operand(inner, output)?
+            }
+            MirExpr::Call { callee, args, .. } => {
+                *callee = self.hoist_operand(callee, output)?;
+                for arg in args {
+                    *arg = *self.hoist_operand(arg, output)?;
+                }
+            }
+            MirExpr::List { elements, .. } | MirExpr::Tuple { elements, .. } => {
+                for element in elements {
+                    *element = *self.hoist_operand(element, output)?;
+                }
+            }
+            MirExpr::Map { entries, .. } => {
+                for (key, value) in entries {
+                    *key = *self.hoist_operand(key, output)?;
+                    *value = *self.hoist_operand(value, output)?;
+                }
+            }
+            MirExpr::StringInterp { parts, .. } => {
+                for part in parts {
+                    if let MirStringPart::Expr(value) = part {
+                        *value = *self.hoist_operand(value, output)?;
+                    }
+                }
+            }
+            MirExpr::Slice {
+                array, start, end, ..
+            } => {
+                *array = self.hoist_operand(array, output)?;
+                *start = self.hoist_operand(start, output)?;
+                *end = self.hoist_operand(end, output)?;
+            }
+            MirExpr::If { condition, .. } => *condition = self.hoist_operand(condition, output)?,
+            MirExpr::Literal { .. }
+            | MirExpr::Variable { .. }
+            | MirExpr::Block { .. }
+            | MirExpr::Lambda { .. } => {}
+        }
+        Ok(expr)
+    }
+
+    /// The Python `assert` statement for a call to the built-in `assert`,
+    /// which has no expression form
+    fn emit_assert(&mut self, expr: &MirExpr) -> Result<Option<String>> {
+        let MirExpr::Call { callee, args, .. } = expr else {
+            return Ok(None);
+        };
+        match (callee.as_ref(), args.as_slice()) {
+            (MirExpr::Variable { name, .. }, [condition, message])
+                if name == "assert" && !self.defined_functions.contains(name) =>
+            {
+                let condition = self.emit_expr(condition)?.code.trim().to_string();
+                let message = self.emit_expr(message)?.code.trim().to_string();
+                Ok(Some(format!("assert {}, {}", condition, message)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Emit one branch of a statement-form conditional, storing its value
+    /// in `target`
+    fn emit_branch_value(
+        &mut self,
+        branch: &MirExpr,
+        target: &str,
+        output: &mut SourceChunk,
+    ) -> Result<()> {
+        let code = self.emit_value_expr(branch, output)?;
+        self.emit_line(output, &format!("{} = {}", target, code));
+        Ok(())
     }
 
     /// Emit a list of MirExprStmt with proper indentation
     fn emit_expr_stmt_list(
         &mut self,
         stmts: &[MirExprStmt],
-        output: &mut BytecodeChunk,
+        output: &mut SourceChunk,
     ) -> Result<()> {
         if stmts.is_empty() {
-            self.output_line(output, "pass");
+            self.emit_line(output, "pass");
             return Ok(());
         }
         for stmt in stmts {
@@ -148,47 +485,74 @@ impl PythonGenerator {
     }
 
     /// Emit a single MirExprStmt
-    fn emit_expr_stmt(&mut self, stmt: &MirExprStmt, output: &mut BytecodeChunk) -> Result<()> {
+    fn emit_expr_stmt(&mut self, stmt: &MirExprStmt, output: &mut SourceChunk) -> Result<()> {
         match stmt {
             MirExprStmt::Let { name, value, .. } => {
-                let expr_chunk = self.emit_expr(value)?;
-                self.output_line(output, &format!("{} = {}", name, expr_chunk.code.trim()));
+                let value_code = self.emit_value_expr(value, output)?;
+                self.emit_line(
+                    output,
+                    &format!("{} = {}", sanitize_ident(name), value_code),
+                );
             }
-            MirExprStmt::Assign { target, value, .. } => {
-                let chunk = self.emit_expr(value)?;
-                self.output_line(output, &format!("{} = {}", target, chunk.code.trim()));
+            MirExprStmt::Assign {
+                target, op, value, ..
+            } => {
+                let value_code = self.emit_value_expr(value, output)?;
+                let assign = self.assign_op(*op, value);
+                self.emit_line(
+                    output,
+                    &format!("{} {} {}", sanitize_ident(target), assign, value_code),
+                );
             }
             MirExprStmt::IndexAssign {
                 array,
                 index,
+                op,
                 value,
                 ..
             } => {
                 let index_chunk = self.emit_expr(index)?;
                 let value_chunk = self.emit_expr(value)?;
-                self.output_line(
+                self.emit_line(
                     output,
                     &format!(
-                        "{}[{}] = {}",
-                        array,
+                        "{}[{}] {} {}",
+                        sanitize_ident(array),
                         index_chunk.code.trim(),
+                        self.assign_op(*op, value),
                         value_chunk.code.trim()
                     ),
                 );
             }
+            MirExprStmt::FieldAssign {
+                object,
+                field,
+                op,
+                value,
+                ..
+            } => {
+                let object_chunk = self.emit_expr(object)?;
+                let value_code = self.emit_value_expr(value, output)?;
+                self.emit_line(
+                    output,
+                    &format!(
+                        "{}.{} {} {}",
+                        object_chunk.code.trim(),
+                        field,
+                        self.assign_op(*op, value),
+                        value_code
+                    ),
+                );
+            }
             MirExprStmt::Expr(expr) => {
-                let chunk = self.emit_expr(expr)?;
-                let code = chunk.code.trim();
-                if !code.is_empty() {
-                    self.output_line(output, code);
-                }
+                self.emit_stmt_expr(expr, output)?;
             }
             MirExprStmt::Return { value, .. } => {
                 if let Some(v) = value {
-                    let chunk = self.emit_expr(v)?;
-                    self.output_line(output, &format!("return {}", chunk.code.trim()));
+                    let code = self.emit_value_expr(v, output)?;
+                    self.emit_line(output, &format!("return {}", code));
                 } else {
-                    self.output_line(output, "return");
+                    self.emit_line(output, "return");
                 }
             }
             MirExprStmt::If {
@@ -198,12 +562,12 @@ impl PythonGenerator {
                 ..
             } => {
                 let cond_chunk = self.emit_expr(condition)?;
-                self.output_line(output, &format!("if {}:", cond_chunk.code.trim()));
+                self.emit_line(output, &format!("if {}:", cond_chunk.code.trim()));
                 self.indent_level += 1;
                 self.emit_expr_stmt_list(then_body, output)?;
                 self.indent_level -= 1;
                 if let Some(else_stmts) = else_body {
-                    self.output_line(output, "else:");
+                    self.emit_line(output, "else:");
                     self.indent_level += 1;
                     self.emit_expr_stmt_list(else_stmts, output)?;
                     self.indent_level -= 1;
@@ -213,40 +577,86 @@ impl PythonGenerator {
                 condition, body, ..
             } => {
                 let cond_chunk = self.emit_expr(condition)?;
-                self.output_line(output, &format!("while {}:", cond_chunk.code.trim()));
+                self.emit_line(output, &format!("while {}:", cond_chunk.code.trim()));
                 self.indent_level += 1;
                 self.emit_expr_stmt_list(body, output)?;
                 self.indent_level -= 1;
             }
             MirExprStmt::For {
-                variable,
+                pattern,
                 iter,
                 body,
                 ..
             } => {
-                let iter_chunk = self.emit_expr(iter)?;
-                self.output_line(
+                let iterable = self.emit_iterable(iter)?;
+                self.emit_line(
                     output,
-                    &format!("for {} in {}:", variable, iter_chunk.code.trim()),
+                    &format!("for {} in {}:", format_for_target(pattern), iterable),
                 );
                 self.indent_level += 1;
                 self.emit_expr_stmt_list(body, output)?;
                 self.indent_level -= 1;
             }
+            MirExprStmt::Try {
+                body,
+                catch_var,
+                catch_body,
+                finally_body,
+                ..
+            } => {
+                self.emit_line(output, "try:");
+                self.indent_level += 1;
+                self.emit_expr_stmt_list(body, output)?;
+                self.indent_level -= 1;
+                if let Some(catch_stmts) = catch_body {
+                    self.emit_except_header(catch_var.as_deref(), output);
+                    self.emit_expr_stmt_list(catch_stmts, output)?;
+                    self.indent_level -= 1;
+                }
+                if let Some(finally_stmts) = finally_body {
+                    self.emit_line(output, "finally:");
+                    self.indent_level += 1;
+                    self.emit_expr_stmt_list(finally_stmts, output)?;
+                    self.indent_level -= 1;
+                }
+            }
+            MirExprStmt::Raise { value, .. } => {
+                let code = self.emit_value_expr(value, output)?;
+                self.emit_line(output, &format!("raise Exception({})", code));
+            }
             MirExprStmt::Break { .. } => {
-                self.output_line(output, "break");
+                self.emit_line(output, "break");
             }
             MirExprStmt::Continue { .. } => {
-                self.output_line(output, "continue");
+                self.emit_line(output, "continue");
             }
         }
         Ok(())
     }
 
+    /// Emit the `except` line of a try statement and indent for its body.
+    ///
+    /// The catch variable is bound to the error's message. Python unbinds an
+    /// `except ... as` name when the clause ends, so the exception gets a name
+    /// of its own rather than the variable's.
+    fn emit_except_header(&mut self, catch_var: Option<&str>, output: &mut SourceChunk) {
+        match catch_var {
+            Some(name) => {
+                self.emit_line(output, "except Exception as __error:");
+                self.indent_level += 1;
+                self.emit_line(output, &format!("{} = str(__error)", sanitize_ident(name)));
+            }
+            None => {
+                self.emit_line(output, "except Exception:");
+                self.indent_level += 1;
+            }
+        }
+    }
+
     /// Emit a list of top-level MirStmt (used for if/while/for body in MirStmt)
-    fn emit_mir_stmt_list(&mut self, stmts: &[MirStmt], output: &mut BytecodeChunk) -> Result<()> {
+    fn emit_mir_stmt_list(&mut self, stmts: &[MirStmt], output: &mut SourceChunk) -> Result<()> {
         if stmts.is_empty() {
-            self.output_line(output, "pass");
+            self.emit_line(output, "pass");
             return Ok(());
         }
         for stmt in stmts {
@@ -256,14 +666,30 @@ impl PythonGenerator {
     }
 
     /// Emit a single top-level MirStmt
-    fn emit_mir_stmt(&mut self, stmt: &MirStmt, output: &mut BytecodeChunk) -> Result<()> {
+    fn emit_mir_stmt(&mut self, stmt: &MirStmt, output: &mut SourceChunk) -> Result<()> {
         match stmt {
             MirStmt::Function {
-                name, params, body, ..
+                is_async,
+                name,
+                params,
+                body,
+                ..
             } => {
-                let params_str: Vec<String> = params.iter().map(|p| p.name.clone()).collect();
+                let params_str: Vec<String> = params
+                    .iter()
+                    .map(|p| sanitize_ident(&p.name).into_owned())
+                    .collect();
                 let params_str = params_str.join(", ");
-                self.output_line(output, &format!("def {}({}):", name, params_str));
+                let def_name = if self.overloads.contains_key(name) {
+                    overload_name(name, params.len())
+                } else {
+                    sanitize_ident(name).into_owned()
+                };
+                let keyword = if *is_async { "async def" } else { "def" };
+                self.emit_line(
+                    output,
+                    &format!("{} {}({}):", keyword, def_name, params_str),
+                );
 
                 self.indent_level += 1;
 
@@ -274,25 +700,94 @@ impl PythonGenerator {
 
                 // Return expression
                 if let Some(expr) = &body.expr {
-                    let chunk = self.emit_expr(expr)?;
-                    self.output_line(output, &format!("return {}", chunk.code.trim()));
+                    let code = self.emit_value_expr(expr, output)?;
+                    self.emit_line(output, &format!("return {}", code));
                 } else if body.statements.is_empty() {
-                    self.output_line(output, "pass");
+                    self.emit_line(output, "pass");
                 }
 
                 self.indent_level -= 1;
                 output.add_line("");
+
+                // The dispatcher follows the last overload of the name, once
+                // every overload it calls is defined
+                let arities = self.overloads.get(name).cloned();
+                if let Some(arities) = arities.filter(|a| a.last() == Some(&params.len())) {
+                    self.emit_overload_dispatcher(name, &arities, output);
+                }
+            }
+            MirStmt::Class {
+                name,
+                extends,
+                inherited,
+                fields,
+                methods,
+                ..
+            } => {
+                let header = match extends {
+                    Some(base) => {
+                        format!("class {}({}):", sanitize_ident(name), sanitize_ident(base))
+                    }
+                    None => format!("class {}:", sanitize_ident(name)),
+                };
+                self.emit_line(output, &header);
+                self.indent_level += 1;
+
+                // Fields without a default are the constructor's parameters,
+                // after those of the classes it extends, which it passes on
+                if !fields.is_empty() {
+                    let inherited: Vec<String> = inherited
+                        .iter()
+                        .map(|field| sanitize_ident(field).into_owned())
+                        .collect();
+                    let params: Vec<String> = std::iter::once("self".to_string())
+                        .chain(inherited.iter().cloned())
+                        .chain(
+                            fields
+                                .iter()
+                                .filter(|field| field.default.is_none())
+                                .map(|field| sanitize_ident(&field.name).into_owned()),
+                        )
+                        .collect();
+                    self.emit_line(output, &format!("def __init__({}):", params.join(", ")));
+                    self.indent_level += 1;
+                    if extends.is_some() {
+                        self.emit_line(
+                            output,
+                            &format!("super().__init__({})", inherited.join(", ")),
+                        );
+                    }
+                    for field in fields {
+                        let value = match &field.default {
+                            Some(default) => self.emit_value_expr(default, output)?,
+                            None => sanitize_ident(&field.name).into_owned(),
+                        };
+                        self.emit_line(output, &format!("self.{} = {}", field.name, value));
+                    }
+                    self.indent_level -= 1;
+                    output.add_line("");
+                }
+
+                for method in methods {
+                    let chunk = self.emit_function(method)?;
+                    output.extend(&chunk);
+                }
+
+                if fields.is_empty() && methods.is_empty() {
+                    self.emit_line(output, "pass");
+                    output.add_line("");
+                }
+                self.indent_level -= 1;
             }
             MirStmt::Let { name, value, .. } => {
-                let expr_chunk = self.emit_expr(value)?;
-                self.output_line(output, &format!("{} = {}", name, expr_chunk.code.trim()));
+                let value_code = self.emit_value_expr(value, output)?;
+                self.emit_line(
+                    output,
+                    &format!("{} = {}", sanitize_ident(name), value_code),
+                );
             }
             MirStmt::Expr(expr) => {
-                let chunk = self.emit_expr(expr)?;
-                let code = chunk.code.trim();
-                if !code.is_empty() {
-                    self.output_line(output, code);
-                }
+                self.emit_stmt_expr(expr, output)?;
             }
             MirStmt::If {
                 condition,
@@ -301,12 +796,12 @@ impl PythonGenerator {
                 ..
             } => {
                 let cond_chunk = self.emit_expr(condition)?;
-                self.output_line(output, &format!("if {}:", cond_chunk.code.trim()));
+                self.emit_line(output, &format!("if {}:", cond_chunk.code.trim()));
                 self.indent_level += 1;
                 self.emit_mir_stmt_list(then_body, output)?;
                 self.indent_level -= 1;
                 if let Some(else_stmts) = else_body {
-                    self.output_line(output, "else:");
+                    self.emit_line(output, "else:");
                     self.indent_level += 1;
                     self.emit_mir_stmt_list(else_stmts, output)?;
                     self.indent_level -= 1;
@@ -316,21 +811,21 @@ impl PythonGenerator {
                 condition, body, ..
             } => {
                 let cond_chunk = self.emit_expr(condition)?;
-                self.output_line(output, &format!("while {}:", cond_chunk.code.trim()));
+                self.emit_line(output, &format!("while {}:", cond_chunk.code.trim()));
                 self.indent_level += 1;
                 self.emit_mir_stmt_list(body, output)?;
                 self.indent_level -= 1;
             }
             MirStmt::For {
-                variable,
+                pattern,
                 iter,
                 body,
                 ..
             } => {
-                let iter_chunk = self.emit_expr(iter)?;
-                self.output_line(
+                let iterable = self.emit_iterable(iter)?;
+                self.emit_line(
                     output,
-                    &format!("for {} in {}:", variable, iter_chunk.code.trim()),
+                    &format!("for {} in {}:", format_for_target(pattern), iterable),
                 );
                 self.indent_level += 1;
                 self.emit_mir_stmt_list(body, output)?;
@@ -338,58 +833,102 @@ impl PythonGenerator {
             }
             MirStmt::Return { value, .. } => {
                 if let Some(v) = value {
-                    let chunk = self.emit_expr(v)?;
-                    self.output_line(output, &format!("return {}", chunk.code.trim()));
+                    let code = self.emit_value_expr(v, output)?;
+                    self.emit_line(output, &format!("return {}", code));
                 } else {
-                    self.output_line(output, "return");
+                    self.emit_line(output, "return");
+                }
+            }
+            MirStmt::Try {
+                body,
+                catch_var,
+                catch_body,
+                finally_body,
+                ..
+            } => {
+                self.emit_line(output, "try:");
+                self.indent_level += 1;
+                self.emit_mir_stmt_list(body, output)?;
+                self.indent_level -= 1;
+                if let Some(catch_stmts) = catch_body {
+                    self.emit_except_header(catch_var.as_deref(), output);
+                    self.emit_mir_stmt_list(catch_stmts, output)?;
+                    self.indent_level -= 1;
                 }
+                if let Some(finally_stmts) = finally_body {
+                    self.emit_line(output, "finally:");
+                    self.indent_level += 1;
+                    self.emit_mir_stmt_list(finally_stmts, output)?;
+                    self.indent_level -= 1;
+                }
+            }
+            MirStmt::Raise { value, .. } => {
+                let code = self.emit_value_expr(value, output)?;
+                self.emit_line(output, &format!("raise Exception({})", code));
             }
             MirStmt::Break { .. } => {
-                self.output_line(output, "break");
+                self.emit_line(output, "break");
             }
             MirStmt::Continue { .. } => {
-                self.output_line(output, "continue");
+                self.emit_line(output, "continue");
             }
             MirStmt::Import {
-                module, symbols, ..
+                module,
+                symbols,
+                alias,
+                ..
             } => {
                 let py_module = self.python_import_path(module);
 
                 match symbols {
                     Some(syms) if !syms.is_empty() => {
+                        let syms: Vec<String> = syms
+                            .iter()
+                            .map(|sym| match &sym.alias {
+                                Some(alias) => format!(
+                                    "{} as {}",
+                                    sanitize_ident(&sym.name),
+                                    sanitize_ident(alias)
+                                ),
+                                None => sanitize_ident(&sym.name).into_owned(),
+                            })
+                            .collect();
                         let syms_str = syms.join(", ");
-                        self.output_line(
-                            output,
-                            &format!("from {} import {}", py_module, syms_str),
-                        );
+                        self.emit_line(output, &format!("from {} import {}", py_module, syms_str));
                     }
                     _ => {
-                        let alias = module.split('/').next_back().unwrap_or(module.as_str());
+                        let alias = sanitize_ident(namespace_binding(module, alias.as_deref()));
                         if py_module == alias {
-                            self.output_line(output, &format!("import {}", py_module));
+                            self.emit_line(output, &format!("import {}", py_module));
                         } else {
-                            self.output_line(output, &format!("import {} as {}", py_module, alias));
+                            self.emit_line(output, &format!("import {} as {}", py_module, alias));
                         }
                     }
                 }
             }
 
+            MirStmt::Match {
+                scrutinee, arms, ..
+            } if self.target != PythonTarget::Py310 => {
+                self.emit_match_chain(scrutinee, arms, output)?;
+            }
+
             MirStmt::Match {
                 scrutinee, arms, ..
             } => {
-                let scrut_chunk = self.emit_expr(scrutinee)?;
-                self.output_line(output, &format!("match {}:", scrut_chunk.code.trim()));
+                let scrut_code = self.emit_value_expr(scrutinee, output)?;
+                self.emit_line(output, &format!("match {}:", scrut_code));
                 self.indent_level += 1;
                 for arm in arms {
                     let pattern_str = format_mir_pattern(&arm.pattern);
                     if let Some(guard) = &arm.guard {
                         let guard_chunk = self.emit_expr(guard)?;
-                        self.output_line(
+                        self.emit_line(
                             output,
                             &format!("case {} if {}:", pattern_str, guard_chunk.code.trim()),
                         );
                     } else {
-                        self.output_line(output, &format!("case {}:", pattern_str));
+                        self.emit_line(output, &format!("case {}:", pattern_str));
                     }
                     self.indent_level += 1;
                     self.emit_mir_stmt_list(&arm.body, output)?;
@@ -400,15 +939,174 @@ impl PythonGenerator {
         }
         Ok(())
     }
+
+    /// Emit a match statement as an `if`/`elif` chain, for Python versions
+    /// without structural pattern matching
+    fn emit_match_chain(
+        &mut self,
+        scrutinee: &MirExpr,
+        arms: &[MirMatchArm],
+        output: &mut SourceChunk,
+    ) -> Result<()> {
+        let scrut_code = self.emit_value_expr(scrutinee, output)?;
+        // Arm tests refer to the subject repeatedly, so evaluate it only once
+        let subject = if matches!(block_value(scrutinee), Some(MirExpr::Variable { .. })) {
+            scrut_code
+        } else {
+            self.emit_line(output, &format!("_match_subject = {}", scrut_code));
+            "_match_subject".to_string()
+        };
+
+        for (i, arm) in arms.iter().enumerate() {
+            let mut bindings = Vec::new();
+            let mut tests = Vec::new();
+            pattern_tests(&arm.pattern, &subject, &mut tests, &mut bindings);
+
+            if let Some(guard) = &arm.guard {
+                let guard_code = self.emit_expr(guard)?.code.trim().to_string();
+                tests.push(if bindings.is_empty() {
+                    guard_code
+                } else {
+                    // The guard sees the arm's bindings through an applied lambda
+                    let (names, values): (Vec<_>, Vec<_>) = bindings.iter().cloned().unzip();
+                    format!(
+                        "(lambda {}: {})({})",
+                        names.join(", "),
+                        guard_code,
+                        values.join(", ")
+                    )
+                });
+            }
+
+            let irrefutable = tests.is_empty();
+            let header = match (i, irrefutable) {
+                (0, true) => "if True:".to_string(),
+                (_, true) => "else:".to_string(),
+                (0, false) => format!("if {}:", tests.join(" and ")),
+                (_, false) => format!("elif {}:", tests.join(" and ")),
+            };
+            self.emit_line(output, &header);
+
+            self.indent_level += 1;
+            for (name, value) in &bindings {
+                self.emit_line(output, &format!("{} = {}", name, value));
+            }
+            self.emit_mir_stmt_list(&arm.body, output)?;
+            self.indent_level -= 1;
+
+            // Arms after a catch-all can never run
+            if irrefutable {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// The `range(...)` call for a range expression; an inclusive range
+    /// stops one past its end
+    fn emit_range(&mut self, start: &MirExpr, end: &MirExpr, inclusive: bool) -> Result<String> {
+        let (start, end) = self.emit_bounds(start, end, inclusive)?;
+        Ok(format!("range({}, {})", start, end))
+    }
+
+    /// Python's exclusive start and end for a range or slice
+    fn emit_bounds(
+        &mut self,
+        start: &MirExpr,
+        end: &MirExpr,
+        inclusive: bool,
+    ) -> Result<(String, String)> {
+        let start = self.emit_expr(start)?.code.trim().to_string();
+        let end = match end {
+            MirExpr::Literal {
+                value: Literal::Int(n),
+                ..
+            } if inclusive => (i128::from(*n) + 1).to_string(),
+            _ => {
+                let end = self.emit_expr(end)?.code.trim().to_string();
+                if inclusive {
+                    format!("{} + 1", end)
+                } else {
+                    end
+                }
+            }
+        };
+        Ok((start, end))
+    }
+
+    /// The Python iterable a for-loop runs over; a range is iterated
+    /// lazily instead of being built as a list first
+    fn emit_iterable(&mut self, iter: &MirExpr) -> Result<String> {
+        match iter {
+            MirExpr::Range {
+                start,
+                end,
+                inclusive,
+                ..
+            } => self.emit_range(start, end, *inclusive),
+            _ => Ok(self.emit_expr(iter)?.code.trim().to_string()),
+        }
+    }
+}
+
+impl LineEmitter for PythonGenerator {
+    fn indent_level(&self) -> usize {
+        self.indent_level
+    }
 }
 
 impl CodeEmitter for PythonGenerator {
-    fn emit_program(&mut self, program: &MirProgram) -> Result<BytecodeChunk> {
-        let mut output = BytecodeChunk::new();
+    type Chunk = SourceChunk;
+
+    fn emit_program(&mut self, program: &MirProgram) -> Result<SourceChunk> {
+        let mut output = SourceChunk::new();
 
         // Add Python header comment
         output.add_line("# Generated by Nevermind compiler");
 
+        self.overloads = collect_overloads(&program.statements);
+        self.defined_functions = program
+            .statements
+            .iter()
+            .filter_map(|stmt| match stmt {
+                MirStmt::Function { name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        // Builtins with no Python counterpart are defined up front, but only
+        // when the program calls them and does not define its own.
+        let helpers: Vec<_> = PRELUDE
+            .iter()
+            .filter(|(name, _, _)| needs_prelude_function(&program.statements, name))
+            .collect();
+        let mut modules: Vec<&str> = helpers
+            .iter()
+            .flat_map(|(_, modules, _)| modules.iter().copied())
+            .collect();
+        // println still needs Python's own print when the program
+        // defines a function called print
+        if self.defined_functions.contains("print")
+            && !self.defined_functions.contains("println")
+            && stmts_mention(&program.statements, "println")
+        {
+            modules.push("builtins");
+        }
+        modules.sort_unstable();
+        modules.dedup();
+        for module in &modules {
+            output.add_line(&format!("import {}", module));
+        }
+        if !modules.is_empty() {
+            output.add_line("");
+        }
+        for (_, _, definition) in helpers {
+            for line in definition.lines() {
+                output.add_line(line);
+            }
+            output.add_line("");
+        }
+
         // Emit import statements first (Python convention: imports at top of file).
         for stmt in &program.statements {
             if matches!(stmt, MirStmt::Import { .. }) {
@@ -423,26 +1121,46 @@ impl CodeEmitter for PythonGenerator {
             }
         }
 
-        // Auto-call main() if it exists
-        let has_main = program
-            .statements
-            .iter()
-            .any(|s| matches!(s, MirStmt::Function { name, .. } if name == "main"));
-        if has_main {
+        // Auto-call main() if it exists and takes no arguments; an async
+        // main is run to completion on an event loop
+        let main = program.statements.iter().find_map(|s| match s {
+            MirStmt::Function {
+                is_async,
+                name,
+                params,
+                ..
+            } if name == "main" && params.is_empty() => Some(*is_async),
+            _ => None,
+        });
+        if let Some(is_async) = main {
             output.add_line("");
-            output.add_line("if __name__ == \"__main__\":");
-            output.add_line("    main()");
+            self.emit_line(&mut output, "if __name__ == \"__main__\":");
+            self.indent_level += 1;
+            if is_async {
+                self.emit_line(&mut output, "import asyncio");
+                self.emit_line(&mut output, "asyncio.run(main())");
+            } else {
+                self.emit_line(&mut output, "main()");
+            }
+            self.indent_level -= 1;
         }
 
         Ok(output)
     }
 
-    fn emit_function(&mut self, func: &MirFunction) -> Result<BytecodeChunk> {
-        let mut output = BytecodeChunk::new();
+    fn emit_function(&mut self, func: &MirFunction) -> Result<SourceChunk> {
+        let mut output = SourceChunk::new();
 
-        let params: Vec<String> = func.params.iter().map(|p| p.name.clone()).collect();
+        let params: Vec<String> = func
+            .params
+            .iter()
+            .map(|p| sanitize_ident(&p.name).into_owned())
+            .collect();
         let params_str = params.join(", ");
-        output.add_line(&format!("def {}({}):", func.name, params_str));
+        self.emit_line(
+            &mut output,
+            &format!("def {}({}):", sanitize_ident(&func.name), params_str),
+        );
 
         self.indent_level += 1;
 
@@ -451,10 +1169,10 @@ impl CodeEmitter for PythonGenerator {
         }
 
         if let Some(expr) = &func.body.expr {
-            let chunk = self.emit_expr(expr)?;
-            self.output_line(&mut output, &format!("return {}", chunk.code.trim()));
+            let code = self.emit_value_expr(expr, &mut output)?;
+            self.emit_line(&mut output, &format!("return {}", code));
         } else if func.body.statements.is_empty() {
-            self.output_line(&mut output, "pass");
+            self.emit_line(&mut output, "pass");
         }
 
         self.indent_level -= 1;
@@ -463,8 +1181,8 @@ impl CodeEmitter for PythonGenerator {
         Ok(output)
     }
 
-    fn emit_expr(&mut self, expr: &MirExpr) -> Result<BytecodeChunk> {
-        let mut output = BytecodeChunk::new();
+    fn emit_expr(&mut self, expr: &MirExpr) -> Result<SourceChunk> {
+        let mut output = SourceChunk::new();
 
         match expr {
             MirExpr::Literal { value, .. } => {
@@ -472,19 +1190,46 @@ impl CodeEmitter for PythonGenerator {
             }
 
             MirExpr::Variable { name, .. } => {
-                output.add_line(name);
+                output.add_line(&sanitize_ident(name));
             }
 
+            // Python's `and`/`or` only evaluate the right side when the left
+            // doesn't settle the result; `&`/`|` would always evaluate both
             MirExpr::Binary {
-                op, left, right, ..
+                op: op @ (BinOp::And | BinOp::Or),
+                left,
+                right,
+                ..
             } => {
+                let keyword = if *op == BinOp::And { "and" } else { "or" };
                 let left_chunk = self.emit_expr(left)?;
                 let right_chunk = self.emit_expr(right)?;
-                let py_op = self.map_binop(*op);
 
                 output.add_line(&format!(
                     "({} {} {})",
                     left_chunk.code.trim(),
+                    keyword,
+                    right_chunk.code.trim()
+                ));
+            }
+
+            MirExpr::Binary {
+                op, left, right, ..
+            } => {
+                let left_chunk = self.emit_expr(left)?;
+                let right_chunk = self.emit_expr(right)?;
+                let py_op = self.typed_binop(*op, left.get_type(), right.get_type());
+
+                // Python's `**` binds tighter than a unary minus on its left,
+                // so `(-x) ** 2` needs the parentheses kept
+                let mut left_code = left_chunk.code.trim().to_string();
+                if *op == BinOp::Pow && is_negated(left) {
+                    left_code = format!("({})", left_code);
+                }
+
+                output.add_line(&format!(
+                    "({} {} {})",
+                    left_code,
                     py_op,
                     right_chunk.code.trim()
                 ));
@@ -497,8 +1242,23 @@ impl CodeEmitter for PythonGenerator {
                 output.add_line(&format!("{}{}", py_op, operand_chunk.code.trim()));
             }
 
+            MirExpr::Await { expr, .. } => {
+                let chunk = self.emit_expr(expr)?;
+                // `await` takes a primary, so a negation needs brackets
+                let operand = if is_negated(expr) {
+                    format!("({})", chunk.code.trim())
+                } else {
+                    chunk.code.trim().to_string()
+                };
+                output.add_line(&format!("(await {})", operand));
+            }
+
             MirExpr::Call { callee, args, .. } => {
                 let callee_chunk = self.emit_expr(callee)?;
+                let mut callee_code = callee_chunk.code.trim().to_string();
+                if matches!(callee.as_ref(), MirExpr::Lambda { .. }) {
+                    callee_code = format!("({})", callee_code);
+                }
                 let mut arg_strings = Vec::new();
 
                 for arg in args {
@@ -506,123 +1266,79 @@ impl CodeEmitter for PythonGenerator {
                     arg_strings.push(chunk.code.trim().to_string());
                 }
 
-                output.add_line(&format!(
-                    "{}({})",
-                    callee_chunk.code.trim(),
-                    arg_strings.join(", ")
-                ));
+                // Python's print always ends the line, which is what println
+                // means; print itself leaves the line open
+                match callee.as_ref() {
+                    MirExpr::Variable { name, .. } if self.defined_functions.contains(name) => {}
+                    MirExpr::Variable { name, .. } if name == "print" => {
+                        arg_strings.push("end=\"\"".to_string());
+                    }
+                    MirExpr::Variable { name, .. } if name == "println" => {
+                        callee_code = if self.defined_functions.contains("print") {
+                            "builtins.print".to_string()
+                        } else {
+                            "print".to_string()
+                        };
+                    }
+                    _ => {}
+                }
+
+                output.add_line(&format!("{}({})", callee_code, arg_strings.join(", ")));
+            }
+
+            // Bindings feeding a value, such as a match temporary, become
+            // applied lambdas so the block stays a single expression
+            MirExpr::Block {
+                statements,
+                expr: Some(value),
+                ..
+            } if statements
+                .iter()
+                .all(|stmt| matches!(stmt, MirExprStmt::Let { .. })) =>
+            {
+                let mut code = self.emit_expr(value)?.code.trim().to_string();
+                for stmt in statements.iter().rev() {
+                    if let MirExprStmt::Let { name, value, .. } = stmt {
+                        let value_chunk = self.emit_expr(value)?;
+                        code = format!(
+                            "(lambda {}: {})({})",
+                            sanitize_ident(name),
+                            code,
+                            value_chunk.code.trim()
+                        );
+                    }
+                }
+                output.add_line(&code);
             }
 
             MirExpr::Block {
                 statements, expr, ..
             } => {
                 for stmt in statements {
-                    match stmt {
-                        MirExprStmt::Let { name, value, .. } => {
-                            let value_chunk = self.emit_expr(value)?;
-                            output.add_line(&format!("{} = {}", name, value_chunk.code.trim()));
-                        }
-                        MirExprStmt::Assign { target, value, .. } => {
-                            let chunk = self.emit_expr(value)?;
-                            output.add_line(&format!("{} = {}", target, chunk.code.trim()));
-                        }
-                        MirExprStmt::IndexAssign {
-                            array,
-                            index,
-                            value,
-                            ..
-                        } => {
-                            let index_chunk = self.emit_expr(index)?;
-                            let value_chunk = self.emit_expr(value)?;
-                            output.add_line(&format!(
-                                "{}[{}] = {}",
-                                array,
-                                index_chunk.code.trim(),
-                                value_chunk.code.trim()
-                            ));
-                        }
-                        MirExprStmt::Expr(e) => {
-                            let chunk = self.emit_expr(e)?;
-                            let code = chunk.code.trim();
-                            if !code.is_empty() {
-                                output.code.push_str(code);
-                                output.code.push('\n');
-                            }
-                        }
-                        MirExprStmt::Return { value, .. } => {
-                            if let Some(v) = value {
-                                let chunk = self.emit_expr(v)?;
-                                output.add_line(&format!("return {}", chunk.code.trim()));
-                            } else {
-                                output.add_line("return");
-                            }
-                        }
-                        MirExprStmt::If {
-                            condition,
-                            then_body,
-                            else_body,
-                            ..
-                        } => {
-                            let cond_chunk = self.emit_expr(condition)?;
-                            output.add_line(&format!(
-                                "{}if {}:",
-                                self.indent(),
-                                cond_chunk.code.trim()
-                            ));
-                            self.indent_level += 1;
-                            self.emit_expr_stmt_list(then_body, &mut output)?;
-                            self.indent_level -= 1;
-                            if let Some(else_stmts) = else_body {
-                                output.add_line(&format!("{}else:", self.indent()));
-                                self.indent_level += 1;
-                                self.emit_expr_stmt_list(else_stmts, &mut output)?;
-                                self.indent_level -= 1;
-                            }
-                        }
-                        MirExprStmt::While {
-                            condition, body, ..
-                        } => {
-                            let cond_chunk = self.emit_expr(condition)?;
-                            output.add_line(&format!(
-                                "{}while {}:",
-                                self.indent(),
-                                cond_chunk.code.trim()
-                            ));
-                            self.indent_level += 1;
-                            self.emit_expr_stmt_list(body, &mut output)?;
-                            self.indent_level -= 1;
-                        }
-                        MirExprStmt::For {
-                            variable,
-                            iter,
-                            body,
-                            ..
-                        } => {
-                            let iter_chunk = self.emit_expr(iter)?;
-                            output.add_line(&format!(
-                                "{}for {} in {}:",
-                                self.indent(),
-                                variable,
-                                iter_chunk.code.trim()
-                            ));
-                            self.indent_level += 1;
-                            self.emit_expr_stmt_list(body, &mut output)?;
-                            self.indent_level -= 1;
-                        }
-                        MirExprStmt::Break { .. } => {
-                            output.add_line(&format!("{}break", self.indent()));
-                        }
-                        MirExprStmt::Continue { .. } => {
-                            output.add_line(&format!("{}continue", self.indent()));
-                        }
-                    }
+                    self.emit_expr_stmt(stmt, &mut output)?;
                 }
-
-                // Final expression
                 if let Some(e) = expr {
                     let chunk = self.emit_expr(e)?;
-                    output.add_line(chunk.code.trim());
+                    self.emit_line(&mut output, chunk.code.trim());
+                }
+            }
+
+            MirExpr::StringInterp { parts, .. } => {
+                let mut pieces = Vec::new();
+                for part in parts {
+                    pieces.push(match part {
+                        MirStringPart::Literal(text) => (false, text.clone()),
+                        MirStringPart::Expr(expr) => {
+                            let code = self.emit_expr(expr)?.code.trim().to_string();
+                            if expr.is_variable() {
+                                (true, code)
+                            } else {
+                                (true, parenthesize(&code))
+                            }
+                        }
+                    });
                 }
+                output.add_line(&f_string(&pieces));
             }
 
             MirExpr::List { elements, .. } => {
@@ -634,6 +1350,49 @@ impl CodeEmitter for PythonGenerator {
                 output.add_line(&format!("[{}]", element_strings.join(", ")));
             }
 
+            MirExpr::Tuple { elements, .. } => {
+                let mut element_strings = Vec::new();
+                for elem in elements {
+                    let chunk = self.emit_expr(elem)?;
+                    element_strings.push(chunk.code.trim().to_string());
+                }
+                // A one-element tuple needs its trailing comma in Python
+                if element_strings.len() == 1 {
+                    output.add_line(&format!("({},)", element_strings[0]));
+                } else {
+                    output.add_line(&format!("({})", element_strings.join(", ")));
+                }
+            }
+
+            MirExpr::Range {
+                start,
+                end,
+                inclusive,
+                ..
+            } => {
+                let range = self.emit_range(start, end, *inclusive)?;
+                output.add_line(&format!("list({})", range));
+            }
+
+            MirExpr::FieldAccess { object, field, .. } => {
+                let object_chunk = self.emit_expr(object)?;
+                output.add_line(&format!("{}.{}", object_chunk.code.trim(), field));
+            }
+
+            MirExpr::Map { entries, .. } => {
+                let mut entry_strings = Vec::new();
+                for (key, value) in entries {
+                    let key_chunk = self.emit_expr(key)?;
+                    let value_chunk = self.emit_expr(value)?;
+                    entry_strings.push(format!(
+                        "{}: {}",
+                        key_chunk.code.trim(),
+                        value_chunk.code.trim()
+                    ));
+                }
+                output.add_line(&format!("{{{}}}", entry_strings.join(", ")));
+            }
+
             MirExpr::If {
                 condition,
                 then_branch,
@@ -663,8 +1422,24 @@ impl CodeEmitter for PythonGenerator {
                 ));
             }
 
+            MirExpr::Slice {
+                array,
+                start,
+                end,
+                inclusive,
+                ..
+            } => {
+                let array_chunk = self.emit_expr(array)?;
+                let (start, end) = self.emit_bounds(start, end, *inclusive)?;
+                output.add_line(&format!("{}[{}:{}]", array_chunk.code.trim(), start, end));
+            }
+
             MirExpr::Lambda { params, body, .. } => {
                 let body_chunk = self.emit_expr(body)?;
+                let params: Vec<String> = params
+                    .iter()
+                    .map(|param| sanitize_ident(param).into_owned())
+                    .collect();
                 let params_str = params.join(", ");
                 output.add_line(&format!(
                     "lambda {}: {}",
@@ -688,34 +1463,633 @@ impl Default for PythonGenerator {
 fn format_mir_pattern(pattern: &nevermind_mir::MirPattern) -> String {
     match pattern {
         nevermind_mir::MirPattern::Wildcard { .. } => "_".to_string(),
-        nevermind_mir::MirPattern::Variable { name, .. } => name.clone(),
+        nevermind_mir::MirPattern::Variable { name, .. } => sanitize_ident(name).into_owned(),
         nevermind_mir::MirPattern::Literal { value, .. } => match value {
             Literal::Int(v) => v.to_string(),
-            Literal::Float(v) => v.to_string(),
-            Literal::String(v) => format!("\"{}\"", v),
+            Literal::Float(v) => format!("{:?}", v),
+            Literal::String(v) => escape_python_string(v),
+            Literal::Char(c) => char_literal(*c),
             Literal::Bool(v) => if *v { "True" } else { "False" }.to_string(),
             Literal::Null => "None".to_string(),
         },
+        nevermind_mir::MirPattern::Tuple { patterns, .. } => {
+            let parts: Vec<String> = patterns.iter().map(format_mir_pattern).collect();
+            match parts.as_slice() {
+                [only] => format!("({},)", only),
+                _ => format!("({})", parts.join(", ")),
+            }
+        }
         nevermind_mir::MirPattern::List { patterns, .. } => {
             let parts: Vec<String> = patterns.iter().map(format_mir_pattern).collect();
             format!("[{}]", parts.join(", "))
         }
+        nevermind_mir::MirPattern::Constructor { name, args, .. } => {
+            if name == "None" && args.is_empty() {
+                return name.clone();
+            }
+            // A bare name would be a capture pattern, so always use a class pattern
+            let parts: Vec<String> = args.iter().map(format_mir_pattern).collect();
+            format!("{}({})", name, parts.join(", "))
+        }
+    }
+}
+
+/// Format the target of a Python for-loop, unpacking a tuple pattern
+/// without the parentheses: `for x, y in pairs:`
+fn format_for_target(pattern: &nevermind_mir::MirPattern) -> String {
+    match pattern {
+        nevermind_mir::MirPattern::Tuple { patterns, .. } if patterns.len() > 1 => patterns
+            .iter()
+            .map(format_mir_pattern)
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => format_mir_pattern(pattern),
+    }
+}
+
+/// Whether a value-position expression has statements that don't fit in a
+/// single Python expression. Blocks of bindings alone become applied lambdas.
+fn runs_statements(expr: &MirExpr) -> bool {
+    match expr {
+        MirExpr::Block {
+            statements, expr, ..
+        } => {
+            statements
+                .iter()
+                .any(|stmt| !matches!(stmt, MirExprStmt::Let { .. }))
+                || expr.as_deref().is_some_and(contains_statements)
+        }
+        MirExpr::If {
+            then_branch,
+            else_branch,
+            ..
+        } => contains_statements(then_branch) || contains_statements(else_branch),
+        _ => false,
+    }
+}
+
+/// Whether `expr` runs statements itself or in any operand that is
+/// evaluated along with it
+fn contains_statements(expr: &MirExpr) -> bool {
+    runs_statements(expr) || has_statement_operands(expr)
+}
+
+/// Whether any operand of `expr` that is evaluated along with it runs
+/// statements, which [`PythonGenerator::hoist_statements`] then moves out
+fn has_statement_operands(expr: &MirExpr) -> bool {
+    match expr {
+        MirExpr::Binary { left, right, .. }
+        | MirExpr::Index {
+            array: left,
+            index: right,
+            ..
+        }
+        | MirExpr::Range {
+            start: left,
+            end: right,
+            ..
+        } => contains_statements(left) || contains_statements(right),
+        MirExpr::Unary { operand: inner, .. }
+        | MirExpr::Await { expr: inner, .. }
+        | MirExpr::FieldAccess { object: inner, .. } => contains_statements(inner),
+        MirExpr::Call { callee, args, .. } => {
+            contains_statements(callee) || args.iter().any(contains_statements)
+        }
+        MirExpr::List { elements, .. } | MirExpr::Tuple { elements, .. } => {
+            elements.iter().any(contains_statements)
+        }
+        MirExpr::Map { entries, .. } => entries
+            .iter()
+            .any(|(key, value)| contains_statements(key) || contains_statements(value)),
+        MirExpr::StringInterp { parts, .. } => parts.iter().any(|part| match part {
+            MirStringPart::Expr(value) => contains_statements(value),
+            MirStringPart::Literal(_) => false,
+        }),
+        MirExpr::Slice {
+            array, start, end, ..
+        } => contains_statements(array) || contains_statements(start) || contains_statements(end),
+        MirExpr::If { condition, .. } => contains_statements(condition),
+        MirExpr::Literal { .. }
+        | MirExpr::Variable { .. }
+        | MirExpr::Block { .. }
+        | MirExpr::Lambda { .. } => false,
+    }
+}
+
+/// Whether `code` names a temporary from
+/// [`PythonGenerator::fresh_branch_value`]
+fn is_branch_value(code: &str) -> bool {
+    code.strip_prefix(BRANCH_VALUE)
+        .and_then(|rest| rest.strip_prefix('_'))
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// The expression a value-position block evaluates to, looking through the
+/// statements `emit_value_expr` hoists out of it
+fn block_value(expr: &MirExpr) -> Option<&MirExpr> {
+    match expr {
+        MirExpr::Block { expr, .. } => expr.as_deref().and_then(block_value),
+        _ => Some(expr),
+    }
+}
+
+/// Collect the conditions under which `pattern` matches the Python value
+/// `subject`, and the variables it binds. No tests means it always matches.
+fn pattern_tests(
+    pattern: &nevermind_mir::MirPattern,
+    subject: &str,
+    tests: &mut Vec<String>,
+    bindings: &mut Vec<(String, String)>,
+) {
+    match pattern {
+        nevermind_mir::MirPattern::Wildcard { .. } => {}
+        nevermind_mir::MirPattern::Variable { name, .. } => {
+            bindings.push((sanitize_ident(name).into_owned(), subject.to_string()));
+        }
+        nevermind_mir::MirPattern::Literal {
+            value: Literal::Null,
+            ..
+        } => tests.push(format!("{} is None", subject)),
+        nevermind_mir::MirPattern::Literal { .. } => {
+            tests.push(format!("{} == {}", subject, format_mir_pattern(pattern)));
+        }
+        nevermind_mir::MirPattern::Tuple { patterns, .. } => {
+            tests.push(format!("isinstance({}, tuple)", subject));
+            tests.push(format!("len({}) == {}", subject, patterns.len()));
+            for (i, element) in patterns.iter().enumerate() {
+                pattern_tests(element, &format!("{}[{}]", subject, i), tests, bindings);
+            }
+        }
+        nevermind_mir::MirPattern::List { patterns, .. } => {
+            tests.push(format!("isinstance({}, list)", subject));
+            tests.push(format!("len({}) == {}", subject, patterns.len()));
+            for (i, element) in patterns.iter().enumerate() {
+                pattern_tests(element, &format!("{}[{}]", subject, i), tests, bindings);
+            }
+        }
         nevermind_mir::MirPattern::Constructor { name, args, .. } => {
             if args.is_empty() {
-                name.clone()
-            } else {
-                let parts: Vec<String> = args.iter().map(format_mir_pattern).collect();
-                format!("{}({})", name, parts.join(", "))
+                let op = if name == "None" { "is" } else { "==" };
+                tests.push(format!("{} {} {}", subject, op, name));
+                return;
+            }
+            tests.push(format!("isinstance({}, {})", subject, name));
+            for (i, arg) in args.iter().enumerate() {
+                pattern_tests(arg, &format!("{}[{}]", subject, i), tests, bindings);
             }
         }
     }
 }
 
-/// Escape a string literal for Python
+/// Render a string as a double-quoted Python string literal
+fn escape_python_string(s: &str) -> String {
+    format!("\"{}\"", escape_string(s))
+}
+
+/// Escape the contents of a Python string literal. Non-ASCII characters are
+/// kept as is, since generated files are UTF-8 like Python source.
 fn escape_string(s: &str) -> String {
-    s.replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-        .replace('\r', "\\r")
-        .replace('\t', "\\t")
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\x{:02x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Render text pieces and expression pieces (flagged `true`) as a Python
+/// f-string. Before Python 3.12 an embedded expression may not contain the
+/// string's own quote character, so single quotes are used when one does.
+fn f_string(pieces: &[(bool, String)]) -> String {
+    let quote = if pieces
+        .iter()
+        .any(|(is_expr, code)| *is_expr && code.contains('"'))
+    {
+        '\''
+    } else {
+        '"'
+    };
+
+    let mut body = String::new();
+    for (is_expr, code) in pieces {
+        if *is_expr {
+            body.push_str(&format!("{{{}}}", code));
+        } else {
+            let text = escape_string(code).replace('{', "{{").replace('}', "}}");
+            if quote == '\'' {
+                body.push_str(&text.replace('\'', "\\'"));
+            } else {
+                body.push_str(&text);
+            }
+        }
+    }
+    format!("f{}{}{}", quote, body, quote)
+}
+
+/// Wrap `code` in parentheses unless one pair already encloses all of it
+fn parenthesize(code: &str) -> String {
+    let mut depth = 0;
+    let mut in_string = None;
+    let mut escaped = false;
+    for (i, c) in code.char_indices() {
+        if let Some(quote) = in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                _ if c == quote => in_string = None,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => in_string = Some(c),
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    if i == 0 || !code.starts_with('(') || i + 1 != code.len() {
+                        break;
+                    }
+                    return code.to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+    format!("({})", code)
+}
+
+/// Format a character as a one-character Python string literal
+fn char_literal(c: char) -> String {
+    match c {
+        '\'' => "'\\''".to_string(),
+        '"' => "'\"'".to_string(),
+        _ => format!("'{}'", escape_string(&c.to_string())),
+    }
+}
+
+/// The prefix of the temporaries statement-form conditionals store their
+/// values in
+const BRANCH_VALUE: &str = "_branch_value";
+
+/// Words Python reserves, which can't be used as names
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// The Python name for a Nevermind identifier: the identifier itself, or with
+/// an underscore appended when it is a Python keyword
+///
+/// Every place a name is declared or used goes through this, so a renamed
+/// identifier stays consistent across the generated module.
+pub fn sanitize_ident(name: &str) -> Cow<'_, str> {
+    if PYTHON_KEYWORDS.contains(&name) {
+        Cow::Owned(format!("{}_", name))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// Apply [`sanitize_ident`] to the identifiers inside the `{...}`
+/// expressions of an interpolated string, leaving the text around them,
+/// quoted strings and attribute names as they are
+fn sanitize_interpolations(text: &str) -> Cow<'_, str> {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut after_dot = false;
+    while let Some((start, c)) = chars.next() {
+        match c {
+            _ if depth == 0 => {
+                if c == '{' {
+                    if chars.peek().is_some_and(|&(_, next)| next == '{') {
+                        output.push(c);
+                        chars.next();
+                    } else {
+                        depth = 1;
+                    }
+                }
+                output.push(c);
+                continue;
+            }
+            _ if quote.is_some() => {
+                if quote == Some(c) {
+                    quote = None;
+                }
+                output.push(c);
+            }
+            '\'' | '"' => {
+                quote = Some(c);
+                output.push(c);
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, next)) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    end = i + next.len_utf8();
+                    chars.next();
+                }
+                let word = &text[start..end];
+                if after_dot {
+                    output.push_str(word);
+                } else {
+                    output.push_str(&sanitize_ident(word));
+                }
+            }
+            _ => {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                output.push(c);
+            }
+        }
+        after_dot = c == '.';
+    }
+    if output == text {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(output)
+    }
+}
+
+/// Python name of the overload of `name` taking `arity` arguments
+fn overload_name(name: &str, arity: usize) -> String {
+    format!("{}__{}", name, arity)
+}
+
+/// Find top-level function names defined with more than one arity, keeping
+/// their arities in definition order.
+fn collect_overloads(statements: &[MirStmt]) -> HashMap<String, Vec<usize>> {
+    let mut arities: HashMap<String, Vec<usize>> = HashMap::new();
+    for stmt in statements {
+        if let MirStmt::Function { name, params, .. } = stmt {
+            let seen = arities.entry(name.clone()).or_default();
+            if !seen.contains(&params.len()) {
+                seen.push(params.len());
+            }
+        }
+    }
+    arities.retain(|_, seen| seen.len() > 1);
+    arities
+}
+
+/// Whether `expr` is emitted with a leading sign or `not`
+fn is_negated(expr: &MirExpr) -> bool {
+    match expr {
+        MirExpr::Unary { .. } => true,
+        MirExpr::Literal {
+            value: Literal::Int(v),
+            ..
+        } => *v < 0,
+        MirExpr::Literal {
+            value: Literal::Float(v),
+            ..
+        } => v.is_sign_negative(),
+        _ => false,
+    }
+}
+
+/// A one-field constructor such as `Some(x)`, read positionally by match
+/// tests and by name in `match` statement class patterns. `None` is Python's.
+macro_rules! variant_class {
+    ($name:literal) => {
+        concat!(
+            "class ",
+            $name,
+            ":\n",
+            "    __match_args__ = (\"value\",)\n\n",
+            "    def __init__(self, value):\n",
+            "        self.value = value\n\n",
+            "    def __getitem__(self, index):\n",
+            "        return (self.value,)[index]\n\n",
+            "    def __eq__(self, other):\n",
+            "        return type(other) is ",
+            $name,
+            " and other.value == self.value\n\n",
+            "    def __repr__(self):\n",
+            "        return \"",
+            $name,
+            "(\" + repr(self.value) + \")\"",
+        )
+    };
+}
+
+/// Builtins defined in Python ahead of the program, as (name, modules the
+/// definition imports, definition). Nevermind's `map` and `filter` take the
+/// list first and return lists, unlike Python's builtins of the same name.
+const PRELUDE: &[(&str, &[&str], &str)] = &[
+    (
+        "product",
+        &["functools", "operator"],
+        "def product(xs):\n    return functools.reduce(operator.mul, xs, 1)",
+    ),
+    ("map", &[], "def map(xs, f):\n    return [f(x) for x in xs]"),
+    (
+        "filter",
+        &[],
+        "def filter(xs, f):\n    return [x for x in xs if f(x)]",
+    ),
+    (
+        "fold",
+        &["functools"],
+        "def fold(xs, initial, f):\n    return functools.reduce(f, xs, initial)",
+    ),
+    ("Some", &[], variant_class!("Some")),
+    ("Ok", &[], variant_class!("Ok")),
+    ("Error", &[], variant_class!("Error")),
+];
+
+/// Whether a builtin helper named `name` must be emitted: the program refers
+/// to it and no top-level function of that name shadows it.
+fn needs_prelude_function(statements: &[MirStmt], name: &str) -> bool {
+    let shadowed = statements.iter().any(|stmt| match stmt {
+        MirStmt::Function { name: defined, .. } | MirStmt::Class { name: defined, .. } => {
+            defined == name
+        }
+        _ => false,
+    });
+    !shadowed && stmts_mention(statements, name)
+}
+
+fn stmts_mention(statements: &[MirStmt], name: &str) -> bool {
+    statements.iter().any(|stmt| match stmt {
+        MirStmt::Function { body, .. } => {
+            expr_stmts_mention(&body.statements, name)
+                || body.expr.as_ref().is_some_and(|e| expr_mentions(e, name))
+        }
+        MirStmt::Class {
+            fields, methods, ..
+        } => {
+            fields
+                .iter()
+                .filter_map(|field| field.default.as_ref())
+                .any(|default| expr_mentions(default, name))
+                || methods.iter().any(|method| {
+                    expr_stmts_mention(&method.body.statements, name)
+                        || method
+                            .body
+                            .expr
+                            .as_ref()
+                            .is_some_and(|e| expr_mentions(e, name))
+                })
+        }
+        MirStmt::Let { value, .. } => expr_mentions(value, name),
+        MirStmt::Expr(expr) => expr_mentions(expr, name),
+        MirStmt::If {
+            condition,
+            then_body,
+            else_body,
+            ..
+        } => {
+            expr_mentions(condition, name)
+                || stmts_mention(then_body, name)
+                || else_body.as_ref().is_some_and(|b| stmts_mention(b, name))
+        }
+        MirStmt::While {
+            condition, body, ..
+        } => expr_mentions(condition, name) || stmts_mention(body, name),
+        MirStmt::For { iter, body, .. } => expr_mentions(iter, name) || stmts_mention(body, name),
+        MirStmt::Return { value, .. } => value.as_ref().is_some_and(|e| expr_mentions(e, name)),
+        MirStmt::Try {
+            body,
+            catch_body,
+            finally_body,
+            ..
+        } => {
+            stmts_mention(body, name)
+                || catch_body.as_ref().is_some_and(|b| stmts_mention(b, name))
+                || finally_body
+                    .as_ref()
+                    .is_some_and(|b| stmts_mention(b, name))
+        }
+        MirStmt::Raise { value, .. } => expr_mentions(value, name),
+        MirStmt::Match {
+            scrutinee, arms, ..
+        } => {
+            expr_mentions(scrutinee, name)
+                || arms.iter().any(|arm| {
+                    arm.guard.as_ref().is_some_and(|g| expr_mentions(g, name))
+                        || stmts_mention(&arm.body, name)
+                })
+        }
+        MirStmt::Break { .. } | MirStmt::Continue { .. } | MirStmt::Import { .. } => false,
+    })
+}
+
+fn expr_stmts_mention(statements: &[MirExprStmt], name: &str) -> bool {
+    statements.iter().any(|stmt| match stmt {
+        MirExprStmt::Let { value, .. } | MirExprStmt::Assign { value, .. } => {
+            expr_mentions(value, name)
+        }
+        MirExprStmt::IndexAssign { index, value, .. } => {
+            expr_mentions(index, name) || expr_mentions(value, name)
+        }
+        MirExprStmt::FieldAssign { object, value, .. } => {
+            expr_mentions(object, name) || expr_mentions(value, name)
+        }
+        MirExprStmt::Expr(expr) => expr_mentions(expr, name),
+        MirExprStmt::Return { value, .. } => value.as_ref().is_some_and(|e| expr_mentions(e, name)),
+        MirExprStmt::If {
+            condition,
+            then_body,
+            else_body,
+            ..
+        } => {
+            expr_mentions(condition, name)
+                || expr_stmts_mention(then_body, name)
+                || else_body
+                    .as_ref()
+                    .is_some_and(|b| expr_stmts_mention(b, name))
+        }
+        MirExprStmt::While {
+            condition, body, ..
+        } => expr_mentions(condition, name) || expr_stmts_mention(body, name),
+        MirExprStmt::For { iter, body, .. } => {
+            expr_mentions(iter, name) || expr_stmts_mention(body, name)
+        }
+        MirExprStmt::Try {
+            body,
+            catch_body,
+            finally_body,
+            ..
+        } => {
+            expr_stmts_mention(body, name)
+                || catch_body
+                    .as_ref()
+                    .is_some_and(|b| expr_stmts_mention(b, name))
+                || finally_body
+                    .as_ref()
+                    .is_some_and(|b| expr_stmts_mention(b, name))
+        }
+        MirExprStmt::Raise { value, .. } => expr_mentions(value, name),
+        MirExprStmt::Break { .. } | MirExprStmt::Continue { .. } => false,
+    })
+}
+
+fn expr_mentions(expr: &MirExpr, name: &str) -> bool {
+    match expr {
+        MirExpr::Literal { .. } => false,
+        MirExpr::Variable { name: var, .. } => var == name,
+        MirExpr::Binary { left, right, .. } => {
+            expr_mentions(left, name) || expr_mentions(right, name)
+        }
+        MirExpr::Unary { operand, .. } | MirExpr::Await { expr: operand, .. } => {
+            expr_mentions(operand, name)
+        }
+        MirExpr::Call { callee, args, .. } => {
+            expr_mentions(callee, name) || args.iter().any(|arg| expr_mentions(arg, name))
+        }
+        MirExpr::Block {
+            statements, expr, ..
+        } => {
+            expr_stmts_mention(statements, name)
+                || expr.as_ref().is_some_and(|e| expr_mentions(e, name))
+        }
+        MirExpr::StringInterp { parts, .. } => parts.iter().any(|part| match part {
+            MirStringPart::Literal(_) => false,
+            MirStringPart::Expr(e) => expr_mentions(e, name),
+        }),
+        MirExpr::List { elements, .. } | MirExpr::Tuple { elements, .. } => {
+            elements.iter().any(|e| expr_mentions(e, name))
+        }
+        MirExpr::FieldAccess { object, .. } => expr_mentions(object, name),
+        MirExpr::Map { entries, .. } => entries
+            .iter()
+            .any(|(k, v)| expr_mentions(k, name) || expr_mentions(v, name)),
+        MirExpr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            expr_mentions(condition, name)
+                || expr_mentions(then_branch, name)
+                || expr_mentions(else_branch, name)
+        }
+        MirExpr::Index { array, index, .. } => {
+            expr_mentions(array, name) || expr_mentions(index, name)
+        }
+        MirExpr::Range { start, end, .. } => expr_mentions(start, name) || expr_mentions(end, name),
+        MirExpr::Slice {
+            array, start, end, ..
+        } => expr_mentions(array, name) || expr_mentions(start, name) || expr_mentions(end, name),
+        MirExpr::Lambda { params, body, .. } => {
+            !params.iter().any(|p| p == name) && expr_mentions(body, name)
+        }
+    }
 }
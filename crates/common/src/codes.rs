@@ -0,0 +1,409 @@
+//! Diagnostic codes and their long-form explanations
+//!
+//! A diagnostic's code is shown in its headline, as in `error[E0001]`, and
+//! `nevermind explain <code>` prints the explanation registered here.
+
+/// A diagnostic code with the text that explains it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode {
+    /// The code itself, such as `E0001`
+    pub code: &'static str,
+
+    /// A one-line summary of the problem
+    pub title: &'static str,
+
+    /// A longer description, with an example and how to fix it
+    pub explanation: &'static str,
+}
+
+/// Look up a code, ignoring case
+pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+    ERROR_CODES
+        .iter()
+        .find(|entry| entry.code.eq_ignore_ascii_case(code))
+}
+
+/// Every code a diagnostic can carry; add new codes at the end
+pub const ERROR_CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "E0001",
+        title: "undefined variable or function",
+        explanation: "\
+A name was used that isn't defined in any enclosing scope.
+
+    let total = count + 1    # `count` was never defined
+
+Check the spelling, or define the name before its use:
+
+    let count = 0
+    let total = count + 1
+",
+    },
+    ErrorCode {
+        code: "E0002",
+        title: "missing export from an imported module",
+        explanation: "\
+An import names something the module doesn't export.
+
+    from \"shapes\" import area    # shapes.nm has no `export fn area`
+
+Mark the definition as exported in the module, or import a name it does
+export:
+
+    export fn area(w, h) do w * h end
+",
+    },
+    ErrorCode {
+        code: "E0003",
+        title: "duplicate definition",
+        explanation: "\
+The same name was defined twice in one scope.
+
+    let limit = 10
+    let limit = 20
+
+Give the second binding a different name, or use `var` and assign to it:
+
+    var limit = 10
+    limit = 20
+",
+    },
+    ErrorCode {
+        code: "E0004",
+        title: "return outside of a function",
+        explanation: "\
+A `return` statement appeared at the top level of a program, where there is
+no function to return from.
+
+    return 1
+
+Move the statement into a function body, or drop the `return`.
+",
+    },
+    ErrorCode {
+        code: "E0005",
+        title: "break or continue outside of a loop",
+        explanation: "\
+`break` and `continue` only make sense inside a `while` or `for` loop.
+
+    if done do
+      break
+    end
+
+Restructure the code so the statement sits inside the loop it controls:
+
+    while true do
+      if done do
+        break
+      end
+    end
+",
+    },
+    ErrorCode {
+        code: "E0006",
+        title: "module failed to load",
+        explanation: "\
+A local module named by an import exists but couldn't be read, lexed or
+parsed.
+
+    use \"broken\"    # broken.nm has a syntax error
+
+Fix the problem reported for the module itself, then compile again.
+",
+    },
+    ErrorCode {
+        code: "E0007",
+        title: "wrong number of arguments",
+        explanation: "\
+A function was called with more or fewer arguments than it declares.
+
+    fn add(a, b) do a + b end
+    add(1)
+
+Pass one argument per parameter, or give the missing parameters defaults:
+
+    fn add(a, b = 0) do a + b end
+",
+    },
+    ErrorCode {
+        code: "E0008",
+        title: "unknown class member",
+        explanation: "\
+A member was accessed on a class instance that neither the class nor any
+class it extends declares.
+
+    class Point {
+      let x: Int
+    }
+    let p = Point(1)
+    print(p.y)
+
+Declare the member in the class, or access one that exists.
+",
+    },
+    ErrorCode {
+        code: "E0009",
+        title: "no matching overload",
+        explanation: "\
+A function is defined with several arities, and none of them takes the
+number of arguments given.
+
+    fn area(side) do side * side end
+    fn area(w, h) do w * h end
+    area(1, 2, 3)
+
+Call it with a number of arguments one of the definitions accepts.
+",
+    },
+    ErrorCode {
+        code: "E0010",
+        title: "assignment to an immutable binding",
+        explanation: "\
+A binding declared with `let` can't be assigned to.
+
+    let count = 0
+    count = count + 1
+
+Declare it with `var` if it needs to change:
+
+    var count = 0
+    count = count + 1
+",
+    },
+    ErrorCode {
+        code: "E0011",
+        title: "mismatched types",
+        explanation: "\
+An expression's type doesn't match the type its context requires.
+
+    let n: Int = \"five\"
+
+Convert the value, or change the annotation to the type it really has:
+
+    let n: Int = int(\"5\")
+",
+    },
+    ErrorCode {
+        code: "E0012",
+        title: "return type mismatch",
+        explanation: "\
+A function returns a value of a different type than its signature declares.
+
+    fn half(n: Int) -> Int do
+      return n / 2.0
+    end
+
+Return a value of the declared type, or change the declared return type:
+
+    fn half(n: Int) -> Float do
+      return n / 2.0
+    end
+",
+    },
+    ErrorCode {
+        code: "E0013",
+        title: "missing return value",
+        explanation: "\
+A function that declares a return type has a path that doesn't produce a
+value: a bare `return`, or a branch that falls off the end of the body.
+
+    fn sign(n: Int) -> Int do
+      if n > 0 do
+        return 1
+      end
+    end
+
+Make every path return a value of the declared type:
+
+    fn sign(n: Int) -> Int do
+      if n > 0 do
+        return 1
+      end
+      return 0
+    end
+",
+    },
+    ErrorCode {
+        code: "E0014",
+        title: "called value is not a function",
+        explanation: "\
+A value that isn't a function was called, either directly or as a stage of
+a pipeline.
+
+    let n = 3
+    let xs = [1, 2] |> n
+
+Call a function instead, or remove the call.
+",
+    },
+    ErrorCode {
+        code: "E0015",
+        title: "type cannot be inferred",
+        explanation: "\
+The checker couldn't work out the type of an expression, such as a tuple
+indexed by a value only known at run time.
+
+    let pair = (1, \"one\")
+    let i = 1
+    print(pair[i])
+
+Index tuples with an integer literal, so the element's type is known:
+
+    print(pair[1])
+",
+    },
+    ErrorCode {
+        code: "E0016",
+        title: "infinite type",
+        explanation: "\
+A value would need a type that contains itself, such as a list whose
+elements are that same list.
+
+    fn wrap(x) do [x, [x]] end
+
+Make sure the parts of a value have consistent, finite types.
+",
+    },
+    ErrorCode {
+        code: "E0017",
+        title: "recursive type alias",
+        explanation: "\
+A type alias expands to a type that refers back to the alias.
+
+    type Tree = List[Tree]
+
+Aliases are only names for other types; define the structure without
+referring to the alias itself.
+",
+    },
+    ErrorCode {
+        code: "E0018",
+        title: "non-exhaustive match",
+        explanation: "\
+A match doesn't cover every possible value of its subject.
+
+    match flag {
+      true => 1
+    }
+
+Add arms for the missing cases, or a catch-all `_` arm:
+
+    match flag {
+      true => 1,
+      false => 0
+    }
+",
+    },
+    ErrorCode {
+        code: "E0019",
+        title: "wrong number of type arguments",
+        explanation: "\
+A generic type alias was applied to more or fewer type arguments than it
+declares.
+
+    type Pair[A, B] = (A, B)
+    let p: Pair[Int] = (1, 2)
+
+Supply one type argument per parameter: `Pair[Int, Int]`.
+",
+    },
+    ErrorCode {
+        code: "E0020",
+        title: "index out of range",
+        explanation: "\
+A constant index is past the end of the tuple or string literal it indexes.
+
+    let point = (1, 2)
+    print(point[2])
+
+Tuple and string indices start at 0, so the last element of `point` is
+`point[1]`.
+",
+    },
+    ErrorCode {
+        code: "E0021",
+        title: "argument is not numeric",
+        explanation: "\
+A function that only works on numbers was given a value of another type.
+
+    abs(\"-3\")
+
+Pass an Int or a Float, converting the value first if needed:
+
+    abs(int(\"-3\"))
+",
+    },
+    ErrorCode {
+        code: "E0022",
+        title: "await outside of an async function",
+        explanation: "\
+`await` waits for an asynchronous computation, which only an `async fn` can
+do. It can't appear at the top level, in an ordinary function or in a lambda.
+
+    fn load() do
+      await fetch()
+    end
+
+Declare the enclosing function `async`:
+
+    async fn load() do
+      await fetch()
+    end
+",
+    },
+    ErrorCode {
+        code: "E0023",
+        title: "binding used in its own initializer",
+        explanation: "\
+A `let` or `var` initializer used the name being declared, but there is no
+earlier binding of that name for it to mean. The new binding doesn't exist
+until its initializer has been evaluated.
+
+    let total = total + 1
+
+Initialize the binding from something already defined:
+
+    let count = 0
+    let total = count + 1
+
+A function that calls itself should be declared with `fn` rather than bound
+to a lambda.
+",
+    },
+    ErrorCode {
+        code: "E0024",
+        title: "argument has no length",
+        explanation: "\
+`len` was given a value that has no length. It takes a String, a List, a Map
+or a tuple.
+
+    len(42)
+
+Convert the value first if you meant the number of its digits:
+
+    len(str(42))
+",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_codes_are_unique_and_explained() {
+        let mut seen = HashSet::new();
+        for entry in ERROR_CODES {
+            assert!(seen.insert(entry.code), "duplicate code {}", entry.code);
+            assert!(!entry.title.is_empty());
+            assert!(!entry.explanation.trim().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_lookup_ignores_case() {
+        assert_eq!(lookup("e0001").map(|entry| entry.code), Some("E0001"));
+        assert!(lookup("E9999").is_none());
+    }
+}
@@ -0,0 +1,432 @@
+//! Diagnostics that point at one or more labeled source locations
+
+use std::fmt;
+use std::io::IsTerminal;
+
+use crate::Span;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+impl Severity {
+    /// The ANSI style of the severity's name and underlines
+    fn style(self) -> &'static str {
+        match self {
+            Severity::Error => "1;31",
+            Severity::Warning => "1;33",
+            Severity::Note => "1;36",
+        }
+    }
+}
+
+/// When rendered diagnostics use ANSI colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Color only when standard error, where diagnostics go, is a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color, checking for a terminal when the choice is `Auto`
+    pub fn use_color(self) -> bool {
+        match self {
+            ColorChoice::Auto => std::io::stderr().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "unknown color choice '{}' (expected auto, always or never)",
+                s
+            )),
+        }
+    }
+}
+
+/// Bold blue, for the gutter and location arrows
+const STRUCTURE_STYLE: &str = "1;34";
+
+/// Wrap `text` in an ANSI style when coloring, leaving it as is otherwise
+fn paint(color: bool, style: &str, text: &str) -> String {
+    if color && !text.is_empty() {
+        format!("\x1b[{}m{}\x1b[0m", style, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// A span with an optional message shown beside its underline
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    /// Create a new label
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+/// A diagnostic with a primary location and any number of secondary ones
+///
+/// When rendered against the source, every label is underlined in source
+/// order, with labels on the same line grouped under a single copy of it.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// How serious the diagnostic is
+    pub severity: Severity,
+
+    /// The code `nevermind explain` describes, if the problem has one
+    pub code: Option<&'static str>,
+
+    /// The headline message
+    pub message: String,
+
+    /// The location the diagnostic is about
+    pub primary: Label,
+
+    /// Related locations (e.g. an earlier definition)
+    pub secondary: Vec<Label>,
+
+    /// Notes without a location
+    pub notes: Vec<String>,
+
+    /// Name shown for the source when the spans don't carry a file
+    pub source_name: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic with an unlabeled primary span
+    pub fn new(severity: Severity, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            severity,
+            code: None,
+            message: message.into(),
+            primary: Label::new(span, ""),
+            secondary: Vec::new(),
+            notes: Vec::new(),
+            source_name: None,
+        }
+    }
+
+    /// Create an error diagnostic
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Error, message, span)
+    }
+
+    /// Create a warning diagnostic
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self::new(Severity::Warning, message, span)
+    }
+
+    /// Give the diagnostic a code from the registry in `codes`
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Set the message shown beside the primary underline
+    pub fn with_primary_label(mut self, message: impl Into<String>) -> Self {
+        self.primary.message = message.into();
+        self
+    }
+
+    /// Add a labeled secondary span
+    pub fn with_secondary(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.secondary.push(Label::new(span, message));
+        self
+    }
+
+    /// Add a note without a location
+    pub fn with_note(mut self, message: impl Into<String>) -> Self {
+        self.notes.push(message.into());
+        self
+    }
+
+    /// Name the source the diagnostic's spans point into
+    pub fn with_source_name(mut self, name: impl Into<String>) -> Self {
+        self.source_name = Some(name.into());
+        self
+    }
+
+    /// Render the diagnostic, underlining each label in `source` if given.
+    ///
+    /// A span covering several lines is underlined on its first line, with
+    /// a note giving the line it ends on.
+    pub fn render(&self, source: Option<&str>) -> String {
+        self.render_with_color(source, false)
+    }
+
+    /// Render the diagnostic as `render` does, in ANSI colors if `color`
+    /// is set: the severity in red, yellow or cyan and the message in bold
+    pub fn render_with_color(&self, source: Option<&str>, color: bool) -> String {
+        let severity = match self.code {
+            Some(code) => format!("{}[{}]", self.severity, code),
+            None => self.severity.to_string(),
+        };
+        let mut output = format!(
+            "{}{}\n",
+            paint(color, self.severity.style(), &severity),
+            paint(color, "1", &format!(": {}", self.message))
+        );
+        let arrow = paint(color, STRUCTURE_STYLE, "-->");
+        let bar = paint(color, STRUCTURE_STYLE, "|");
+
+        if is_located(&self.primary.span) {
+            output.push_str(&format!(
+                "  {} {}\n",
+                arrow,
+                self.location(&self.primary.span)
+            ));
+        }
+
+        let lines: Vec<&str> = source.map(|s| s.lines().collect()).unwrap_or_default();
+        let mut labels: Vec<(&Label, bool)> = std::iter::once((&self.primary, true))
+            .chain(self.secondary.iter().map(|label| (label, false)))
+            .filter(|(label, _)| is_located(&label.span))
+            .collect();
+        labels.sort_by_key(|(label, _)| (label.span.start.line, label.span.start.column));
+
+        let (shown, unshown): (Vec<_>, Vec<_>) = labels
+            .into_iter()
+            .partition(|(label, _)| label.span.start.line <= lines.len());
+
+        if !shown.is_empty() {
+            let width = shown
+                .iter()
+                .map(|(label, _)| label.span.start.line.to_string().len())
+                .max()
+                .unwrap_or(1);
+            let gutter = " ".repeat(width);
+            output.push_str(&format!(" {} {}\n", gutter, bar));
+
+            let mut previous_line: Option<usize> = None;
+            for (label, is_primary) in shown {
+                let line_num = label.span.start.line;
+                if previous_line != Some(line_num) {
+                    if previous_line.is_some_and(|prev| line_num > prev + 1) {
+                        output.push_str(&format!(" {}...\n", gutter));
+                    }
+                    output.push_str(&format!(
+                        " {} {} {}\n",
+                        paint(
+                            color,
+                            STRUCTURE_STYLE,
+                            &format!("{:>width$}", line_num, width = width)
+                        ),
+                        bar,
+                        lines[line_num - 1]
+                    ));
+                    previous_line = Some(line_num);
+                }
+
+                let line_len = lines[line_num - 1].chars().count();
+                let marks = underline(&label.span, line_len, is_primary, &label.message);
+                let marks = marks.trim_end();
+                let indent = marks.len() - marks.trim_start().len();
+                let style = if is_primary {
+                    self.severity.style()
+                } else {
+                    STRUCTURE_STYLE
+                };
+                output.push_str(&format!(
+                    " {} {} {}{}\n",
+                    gutter,
+                    bar,
+                    &marks[..indent],
+                    paint(color, style, &marks[indent..])
+                ));
+                if label.span.end.line > line_num {
+                    output.push_str(&format!(
+                        " {} {} ...continues to line {}\n",
+                        gutter, bar, label.span.end.line
+                    ));
+                }
+            }
+        }
+
+        for (label, _) in unshown {
+            if !label.message.is_empty() {
+                output.push_str(&format!(
+                    "  {} {}: {}\n",
+                    arrow,
+                    self.location(&label.span),
+                    label.message
+                ));
+            }
+        }
+
+        for note in &self.notes {
+            output.push_str(&format!("  {} {}\n", paint(color, "1", "note:"), note));
+        }
+
+        output
+    }
+
+    fn location(&self, span: &Span) -> String {
+        let file = span
+            .file()
+            .and_then(|p| p.to_str())
+            .or(self.source_name.as_deref())
+            .unwrap_or("<anon>");
+        format!("{}:{}:{}", file, span.start.line, span.start.column)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(None))
+    }
+}
+
+/// Synthetic nodes carry a dummy span that points nowhere useful
+fn is_located(span: &Span) -> bool {
+    span.start.line > 0 && *span != Span::dummy()
+}
+
+/// Build the marker line for a label: `^` under the primary span, `-` under
+/// secondary ones. Spans running past the line are underlined to its end.
+fn underline(span: &Span, line_len: usize, is_primary: bool, message: &str) -> String {
+    let col_start = span.start.column.max(1);
+    let col_end = if span.start.line == span.end.line {
+        span.end.column
+    } else {
+        line_len + 1
+    };
+    let marker = if is_primary { "^" } else { "-" };
+
+    format!(
+        "{}{} {}",
+        " ".repeat(col_start - 1),
+        marker.repeat(col_end.saturating_sub(col_start).max(1)),
+        message
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SourceLocation;
+
+    fn span(line: usize, start_col: usize, end_col: usize) -> Span {
+        Span::new(
+            SourceLocation::new(None, line, start_col, 0),
+            SourceLocation::new(None, line, end_col, 0),
+        )
+    }
+
+    #[test]
+    fn test_render_without_source() {
+        let diagnostic = Diagnostic::error("something broke", span(2, 3, 5))
+            .with_secondary(span(1, 1, 2), "related")
+            .with_note("try again");
+
+        let rendered = diagnostic.render(None);
+        assert!(rendered.starts_with("error: something broke\n  --> <anon>:2:3\n"));
+        assert!(rendered.contains("  --> <anon>:1:1: related\n"));
+        assert!(rendered.ends_with("  note: try again\n"));
+    }
+
+    #[test]
+    fn test_render_shows_the_code() {
+        let diagnostic = Diagnostic::error("something broke", span(1, 1, 2)).with_code("E0001");
+
+        assert!(diagnostic
+            .render(None)
+            .starts_with("error[E0001]: something broke\n"));
+    }
+
+    #[test]
+    fn test_color_is_only_added_when_asked_for() {
+        let source = "let total = a + b";
+        let diagnostic = Diagnostic::warning("unused", span(1, 5, 10)).with_note("remove it");
+
+        let plain = diagnostic.render_with_color(Some(source), false);
+        assert_eq!(plain, diagnostic.render(Some(source)));
+        assert!(!plain.contains('\x1b'));
+
+        let colored = diagnostic.render_with_color(Some(source), true);
+        assert!(colored.starts_with("\x1b[1;33mwarning\x1b[0m"));
+        assert!(colored.contains("\x1b[1;33m^^^^^\x1b[0m"));
+    }
+
+    #[test]
+    fn test_labels_on_one_line_share_the_source_line() {
+        let source = "let total = a + b";
+        let diagnostic = Diagnostic::error("mismatched operands", span(1, 13, 14))
+            .with_primary_label("this is Int")
+            .with_secondary(span(1, 17, 18), "this is String");
+
+        let rendered = diagnostic.render(Some(source));
+        assert_eq!(rendered.matches("let total = a + b").count(), 1);
+
+        let primary = rendered.find("^ this is Int").unwrap();
+        let secondary = rendered.find("- this is String").unwrap();
+        assert!(primary < secondary);
+    }
+
+    #[test]
+    fn test_caret_underlines_the_span_columns() {
+        let source = "let x = 1\nlet name: Int = \"abc\"\n";
+        let rendered = Diagnostic::error("type mismatch", span(2, 17, 22))
+            .with_source_name("prog.nm")
+            .render(Some(source));
+
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[1], "  --> prog.nm:2:17");
+        assert_eq!(lines[3], " 2 | let name: Int = \"abc\"");
+        assert_eq!(lines[4], "   |                 ^^^^^");
+
+        // The carets sit exactly under the string literal
+        let code_start = lines[3].find("let").unwrap();
+        let caret_start = lines[4].find('^').unwrap();
+        assert_eq!(&lines[3][caret_start..caret_start + 5], "\"abc\"");
+        assert_eq!(caret_start - code_start, 16);
+    }
+
+    #[test]
+    fn test_multi_line_span_notes_where_it_ends() {
+        let source = "fn main() do\n  print 1\nend\n";
+        let span = Span::new(
+            SourceLocation::new(None, 1, 1, 0),
+            SourceLocation::new(None, 3, 4, 0),
+        );
+        let rendered = Diagnostic::error("bad function", span).render(Some(source));
+
+        assert!(rendered.contains(" 1 | fn main() do\n   | ^^^^^^^^^^^^\n"));
+        assert!(rendered.contains("   | ...continues to line 3\n"));
+    }
+}
@@ -1,9 +1,15 @@
 //! Nevermind Common - Shared types and utilities
 
+pub mod codes;
+pub mod diagnostic;
 pub mod error;
+pub mod options;
 pub mod source;
 pub mod span;
 
+pub use codes::ErrorCode;
+pub use diagnostic::{ColorChoice, Diagnostic, Label, Severity};
 pub use error::{Error, ErrorKind, Result};
+pub use options::{CompileOptions, PythonTarget};
 pub use source::SourceLocation;
 pub use span::Span;
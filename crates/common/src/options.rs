@@ -0,0 +1,44 @@
+//! Settings that control a compilation from end to end
+
+/// Python version the generated code must run on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PythonTarget {
+    /// Any Python 3 release the runtime supports
+    #[default]
+    Py3,
+    /// Python 3.10 and later, which have structural `match` statements
+    Py310,
+}
+
+impl std::str::FromStr for PythonTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "py3" => Ok(PythonTarget::Py3),
+            "py310" => Ok(PythonTarget::Py310),
+            _ => Err(format!(
+                "unknown Python target '{}' (expected py3 or py310)",
+                s
+            )),
+        }
+    }
+}
+
+/// Flags threaded through every stage of the compiler
+///
+/// The defaults match `nevermind compile` run without any flags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompileOptions {
+    /// Python version to generate code for
+    pub target: PythonTarget,
+
+    /// Fold constant expressions after lowering
+    pub optimize: bool,
+
+    /// Make a match that isn't provably exhaustive an error
+    pub strict_match: bool,
+
+    /// Warn when a local binding shadows one from an enclosing scope
+    pub warn_shadowing: bool,
+}
@@ -31,6 +31,17 @@ impl Span {
         Self::point(SourceLocation::anonymous())
     }
 
+    /// Byte offset of the start of the span in its source
+    pub fn start_offset(&self) -> usize {
+        self.start.offset
+    }
+
+    /// Byte offset just past the end of the span, so that
+    /// `&source[span.start_offset()..span.end_offset()]` is the spanned text
+    pub fn end_offset(&self) -> usize {
+        self.end.offset
+    }
+
     /// Get the length in bytes
     pub fn len(&self) -> usize {
         self.end.offset.saturating_sub(self.start.offset)
@@ -41,12 +52,35 @@ impl Span {
         self.start.offset == self.end.offset
     }
 
-    /// Merge two spans
+    /// The smallest span covering both spans, in either order
     pub fn merge(&self, other: &Span) -> Span {
-        Span {
-            start: self.start.clone(),
-            end: other.end.clone(),
+        let start = if other.start.offset < self.start.offset {
+            &other.start
+        } else {
+            &self.start
+        };
+        let end = if other.end.offset > self.end.offset {
+            &other.end
+        } else {
+            &self.end
+        };
+        Span::new(start.clone(), end.clone())
+    }
+
+    /// Check if `loc` falls inside the span
+    ///
+    /// The end is where the next token starts, so it isn't inside; a
+    /// zero-length span contains just its own location.
+    pub fn contains(&self, loc: &SourceLocation) -> bool {
+        if self.is_empty() {
+            return loc.offset == self.start.offset;
         }
+        self.start.offset <= loc.offset && loc.offset < self.end.offset
+    }
+
+    /// Check if the two spans share any location
+    pub fn overlaps(&self, other: &Span) -> bool {
+        self.contains(&other.start) || other.contains(&self.start)
     }
 
     /// Extend this span to include another
@@ -108,5 +142,39 @@ mod tests {
         let merged = span1.merge(&span2);
 
         assert_eq!(merged.len(), 11);
+        assert_eq!(span2.merge(&span1), merged);
+    }
+
+    #[test]
+    fn test_span_contains_point() {
+        let start = SourceLocation::anonymous();
+        let mut inside = start.clone();
+        inside.advance_str("he");
+        let mut end = inside.clone();
+        end.advance_str("llo");
+
+        let span = Span::new(start.clone(), end.clone());
+        assert!(span.contains(&start));
+        assert!(span.contains(&inside));
+        assert!(!span.contains(&end));
+        assert!(Span::point(inside.clone()).contains(&inside));
+    }
+
+    #[test]
+    fn test_span_overlaps() {
+        let loc1 = SourceLocation::anonymous();
+        let mut loc2 = loc1.clone();
+        loc2.advance_str("hello");
+        let mut loc3 = loc2.clone();
+        loc3.advance_str(" world");
+
+        let first = Span::new(loc1.clone(), loc2.clone());
+        let second = Span::new(loc2.clone(), loc3.clone());
+        let whole = Span::new(loc1, loc3);
+
+        // Adjacent spans touch but don't share a location
+        assert!(!first.overlaps(&second));
+        assert!(first.overlaps(&whole));
+        assert!(whole.overlaps(&second));
     }
 }
@@ -22,6 +22,13 @@ pub struct Lexer<'a> {
 
     /// Pending dedent tokens
     pending_dedents: usize,
+
+    /// How many brackets are open; indentation inside them is ignored
+    bracket_depth: usize,
+
+    /// Whether the last token was a `.`, after which a number is a tuple
+    /// index, so `t.0.1` isn't read as `t` and the float `0.1`
+    after_dot: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -33,6 +40,8 @@ impl<'a> Lexer<'a> {
             indent_stack: vec![0],
             at_line_start: true,
             pending_dedents: 0,
+            bracket_depth: 0,
+            after_dot: false,
         }
     }
 
@@ -66,7 +75,17 @@ impl<'a> Lexer<'a> {
 
     /// Get the next token
     pub fn next_token(&mut self) -> Result<Token> {
-        // Emit pending dedents first
+        let after_dot = std::mem::take(&mut self.after_dot);
+
+        // Handle indentation at the start of a line
+        if self.at_line_start {
+            self.at_line_start = false;
+            if self.bracket_depth == 0 {
+                self.handle_indentation()?;
+            }
+        }
+
+        // Emit pending dedents before the first token of the dedented line
         if self.pending_dedents > 0 {
             self.pending_dedents -= 1;
             return Ok(Token::new(
@@ -76,19 +95,13 @@ impl<'a> Lexer<'a> {
             ));
         }
 
-        // Handle indentation at the start of a line
-        if self.at_line_start {
-            self.handle_indentation()?;
-            self.at_line_start = false;
-        }
-
         // Skip whitespace (except newlines which are handled above)
         self.skip_whitespace();
 
         // Check for EOF
         if self.peek().is_none() {
             // Emit remaining dedents
-            let dedent_count = self.indent_stack.len() - 1;
+            let dedent_count = self.indent_stack.len().saturating_sub(1);
             if dedent_count > 0 {
                 self.indent_stack.truncate(1);
                 if dedent_count > 1 {
@@ -109,11 +122,16 @@ impl<'a> Lexer<'a> {
         }
 
         // Get the next character
-        let c = self.peek().unwrap();
+        let Some(c) = self.peek() else {
+            return Err(Error::lexical(
+                "unexpected end of input",
+                Span::point(self.location.clone()),
+            ));
+        };
 
         // Dispatch based on the character
         let token = match c {
-            '0'..='9' => self.lex_number()?,
+            '0'..='9' => self.lex_number(after_dot)?,
 
             '"' => self.lex_string()?,
 
@@ -134,12 +152,12 @@ impl<'a> Lexer<'a> {
                 self.next_token()?
             }
 
-            '(' => self.lex_delimiter(Delimiter::LParen),
-            ')' => self.lex_delimiter(Delimiter::RParen),
-            '{' => self.lex_delimiter(Delimiter::LBrace),
-            '}' => self.lex_delimiter(Delimiter::RBrace),
-            '[' => self.lex_delimiter(Delimiter::LBracket),
-            ']' => self.lex_delimiter(Delimiter::RBracket),
+            '(' => self.lex_open_bracket(Delimiter::LParen),
+            ')' => self.lex_close_bracket(Delimiter::RParen),
+            '{' => self.lex_open_bracket(Delimiter::LBrace),
+            '}' => self.lex_close_bracket(Delimiter::RBrace),
+            '[' => self.lex_open_bracket(Delimiter::LBracket),
+            ']' => self.lex_close_bracket(Delimiter::RBracket),
             ',' => self.lex_delimiter(Delimiter::Comma),
             ':' => self.lex_delimiter(Delimiter::Colon),
             ';' => self.lex_delimiter(Delimiter::Semicolon),
@@ -149,17 +167,18 @@ impl<'a> Lexer<'a> {
             '`' => self.lex_delimiter(Delimiter::Backtick),
 
             '\n' | '\r' => {
-                self.advance();
+                self.consume_newline();
                 self.at_line_start = true;
                 self.next_token()?
             }
 
             _ => {
-                // Try to lex operators or identifiers
-                if let Some(token) = self.lex_operator_or_keyword()? {
-                    token
-                } else if c.is_alphabetic() || c == '_' {
+                // Identifiers are read whole before checking for word
+                // operators, so `ord` isn't lexed as `or` followed by `d`
+                if c.is_alphabetic() || c == '_' {
                     self.lex_identifier_or_keyword()?
+                } else if let Some(token) = self.lex_operator_or_keyword()? {
+                    token
                 } else {
                     return Err(Error::lexical(
                         format!("unexpected character '{}'", c),
@@ -169,6 +188,7 @@ impl<'a> Lexer<'a> {
             }
         };
 
+        self.after_dot = matches!(token.kind, TokenType::Operator(Operator::Dot));
         Ok(token)
     }
 
@@ -190,7 +210,13 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        let current_indent = *self.indent_stack.last().unwrap();
+        // Blank and comment-only lines don't change the indentation
+        match (self.peek(), self.peek2()) {
+            (None | Some('\n' | '\r' | '#'), _) | (Some('/'), Some('/' | '*')) => return Ok(()),
+            _ => {}
+        }
+
+        let current_indent = self.indent_stack.last().copied().unwrap_or(0);
 
         if spaces > current_indent {
             // Increase indentation
@@ -218,8 +244,8 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
 
-    /// Lex a number
-    fn lex_number(&mut self) -> Result<Token> {
+    /// Lex a number, only its digits when it is a tuple index
+    fn lex_number(&mut self, tuple_index: bool) -> Result<Token> {
         let start = self.location.clone();
         let mut text = String::new();
         let mut is_float = false;
@@ -234,12 +260,22 @@ impl<'a> Lexer<'a> {
             }
         }
 
+        if tuple_index {
+            let span = Span::new(start, self.location.clone());
+            return Ok(Token::new(
+                TokenType::Literal(LiteralType::Integer),
+                span,
+                text,
+            ));
+        }
+
         // Fractional part
         if self.peek() == Some('.') {
             if let Some(c) = self.peek2() {
                 if c.is_ascii_digit() {
                     is_float = true;
-                    text.push(self.advance().unwrap()); // consume '.'
+                    text.push('.');
+                    self.advance();
                     while let Some(&c) = self.chars.peek() {
                         if c.is_ascii_digit() {
                             text.push(c);
@@ -255,10 +291,10 @@ impl<'a> Lexer<'a> {
         // Exponent part
         if self.peek() == Some('e') || self.peek() == Some('E') {
             is_float = true;
-            text.push(self.advance().unwrap()); // consume 'e' or 'E'
+            text.extend(self.advance()); // consume 'e' or 'E'
 
             if self.peek() == Some('+') || self.peek() == Some('-') {
-                text.push(self.advance().unwrap());
+                text.extend(self.advance());
             }
 
             while let Some(&c) = self.chars.peek() {
@@ -314,6 +350,13 @@ impl<'a> Lexer<'a> {
                         Span::new(start, self.location.clone()),
                     ));
                 }
+                '$' => {
+                    text.push(c);
+                    self.advance();
+                    if self.peek() == Some('{') {
+                        self.lex_interpolation_source(&mut text, &start)?;
+                    }
+                }
                 _ => {
                     text.push(c);
                     self.advance();
@@ -327,6 +370,44 @@ impl<'a> Lexer<'a> {
         ))
     }
 
+    /// Copy the source of a `${...}` placeholder into a string's text as is,
+    /// so the parser can parse it as an expression. Quotes inside the
+    /// placeholder start nested strings rather than ending the outer one.
+    fn lex_interpolation_source(
+        &mut self,
+        text: &mut String,
+        start: &SourceLocation,
+    ) -> Result<()> {
+        let mut depth = 0;
+        let mut in_string = false;
+
+        while let Some(c) = self.advance() {
+            text.push(c);
+            match c {
+                '\n' | '\r' => break,
+                '\\' if in_string => {
+                    if let Some(escaped) = self.advance() {
+                        text.push(escaped);
+                    }
+                }
+                '"' => in_string = !in_string,
+                '{' if !in_string => depth += 1,
+                '}' if !in_string => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Err(Error::lexical(
+            "unterminated string literal",
+            Span::new(start.clone(), self.location.clone()),
+        ))
+    }
+
     /// Lex a character literal
     fn lex_char(&mut self) -> Result<Token> {
         let start = self.location.clone();
@@ -388,11 +469,9 @@ impl<'a> Lexer<'a> {
                 // Hex escape \xNN
                 let mut code = 0;
                 for _ in 0..2 {
-                    if let Some(c) = self.peek() {
-                        if c.is_ascii_hexdigit() {
-                            code = code * 16 + c.to_digit(16).unwrap();
-                            self.advance();
-                        }
+                    if let Some(digit) = self.peek().and_then(|c| c.to_digit(16)) {
+                        code = code * 16 + digit;
+                        self.advance();
                     }
                 }
                 std::char::from_u32(code)
@@ -407,20 +486,28 @@ impl<'a> Lexer<'a> {
                 }
                 self.advance(); // consume '{'
 
-                let mut code = 0;
+                let mut code: u32 = 0;
                 while let Some(&c) = self.chars.peek() {
                     if c == '}' {
                         break;
                     }
-                    if c.is_ascii_hexdigit() {
-                        code = code * 16 + c.to_digit(16).unwrap();
-                        self.advance();
-                    } else {
+                    let Some(digit) = c.to_digit(16) else {
                         return Err(Error::lexical(
                             "invalid unicode escape",
                             Span::point(self.location.clone()),
                         ));
-                    }
+                    };
+                    code = code
+                        .checked_mul(16)
+                        .and_then(|code| code.checked_add(digit))
+                        .filter(|&code| code <= 0x10FFFF)
+                        .ok_or_else(|| {
+                            Error::lexical(
+                                "unicode escape out of range",
+                                Span::point(self.location.clone()),
+                            )
+                        })?;
+                    self.advance();
                 }
 
                 if self.peek() != Some('}') {
@@ -431,7 +518,11 @@ impl<'a> Lexer<'a> {
                 }
                 self.advance(); // consume '}'
 
-                std::char::from_u32(code)
+                // Surrogate code points are not characters
+                let c = std::char::from_u32(code).ok_or_else(|| {
+                    Error::lexical("invalid unicode escape", Span::point(self.location.clone()))
+                })?;
+                Some(c)
             }
             _ => Some(c),
         })
@@ -530,10 +621,22 @@ impl<'a> Lexer<'a> {
     /// Lex a delimiter
     fn lex_delimiter(&mut self, delimiter: Delimiter) -> Token {
         let start = self.location.clone();
-        let c = self.advance().unwrap();
+        let text = self.advance().map(String::from).unwrap_or_default();
         let span = Span::new(start, self.location.clone());
 
-        Token::new(TokenType::Delimiter(delimiter), span, c.to_string())
+        Token::new(TokenType::Delimiter(delimiter), span, text)
+    }
+
+    /// Lex `(`, `[` or `{`
+    fn lex_open_bracket(&mut self, delimiter: Delimiter) -> Token {
+        self.bracket_depth += 1;
+        self.lex_delimiter(delimiter)
+    }
+
+    /// Lex `)`, `]` or `}`
+    fn lex_close_bracket(&mut self, delimiter: Delimiter) -> Token {
+        self.bracket_depth = self.bracket_depth.saturating_sub(1);
+        self.lex_delimiter(delimiter)
     }
 
     /// Consume a line comment
@@ -554,10 +657,11 @@ impl<'a> Lexer<'a> {
 
     /// Consume a block comment
     fn consume_block_comment(&mut self) -> Result<()> {
+        let start = self.location.clone();
         self.advance(); // consume first '/'
         self.advance(); // consume '*'
 
-        let mut depth = 1;
+        let mut depth: usize = 1;
 
         while depth > 0 {
             if let Some(c) = self.advance() {
@@ -570,8 +674,12 @@ impl<'a> Lexer<'a> {
                 }
             } else {
                 return Err(Error::lexical(
-                    "unterminated block comment",
-                    Span::point(self.location.clone()),
+                    format!(
+                        "unterminated block comment ({} level{} still open)",
+                        depth,
+                        if depth == 1 { "" } else { "s" }
+                    ),
+                    Span::new(start, self.location.clone()),
                 ));
             }
         }
@@ -579,6 +687,18 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
 
+    /// Consume one line terminator: `\n`, `\r\n` or a lone `\r`
+    fn consume_newline(&mut self) {
+        if self.advance() == Some('\r') {
+            if self.peek() == Some('\n') {
+                self.advance();
+            } else {
+                self.location.line += 1;
+                self.location.column = 1;
+            }
+        }
+    }
+
     /// Skip whitespace (not including newlines)
     fn skip_whitespace(&mut self) {
         while let Some(&c) = self.chars.peek() {
@@ -657,6 +777,33 @@ mod tests {
         assert_eq!(tokens[9].kind, TokenType::Operator(Operator::Ge));
     }
 
+    #[test]
+    fn test_identifiers_starting_with_word_operators() {
+        let mut lexer = Lexer::new("ord order notes or not");
+
+        let tokens = lexer.tokenize().unwrap();
+        assert_eq!(tokens[0].kind, TokenType::Identifier);
+        assert_eq!(tokens[0].text, "ord");
+        assert_eq!(tokens[1].text, "order");
+        assert_eq!(tokens[2].text, "notes");
+        assert_eq!(tokens[3].kind, TokenType::Operator(Operator::Or));
+        assert_eq!(tokens[4].kind, TokenType::Operator(Operator::Not));
+    }
+
+    #[test]
+    fn test_token_offsets_slice_their_source_text() {
+        let source = "let café = \"ünï\" + naïve";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+
+        let slice = |index: usize| {
+            let span = &tokens[index].span;
+            &source[span.start_offset()..span.end_offset()]
+        };
+        assert_eq!(slice(1), "café");
+        assert_eq!(slice(3), "\"ünï\"");
+        assert_eq!(slice(5), "naïve");
+    }
+
     #[test]
     fn test_lex_string() {
         let mut lexer = Lexer::new(r#""hello""#);
@@ -680,4 +827,30 @@ let z = 3
         // Should have tokens for indentation changes
         // (exact behavior depends on how we encode indentation in tokens)
     }
+
+    #[test]
+    fn test_dedent_comes_before_the_dedented_line() {
+        let source = "match n\n  case 1 => a\n\n  case 2 => b\nprint(c)";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+
+        // The blank line between the arms isn't a dedent
+        assert_eq!(
+            texts,
+            [
+                "match", "n", "case", "1", "=>", "a", "case", "2", "=>", "b", "\n", "print", "(",
+                "c", ")", ""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_indentation_inside_brackets_is_ignored() {
+        let source = "let xs = [\n    1,\n  2\n]\nxs";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+
+        assert!(!tokens
+            .iter()
+            .any(|token| token.kind == TokenType::Delimiter(Delimiter::Semicolon)));
+    }
 }
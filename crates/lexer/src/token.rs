@@ -260,10 +260,16 @@ pub enum Operator {
     // Other
     Pipe,      // |>
     Assign,    // =
+    AddAssign, // +=
+    SubAssign, // -=
+    MulAssign, // *=
+    DivAssign, // /=
+    ModAssign, // %=
     Arrow,     // ->
     FatArrow,  // =>
     Dot,       // .
     DotDot,    // ..
+    DotDotEq,  // ..=
     DotDotDot, // ...
     Concat,    // ++
 }
@@ -296,10 +302,16 @@ impl Operator {
             ">>" => Some(Operator::ShiftRight),
             "|>" => Some(Operator::Pipe),
             "=" => Some(Operator::Assign),
+            "+=" => Some(Operator::AddAssign),
+            "-=" => Some(Operator::SubAssign),
+            "*=" => Some(Operator::MulAssign),
+            "/=" => Some(Operator::DivAssign),
+            "%=" => Some(Operator::ModAssign),
             "->" => Some(Operator::Arrow),
             "=>" => Some(Operator::FatArrow),
             "." => Some(Operator::Dot),
             ".." => Some(Operator::DotDot),
+            "..=" => Some(Operator::DotDotEq),
             "..." => Some(Operator::DotDotDot),
             "++" => Some(Operator::Concat),
             _ => None,
@@ -332,10 +344,16 @@ impl Operator {
             Operator::ShiftRight => ">>",
             Operator::Pipe => "|>",
             Operator::Assign => "=",
+            Operator::AddAssign => "+=",
+            Operator::SubAssign => "-=",
+            Operator::MulAssign => "*=",
+            Operator::DivAssign => "/=",
+            Operator::ModAssign => "%=",
             Operator::Arrow => "->",
             Operator::FatArrow => "=>",
             Operator::Dot => ".",
             Operator::DotDot => "..",
+            Operator::DotDotEq => "..=",
             Operator::DotDotDot => "...",
             Operator::Concat => "++",
         }
@@ -2,6 +2,7 @@
 
 use nevermind_lexer::token::{Delimiter, Keyword, LiteralType, Operator, Token, TokenType};
 use nevermind_lexer::Lexer;
+use proptest::prelude::*;
 
 /// Helper function to tokenize source and return the tokens (excluding EOF)
 fn tokenize(source: &str) -> Vec<Token> {
@@ -328,6 +329,22 @@ fn test_other_operators() {
     );
 }
 
+#[test]
+fn test_compound_assignment_operators() {
+    let source = "+= -= *= /= %=";
+    let tokens = tokenize(source);
+    assert_token_kinds(
+        &tokens,
+        &[
+            TokenType::Operator(Operator::AddAssign),
+            TokenType::Operator(Operator::SubAssign),
+            TokenType::Operator(Operator::MulAssign),
+            TokenType::Operator(Operator::DivAssign),
+            TokenType::Operator(Operator::ModAssign),
+        ],
+    );
+}
+
 // ============================================================================
 // Delimiter Tests
 // ============================================================================
@@ -523,6 +540,23 @@ fn test_float_with_zero_integer_part() {
     assert_token_texts(&tokens, &["0.5"]);
 }
 
+#[test]
+fn test_nested_tuple_index_is_not_a_float() {
+    let source = "t.0.1";
+    let tokens = tokenize(source);
+    assert_token_kinds(
+        &tokens,
+        &[
+            TokenType::Identifier,
+            TokenType::Operator(Operator::Dot),
+            TokenType::Literal(LiteralType::Integer),
+            TokenType::Operator(Operator::Dot),
+            TokenType::Literal(LiteralType::Integer),
+        ],
+    );
+    assert_token_texts(&tokens, &["t", ".", "0", ".", "1"]);
+}
+
 #[test]
 fn test_float_scientific_notation_lower() {
     let source = "1e10";
@@ -728,6 +762,14 @@ fn test_nested_block_comments() {
     assert_eq!(tokens[0].kind, TokenType::Keyword(Keyword::Let));
 }
 
+#[test]
+fn test_balanced_nested_block_comment_is_fully_consumed() {
+    let source = "/* a /* b /* c */ */ */ let x = 5";
+    let tokens = tokenize(source);
+    assert_eq!(tokens[0].kind, TokenType::Keyword(Keyword::Let));
+    assert_eq!(tokens[1].text, "x");
+}
+
 // ============================================================================
 // Edge Cases
 // ============================================================================
@@ -754,6 +796,17 @@ fn test_newlines_only() {
     // This test just ensures it doesn't crash
 }
 
+#[test]
+fn test_crlf_is_a_single_line_break() {
+    let tokens = tokenize("a\r\nb");
+    assert_eq!(tokens.last().unwrap().text, "b");
+    assert_eq!(tokens.last().unwrap().span.start.line, 2);
+
+    let tokens = tokenize("a\rb\nc");
+    assert_eq!(tokens[tokens.len() - 2].span.start.line, 2);
+    assert_eq!(tokens.last().unwrap().span.start.line, 3);
+}
+
 #[test]
 fn test_tokens_with_whitespace() {
     let source = "let   x    =    5";
@@ -1071,6 +1124,24 @@ fn test_unterminated_block_comment() {
     }
 }
 
+#[test]
+fn test_unbalanced_nested_block_comment_reports_open_levels() {
+    let mut lexer = Lexer::new("/* /* */");
+    let err = lexer.tokenize().unwrap_err();
+    assert_eq!(
+        err.message,
+        "unterminated block comment (1 level still open)"
+    );
+    assert_eq!(err.span.start.column, 1);
+
+    let mut lexer = Lexer::new("/* a /* b");
+    let err = lexer.tokenize().unwrap_err();
+    assert_eq!(
+        err.message,
+        "unterminated block comment (2 levels still open)"
+    );
+}
+
 // ============================================================================
 // Indentation Tests
 // ============================================================================
@@ -1292,3 +1363,58 @@ fn test_logical_operators_with_identifiers() {
         ],
     );
 }
+
+/// Characters the lexer treats specially, weighted into the random inputs
+/// so they reach escape, number, comment and indentation handling
+const INTERESTING_CHARS: &str = "\"'\\${}()[]#/*\n\r\t .0123456789eExu+-=<>|&!^%:,;_aZ";
+
+/// The starts of multi-character constructs that single characters rarely
+/// assemble
+const FRAGMENTS: [&str; 8] = ["\\u{", "\\x", "${", "/*", "*/", "\n    ", "1e", "0."];
+
+/// A random character, mostly one the lexer treats specially, or sometimes
+/// a fragment of a longer construct
+fn random_piece() -> impl Strategy<Value = String> {
+    let interesting: Vec<char> = INTERESTING_CHARS.chars().collect();
+    prop_oneof![
+        6 => proptest::sample::select(interesting).prop_map(String::from),
+        2 => any::<char>().prop_map(String::from),
+        1 => proptest::sample::select(FRAGMENTS.to_vec()).prop_map(str::to_string),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(5_000))]
+
+    #[test]
+    fn test_tokenize_never_panics_on_random_input(
+        pieces in proptest::collection::vec(random_piece(), 0..48)
+    ) {
+        let source = pieces.concat();
+        if let Ok(tokens) = Lexer::new(&source).tokenize() {
+            prop_assert!(
+                tokens.last().is_some_and(|t| t.is_eof()),
+                "tokens for {:?} do not end in EOF",
+                source
+            );
+        }
+    }
+}
+
+#[test]
+fn test_out_of_range_unicode_escapes_are_errors() {
+    for source in [r#""\u{FFFFFFFFFFFF}""#, r#""\u{110000}""#, r#""\u{D800}""#] {
+        let err = Lexer::new(source)
+            .tokenize()
+            .expect_err("escape should be rejected");
+        assert!(
+            err.message.contains("unicode escape"),
+            "unexpected error for {:?}: {}",
+            source,
+            err.message
+        );
+    }
+
+    let tokens = tokenize(r#""\u{10FFFF}""#);
+    assert_eq!(tokens[0].text, "\u{10FFFF}");
+}
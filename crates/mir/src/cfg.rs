@@ -0,0 +1,344 @@
+//! Control-flow graphs over MIR functions
+//!
+//! A function body is split into basic blocks of straight-line statements,
+//! each ending in a terminator that names the blocks control can go to next.
+//! Statements after a `return`, `raise`, `break` or `continue` in the same
+//! block can never run and are left out of the graph.
+
+use super::{MirExpr, MirExprStmt, MirFunction, MirPattern};
+
+/// Index of a block in [`ControlFlowGraph::blocks`]
+pub type BlockId = usize;
+
+/// How control leaves a basic block
+#[derive(Debug, Clone)]
+pub enum Terminator {
+    /// Continue at another block
+    Jump(BlockId),
+
+    /// Go to `then_block` when `condition` holds, otherwise to `else_block`
+    Branch {
+        condition: MirExpr,
+        then_block: BlockId,
+        else_block: BlockId,
+    },
+
+    /// Bind the next item of `iter` to `pattern` and go to `body`, or go to
+    /// `exit` once `iter` is exhausted
+    Iterate {
+        pattern: MirPattern,
+        iter: MirExpr,
+        body: BlockId,
+        exit: BlockId,
+    },
+
+    /// Run the `try` body starting at `body`. An error raised there goes to
+    /// `handler`, the start of the `catch` body, when there is one
+    Try {
+        body: BlockId,
+        handler: Option<BlockId>,
+    },
+
+    /// Leave the function, with a value if there is one
+    Return(Option<MirExpr>),
+
+    /// Raise an error with the given message, leaving the function unless
+    /// an enclosing `try` catches it
+    Raise(MirExpr),
+}
+
+impl Terminator {
+    /// The blocks control can go to next
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Jump(target) => vec![*target],
+            Terminator::Branch {
+                then_block,
+                else_block,
+                ..
+            } => vec![*then_block, *else_block],
+            Terminator::Iterate { body, exit, .. } => vec![*body, *exit],
+            Terminator::Try { body, handler } => {
+                [Some(*body), *handler].into_iter().flatten().collect()
+            }
+            Terminator::Return(_) | Terminator::Raise(_) => Vec::new(),
+        }
+    }
+}
+
+/// Straight-line statements that always run together
+#[derive(Debug, Clone)]
+pub struct BasicBlock {
+    pub id: BlockId,
+    /// Only `Let`, `Assign`, `IndexAssign` and `Expr` statements
+    pub statements: Vec<MirExprStmt>,
+    pub terminator: Terminator,
+}
+
+impl BasicBlock {
+    /// The blocks control can go to after this one
+    pub fn successors(&self) -> Vec<BlockId> {
+        self.terminator.successors()
+    }
+}
+
+/// The basic blocks of one function, starting at `entry`
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>,
+    pub entry: BlockId,
+}
+
+impl ControlFlowGraph {
+    /// Build the graph for a function's body
+    pub fn from_function(function: &MirFunction) -> Self {
+        let mut builder = Builder::default();
+        let entry = builder.new_block();
+        let mut current = Some(entry);
+
+        builder.lower_stmts(&function.body.statements, &mut current);
+        if let Some(block) = current {
+            let value = function.body.expr.as_deref().cloned();
+            builder.terminate(block, Terminator::Return(value));
+        }
+
+        let blocks = builder
+            .blocks
+            .into_iter()
+            .enumerate()
+            .map(|(id, (statements, terminator))| BasicBlock {
+                id,
+                statements,
+                terminator: terminator.expect("every block is terminated"),
+            })
+            .collect();
+
+        Self { blocks, entry }
+    }
+
+    /// The block with the given id
+    pub fn block(&self, id: BlockId) -> &BasicBlock {
+        &self.blocks[id]
+    }
+
+    /// The blocks that can go to `id` next
+    pub fn predecessors(&self, id: BlockId) -> Vec<BlockId> {
+        self.blocks
+            .iter()
+            .filter(|block| block.successors().contains(&id))
+            .map(|block| block.id)
+            .collect()
+    }
+
+    /// The number of edges between blocks
+    pub fn edge_count(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.successors().len())
+            .sum()
+    }
+}
+
+/// A loop's `continue` and `break` targets
+#[derive(Clone, Copy)]
+struct LoopTargets {
+    header: BlockId,
+    exit: BlockId,
+}
+
+#[derive(Default)]
+struct Builder {
+    blocks: Vec<(Vec<MirExprStmt>, Option<Terminator>)>,
+    loops: Vec<LoopTargets>,
+}
+
+impl Builder {
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push((Vec::new(), None));
+        self.blocks.len() - 1
+    }
+
+    fn terminate(&mut self, block: BlockId, terminator: Terminator) {
+        self.blocks[block].1 = Some(terminator);
+    }
+
+    /// Lower `stmts` starting in `current`, leaving `current` as the block
+    /// control falls out of, or `None` if it never does
+    fn lower_stmts(&mut self, stmts: &[MirExprStmt], current: &mut Option<BlockId>) {
+        for stmt in stmts {
+            let Some(block) = *current else {
+                return;
+            };
+
+            match stmt {
+                MirExprStmt::Let { .. }
+                | MirExprStmt::Assign { .. }
+                | MirExprStmt::IndexAssign { .. }
+                | MirExprStmt::FieldAssign { .. }
+                | MirExprStmt::Expr(_) => self.blocks[block].0.push(stmt.clone()),
+
+                MirExprStmt::Return { value, .. } => {
+                    let value = value.as_deref().cloned();
+                    self.terminate(block, Terminator::Return(value));
+                    *current = None;
+                }
+
+                MirExprStmt::Raise { value, .. } => {
+                    self.terminate(block, Terminator::Raise(value.clone()));
+                    *current = None;
+                }
+
+                MirExprStmt::Break { .. } | MirExprStmt::Continue { .. } => {
+                    // Outside a loop there is nowhere to go, so it's skipped
+                    let Some(targets) = self.loops.last().copied() else {
+                        continue;
+                    };
+                    let target = match stmt {
+                        MirExprStmt::Break { .. } => targets.exit,
+                        _ => targets.header,
+                    };
+                    self.terminate(block, Terminator::Jump(target));
+                    *current = None;
+                }
+
+                MirExprStmt::If {
+                    condition,
+                    then_body,
+                    else_body,
+                    ..
+                } => {
+                    let then_block = self.new_block();
+                    let else_block = self.new_block();
+                    self.terminate(
+                        block,
+                        Terminator::Branch {
+                            condition: condition.clone(),
+                            then_block,
+                            else_block,
+                        },
+                    );
+
+                    let mut then_end = Some(then_block);
+                    self.lower_stmts(then_body, &mut then_end);
+
+                    let Some(else_body) = else_body else {
+                        // With no else branch, the empty else block is
+                        // where the two paths meet
+                        if let Some(end) = then_end {
+                            self.terminate(end, Terminator::Jump(else_block));
+                        }
+                        *current = Some(else_block);
+                        continue;
+                    };
+                    let mut else_end = Some(else_block);
+                    self.lower_stmts(else_body, &mut else_end);
+
+                    // The join block is only needed if a branch falls through
+                    let ends: Vec<BlockId> = [then_end, else_end].into_iter().flatten().collect();
+                    *current = if ends.is_empty() {
+                        None
+                    } else {
+                        let join = self.new_block();
+                        for end in ends {
+                            self.terminate(end, Terminator::Jump(join));
+                        }
+                        Some(join)
+                    };
+                }
+
+                MirExprStmt::While {
+                    condition, body, ..
+                } => {
+                    let header = self.new_block();
+                    let body_block = self.new_block();
+                    let exit = self.new_block();
+                    self.terminate(block, Terminator::Jump(header));
+                    self.terminate(
+                        header,
+                        Terminator::Branch {
+                            condition: condition.clone(),
+                            then_block: body_block,
+                            else_block: exit,
+                        },
+                    );
+                    self.lower_loop_body(body, body_block, LoopTargets { header, exit });
+                    *current = Some(exit);
+                }
+
+                MirExprStmt::For {
+                    pattern,
+                    iter,
+                    body,
+                    ..
+                } => {
+                    let header = self.new_block();
+                    let body_block = self.new_block();
+                    let exit = self.new_block();
+                    self.terminate(block, Terminator::Jump(header));
+                    self.terminate(
+                        header,
+                        Terminator::Iterate {
+                            pattern: pattern.clone(),
+                            iter: iter.clone(),
+                            body: body_block,
+                            exit,
+                        },
+                    );
+                    self.lower_loop_body(body, body_block, LoopTargets { header, exit });
+                    *current = Some(exit);
+                }
+
+                MirExprStmt::Try {
+                    body,
+                    catch_body,
+                    finally_body,
+                    ..
+                } => {
+                    let body_block = self.new_block();
+                    let handler = catch_body.as_ref().map(|_| self.new_block());
+                    self.terminate(
+                        block,
+                        Terminator::Try {
+                            body: body_block,
+                            handler,
+                        },
+                    );
+
+                    let mut body_end = Some(body_block);
+                    self.lower_stmts(body, &mut body_end);
+                    let mut catch_end = None;
+                    if let (Some(handler), Some(catch_body)) = (handler, catch_body) {
+                        catch_end = Some(handler);
+                        self.lower_stmts(catch_body, &mut catch_end);
+                    }
+
+                    // The finally body is only followed on the paths that
+                    // fall out of the try, not on those that return early
+                    let ends: Vec<BlockId> = [body_end, catch_end].into_iter().flatten().collect();
+                    *current = if ends.is_empty() {
+                        None
+                    } else {
+                        let join = self.new_block();
+                        for end in ends {
+                            self.terminate(end, Terminator::Jump(join));
+                        }
+                        Some(join)
+                    };
+                    if let Some(finally_body) = finally_body {
+                        self.lower_stmts(finally_body, current);
+                    }
+                }
+            }
+        }
+    }
+
+    fn lower_loop_body(&mut self, body: &[MirExprStmt], start: BlockId, targets: LoopTargets) {
+        self.loops.push(targets);
+        let mut end = Some(start);
+        self.lower_stmts(body, &mut end);
+        if let Some(end) = end {
+            self.terminate(end, Terminator::Jump(targets.header));
+        }
+        self.loops.pop();
+    }
+}
@@ -1,6 +1,6 @@
 //! MIR expressions
 
-use super::NodeId;
+use super::{MirPattern, NodeId};
 use nevermind_type_checker::Type;
 
 /// Mid-level IR expression
@@ -33,6 +33,13 @@ pub enum MirExpr {
         id: NodeId,
     },
 
+    /// Await of an asynchronous computation
+    Await {
+        expr: Box<MirExpr>,
+        ty: Type,
+        id: NodeId,
+    },
+
     /// Function call
     Call {
         callee: Box<MirExpr>,
@@ -49,6 +56,13 @@ pub enum MirExpr {
         id: NodeId,
     },
 
+    /// String built from text and interpolated values
+    StringInterp {
+        parts: Vec<MirStringPart>,
+        ty: Type,
+        id: NodeId,
+    },
+
     /// List literal
     List {
         elements: Vec<MirExpr>,
@@ -56,6 +70,28 @@ pub enum MirExpr {
         id: NodeId,
     },
 
+    /// Tuple literal
+    Tuple {
+        elements: Vec<MirExpr>,
+        ty: Type,
+        id: NodeId,
+    },
+
+    /// Field access: object.field (a method call is a call whose callee is one)
+    FieldAccess {
+        object: Box<MirExpr>,
+        field: String,
+        ty: Type,
+        id: NodeId,
+    },
+
+    /// Map literal: {key: value, ...}
+    Map {
+        entries: Vec<(MirExpr, MirExpr)>,
+        ty: Type,
+        id: NodeId,
+    },
+
     /// If expression
     If {
         condition: Box<MirExpr>,
@@ -80,6 +116,33 @@ pub enum MirExpr {
         ty: Type,
         id: NodeId,
     },
+
+    /// Integer range: start..end, or start..=end when `inclusive`
+    Range {
+        start: Box<MirExpr>,
+        end: Box<MirExpr>,
+        inclusive: bool,
+        ty: Type,
+        id: NodeId,
+    },
+
+    /// Slice of a list or string from start to end, including end when
+    /// `inclusive`
+    Slice {
+        array: Box<MirExpr>,
+        start: Box<MirExpr>,
+        end: Box<MirExpr>,
+        inclusive: bool,
+        ty: Type,
+        id: NodeId,
+    },
+}
+
+/// A piece of an interpolated string
+#[derive(Debug, Clone)]
+pub enum MirStringPart {
+    Literal(String),
+    Expr(MirExpr),
 }
 
 impl MirExpr {
@@ -90,12 +153,42 @@ impl MirExpr {
             MirExpr::Variable { ty, .. } => ty,
             MirExpr::Binary { ty, .. } => ty,
             MirExpr::Unary { ty, .. } => ty,
+            MirExpr::Await { ty, .. } => ty,
             MirExpr::Call { ty, .. } => ty,
             MirExpr::Block { ty, .. } => ty,
+            MirExpr::StringInterp { ty, .. } => ty,
             MirExpr::List { ty, .. } => ty,
+            MirExpr::Tuple { ty, .. } => ty,
+            MirExpr::Map { ty, .. } => ty,
+            MirExpr::FieldAccess { ty, .. } => ty,
             MirExpr::If { ty, .. } => ty,
             MirExpr::Index { ty, .. } => ty,
             MirExpr::Lambda { ty, .. } => ty,
+            MirExpr::Range { ty, .. } => ty,
+            MirExpr::Slice { ty, .. } => ty,
+        }
+    }
+
+    /// Get a mutable reference to the expression's type
+    pub fn ty_mut(&mut self) -> &mut Type {
+        match self {
+            MirExpr::Literal { ty, .. } => ty,
+            MirExpr::Variable { ty, .. } => ty,
+            MirExpr::Binary { ty, .. } => ty,
+            MirExpr::Unary { ty, .. } => ty,
+            MirExpr::Await { ty, .. } => ty,
+            MirExpr::Call { ty, .. } => ty,
+            MirExpr::Block { ty, .. } => ty,
+            MirExpr::StringInterp { ty, .. } => ty,
+            MirExpr::List { ty, .. } => ty,
+            MirExpr::Tuple { ty, .. } => ty,
+            MirExpr::Map { ty, .. } => ty,
+            MirExpr::FieldAccess { ty, .. } => ty,
+            MirExpr::If { ty, .. } => ty,
+            MirExpr::Index { ty, .. } => ty,
+            MirExpr::Lambda { ty, .. } => ty,
+            MirExpr::Range { ty, .. } => ty,
+            MirExpr::Slice { ty, .. } => ty,
         }
     }
 
@@ -106,12 +199,19 @@ impl MirExpr {
             MirExpr::Variable { id, .. } => *id,
             MirExpr::Binary { id, .. } => *id,
             MirExpr::Unary { id, .. } => *id,
+            MirExpr::Await { id, .. } => *id,
             MirExpr::Call { id, .. } => *id,
             MirExpr::Block { id, .. } => *id,
+            MirExpr::StringInterp { id, .. } => *id,
             MirExpr::List { id, .. } => *id,
+            MirExpr::Tuple { id, .. } => *id,
+            MirExpr::Map { id, .. } => *id,
+            MirExpr::FieldAccess { id, .. } => *id,
             MirExpr::If { id, .. } => *id,
             MirExpr::Index { id, .. } => *id,
             MirExpr::Lambda { id, .. } => *id,
+            MirExpr::Range { id, .. } => *id,
+            MirExpr::Slice { id, .. } => *id,
         }
     }
 
@@ -134,6 +234,14 @@ pub enum BinOp {
     Div,
     Mod,
     Pow,
+    Concat,
+
+    // Bitwise
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
 
     // Comparison
     Eq,
@@ -153,6 +261,7 @@ pub enum BinOp {
 pub enum UnaryOp {
     Neg,
     Not,
+    BitNot,
 }
 
 /// Literal values
@@ -161,6 +270,7 @@ pub enum Literal {
     Int(i64),
     Float(f64),
     String(String),
+    Char(char),
     Bool(bool),
     Null,
 }
@@ -176,17 +286,28 @@ pub enum MirExprStmt {
         id: NodeId,
     },
 
-    /// Assignment: x = value
+    /// Assignment: x = value, or x op= value
     Assign {
         target: String,
+        op: Option<BinOp>,
         value: MirExpr,
         id: NodeId,
     },
 
-    /// Index assignment: arr[index] = value
+    /// Index assignment: arr[index] = value, or arr[index] op= value
     IndexAssign {
         array: String,
         index: MirExpr,
+        op: Option<BinOp>,
+        value: MirExpr,
+        id: NodeId,
+    },
+
+    /// Field assignment: obj.field = value, or obj.field op= value
+    FieldAssign {
+        object: MirExpr,
+        field: String,
+        op: Option<BinOp>,
         value: MirExpr,
         id: NodeId,
     },
@@ -217,12 +338,24 @@ pub enum MirExprStmt {
 
     /// For loop inside a block
     For {
-        variable: String,
+        pattern: MirPattern,
         iter: MirExpr,
         body: Vec<MirExprStmt>,
         id: NodeId,
     },
 
+    /// Try statement inside a block
+    Try {
+        body: Vec<MirExprStmt>,
+        catch_var: Option<String>,
+        catch_body: Option<Vec<MirExprStmt>>,
+        finally_body: Option<Vec<MirExprStmt>>,
+        id: NodeId,
+    },
+
+    /// Raise statement inside a block
+    Raise { value: MirExpr, id: NodeId },
+
     /// Break statement
     Break { id: NodeId },
 
@@ -236,11 +369,14 @@ impl MirExprStmt {
             MirExprStmt::Let { id, .. } => *id,
             MirExprStmt::Assign { id, .. } => *id,
             MirExprStmt::IndexAssign { id, .. } => *id,
+            MirExprStmt::FieldAssign { id, .. } => *id,
             MirExprStmt::Expr(expr) => expr.get_id(),
             MirExprStmt::Return { id, .. } => *id,
             MirExprStmt::If { id, .. } => *id,
             MirExprStmt::While { id, .. } => *id,
             MirExprStmt::For { id, .. } => *id,
+            MirExprStmt::Try { id, .. } => *id,
+            MirExprStmt::Raise { id, .. } => *id,
             MirExprStmt::Break { id } => *id,
             MirExprStmt::Continue { id } => *id,
         }
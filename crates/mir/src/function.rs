@@ -1,6 +1,7 @@
 //! MIR functions and programs
 
 use super::expr::MirBlock;
+use super::lowering::FreshNames;
 use super::stmt::Param;
 use super::{MirStmt, NodeId};
 use nevermind_type_checker::Type;
@@ -37,6 +38,8 @@ impl MirFunction {
 #[derive(Debug, Default, Clone)]
 pub struct MirProgram {
     pub statements: Vec<MirStmt>,
+    /// Where the ids and temporaries for nodes added after lowering come from
+    pub fresh_names: FreshNames,
 }
 
 impl MirProgram {
@@ -56,6 +59,7 @@ impl MirProgram {
                 params,
                 body,
                 return_type,
+                ..
             } = stmt
             {
                 Some(MirFunction {
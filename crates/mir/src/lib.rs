@@ -8,28 +8,85 @@
 //! - Explicit temporaries for complex expressions
 //! - Type information attached to all nodes
 
+pub mod cfg;
 mod expr;
 mod function;
 pub mod lowering;
+pub mod optimize;
 mod pattern;
 mod stmt;
+mod typing;
 
-pub use expr::{BinOp, Literal, MirBlock, MirExpr, MirExprStmt, UnaryOp};
+pub use expr::{BinOp, Literal, MirBlock, MirExpr, MirExprStmt, MirStringPart, UnaryOp};
 pub use function::{MirFunction, MirProgram};
+pub use nevermind_ast::stmt::{namespace_binding, ImportedSymbol};
 pub use pattern::MirPattern;
-pub use stmt::{MirMatchArm, MirStmt, Param};
+pub use stmt::{MirField, MirMatchArm, MirStmt, Param};
+
+use nevermind_common::CompileOptions;
+use nevermind_type_checker::Type;
+use std::collections::HashMap;
 
 /// Unique identifier for MIR nodes (re-export from AST crate)
 pub type NodeId = nevermind_ast::NodeId;
 
 /// Lower a typed AST program to MIR
-pub fn lower_program(ast_program: &Vec<nevermind_ast::Stmt>) -> lowering::Result<MirProgram> {
+///
+/// Constant expressions are folded when `options.optimize` is set.
+pub fn lower_program(
+    ast_program: &[nevermind_ast::Stmt],
+    options: &CompileOptions,
+) -> lowering::Result<MirProgram> {
+    let mut program = lower_statements(&[], ast_program, None)?;
+    if options.optimize {
+        optimize::fold_constants(&mut program);
+    }
+    Ok(program)
+}
+
+/// Lower an AST program to MIR, stamping each expression with the type the
+/// checker inferred for its node (see `TypeChecker::node_types`)
+pub fn lower_program_with_types(
+    ast_program: &[nevermind_ast::Stmt],
+    types: &HashMap<NodeId, Type>,
+    options: &CompileOptions,
+) -> lowering::Result<MirProgram> {
+    lower_continuation_with_types(&[], ast_program, types, options)
+}
+
+/// Lower statements that follow `earlier`, a program already lowered and
+/// run, as each input to the REPL does. Only `ast_program` is lowered, but
+/// it can use the functions and extend the classes `earlier` declares.
+pub fn lower_continuation_with_types(
+    earlier: &[nevermind_ast::Stmt],
+    ast_program: &[nevermind_ast::Stmt],
+    types: &HashMap<NodeId, Type>,
+    options: &CompileOptions,
+) -> lowering::Result<MirProgram> {
+    let mut program = lower_statements(earlier, ast_program, Some(types))?;
+    typing::annotate_program(&mut program, types);
+    if options.optimize {
+        optimize::fold_constants(&mut program);
+    }
+    Ok(program)
+}
+
+fn lower_statements(
+    earlier: &[nevermind_ast::Stmt],
+    ast_program: &[nevermind_ast::Stmt],
+    types: Option<&HashMap<NodeId, Type>>,
+) -> lowering::Result<MirProgram> {
     let mut program = MirProgram::new();
+    let mut cx = lowering::Context::new(earlier.iter().chain(ast_program));
+    if let Some(types) = types {
+        cx = cx.with_types(types);
+    }
 
     for stmt in ast_program {
-        let mir_stmt = lowering::lower_statement(stmt)?;
+        let mir_stmt = lowering::lower_statement(&cx, stmt)?;
         program.add_statement(mir_stmt);
     }
+    program.fresh_names = cx.fresh_names();
 
     Ok(program)
 }
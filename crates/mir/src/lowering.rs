@@ -1,11 +1,16 @@
 //! MIR lowering - convert typed AST to MIR
 
 use super::{
-    BinOp, Literal, MirBlock, MirExpr, MirExprStmt, MirMatchArm, MirPattern, MirStmt, NodeId,
-    Param, UnaryOp,
+    BinOp, Literal, MirBlock, MirExpr, MirExprStmt, MirField, MirFunction, MirMatchArm, MirPattern,
+    MirStmt, MirStringPart, NodeId, Param, UnaryOp,
 };
-use nevermind_ast::{Expr, Pattern, Stmt};
+use nevermind_ast::stmt::ClassMember;
+use nevermind_ast::{Expr, Pattern, Stmt, StringPart};
 use nevermind_type_checker::Type;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
 /// Error during MIR lowering
 #[derive(Debug, thiserror::Error)]
@@ -19,10 +24,153 @@ pub enum LoweringError {
 
 pub type Result<T> = std::result::Result<T, LoweringError>;
 
+/// What lowering a statement needs to know about the program around it
+#[derive(Debug, Clone, Default)]
+pub struct Context<'t> {
+    /// The top-level classes of the program
+    classes: HashMap<String, ClassInfo>,
+    /// Inside a method, the members of its class that no local hides. They
+    /// are read and assigned through the method's `self` parameter.
+    members: HashSet<String>,
+    /// The program's top-level functions, which shadow builtins such as
+    /// `print` of the same name
+    functions: HashSet<String>,
+    /// The types the checker inferred, by node, when lowering has them
+    types: Option<&'t HashMap<NodeId, Type>>,
+    /// Shared by every scope of the program, so no two nodes or
+    /// temporaries are given the same name
+    names: Rc<FreshNames>,
+}
+
+/// The members a class declares, and the class it extends
+#[derive(Debug, Clone)]
+struct ClassInfo {
+    extends: Option<String>,
+    members: Vec<String>,
+    /// Fields without a default, which the constructor takes in order
+    required_fields: Vec<String>,
+}
+
+impl<'t> Context<'t> {
+    /// The context for lowering the statements of `program`
+    pub fn new<'a>(program: impl IntoIterator<Item = &'a Stmt>) -> Self {
+        let mut classes = HashMap::new();
+        let mut functions = HashSet::new();
+        for stmt in program {
+            let stmt = match stmt {
+                Stmt::Export { stmt, .. } => stmt.as_ref(),
+                stmt => stmt,
+            };
+            if let Stmt::Function { name, .. } = stmt {
+                functions.insert(name.clone());
+            }
+            let Stmt::Class {
+                name,
+                extends,
+                members,
+                ..
+            } = stmt
+            else {
+                continue;
+            };
+            let info = ClassInfo {
+                extends: extends.clone(),
+                members: members.iter().map(member_name).cloned().collect(),
+                required_fields: members
+                    .iter()
+                    .filter_map(|member| match member {
+                        ClassMember::Field {
+                            name,
+                            default_value: None,
+                            ..
+                        } => Some(name.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+            };
+            classes.insert(name.clone(), info);
+        }
+        Self {
+            classes,
+            members: HashSet::new(),
+            functions,
+            types: None,
+            names: Rc::default(),
+        }
+    }
+
+    /// The ids and temporaries handed out so far
+    pub fn fresh_names(&self) -> FreshNames {
+        FreshNames::clone(&self.names)
+    }
+
+    fn fresh_node_id(&self) -> NodeId {
+        self.names.node_id()
+    }
+
+    fn fresh_temp(&self, prefix: &str) -> String {
+        self.names.temp(prefix)
+    }
+
+    /// Type the temporaries lowering introduces after the checker's `types`
+    /// (see `TypeChecker::node_types`)
+    pub fn with_types(mut self, types: &'t HashMap<NodeId, Type>) -> Self {
+        self.types = Some(types);
+        self
+    }
+
+    /// The type the checker inferred for `expr`, if it is known
+    fn checked_type(&self, expr: &Expr) -> Option<Type> {
+        self.types?.get(&expr.id()?).cloned()
+    }
+
+    /// The classes `class` extends, nearest first, as far as they are
+    /// declared in the program
+    fn superclasses(&self, class: Option<&String>) -> Vec<&ClassInfo> {
+        let mut chain = Vec::new();
+        let mut visited = HashSet::new();
+        let mut current = class;
+        while let Some(name) = current.filter(|name| visited.insert(*name)) {
+            let Some(info) = self.classes.get(name) else {
+                break;
+            };
+            chain.push(info);
+            current = info.extends.as_ref();
+        }
+        chain
+    }
+
+    /// The context inside a scope that binds `names`, which hide any
+    /// members of the same names
+    fn hiding<'a, I>(&self, names: I) -> Cow<'_, Self>
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        let hidden: Vec<&String> = names
+            .into_iter()
+            .filter(|name| self.members.contains(*name))
+            .collect();
+        if hidden.is_empty() {
+            return Cow::Borrowed(self);
+        }
+        let mut scope = self.clone();
+        for name in hidden {
+            scope.members.remove(name);
+        }
+        Cow::Owned(scope)
+    }
+}
+
+fn member_name(member: &ClassMember) -> &String {
+    match member {
+        ClassMember::Field { name, .. } | ClassMember::Method { name, .. } => name,
+    }
+}
+
 /// Lower a typed AST statement to MIR
-pub fn lower_statement(stmt: &Stmt) -> Result<MirStmt> {
+pub fn lower_statement(cx: &Context, stmt: &Stmt) -> Result<MirStmt> {
     match stmt {
-        Stmt::Export { stmt, .. } => lower_statement(stmt),
+        Stmt::Export { stmt, .. } => lower_statement(cx, stmt),
         Stmt::Let {
             name,
             value,
@@ -30,7 +178,7 @@ pub fn lower_statement(stmt: &Stmt) -> Result<MirStmt> {
             type_annotation,
             ..
         } => {
-            let mir_value = lower_expression(value)?;
+            let mir_value = lower_expression(cx, value)?;
             let mir_type = type_annotation
                 .as_ref()
                 .and_then(resolve_type_annotation)
@@ -40,11 +188,12 @@ pub fn lower_statement(stmt: &Stmt) -> Result<MirStmt> {
                 name: name.clone(),
                 value: mir_value,
                 ty: mir_type,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
         Stmt::Function {
+            is_async,
             name,
             params,
             body,
@@ -66,19 +215,21 @@ pub fn lower_statement(stmt: &Stmt) -> Result<MirStmt> {
                 })
                 .collect::<Result<Vec<_>>>()?;
 
-            let mir_block = lower_function_body(body)?;
+            let scope = cx.hiding(params.iter().map(|p| &p.name));
+            let mir_block = lower_function_body(&scope, body)?;
 
             Ok(MirStmt::Function {
+                is_async: *is_async,
                 name: name.clone(),
                 params: mir_params,
                 body: mir_block,
                 return_type: Type::Unit,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
         Stmt::ExprStmt { expr, .. } => {
-            let mir_expr = lower_expression(expr)?;
+            let mir_expr = lower_expression(cx, expr)?;
             Ok(MirStmt::Expr(mir_expr))
         }
 
@@ -88,29 +239,29 @@ pub fn lower_statement(stmt: &Stmt) -> Result<MirStmt> {
             else_branch,
             ..
         } => {
-            let mir_cond = lower_expression(condition)?;
-            let mir_then = lower_statements(then_branch)?;
+            let mir_cond = lower_expression(cx, condition)?;
+            let mir_then = lower_statements(cx, then_branch)?;
             let mir_else = match else_branch {
-                Some(stmts) => Some(lower_statements(stmts)?),
+                Some(stmts) => Some(lower_statements(cx, stmts)?),
                 None => None,
             };
             Ok(MirStmt::If {
                 condition: mir_cond,
                 then_body: mir_then,
                 else_body: mir_else,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
         Stmt::While {
             condition, body, ..
         } => {
-            let mir_cond = lower_expression(condition)?;
-            let mir_body = lower_statements(body)?;
+            let mir_cond = lower_expression(cx, condition)?;
+            let mir_body = lower_statements(cx, body)?;
             Ok(MirStmt::While {
                 condition: mir_cond,
                 body: mir_body,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
@@ -120,50 +271,85 @@ pub fn lower_statement(stmt: &Stmt) -> Result<MirStmt> {
             body,
             ..
         } => {
-            let var_name = pattern_to_variable_name(variable);
-            let mir_iter = lower_expression(iter)?;
-            let mir_body = lower_statements(body)?;
+            let pattern = lower_for_pattern(cx, variable)?;
+            let mir_iter = lower_expression(cx, iter)?;
+            let mir_body = lower_statements(&cx.hiding(&variable.collect_variables()), body)?;
             Ok(MirStmt::For {
-                variable: var_name,
+                pattern,
                 iter: mir_iter,
                 body: mir_body,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
         Stmt::Return { value, .. } => {
             let mir_value = match value {
-                Some(v) => Some(lower_expression(v)?),
+                Some(v) => Some(lower_expression(cx, v)?),
                 None => None,
             };
             Ok(MirStmt::Return {
                 value: mir_value,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
+        Stmt::Try {
+            body,
+            catch_var,
+            catch_body,
+            finally_body,
+            ..
+        } => Ok(MirStmt::Try {
+            body: lower_statements(cx, body)?,
+            catch_var: catch_var.clone(),
+            catch_body: catch_body
+                .as_deref()
+                .map(|body| lower_statements(&cx.hiding(catch_var), body))
+                .transpose()?,
+            finally_body: finally_body
+                .as_deref()
+                .map(|body| lower_statements(cx, body))
+                .transpose()?,
+            id: cx.fresh_node_id(),
+        }),
+
+        Stmt::Raise { value, .. } => Ok(MirStmt::Raise {
+            value: lower_expression(cx, value)?,
+            id: cx.fresh_node_id(),
+        }),
+
         Stmt::Break { .. } => Ok(MirStmt::Break {
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         }),
 
         Stmt::Continue { .. } => Ok(MirStmt::Continue {
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         }),
 
         Stmt::Match {
             scrutinee, arms, ..
         } => {
-            let mir_scrutinee = lower_expression(scrutinee)?;
+            let (binding, subject) = bind_scrutinee(cx, lower_expression(cx, scrutinee)?);
+            let mir_scrutinee = match binding {
+                Some(binding) => MirExpr::Block {
+                    ty: subject.get_type().clone(),
+                    statements: vec![binding],
+                    expr: Some(Box::new(subject)),
+                    id: cx.fresh_node_id(),
+                },
+                None => subject,
+            };
             let mir_arms = arms
                 .iter()
                 .map(|arm| {
-                    let pattern = lower_pattern(&arm.pattern)?;
+                    let pattern = lower_pattern(cx, &arm.pattern)?;
+                    let scope = cx.hiding(&arm.pattern.collect_variables());
                     let guard = match &arm.guard {
-                        Some(g) => Some(lower_expression(g)?),
+                        Some(g) => Some(lower_expression(&scope, g)?),
                         None => None,
                     };
                     // Match arm body is an Expr; wrap it as a single Expr statement
-                    let body_expr = lower_expression(&arm.body)?;
+                    let body_expr = lower_expression(&scope, &arm.body)?;
                     let body = vec![MirStmt::Expr(body_expr)];
                     Ok(MirMatchArm {
                         pattern,
@@ -175,74 +361,154 @@ pub fn lower_statement(stmt: &Stmt) -> Result<MirStmt> {
             Ok(MirStmt::Match {
                 scrutinee: mir_scrutinee,
                 arms: mir_arms,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
         Stmt::Import {
-            module, symbols, ..
+            module,
+            symbols,
+            alias,
+            ..
         } => Ok(MirStmt::Import {
             module: module.clone(),
             symbols: symbols.clone(),
-            id: fresh_node_id(),
+            alias: alias.clone(),
+            id: cx.fresh_node_id(),
         }),
 
-        // Type aliases and class declarations don't produce runtime code.
-        Stmt::TypeAlias { .. } | Stmt::Class { .. } => Ok(MirStmt::Expr(MirExpr::Literal {
+        Stmt::Class {
+            name,
+            extends,
+            members,
+            ..
+        } => lower_class(cx, name, extends.as_ref(), members),
+
+        // Type aliases don't produce runtime code.
+        Stmt::TypeAlias { .. } => Ok(MirStmt::Expr(MirExpr::Literal {
             value: Literal::Null,
             ty: Type::Unit,
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         })),
     }
 }
 
+/// Lower a class declaration. Inside its methods, the members of the class
+/// and of the classes it extends are read and assigned through the `self`
+/// parameter each method gains.
+fn lower_class(
+    cx: &Context,
+    name: &str,
+    extends: Option<&String>,
+    members: &[ClassMember],
+) -> Result<MirStmt> {
+    let superclasses = cx.superclasses(extends);
+    let mut member_names: HashSet<String> = members.iter().map(member_name).cloned().collect();
+    for superclass in &superclasses {
+        member_names.extend(superclass.members.iter().cloned());
+    }
+    // The base constructor's parameters: the required fields of every
+    // superclass, the most distant first
+    let inherited = superclasses
+        .iter()
+        .rev()
+        .flat_map(|superclass| superclass.required_fields.iter().cloned())
+        .collect();
+
+    let mut fields = Vec::new();
+    for member in members {
+        if let ClassMember::Field {
+            name,
+            type_annotation,
+            default_value,
+            ..
+        } = member
+        {
+            fields.push(MirField {
+                name: name.clone(),
+                default: default_value
+                    .as_ref()
+                    .map(|value| lower_expression(cx, value))
+                    .transpose()?,
+                ty: type_annotation
+                    .as_ref()
+                    .and_then(resolve_type_annotation)
+                    .unwrap_or(Type::Unit),
+            });
+        }
+    }
+
+    let class_scope = Context {
+        members: member_names,
+        ..cx.clone()
+    };
+    let mut methods = Vec::new();
+    for member in members {
+        let ClassMember::Method {
+            name: method_name,
+            params,
+            body,
+            ..
+        } = member
+        else {
+            continue;
+        };
+
+        let mut mir_params = vec![Param {
+            name: "self".to_string(),
+            ty: Type::User(name.to_string()),
+            id: cx.fresh_node_id(),
+        }];
+        mir_params.extend(params.iter().map(|p| {
+            Param {
+                name: p.name.clone(),
+                ty: p
+                    .type_annotation
+                    .as_ref()
+                    .and_then(resolve_type_annotation)
+                    .unwrap_or(Type::Unit),
+                id: p.id,
+            }
+        }));
+
+        // Parameters hide members of the same name
+        let scope = class_scope.hiding(params.iter().map(|p| &p.name));
+        methods.push(MirFunction::new(
+            method_name.clone(),
+            mir_params,
+            lower_function_body(&scope, body)?,
+            Type::Unit,
+            cx.fresh_node_id(),
+        ));
+    }
+
+    Ok(MirStmt::Class {
+        name: name.to_string(),
+        extends: extends.cloned(),
+        inherited,
+        fields,
+        methods,
+        id: cx.fresh_node_id(),
+    })
+}
+
 /// Lower a function body expression to a MIR block
 /// If the body is a Block, flattens its statements into the MirBlock
-fn lower_function_body(body: &Expr) -> Result<MirBlock> {
+fn lower_function_body(cx: &Context, body: &Expr) -> Result<MirBlock> {
     match body {
-        Expr::Block { statements, .. } => {
-            let mut mir_stmts = Vec::new();
-            let mut final_expr = None;
-
-            for (i, stmt) in statements.iter().enumerate() {
-                let is_last = i == statements.len() - 1;
-
-                // If the last statement is an ExprStmt, it becomes the block's return expression
-                if is_last {
-                    if let Stmt::ExprStmt { expr, .. } = stmt {
-                        let mir_expr = lower_expression(expr)?;
-                        // Only treat as return value if it's not a call to a void function (like print)
-                        // For now, we check if it's a Call to "print" - those are statements
-                        if is_void_call(expr) {
-                            mir_stmts.push(MirExprStmt::Expr(mir_expr));
-                        } else {
-                            final_expr = Some(Box::new(mir_expr));
-                        }
-                    } else {
-                        mir_stmts.push(lower_expr_stmt(stmt)?);
-                    }
-                } else {
-                    mir_stmts.push(lower_expr_stmt(stmt)?);
-                }
-            }
-
-            Ok(MirBlock {
-                statements: mir_stmts,
-                expr: final_expr,
-            })
-        }
+        Expr::Block { statements, .. } => lower_block(cx, statements),
         _ => {
             // Non-block body
-            if is_void_call(body) {
+            if is_void_call(cx, body) {
                 // Void calls go in statements, not as return expression
-                let mir_expr = lower_expression(body)?;
+                let mir_expr = lower_expression(cx, body)?;
                 Ok(MirBlock {
                     statements: vec![MirExprStmt::Expr(mir_expr)],
                     expr: None,
                 })
             } else {
                 // Everything else becomes the return expression
-                let mir_expr = lower_expression(body)?;
+                let mir_expr = lower_expression(cx, body)?;
                 Ok(MirBlock {
                     statements: Vec::new(),
                     expr: Some(Box::new(mir_expr)),
@@ -252,13 +518,50 @@ fn lower_function_body(body: &Expr) -> Result<MirBlock> {
     }
 }
 
+/// Lower the statements of a block, keeping a trailing expression statement
+/// as the block's value
+fn lower_block(cx: &Context, statements: &[Stmt]) -> Result<MirBlock> {
+    let mut mir_stmts = Vec::new();
+    let mut final_expr = None;
+    let mut scope = Cow::Borrowed(cx);
+
+    for (i, stmt) in statements.iter().enumerate() {
+        let is_last = i == statements.len() - 1;
+
+        // If the last statement is an ExprStmt, it becomes the block's return expression
+        if is_last {
+            if let Stmt::ExprStmt { expr, .. } = stmt {
+                let mir_expr = lower_expression(&scope, expr)?;
+                // Only treat as return value if it's not a call to a void function (like print)
+                // For now, we check if it's a Call to "print" - those are statements
+                if is_void_call(&scope, expr) {
+                    mir_stmts.push(MirExprStmt::Expr(mir_expr));
+                } else {
+                    final_expr = Some(Box::new(mir_expr));
+                }
+            } else {
+                mir_stmts.push(lower_expr_stmt(&scope, stmt)?);
+            }
+        } else {
+            mir_stmts.push(lower_expr_stmt(&scope, stmt)?);
+        }
+        hide_local(&mut scope, stmt);
+    }
+
+    Ok(MirBlock {
+        statements: mir_stmts,
+        expr: final_expr,
+    })
+}
+
 /// Check if an expression should be treated as a pure statement (no return value).
 /// This includes void built-in calls and all assignment expressions.
-fn is_void_call(expr: &Expr) -> bool {
+fn is_void_call(cx: &Context, expr: &Expr) -> bool {
     match expr {
         Expr::Call { callee, .. } => {
             if let Expr::Variable { name, .. } = callee.as_ref() {
-                matches!(name.as_str(), "print" | "println")
+                matches!(name.as_str(), "print" | "println" | "assert")
+                    && !cx.functions.contains(name)
             } else {
                 false
             }
@@ -270,73 +573,109 @@ fn is_void_call(expr: &Expr) -> bool {
 }
 
 /// Lower a vector of AST statements to MIR statements
-fn lower_statements(stmts: &[Stmt]) -> Result<Vec<MirStmt>> {
-    stmts.iter().map(lower_statement).collect()
+fn lower_statements(cx: &Context, stmts: &[Stmt]) -> Result<Vec<MirStmt>> {
+    stmts.iter().map(|stmt| lower_statement(cx, stmt)).collect()
 }
 
 /// Lower a vector of AST statements to MIR expression-level statements (for inside blocks)
-fn lower_expr_stmts(stmts: &[Stmt]) -> Result<Vec<MirExprStmt>> {
-    stmts.iter().map(lower_expr_stmt).collect()
+fn lower_expr_stmts(cx: &Context, stmts: &[Stmt]) -> Result<Vec<MirExprStmt>> {
+    let mut scope = Cow::Borrowed(cx);
+    let mut mir_stmts = Vec::with_capacity(stmts.len());
+    for stmt in stmts {
+        mir_stmts.push(lower_expr_stmt(&scope, stmt)?);
+        hide_local(&mut scope, stmt);
+    }
+    Ok(mir_stmts)
 }
 
-/// Extract a variable name from a pattern (for for-loops)
-fn pattern_to_variable_name(pattern: &Pattern) -> String {
+/// Hide any member that `stmt` declares a local of the same name as from
+/// the statements after it
+fn hide_local(scope: &mut Cow<'_, Context>, stmt: &Stmt) {
+    let (Stmt::Let { name, .. } | Stmt::Function { name, .. }) = stmt else {
+        return;
+    };
+    if scope.members.contains(name) {
+        scope.to_mut().members.remove(name);
+    }
+}
+
+/// Lower the pattern a for-loop binds each item to, which can't fail to
+/// match: a variable, a wildcard, or a tuple of those
+fn lower_for_pattern(cx: &Context, pattern: &Pattern) -> Result<MirPattern> {
     match pattern {
-        Pattern::Variable { name, .. } => name.clone(),
-        Pattern::Wildcard { .. } => "_".to_string(),
-        _ => "_".to_string(),
+        Pattern::Variable { .. } | Pattern::Wildcard { .. } => lower_pattern(cx, pattern),
+        Pattern::Tuple { patterns, .. } => Ok(MirPattern::Tuple {
+            patterns: patterns
+                .iter()
+                .map(|pattern| lower_for_pattern(cx, pattern))
+                .collect::<Result<Vec<_>>>()?,
+            id: cx.fresh_node_id(),
+        }),
+        _ => Err(LoweringError::UnsupportedNode(
+            "refutable pattern in a for loop".to_string(),
+        )),
     }
 }
 
 /// Lower an AST pattern to a MIR pattern
-fn lower_pattern(pattern: &Pattern) -> Result<MirPattern> {
+fn lower_pattern(cx: &Context, pattern: &Pattern) -> Result<MirPattern> {
     match pattern {
         Pattern::Wildcard { .. } => Ok(MirPattern::Wildcard {
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         }),
         Pattern::Variable { name, .. } => Ok(MirPattern::Variable {
             name: name.clone(),
             ty: Type::Unit,
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         }),
         Pattern::Literal { value, .. } => {
             let lit = lower_ast_literal(value)?;
             Ok(MirPattern::Literal {
                 value: lit,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
         Pattern::Constructor { name, args, .. } => {
-            let mir_args = args.iter().map(lower_pattern).collect::<Result<Vec<_>>>()?;
+            let mir_args = args
+                .iter()
+                .map(|pattern| lower_pattern(cx, pattern))
+                .collect::<Result<Vec<_>>>()?;
             Ok(MirPattern::Constructor {
                 name: name.clone(),
                 args: mir_args,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
+        Pattern::Tuple { patterns, .. } => Ok(MirPattern::Tuple {
+            patterns: patterns
+                .iter()
+                .map(|pattern| lower_pattern(cx, pattern))
+                .collect::<Result<Vec<_>>>()?,
+            id: cx.fresh_node_id(),
+        }),
         Pattern::List { patterns, .. } => {
             let mir_patterns = patterns
                 .iter()
-                .map(lower_pattern)
+                .map(|pattern| lower_pattern(cx, pattern))
                 .collect::<Result<Vec<_>>>()?;
             Ok(MirPattern::List {
                 patterns: mir_patterns,
                 rest: None,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
         Pattern::Or { patterns, .. } => {
             // Lower first pattern as fallback
             if let Some(first) = patterns.first() {
-                lower_pattern(first)
+                lower_pattern(cx, first)
             } else {
                 Ok(MirPattern::Wildcard {
-                    id: fresh_node_id(),
+                    id: cx.fresh_node_id(),
                 })
             }
         }
         _ => Ok(MirPattern::Wildcard {
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         }),
     }
 }
@@ -349,21 +688,31 @@ fn lower_ast_literal(lit: &nevermind_ast::expr::Literal) -> Result<Literal> {
         nevermind_ast::expr::Literal::String(v, _) => Ok(Literal::String(v.clone())),
         nevermind_ast::expr::Literal::Boolean(v, _) => Ok(Literal::Bool(*v)),
         nevermind_ast::expr::Literal::Null(_) => Ok(Literal::Null),
-        nevermind_ast::expr::Literal::Char(_, _) => Ok(Literal::Int(0)),
+        nevermind_ast::expr::Literal::Char(v, _) => Ok(Literal::Char(*v)),
     }
 }
 
 /// Lower a typed AST expression to MIR
-pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
+pub fn lower_expression(cx: &Context, expr: &Expr) -> Result<MirExpr> {
     match expr {
-        Expr::Literal(literal) => lower_literal(literal),
+        Expr::Literal(literal) => lower_literal(cx, literal),
 
-        Expr::Variable { name, id, .. } => Ok(MirExpr::Variable {
-            name: name.clone(),
+        // Python spells the unit value `None`
+        Expr::Unit { id, .. } => Ok(MirExpr::Literal {
+            value: Literal::Null,
             ty: Type::Unit,
             id: *id,
         }),
 
+        Expr::Variable { name, id, .. } if cx.members.contains(name) => Ok(MirExpr::FieldAccess {
+            object: Box::new(self_variable(cx)),
+            field: name.clone(),
+            ty: Type::Unit,
+            id: *id,
+        }),
+
+        Expr::Variable { name, id, .. } => Ok(variable(name, *id)),
+
         Expr::Binary {
             left,
             op,
@@ -371,8 +720,8 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
             id,
             ..
         } => {
-            let mir_left = Box::new(lower_expression(left)?);
-            let mir_right = Box::new(lower_expression(right)?);
+            let mir_left = Box::new(lower_expression(cx, left)?);
+            let mir_right = Box::new(lower_expression(cx, right)?);
             let mir_op = map_binary_op(op);
 
             Ok(MirExpr::Binary {
@@ -391,8 +740,8 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
             id,
             ..
         } => {
-            let mir_left = Box::new(lower_expression(left)?);
-            let mir_right = Box::new(lower_expression(right)?);
+            let mir_left = Box::new(lower_expression(cx, left)?);
+            let mir_right = Box::new(lower_expression(cx, right)?);
             let mir_op = map_comparison_op(op);
 
             Ok(MirExpr::Binary {
@@ -411,8 +760,20 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
             id,
             ..
         } => {
-            let mir_left = Box::new(lower_expression(left)?);
-            let mir_right = Box::new(lower_expression(right)?);
+            if let Some((operands, ops)) = comparison_chain(expr) {
+                let mut values = operands.into_iter().map(|expr| lower_expression(cx, expr));
+                let first = values.next().unwrap()?;
+                let rest = ops
+                    .into_iter()
+                    .map(map_comparison_op)
+                    .zip(values)
+                    .map(|(op, value)| Ok((op, value?)))
+                    .collect::<Result<Vec<_>>>()?;
+                return Ok(chain_comparisons(cx, first, rest));
+            }
+
+            let mir_left = Box::new(lower_expression(cx, left)?);
+            let mir_right = Box::new(lower_expression(cx, right)?);
             let mir_op = map_logical_op(op);
 
             Ok(MirExpr::Binary {
@@ -425,8 +786,8 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
         }
 
         Expr::Unary { op, expr, id, .. } => {
-            let mir_operand = Box::new(lower_expression(expr)?);
-            let mir_op = map_unary_op(op);
+            let mir_operand = Box::new(lower_expression(cx, expr)?);
+            let mir_op = map_unary_op(op)?;
 
             Ok(MirExpr::Unary {
                 op: mir_op,
@@ -436,15 +797,57 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
             })
         }
 
+        Expr::Await { expr, id, .. } => {
+            let mir_expr = lower_expression(cx, expr)?;
+            Ok(MirExpr::Await {
+                ty: mir_expr.get_type().clone(),
+                expr: Box::new(mir_expr),
+                id: *id,
+            })
+        }
+
         Expr::Call {
-            callee, args, id, ..
+            callee,
+            args,
+            id,
+            span,
         } => {
-            let mir_callee = Box::new(lower_expression(callee)?);
-            let mir_args = args
+            let mir_callee = Box::new(lower_expression(cx, callee)?);
+            let mut mir_args = args
                 .iter()
-                .map(lower_expression)
+                .map(|expr| lower_expression(cx, expr))
                 .collect::<Result<Vec<_>>>()?;
 
+            // A failed assertion's message says which line it's on
+            if matches!(callee.as_ref(), Expr::Variable { name, .. } if name == "assert")
+                && !cx.functions.contains("assert")
+            {
+                let location = format!("line {}: ", span.start.line);
+                match mir_args.pop() {
+                    Some(MirExpr::Literal {
+                        value: Literal::String(text),
+                        id,
+                        ..
+                    }) => mir_args.push(MirExpr::Literal {
+                        value: Literal::String(location + &text),
+                        ty: Type::String,
+                        id,
+                    }),
+                    Some(message) => mir_args.push(MirExpr::Binary {
+                        op: BinOp::Add,
+                        left: Box::new(MirExpr::Literal {
+                            value: Literal::String(location),
+                            ty: Type::String,
+                            id: cx.fresh_node_id(),
+                        }),
+                        right: Box::new(message),
+                        ty: Type::String,
+                        id: cx.fresh_node_id(),
+                    }),
+                    None => {}
+                }
+            }
+
             Ok(MirExpr::Call {
                 callee: mir_callee,
                 args: mir_args,
@@ -454,23 +857,36 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
         }
 
         Expr::Block { statements, id, .. } => {
-            let mut mir_statements = Vec::new();
-            for stmt in statements {
-                mir_statements.push(lower_expr_stmt(stmt)?);
-            }
+            let block = lower_block(cx, statements)?;
 
             Ok(MirExpr::Block {
-                statements: mir_statements,
-                expr: None,
+                statements: block.statements,
+                expr: block.expr,
                 ty: Type::Unit,
                 id: *id,
             })
         }
 
+        Expr::StringInterp { parts, id, .. } => {
+            let mir_parts = parts
+                .iter()
+                .map(|part| match part {
+                    StringPart::Literal(text) => Ok(MirStringPart::Literal(text.clone())),
+                    StringPart::Expr(expr) => Ok(MirStringPart::Expr(lower_expression(cx, expr)?)),
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(MirExpr::StringInterp {
+                parts: mir_parts,
+                ty: Type::String,
+                id: *id,
+            })
+        }
+
         Expr::List { elements, id, .. } => {
             let mir_elements = elements
                 .iter()
-                .map(lower_expression)
+                .map(|expr| lower_expression(cx, expr))
                 .collect::<Result<Vec<_>>>()?;
 
             Ok(MirExpr::List {
@@ -480,6 +896,19 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
             })
         }
 
+        Expr::Tuple { elements, id, .. } => {
+            let mir_elements = elements
+                .iter()
+                .map(|expr| lower_expression(cx, expr))
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(MirExpr::Tuple {
+                ty: Type::Tuple(mir_elements.iter().map(|e| e.get_type().clone()).collect()),
+                elements: mir_elements,
+                id: *id,
+            })
+        }
+
         Expr::If {
             condition,
             then_branch,
@@ -487,9 +916,9 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
             id,
             ..
         } => {
-            let mir_condition = Box::new(lower_expression(condition)?);
-            let mir_then = Box::new(lower_expression(then_branch)?);
-            let mir_else = Box::new(lower_expression(else_branch)?);
+            let mir_condition = Box::new(lower_expression(cx, condition)?);
+            let mir_then = Box::new(lower_expression(cx, then_branch)?);
+            let mir_else = Box::new(lower_expression(cx, else_branch)?);
 
             Ok(MirExpr::If {
                 condition: mir_condition,
@@ -503,8 +932,8 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
         Expr::Index {
             array, index, id, ..
         } => {
-            let mir_array = Box::new(lower_expression(array)?);
-            let mir_index = Box::new(lower_expression(index)?);
+            let mir_array = Box::new(lower_expression(cx, array)?);
+            let mir_index = Box::new(lower_expression(cx, index)?);
 
             Ok(MirExpr::Index {
                 array: mir_array,
@@ -514,34 +943,123 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
             })
         }
 
+        Expr::Range {
+            start,
+            end,
+            inclusive,
+            id,
+            ..
+        } => Ok(MirExpr::Range {
+            start: Box::new(lower_expression(cx, start)?),
+            end: Box::new(lower_expression(cx, end)?),
+            inclusive: *inclusive,
+            ty: Type::List(Box::new(Type::Int)),
+            id: *id,
+        }),
+
+        Expr::Slice {
+            array,
+            start,
+            end,
+            inclusive,
+            id,
+            ..
+        } => Ok(MirExpr::Slice {
+            array: Box::new(lower_expression(cx, array)?),
+            start: Box::new(lower_expression(cx, start)?),
+            end: Box::new(lower_expression(cx, end)?),
+            inclusive: *inclusive,
+            ty: Type::Unit,
+            id: *id,
+        }),
+
         Expr::Assign {
-            target, value, id, ..
+            target,
+            op,
+            value,
+            id,
+            ..
         } => {
-            let mir_value = lower_expression(value)?;
-            if let Expr::Variable { name, .. } = target.as_ref() {
+            let mir_op = op.as_ref().map(map_binary_op);
+            let mir_value = lower_expression(cx, value)?;
+            let field_target = match target.as_ref() {
+                Expr::Variable { name, .. } if cx.members.contains(name) => {
+                    Some((self_variable(cx), name))
+                }
+                Expr::MemberAccess { object, member, .. } if member.parse::<usize>().is_err() => {
+                    Some((lower_expression(cx, object)?, member))
+                }
+                _ => None,
+            };
+            if let Some((object, field)) = field_target {
+                Ok(MirExpr::Block {
+                    statements: vec![MirExprStmt::FieldAssign {
+                        object,
+                        field: field.clone(),
+                        op: mir_op,
+                        value: mir_value,
+                        id: *id,
+                    }],
+                    expr: None,
+                    ty: Type::Unit,
+                    id: cx.fresh_node_id(),
+                })
+            } else if let Expr::Variable { name, .. } = target.as_ref() {
                 Ok(MirExpr::Block {
                     statements: vec![MirExprStmt::Assign {
                         target: name.clone(),
+                        op: mir_op,
                         value: mir_value,
                         id: *id,
                     }],
                     expr: None,
                     ty: Type::Unit,
-                    id: fresh_node_id(),
+                    id: cx.fresh_node_id(),
                 })
             } else if let Expr::Index { array, index, .. } = target.as_ref() {
                 if let Expr::Variable { name, .. } = array.as_ref() {
-                    let mir_index = lower_expression(index)?;
+                    let mut statements = Vec::new();
+                    let mut mir_index = lower_expression(cx, index)?;
+
+                    // A compound assignment reads and writes the same slot, so
+                    // evaluate a non-trivial index once into a temporary.
+                    if mir_op.is_some()
+                        && !matches!(
+                            mir_index,
+                            MirExpr::Variable { .. } | MirExpr::Literal { .. }
+                        )
+                    {
+                        // Keyed by whatever the index is, so a map's key
+                        // keeps its own type
+                        let temp = cx.fresh_temp("index");
+                        let ty = cx
+                            .checked_type(index)
+                            .unwrap_or_else(|| mir_index.get_type().clone());
+                        statements.push(MirExprStmt::Let {
+                            name: temp.clone(),
+                            value: mir_index,
+                            ty: ty.clone(),
+                            id: cx.fresh_node_id(),
+                        });
+                        mir_index = MirExpr::Variable {
+                            name: temp,
+                            ty,
+                            id: cx.fresh_node_id(),
+                        };
+                    }
+
+                    statements.push(MirExprStmt::IndexAssign {
+                        array: name.clone(),
+                        index: mir_index,
+                        op: mir_op,
+                        value: mir_value,
+                        id: *id,
+                    });
                     Ok(MirExpr::Block {
-                        statements: vec![MirExprStmt::IndexAssign {
-                            array: name.clone(),
-                            index: mir_index,
-                            value: mir_value,
-                            id: *id,
-                        }],
+                        statements,
                         expr: None,
                         ty: Type::Unit,
-                        id: fresh_node_id(),
+                        id: cx.fresh_node_id(),
                     })
                 } else {
                     Ok(mir_value)
@@ -554,20 +1072,26 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
         Expr::MemberAccess {
             object, member, id, ..
         } => {
-            let mir_obj = lower_expression(object)?;
-            if let MirExpr::Variable { name, ty, .. } = &mir_obj {
-                Ok(MirExpr::Variable {
-                    name: format!("{}.{}", name, member),
-                    ty: ty.clone(),
-                    id: *id,
-                })
-            } else {
-                Ok(MirExpr::Variable {
-                    name: member.clone(),
+            let mir_obj = lower_expression(cx, object)?;
+            // `t.0` on a tuple is plain positional indexing
+            if let Ok(position) = member.parse::<i64>() {
+                return Ok(MirExpr::Index {
+                    array: Box::new(mir_obj),
+                    index: Box::new(MirExpr::Literal {
+                        value: Literal::Int(position),
+                        ty: Type::Int,
+                        id: cx.fresh_node_id(),
+                    }),
                     ty: Type::Unit,
                     id: *id,
-                })
+                });
             }
+            Ok(MirExpr::FieldAccess {
+                object: Box::new(mir_obj),
+                field: member.clone(),
+                ty: Type::Unit,
+                id: *id,
+            })
         }
 
         Expr::Pipeline { stages, id: _, .. } => {
@@ -575,17 +1099,27 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
                 return Ok(MirExpr::Literal {
                     value: Literal::Null,
                     ty: Type::Unit,
-                    id: fresh_node_id(),
+                    id: cx.fresh_node_id(),
                 });
             }
-            let mut result = lower_expression(&stages[0])?;
+            let mut result = lower_expression(cx, &stages[0])?;
             for stage in &stages[1..] {
-                let func = lower_expression(stage)?;
+                // A call stage receives the piped value as its first argument
+                let (callee, mut args) = match stage {
+                    Expr::Call { callee, args, .. } => (
+                        lower_expression(cx, callee)?,
+                        args.iter()
+                            .map(|expr| lower_expression(cx, expr))
+                            .collect::<Result<Vec<_>>>()?,
+                    ),
+                    _ => (lower_expression(cx, stage)?, Vec::new()),
+                };
+                args.insert(0, result);
                 result = MirExpr::Call {
-                    callee: Box::new(func),
-                    args: vec![result],
+                    callee: Box::new(callee),
+                    args,
                     ty: Type::Unit,
-                    id: fresh_node_id(),
+                    id: cx.fresh_node_id(),
                 };
             }
             Ok(result)
@@ -595,7 +1129,8 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
             params, body, id, ..
         } => {
             let param_names = params.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
-            let mir_body = Box::new(lower_expression(body)?);
+            let scope = cx.hiding(&param_names);
+            let mir_body = Box::new(lower_expression(&scope, body)?);
             Ok(MirExpr::Lambda {
                 params: param_names,
                 body: mir_body,
@@ -605,117 +1140,410 @@ pub fn lower_expression(expr: &Expr) -> Result<MirExpr> {
         }
 
         Expr::Map { entries, id, .. } => {
-            let mut elements = Vec::new();
+            let mut mir_entries = Vec::new();
             for (key, value) in entries {
-                elements.push(lower_expression(key)?);
-                elements.push(lower_expression(value)?);
+                mir_entries.push((lower_expression(cx, key)?, lower_expression(cx, value)?));
             }
-            Ok(MirExpr::List {
-                elements,
+            Ok(MirExpr::Map {
+                entries: mir_entries,
                 ty: Type::Unit,
                 id: *id,
             })
         }
 
         Expr::Match {
-            scrutinee,
-            arms,
-            id: _,
-            ..
-        } => {
-            // Lower match expression to nested if-else chain
-            let mir_scrutinee = lower_expression(scrutinee)?;
+            scrutinee, arms, ..
+        } => lower_match_expression(cx, scrutinee, arms),
+    }
+}
 
-            if arms.is_empty() {
-                return Ok(mir_scrutinee);
-            }
+/// Lower a match expression to a chain of conditional expressions
+///
+/// Each arm becomes `body if test else <later arms>`, where the test checks the
+/// arm's pattern (and guard) against the scrutinee. Variables bound by a
+/// pattern are passed to the body and guard through an immediately applied
+/// lambda, since a conditional expression cannot hold assignments. When no arm
+/// matches, the chain evaluates to `None`.
+fn lower_match_expression(
+    cx: &Context,
+    scrutinee: &Expr,
+    arms: &[nevermind_ast::MatchArm],
+) -> Result<MirExpr> {
+    let mir_scrutinee = lower_expression(cx, scrutinee)?;
+
+    if arms.is_empty() {
+        return Ok(mir_scrutinee);
+    }
+
+    let (binding, mir_scrutinee) = bind_scrutinee(cx, mir_scrutinee);
+
+    // Build the chain from last arm backwards
+    let mut result: Option<MirExpr> = None;
+
+    for arm in arms.iter().rev() {
+        let mut bindings = Vec::new();
+        let test = lower_pattern_test(cx, &arm.pattern, &mir_scrutinee, &mut bindings)?;
+        let scope = cx.hiding(bindings.iter().map(|(name, _)| name));
+        let body = bind_pattern_variables(cx, lower_expression(&scope, &arm.body)?, &bindings);
+        let guard = match &arm.guard {
+            Some(guard) => Some(bind_pattern_variables(
+                cx,
+                lower_expression(&scope, guard)?,
+                &bindings,
+            )),
+            None => None,
+        };
+
+        result = Some(match conjoin(cx, test, guard) {
+            // Irrefutable and unguarded: later arms are unreachable
+            None => body,
+            Some(condition) => MirExpr::If {
+                condition: Box::new(condition),
+                then_branch: Box::new(body),
+                else_branch: Box::new(result.unwrap_or_else(|| MirExpr::Literal {
+                    value: Literal::Null,
+                    ty: Type::Unit,
+                    id: cx.fresh_node_id(),
+                })),
+                ty: Type::Unit,
+                id: cx.fresh_node_id(),
+            },
+        });
+    }
 
-            // Build the chain from last arm backwards
-            // Last arm should be a wildcard/variable (catch-all) or treated as else
-            let mut result: Option<MirExpr> = None;
+    let chain = result.unwrap_or(mir_scrutinee);
+    Ok(match binding {
+        Some(binding) => MirExpr::Block {
+            ty: chain.get_type().clone(),
+            statements: vec![binding],
+            expr: Some(Box::new(chain)),
+            id: cx.fresh_node_id(),
+        },
+        None => chain,
+    })
+}
 
-            for arm in arms.iter().rev() {
-                let body = lower_expression(&arm.body)?;
+/// Bind a match scrutinee to a fresh temporary, unless it is already a
+/// variable or literal, so that every arm test reads the value computed once.
+/// Returns the binding and the expression the arms should test.
+fn bind_scrutinee(cx: &Context, scrutinee: MirExpr) -> (Option<MirExprStmt>, MirExpr) {
+    bind_once(cx, scrutinee, "match")
+}
 
-                match &arm.pattern {
-                    Pattern::Wildcard { .. } | Pattern::Variable { .. } => {
-                        // Catch-all: this becomes the else branch
-                        result = Some(body);
-                    }
-                    Pattern::Literal { value, .. } => {
-                        let lit_expr = lower_literal(value)?;
-                        let condition = MirExpr::Binary {
-                            op: BinOp::Eq,
-                            left: Box::new(mir_scrutinee.clone()),
-                            right: Box::new(lit_expr),
-                            ty: Type::Bool,
-                            id: fresh_node_id(),
-                        };
-                        let else_branch = result.unwrap_or_else(|| MirExpr::Literal {
-                            value: Literal::Null,
-                            ty: Type::Unit,
-                            id: fresh_node_id(),
-                        });
-                        result = Some(MirExpr::If {
-                            condition: Box::new(condition),
-                            then_branch: Box::new(body),
-                            else_branch: Box::new(else_branch),
-                            ty: Type::Unit,
-                            id: fresh_node_id(),
-                        });
-                    }
-                    _ => {
-                        // For unsupported patterns, treat as catch-all
-                        result = Some(body);
-                    }
+/// Bind `value` to a fresh temporary named after `prefix`, unless it is a
+/// variable or literal that can be read again for free
+fn bind_once(cx: &Context, value: MirExpr, prefix: &str) -> (Option<MirExprStmt>, MirExpr) {
+    if is_trivial(&value) {
+        return (None, value);
+    }
+
+    let temp = cx.fresh_temp(prefix);
+    let ty = value.get_type().clone();
+    let binding = MirExprStmt::Let {
+        name: temp.clone(),
+        value,
+        ty: ty.clone(),
+        id: cx.fresh_node_id(),
+    };
+    let subject = MirExpr::Variable {
+        name: temp,
+        ty,
+        id: cx.fresh_node_id(),
+    };
+    (Some(binding), subject)
+}
+
+fn is_trivial(expr: &MirExpr) -> bool {
+    matches!(expr, MirExpr::Variable { .. } | MirExpr::Literal { .. })
+}
+
+/// The operands and operators of a chained comparison such as `a < b < c`,
+/// which the parser spells `a < b and b < c` with the same `b` node on both
+/// sides. Literal operands carry no id and are simply read twice.
+fn comparison_chain(expr: &Expr) -> Option<(Vec<&Expr>, Vec<&nevermind_ast::op::ComparisonOp>)> {
+    let mut conjuncts = Vec::new();
+    let mut rest = expr;
+    while let Expr::Logical {
+        left,
+        op: nevermind_ast::op::LogicalOp::And,
+        right,
+        ..
+    } = rest
+    {
+        conjuncts.push(right.as_ref());
+        rest = left;
+    }
+    conjuncts.push(rest);
+    conjuncts.reverse();
+
+    let mut operands: Vec<&Expr> = Vec::new();
+    let mut ops = Vec::new();
+    for conjunct in conjuncts {
+        let Expr::Comparison {
+            left, op, right, ..
+        } = conjunct
+        else {
+            return None;
+        };
+        match operands.last() {
+            None => operands.push(left),
+            Some(shared) if shared.id().is_some() && shared.id() == left.id() => {}
+            Some(_) => return None,
+        }
+        ops.push(op);
+        operands.push(right);
+    }
+
+    (ops.len() > 1).then_some((operands, ops))
+}
+
+/// Compare `first` with each following operand in turn, stopping at the
+/// first false test. Operands shared by two tests are bound to temporaries,
+/// along with the operand before them so evaluation stays left to right.
+fn chain_comparisons(cx: &Context, first: MirExpr, mut rest: Vec<(BinOp, MirExpr)>) -> MirExpr {
+    let (op, next) = rest.remove(0);
+    if rest.is_empty() {
+        return binary(cx, op, first, next);
+    }
+
+    let (first_binding, first) = if is_trivial(&next) {
+        (None, first)
+    } else {
+        bind_once(cx, first, "chain")
+    };
+    let (next_binding, next) = bind_once(cx, next, "chain");
+    let chain = binary(
+        cx,
+        BinOp::And,
+        binary(cx, op, first, next.clone()),
+        chain_comparisons(cx, next, rest),
+    );
+
+    let statements: Vec<_> = first_binding.into_iter().chain(next_binding).collect();
+    if statements.is_empty() {
+        return chain;
+    }
+    MirExpr::Block {
+        ty: Type::Bool,
+        statements,
+        expr: Some(Box::new(chain)),
+        id: cx.fresh_node_id(),
+    }
+}
+
+/// Build the test that `subject` matches `pattern`, or `None` when the
+/// pattern is irrefutable. Variables the pattern binds are pushed onto
+/// `bindings` along with the part of the subject they bind to.
+///
+/// Constructor values with arguments are tested with `isinstance` against a
+/// class named after the constructor, and their arguments are read by
+/// position; nullary constructors are compared by equality.
+fn lower_pattern_test(
+    cx: &Context,
+    pattern: &Pattern,
+    subject: &MirExpr,
+    bindings: &mut Vec<(String, MirExpr)>,
+) -> Result<Option<MirExpr>> {
+    match pattern {
+        Pattern::Wildcard { .. } => Ok(None),
+        Pattern::Variable { name, .. } => {
+            bindings.push((name.clone(), subject.clone()));
+            Ok(None)
+        }
+        Pattern::Literal { value, .. } => Ok(Some(binary(
+            cx,
+            BinOp::Eq,
+            subject.clone(),
+            lower_literal(cx, value)?,
+        ))),
+        Pattern::Or { patterns, .. } => {
+            let mut tests = Vec::new();
+            for alternative in patterns {
+                let mut alternative_bindings = Vec::new();
+                let test = lower_pattern_test(cx, alternative, subject, &mut alternative_bindings)?;
+                if !alternative_bindings.is_empty() {
+                    return Err(LoweringError::UnsupportedNode(
+                        "variable binding inside an or-pattern".to_string(),
+                    ));
+                }
+                match test {
+                    Some(test) => tests.push(test),
+                    None => return Ok(None),
                 }
             }
-
-            Ok(result.unwrap_or(mir_scrutinee))
+            Ok(tests
+                .into_iter()
+                .reduce(|left, right| binary(cx, BinOp::Or, left, right)))
         }
+        Pattern::Tuple { patterns, .. } => {
+            lower_positional_tests(cx, patterns, subject, None, bindings)
+        }
+        Pattern::List { patterns, .. } => {
+            let length = binary(
+                cx,
+                BinOp::Eq,
+                call(cx, "len", vec![subject.clone()]),
+                int_literal(cx, patterns.len() as i64),
+            );
+            lower_positional_tests(cx, patterns, subject, Some(length), bindings)
+        }
+        Pattern::Constructor { name, args, .. } => {
+            let constructor = variable(name, cx.fresh_node_id());
+            if args.is_empty() {
+                return Ok(Some(binary(cx, BinOp::Eq, subject.clone(), constructor)));
+            }
+            let is_instance = call(cx, "isinstance", vec![subject.clone(), constructor]);
+            lower_positional_tests(cx, args, subject, Some(is_instance), bindings)
+        }
+        Pattern::ListCons { .. } => Err(LoweringError::UnsupportedNode(
+            "list cons pattern in a match expression".to_string(),
+        )),
+        Pattern::Struct { .. } => Err(LoweringError::UnsupportedNode(
+            "struct pattern in a match expression".to_string(),
+        )),
+        Pattern::Range { .. } => Err(LoweringError::UnsupportedNode(
+            "range pattern in a match expression".to_string(),
+        )),
+    }
+}
+
+/// Test each of `patterns` against the element of `subject` at its position,
+/// after an optional test of the subject itself
+fn lower_positional_tests(
+    cx: &Context,
+    patterns: &[Pattern],
+    subject: &MirExpr,
+    first: Option<MirExpr>,
+    bindings: &mut Vec<(String, MirExpr)>,
+) -> Result<Option<MirExpr>> {
+    let mut test = first;
+    for (position, pattern) in patterns.iter().enumerate() {
+        let element = MirExpr::Index {
+            array: Box::new(subject.clone()),
+            index: Box::new(int_literal(cx, position as i64)),
+            ty: Type::Unit,
+            id: cx.fresh_node_id(),
+        };
+        let element_test = lower_pattern_test(cx, pattern, &element, bindings)?;
+        test = conjoin(cx, test, element_test);
+    }
+    Ok(test)
+}
+
+/// Make the variables a pattern bound visible to `expr` by applying a lambda
+/// over them to the values they were bound to
+fn bind_pattern_variables(cx: &Context, expr: MirExpr, bindings: &[(String, MirExpr)]) -> MirExpr {
+    if bindings.is_empty() {
+        return expr;
+    }
+    MirExpr::Call {
+        callee: Box::new(MirExpr::Lambda {
+            params: bindings.iter().map(|(name, _)| name.clone()).collect(),
+            body: Box::new(expr),
+            ty: Type::Unit,
+            id: cx.fresh_node_id(),
+        }),
+        args: bindings.iter().map(|(_, value)| value.clone()).collect(),
+        ty: Type::Unit,
+        id: cx.fresh_node_id(),
+    }
+}
+
+/// Combine two optional tests with `and`
+fn conjoin(cx: &Context, left: Option<MirExpr>, right: Option<MirExpr>) -> Option<MirExpr> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(binary(cx, BinOp::And, left, right)),
+        (left, right) => left.or(right),
+    }
+}
+
+/// A reference to `name`; the `None` constructor is Python's own `None`
+fn variable(name: &str, id: NodeId) -> MirExpr {
+    if name == "None" {
+        return MirExpr::Literal {
+            value: Literal::Null,
+            ty: Type::Unit,
+            id,
+        };
+    }
+    MirExpr::Variable {
+        name: name.to_string(),
+        ty: Type::Unit,
+        id,
+    }
+}
+
+fn binary(cx: &Context, op: BinOp, left: MirExpr, right: MirExpr) -> MirExpr {
+    MirExpr::Binary {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+        ty: Type::Bool,
+        id: cx.fresh_node_id(),
+    }
+}
+
+fn call(cx: &Context, function: &str, args: Vec<MirExpr>) -> MirExpr {
+    MirExpr::Call {
+        callee: Box::new(MirExpr::Variable {
+            name: function.to_string(),
+            ty: Type::Unit,
+            id: cx.fresh_node_id(),
+        }),
+        args,
+        ty: Type::Unit,
+        id: cx.fresh_node_id(),
+    }
+}
+
+fn int_literal(cx: &Context, value: i64) -> MirExpr {
+    MirExpr::Literal {
+        value: Literal::Int(value),
+        ty: Type::Int,
+        id: cx.fresh_node_id(),
     }
 }
 
 /// Lower a literal value
-fn lower_literal(literal: &nevermind_ast::expr::Literal) -> Result<MirExpr> {
+fn lower_literal(cx: &Context, literal: &nevermind_ast::expr::Literal) -> Result<MirExpr> {
     Ok(match literal {
         nevermind_ast::expr::Literal::Integer(value, _) => MirExpr::Literal {
             value: Literal::Int(*value),
             ty: Type::Int,
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         },
         nevermind_ast::expr::Literal::Float(value, _) => MirExpr::Literal {
             value: Literal::Float(*value),
             ty: Type::Float,
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         },
         nevermind_ast::expr::Literal::String(value, _) => MirExpr::Literal {
             value: Literal::String(value.clone()),
             ty: Type::String,
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         },
         nevermind_ast::expr::Literal::Boolean(value, _) => MirExpr::Literal {
             value: Literal::Bool(*value),
             ty: Type::Bool,
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         },
         nevermind_ast::expr::Literal::Null(_) => MirExpr::Literal {
             value: Literal::Null,
             ty: Type::Null,
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         },
-        nevermind_ast::expr::Literal::Char(_, _) => MirExpr::Literal {
-            value: Literal::Int(0),
-            ty: Type::Int,
-            id: fresh_node_id(),
+        nevermind_ast::expr::Literal::Char(value, _) => MirExpr::Literal {
+            value: Literal::Char(*value),
+            ty: Type::Char,
+            id: cx.fresh_node_id(),
         },
     })
 }
 
 /// Lower a statement inside an expression block
-pub fn lower_expr_stmt(stmt: &Stmt) -> Result<MirExprStmt> {
+pub fn lower_expr_stmt(cx: &Context, stmt: &Stmt) -> Result<MirExprStmt> {
     match stmt {
-        Stmt::Export { stmt, .. } => lower_expr_stmt(stmt),
+        Stmt::Export { stmt, .. } => lower_expr_stmt(cx, stmt),
         Stmt::Let {
             id,
             name,
@@ -723,7 +1551,7 @@ pub fn lower_expr_stmt(stmt: &Stmt) -> Result<MirExprStmt> {
             type_annotation,
             ..
         } => {
-            let mir_value = lower_expression(value)?;
+            let mir_value = lower_expression(cx, value)?;
             let ty = type_annotation
                 .as_ref()
                 .and_then(resolve_type_annotation)
@@ -738,18 +1566,19 @@ pub fn lower_expr_stmt(stmt: &Stmt) -> Result<MirExprStmt> {
         }
 
         Stmt::ExprStmt { expr, .. } => {
-            let mir_expr = lower_expression(expr)?;
+            let mir_expr = lower_expression(cx, expr)?;
             Ok(MirExprStmt::Expr(mir_expr))
         }
 
         Stmt::Function {
             name: _,
-            params: _,
+            params,
             body,
             ..
         } => {
             // Nested function definitions: just lower the body as an expression
-            let mir_body = lower_expression(body)?;
+            let scope = cx.hiding(params.iter().map(|p| &p.name));
+            let mir_body = lower_expression(&scope, body)?;
             Ok(MirExprStmt::Expr(mir_body))
         }
 
@@ -759,29 +1588,29 @@ pub fn lower_expr_stmt(stmt: &Stmt) -> Result<MirExprStmt> {
             else_branch,
             ..
         } => {
-            let mir_cond = lower_expression(condition)?;
-            let mir_then = lower_expr_stmts(then_branch)?;
+            let mir_cond = lower_expression(cx, condition)?;
+            let mir_then = lower_expr_stmts(cx, then_branch)?;
             let mir_else = match else_branch {
-                Some(stmts) => Some(lower_expr_stmts(stmts)?),
+                Some(stmts) => Some(lower_expr_stmts(cx, stmts)?),
                 None => None,
             };
             Ok(MirExprStmt::If {
                 condition: mir_cond,
                 then_body: mir_then,
                 else_body: mir_else,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
         Stmt::While {
             condition, body, ..
         } => {
-            let mir_cond = lower_expression(condition)?;
-            let mir_body = lower_expr_stmts(body)?;
+            let mir_cond = lower_expression(cx, condition)?;
+            let mir_body = lower_expr_stmts(cx, body)?;
             Ok(MirExprStmt::While {
                 condition: mir_cond,
                 body: mir_body,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
@@ -791,47 +1620,78 @@ pub fn lower_expr_stmt(stmt: &Stmt) -> Result<MirExprStmt> {
             body,
             ..
         } => {
-            let var_name = pattern_to_variable_name(variable);
-            let mir_iter = lower_expression(iter)?;
-            let mir_body = lower_expr_stmts(body)?;
+            let pattern = lower_for_pattern(cx, variable)?;
+            let mir_iter = lower_expression(cx, iter)?;
+            let mir_body = lower_expr_stmts(&cx.hiding(&variable.collect_variables()), body)?;
             Ok(MirExprStmt::For {
-                variable: var_name,
+                pattern,
                 iter: mir_iter,
                 body: mir_body,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
         Stmt::Return { value, .. } => {
-            let mir_value = value.as_ref().map(lower_expression).transpose()?;
+            let mir_value = value
+                .as_ref()
+                .map(|value| lower_expression(cx, value))
+                .transpose()?;
             Ok(MirExprStmt::Return {
                 value: mir_value.map(Box::new),
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             })
         }
 
+        Stmt::Try {
+            body,
+            catch_var,
+            catch_body,
+            finally_body,
+            ..
+        } => Ok(MirExprStmt::Try {
+            body: lower_expr_stmts(cx, body)?,
+            catch_var: catch_var.clone(),
+            catch_body: catch_body
+                .as_deref()
+                .map(|body| lower_expr_stmts(&cx.hiding(catch_var), body))
+                .transpose()?,
+            finally_body: finally_body
+                .as_deref()
+                .map(|body| lower_expr_stmts(cx, body))
+                .transpose()?,
+            id: cx.fresh_node_id(),
+        }),
+
+        Stmt::Raise { value, .. } => Ok(MirExprStmt::Raise {
+            value: lower_expression(cx, value)?,
+            id: cx.fresh_node_id(),
+        }),
+
         Stmt::Break { .. } => Ok(MirExprStmt::Break {
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         }),
 
         Stmt::Continue { .. } => Ok(MirExprStmt::Continue {
-            id: fresh_node_id(),
+            id: cx.fresh_node_id(),
         }),
 
         Stmt::Match {
             scrutinee, arms, ..
         } => {
-            let mir_scrutinee = lower_expression(scrutinee)?;
+            let mir_scrutinee = lower_expression(cx, scrutinee)?;
 
             if arms.is_empty() {
                 return Ok(MirExprStmt::Expr(mir_scrutinee));
             }
 
+            let (binding, mir_scrutinee) = bind_scrutinee(cx, mir_scrutinee);
+
             // Lower match to nested if-else chain as expression statements
             let mut result: Option<MirExprStmt> = None;
 
             for arm in arms.iter().rev() {
-                let body_expr = lower_expression(&arm.body)?;
+                let scope = cx.hiding(&arm.pattern.collect_variables());
+                let body_expr = lower_expression(&scope, &arm.body)?;
                 let body_stmt = vec![MirExprStmt::Expr(body_expr)];
 
                 match &arm.pattern {
@@ -843,11 +1703,11 @@ pub fn lower_expr_stmt(stmt: &Stmt) -> Result<MirExprStmt> {
                                 condition: MirExpr::Literal {
                                     value: Literal::Bool(true),
                                     ty: Type::Bool,
-                                    id: fresh_node_id(),
+                                    id: cx.fresh_node_id(),
                                 },
                                 then_body: body_stmt,
                                 else_body: None,
-                                id: fresh_node_id(),
+                                id: cx.fresh_node_id(),
                             });
                         } else {
                             // Wrap existing result as else, this as then with always-true
@@ -856,38 +1716,47 @@ pub fn lower_expr_stmt(stmt: &Stmt) -> Result<MirExprStmt> {
                                 condition: MirExpr::Literal {
                                     value: Literal::Bool(true),
                                     ty: Type::Bool,
-                                    id: fresh_node_id(),
+                                    id: cx.fresh_node_id(),
                                 },
                                 then_body: body_stmt,
                                 else_body,
-                                id: fresh_node_id(),
+                                id: cx.fresh_node_id(),
                             });
                         }
                     }
                     Pattern::Literal { value, .. } => {
-                        let lit_expr = lower_literal(value)?;
+                        let lit_expr = lower_literal(cx, value)?;
                         let condition = MirExpr::Binary {
                             op: BinOp::Eq,
                             left: Box::new(mir_scrutinee.clone()),
                             right: Box::new(lit_expr),
                             ty: Type::Bool,
-                            id: fresh_node_id(),
+                            id: cx.fresh_node_id(),
                         };
                         let else_body = result.map(|r| vec![r]);
                         result = Some(MirExprStmt::If {
                             condition,
                             then_body: body_stmt,
                             else_body,
-                            id: fresh_node_id(),
+                            id: cx.fresh_node_id(),
                         });
                     }
                     _ => {
-                        result = Some(MirExprStmt::Expr(lower_expression(&arm.body)?));
+                        result = Some(MirExprStmt::Expr(lower_expression(&scope, &arm.body)?));
                     }
                 }
             }
 
-            Ok(result.unwrap_or(MirExprStmt::Expr(mir_scrutinee)))
+            let chain = result.unwrap_or(MirExprStmt::Expr(mir_scrutinee));
+            Ok(match binding {
+                Some(binding) => MirExprStmt::Expr(MirExpr::Block {
+                    statements: vec![binding, chain],
+                    expr: None,
+                    ty: Type::Unit,
+                    id: cx.fresh_node_id(),
+                }),
+                None => chain,
+            })
         }
 
         // Imports and declarations inside expression blocks are treated as no-ops.
@@ -895,7 +1764,7 @@ pub fn lower_expr_stmt(stmt: &Stmt) -> Result<MirExprStmt> {
             Ok(MirExprStmt::Expr(MirExpr::Literal {
                 value: Literal::Null,
                 ty: Type::Unit,
-                id: fresh_node_id(),
+                id: cx.fresh_node_id(),
             }))
         }
     }
@@ -908,6 +1777,7 @@ fn resolve_type_annotation(ty: &nevermind_ast::TypeAnnotation) -> Option<Type> {
             nevermind_ast::types::PrimitiveType::Int => Some(Type::Int),
             nevermind_ast::types::PrimitiveType::Float => Some(Type::Float),
             nevermind_ast::types::PrimitiveType::String => Some(Type::String),
+            nevermind_ast::types::PrimitiveType::Char => Some(Type::Char),
             nevermind_ast::types::PrimitiveType::Bool => Some(Type::Bool),
             _ => Some(Type::Unit),
         },
@@ -925,7 +1795,12 @@ fn map_binary_op(op: &nevermind_ast::op::BinaryOp) -> BinOp {
         nevermind_ast::op::BinaryOp::Div => BinOp::Div,
         nevermind_ast::op::BinaryOp::Mod => BinOp::Mod,
         nevermind_ast::op::BinaryOp::Pow => BinOp::Pow,
-        _ => BinOp::Add, // Fallback
+        nevermind_ast::op::BinaryOp::Concat => BinOp::Concat,
+        nevermind_ast::op::BinaryOp::BitAnd => BinOp::BitAnd,
+        nevermind_ast::op::BinaryOp::BitOr => BinOp::BitOr,
+        nevermind_ast::op::BinaryOp::BitXor => BinOp::BitXor,
+        nevermind_ast::op::BinaryOp::ShiftLeft => BinOp::ShiftLeft,
+        nevermind_ast::op::BinaryOp::ShiftRight => BinOp::ShiftRight,
     }
 }
 
@@ -942,11 +1817,14 @@ fn map_comparison_op(op: &nevermind_ast::op::ComparisonOp) -> BinOp {
 }
 
 /// Map an AST unary operator to MIR unary operator
-fn map_unary_op(op: &nevermind_ast::op::UnaryOp) -> UnaryOp {
+fn map_unary_op(op: &nevermind_ast::op::UnaryOp) -> Result<UnaryOp> {
     match op {
-        nevermind_ast::op::UnaryOp::Neg => UnaryOp::Neg,
-        nevermind_ast::op::UnaryOp::Not => UnaryOp::Not,
-        _ => UnaryOp::Neg,
+        nevermind_ast::op::UnaryOp::Neg => Ok(UnaryOp::Neg),
+        nevermind_ast::op::UnaryOp::Not => Ok(UnaryOp::Not),
+        nevermind_ast::op::UnaryOp::BitNot => Ok(UnaryOp::BitNot),
+        nevermind_ast::op::UnaryOp::Deref | nevermind_ast::op::UnaryOp::Ref => Err(
+            LoweringError::UnsupportedNode(format!("unary operator '{}'", op.symbol())),
+        ),
     }
 }
 
@@ -958,9 +1836,44 @@ fn map_logical_op(op: &nevermind_ast::op::LogicalOp) -> BinOp {
     }
 }
 
-/// Generate a fresh node ID
-fn fresh_node_id() -> NodeId {
-    use std::sync::atomic::{AtomicUsize, Ordering};
-    static COUNTER: AtomicUsize = AtomicUsize::new(10000);
-    COUNTER.fetch_add(1, Ordering::SeqCst) as NodeId
+/// Fresh MIR node ids start far above the parser's so the two never collide
+const FIRST_FRESH_NODE_ID: NodeId = NodeId::MAX / 2;
+
+/// The node ids and temporary names handed out while lowering one program,
+/// returned with it so later passes can keep drawing from them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FreshNames {
+    next_node_id: Cell<NodeId>,
+    next_temp: Cell<usize>,
+}
+
+impl Default for FreshNames {
+    fn default() -> Self {
+        Self {
+            next_node_id: Cell::new(FIRST_FRESH_NODE_ID),
+            next_temp: Cell::new(0),
+        }
+    }
+}
+
+impl FreshNames {
+    /// Generate a fresh node ID
+    pub fn node_id(&self) -> NodeId {
+        self.next_node_id.replace(self.next_node_id.get() + 1)
+    }
+
+    /// Name a compiler temporary, unique within the program being lowered
+    pub fn temp(&self, prefix: &str) -> String {
+        let index = self.next_temp.replace(self.next_temp.get() + 1);
+        format!("__{}_{}", prefix, index)
+    }
+}
+
+/// The instance a method was called on
+fn self_variable(cx: &Context) -> MirExpr {
+    MirExpr::Variable {
+        name: "self".to_string(),
+        ty: Type::Unit,
+        id: cx.fresh_node_id(),
+    }
 }
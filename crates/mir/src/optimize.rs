@@ -0,0 +1,716 @@
+//! Optional optimization passes over MIR
+
+use super::lowering::FreshNames;
+use super::{
+    BinOp, Literal, MirBlock, MirExpr, MirExprStmt, MirProgram, MirStmt, MirStringPart, UnaryOp,
+};
+
+/// Evaluate operators whose operands are all literals, and replace `if`s with
+/// a literal condition by the branch that runs.
+///
+/// Folding follows the semantics of the generated Python: integer division
+/// and modulo round toward negative infinity. Anything whose result Python
+/// would compute differently — division by zero, integer overflow into big
+/// integers, float division — is left for the runtime.
+pub fn fold_constants(program: &mut MirProgram) {
+    fold_stmts(&mut program.statements);
+}
+
+fn fold_stmts(stmts: &mut Vec<MirStmt>) {
+    let mut folded = Vec::with_capacity(stmts.len());
+
+    for mut stmt in stmts.drain(..) {
+        match &mut stmt {
+            MirStmt::Function { body, .. } => fold_block(body),
+            MirStmt::Class {
+                fields, methods, ..
+            } => {
+                for default in fields.iter_mut().filter_map(|f| f.default.as_mut()) {
+                    fold_expr(default);
+                }
+                for method in methods {
+                    fold_block(&mut method.body);
+                }
+            }
+            MirStmt::Let { value, .. } => fold_expr(value),
+            MirStmt::Expr(expr) => fold_expr(expr),
+            MirStmt::If {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => {
+                fold_expr(condition);
+                fold_stmts(then_body);
+                if let Some(else_body) = else_body {
+                    fold_stmts(else_body);
+                }
+                if let Some(taken) = literal_bool(condition) {
+                    let branch = if taken {
+                        std::mem::take(then_body)
+                    } else {
+                        else_body.take().unwrap_or_default()
+                    };
+                    folded.extend(branch);
+                    continue;
+                }
+            }
+            MirStmt::While {
+                condition, body, ..
+            } => {
+                fold_expr(condition);
+                fold_stmts(body);
+            }
+            MirStmt::For { iter, body, .. } => {
+                fold_expr(iter);
+                fold_stmts(body);
+            }
+            MirStmt::Return { value, .. } => {
+                if let Some(value) = value {
+                    fold_expr(value);
+                }
+            }
+            MirStmt::Try {
+                body,
+                catch_body,
+                finally_body,
+                ..
+            } => {
+                fold_stmts(body);
+                if let Some(catch_body) = catch_body {
+                    fold_stmts(catch_body);
+                }
+                if let Some(finally_body) = finally_body {
+                    fold_stmts(finally_body);
+                }
+            }
+            MirStmt::Raise { value, .. } => fold_expr(value),
+            MirStmt::Match {
+                scrutinee, arms, ..
+            } => {
+                fold_expr(scrutinee);
+                for arm in arms {
+                    if let Some(guard) = &mut arm.guard {
+                        fold_expr(guard);
+                    }
+                    fold_stmts(&mut arm.body);
+                }
+            }
+            MirStmt::Break { .. } | MirStmt::Continue { .. } | MirStmt::Import { .. } => {}
+        }
+        folded.push(stmt);
+    }
+
+    *stmts = folded;
+}
+
+fn fold_block(block: &mut MirBlock) {
+    fold_expr_stmts(&mut block.statements);
+    if let Some(expr) = &mut block.expr {
+        fold_expr(expr);
+    }
+}
+
+fn fold_expr_stmts(stmts: &mut Vec<MirExprStmt>) {
+    let mut folded = Vec::with_capacity(stmts.len());
+
+    for mut stmt in stmts.drain(..) {
+        match &mut stmt {
+            MirExprStmt::Let { value, .. } | MirExprStmt::Assign { value, .. } => fold_expr(value),
+            MirExprStmt::IndexAssign { index, value, .. } => {
+                fold_expr(index);
+                fold_expr(value);
+            }
+            MirExprStmt::FieldAssign { object, value, .. } => {
+                fold_expr(object);
+                fold_expr(value);
+            }
+            MirExprStmt::Expr(expr) => fold_expr(expr),
+            MirExprStmt::Return { value, .. } => {
+                if let Some(value) = value {
+                    fold_expr(value);
+                }
+            }
+            MirExprStmt::Try {
+                body,
+                catch_body,
+                finally_body,
+                ..
+            } => {
+                fold_expr_stmts(body);
+                if let Some(catch_body) = catch_body {
+                    fold_expr_stmts(catch_body);
+                }
+                if let Some(finally_body) = finally_body {
+                    fold_expr_stmts(finally_body);
+                }
+            }
+            MirExprStmt::Raise { value, .. } => fold_expr(value),
+            MirExprStmt::If {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => {
+                fold_expr(condition);
+                fold_expr_stmts(then_body);
+                if let Some(else_body) = else_body {
+                    fold_expr_stmts(else_body);
+                }
+                if let Some(taken) = literal_bool(condition) {
+                    let branch = if taken {
+                        std::mem::take(then_body)
+                    } else {
+                        else_body.take().unwrap_or_default()
+                    };
+                    folded.extend(branch);
+                    continue;
+                }
+            }
+            MirExprStmt::While {
+                condition, body, ..
+            } => {
+                fold_expr(condition);
+                fold_expr_stmts(body);
+            }
+            MirExprStmt::For { iter, body, .. } => {
+                fold_expr(iter);
+                fold_expr_stmts(body);
+            }
+            MirExprStmt::Break { .. } | MirExprStmt::Continue { .. } => {}
+        }
+        folded.push(stmt);
+    }
+
+    *stmts = folded;
+}
+
+fn fold_expr(expr: &mut MirExpr) {
+    match expr {
+        MirExpr::Literal { .. } | MirExpr::Variable { .. } => {}
+        MirExpr::Binary {
+            op,
+            left,
+            right,
+            ty,
+            id,
+        } => {
+            fold_expr(left);
+            fold_expr(right);
+            if let (MirExpr::Literal { value: l, .. }, MirExpr::Literal { value: r, .. }) =
+                (left.as_ref(), right.as_ref())
+            {
+                if let Some(value) = fold_binary(*op, l, r) {
+                    *expr = literal(value, ty.clone(), *id);
+                }
+            }
+        }
+        MirExpr::Unary {
+            op,
+            operand,
+            ty,
+            id,
+        } => {
+            fold_expr(operand);
+            if let MirExpr::Literal { value, .. } = operand.as_ref() {
+                if let Some(value) = fold_unary(*op, value) {
+                    *expr = literal(value, ty.clone(), *id);
+                }
+            }
+        }
+        MirExpr::Await { expr, .. } => fold_expr(expr),
+        MirExpr::Call { callee, args, .. } => {
+            fold_expr(callee);
+            for arg in args {
+                fold_expr(arg);
+            }
+        }
+        MirExpr::Block {
+            statements, expr, ..
+        } => {
+            fold_expr_stmts(statements);
+            if let Some(expr) = expr {
+                fold_expr(expr);
+            }
+        }
+        MirExpr::StringInterp { parts, .. } => {
+            for part in parts {
+                if let MirStringPart::Expr(expr) = part {
+                    fold_expr(expr);
+                }
+            }
+        }
+        MirExpr::List { elements, .. } | MirExpr::Tuple { elements, .. } => {
+            for element in elements {
+                fold_expr(element);
+            }
+        }
+        MirExpr::FieldAccess { object, .. } => fold_expr(object),
+        MirExpr::Map { entries, .. } => {
+            for (key, value) in entries {
+                fold_expr(key);
+                fold_expr(value);
+            }
+        }
+        MirExpr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            fold_expr(condition);
+            fold_expr(then_branch);
+            fold_expr(else_branch);
+            if let Some(taken) = literal_bool(condition) {
+                let branch = if taken { then_branch } else { else_branch };
+                let branch = std::mem::replace(branch.as_mut(), placeholder());
+                *expr = branch;
+            }
+        }
+        MirExpr::Index { array, index, .. } => {
+            fold_expr(array);
+            fold_expr(index);
+        }
+        MirExpr::Lambda { body, .. } => fold_expr(body),
+        MirExpr::Range { start, end, .. } => {
+            fold_expr(start);
+            fold_expr(end);
+        }
+        MirExpr::Slice {
+            array, start, end, ..
+        } => {
+            fold_expr(array);
+            fold_expr(start);
+            fold_expr(end);
+        }
+    }
+}
+
+/// Evaluate a binary operator over two literals, if Python would agree
+fn fold_binary(op: BinOp, left: &Literal, right: &Literal) -> Option<Literal> {
+    use Literal::{Bool, Float, Int};
+
+    match (left, right) {
+        (Int(l), Int(r)) => {
+            let (l, r) = (*l, *r);
+            Some(match op {
+                BinOp::Add => Int(l.checked_add(r)?),
+                BinOp::Sub => Int(l.checked_sub(r)?),
+                BinOp::Mul => Int(l.checked_mul(r)?),
+                BinOp::Div => Int(floor_div(l, r)?),
+                BinOp::Mod => Int(floor_mod(l, r)?),
+                BinOp::Pow => Int(l.checked_pow(u32::try_from(r).ok()?)?),
+                BinOp::BitAnd => Int(l & r),
+                BinOp::BitOr => Int(l | r),
+                BinOp::BitXor => Int(l ^ r),
+                BinOp::ShiftLeft => {
+                    let shifted = l.checked_shl(u32::try_from(r).ok()?)?;
+                    // Python integers never overflow, so only fold lossless shifts
+                    if shifted >> r != l {
+                        return None;
+                    }
+                    Int(shifted)
+                }
+                BinOp::ShiftRight => Int(l.checked_shr(u32::try_from(r).ok()?.min(63))?),
+                _ => Bool(compare(op, l.cmp(&r))?),
+            })
+        }
+        (Float(l), Float(r)) => {
+            let (l, r) = (*l, *r);
+            let value = match op {
+                BinOp::Add => l + r,
+                BinOp::Sub => l - r,
+                BinOp::Mul => l * r,
+                BinOp::Pow => l.powf(r),
+                _ => return Some(Bool(compare(op, l.partial_cmp(&r)?)?)),
+            };
+            value.is_finite().then_some(Float(value))
+        }
+        (Bool(l), Bool(r)) => Some(match op {
+            BinOp::And => Bool(*l && *r),
+            BinOp::Or => Bool(*l || *r),
+            BinOp::Eq => Bool(l == r),
+            BinOp::Ne => Bool(l != r),
+            _ => return None,
+        }),
+        (Literal::String(l), Literal::String(r)) => Some(match op {
+            BinOp::Concat | BinOp::Add => {
+                let joined = format!("{}{}", l, r);
+                // Codegen reads braces in a string literal as interpolation,
+                // so joining "x{" and "y}" would change what is printed
+                if joined.contains(['{', '}']) {
+                    return None;
+                }
+                Literal::String(joined)
+            }
+            _ => Bool(compare(op, l.cmp(r))?),
+        }),
+        _ => None,
+    }
+}
+
+/// Evaluate a unary operator over a literal
+fn fold_unary(op: UnaryOp, operand: &Literal) -> Option<Literal> {
+    match (op, operand) {
+        (UnaryOp::Neg, Literal::Int(v)) => Some(Literal::Int(v.checked_neg()?)),
+        (UnaryOp::Neg, Literal::Float(v)) => Some(Literal::Float(-v)),
+        (UnaryOp::Not, Literal::Bool(v)) => Some(Literal::Bool(!v)),
+        (UnaryOp::BitNot, Literal::Int(v)) => Some(Literal::Int(!v)),
+        _ => None,
+    }
+}
+
+/// Result of a comparison operator given how its operands order
+fn compare(op: BinOp, ordering: std::cmp::Ordering) -> Option<bool> {
+    use std::cmp::Ordering::{Equal, Greater, Less};
+
+    Some(match op {
+        BinOp::Eq => ordering == Equal,
+        BinOp::Ne => ordering != Equal,
+        BinOp::Lt => ordering == Less,
+        BinOp::Le => ordering != Greater,
+        BinOp::Gt => ordering == Greater,
+        BinOp::Ge => ordering != Less,
+        _ => return None,
+    })
+}
+
+/// Integer division rounding toward negative infinity, like Python's `//`
+fn floor_div(l: i64, r: i64) -> Option<i64> {
+    let quotient = l.checked_div(r)?;
+    if l % r != 0 && (l < 0) != (r < 0) {
+        Some(quotient - 1)
+    } else {
+        Some(quotient)
+    }
+}
+
+/// Integer remainder taking the sign of the divisor, like Python's `%`
+fn floor_mod(l: i64, r: i64) -> Option<i64> {
+    let remainder = l.checked_rem(r)?;
+    if remainder != 0 && (remainder < 0) != (r < 0) {
+        Some(remainder + r)
+    } else {
+        Some(remainder)
+    }
+}
+
+fn literal_bool(expr: &MirExpr) -> Option<bool> {
+    match expr {
+        MirExpr::Literal {
+            value: Literal::Bool(value),
+            ..
+        } => Some(*value),
+        _ => None,
+    }
+}
+
+fn literal(value: Literal, ty: nevermind_type_checker::Type, id: super::NodeId) -> MirExpr {
+    MirExpr::Literal { value, ty, id }
+}
+
+/// A throwaway expression to move a branch out of its parent with
+fn placeholder() -> MirExpr {
+    MirExpr::Literal {
+        value: Literal::Null,
+        ty: nevermind_type_checker::Type::Unit,
+        id: 0,
+    }
+}
+
+/// Drop statements that follow a `return`, `raise`, `break` or `continue` in
+/// the same block, and `if` branches left with nothing to run.
+pub fn remove_dead_code(program: &mut MirProgram) {
+    let MirProgram {
+        statements,
+        fresh_names,
+    } = program;
+    prune_stmts(fresh_names, statements);
+}
+
+fn prune_stmts(names: &FreshNames, stmts: &mut Vec<MirStmt>) {
+    for stmt in stmts.iter_mut() {
+        match stmt {
+            MirStmt::Function { body, .. } => prune_block(names, body),
+            MirStmt::Class {
+                fields, methods, ..
+            } => {
+                for default in fields.iter_mut().filter_map(|f| f.default.as_mut()) {
+                    prune_expr(names, default);
+                }
+                for method in methods {
+                    prune_block(names, &mut method.body);
+                }
+            }
+            MirStmt::Let { value, .. } => prune_expr(names, value),
+            MirStmt::Expr(expr) => prune_expr(names, expr),
+            MirStmt::If {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => {
+                prune_expr(names, condition);
+                prune_stmts(names, then_body);
+                if let Some(body) = else_body {
+                    prune_stmts(names, body);
+                }
+                if else_body.as_ref().is_some_and(|body| body.is_empty()) {
+                    *else_body = None;
+                }
+                if then_body.is_empty() {
+                    match else_body.take() {
+                        Some(body) => {
+                            *then_body = body;
+                            negate(names, condition);
+                        }
+                        None => *stmt = MirStmt::Expr(std::mem::replace(condition, placeholder())),
+                    }
+                }
+            }
+            MirStmt::While {
+                condition, body, ..
+            } => {
+                prune_expr(names, condition);
+                prune_stmts(names, body);
+            }
+            MirStmt::For { iter, body, .. } => {
+                prune_expr(names, iter);
+                prune_stmts(names, body);
+            }
+            MirStmt::Return { value, .. } => {
+                if let Some(value) = value {
+                    prune_expr(names, value);
+                }
+            }
+            MirStmt::Try {
+                body,
+                catch_body,
+                finally_body,
+                ..
+            } => {
+                prune_stmts(names, body);
+                if let Some(catch_body) = catch_body {
+                    prune_stmts(names, catch_body);
+                }
+                if let Some(finally_body) = finally_body {
+                    prune_stmts(names, finally_body);
+                }
+            }
+            MirStmt::Raise { value, .. } => prune_expr(names, value),
+            MirStmt::Match {
+                scrutinee, arms, ..
+            } => {
+                prune_expr(names, scrutinee);
+                for arm in arms {
+                    if let Some(guard) = &mut arm.guard {
+                        prune_expr(names, guard);
+                    }
+                    prune_stmts(names, &mut arm.body);
+                }
+            }
+            MirStmt::Break { .. } | MirStmt::Continue { .. } | MirStmt::Import { .. } => {}
+        }
+    }
+
+    let terminator = stmts.iter().position(|stmt| {
+        matches!(
+            stmt,
+            MirStmt::Return { .. }
+                | MirStmt::Raise { .. }
+                | MirStmt::Break { .. }
+                | MirStmt::Continue { .. }
+        )
+    });
+    if let Some(index) = terminator {
+        stmts.truncate(index + 1);
+    }
+}
+
+fn prune_block(names: &FreshNames, block: &mut MirBlock) {
+    if prune_expr_stmts(names, &mut block.statements) {
+        // The trailing value would be returned after the block already left
+        block.expr = None;
+    } else if let Some(expr) = &mut block.expr {
+        prune_expr(names, expr);
+    }
+}
+
+/// Prune a statement list, returning whether it ends in a terminator
+fn prune_expr_stmts(names: &FreshNames, stmts: &mut Vec<MirExprStmt>) -> bool {
+    for stmt in stmts.iter_mut() {
+        match stmt {
+            MirExprStmt::Let { value, .. } | MirExprStmt::Assign { value, .. } => {
+                prune_expr(names, value)
+            }
+            MirExprStmt::IndexAssign { index, value, .. } => {
+                prune_expr(names, index);
+                prune_expr(names, value);
+            }
+            MirExprStmt::FieldAssign { object, value, .. } => {
+                prune_expr(names, object);
+                prune_expr(names, value);
+            }
+            MirExprStmt::Expr(expr) => prune_expr(names, expr),
+            MirExprStmt::Return { value, .. } => {
+                if let Some(value) = value {
+                    prune_expr(names, value);
+                }
+            }
+            MirExprStmt::Try {
+                body,
+                catch_body,
+                finally_body,
+                ..
+            } => {
+                prune_expr_stmts(names, body);
+                if let Some(catch_body) = catch_body {
+                    prune_expr_stmts(names, catch_body);
+                }
+                if let Some(finally_body) = finally_body {
+                    prune_expr_stmts(names, finally_body);
+                }
+            }
+            MirExprStmt::Raise { value, .. } => prune_expr(names, value),
+            MirExprStmt::If {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => {
+                prune_expr(names, condition);
+                prune_expr_stmts(names, then_body);
+                if let Some(body) = else_body {
+                    prune_expr_stmts(names, body);
+                }
+                if else_body.as_ref().is_some_and(|body| body.is_empty()) {
+                    *else_body = None;
+                }
+                if then_body.is_empty() {
+                    match else_body.take() {
+                        Some(body) => {
+                            *then_body = body;
+                            negate(names, condition);
+                        }
+                        None => {
+                            *stmt = MirExprStmt::Expr(std::mem::replace(condition, placeholder()))
+                        }
+                    }
+                }
+            }
+            MirExprStmt::While {
+                condition, body, ..
+            } => {
+                prune_expr(names, condition);
+                prune_expr_stmts(names, body);
+            }
+            MirExprStmt::For { iter, body, .. } => {
+                prune_expr(names, iter);
+                prune_expr_stmts(names, body);
+            }
+            MirExprStmt::Break { .. } | MirExprStmt::Continue { .. } => {}
+        }
+    }
+
+    let terminator = stmts.iter().position(|stmt| {
+        matches!(
+            stmt,
+            MirExprStmt::Return { .. }
+                | MirExprStmt::Raise { .. }
+                | MirExprStmt::Break { .. }
+                | MirExprStmt::Continue { .. }
+        )
+    });
+    match terminator {
+        Some(index) => {
+            stmts.truncate(index + 1);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Prune the statement blocks nested inside an expression
+fn prune_expr(names: &FreshNames, expr: &mut MirExpr) {
+    match expr {
+        MirExpr::Literal { .. } | MirExpr::Variable { .. } => {}
+        MirExpr::Binary { left, right, .. } => {
+            prune_expr(names, left);
+            prune_expr(names, right);
+        }
+        MirExpr::Unary { operand, .. } | MirExpr::Await { expr: operand, .. } => {
+            prune_expr(names, operand)
+        }
+        MirExpr::Call { callee, args, .. } => {
+            prune_expr(names, callee);
+            for arg in args {
+                prune_expr(names, arg);
+            }
+        }
+        MirExpr::Block {
+            statements, expr, ..
+        } => {
+            if prune_expr_stmts(names, statements) {
+                *expr = None;
+            } else if let Some(expr) = expr {
+                prune_expr(names, expr);
+            }
+        }
+        MirExpr::StringInterp { parts, .. } => {
+            for part in parts {
+                if let MirStringPart::Expr(expr) = part {
+                    prune_expr(names, expr);
+                }
+            }
+        }
+        MirExpr::List { elements, .. } | MirExpr::Tuple { elements, .. } => {
+            for element in elements {
+                prune_expr(names, element);
+            }
+        }
+        MirExpr::FieldAccess { object, .. } => prune_expr(names, object),
+        MirExpr::Map { entries, .. } => {
+            for (key, value) in entries {
+                prune_expr(names, key);
+                prune_expr(names, value);
+            }
+        }
+        MirExpr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            prune_expr(names, condition);
+            prune_expr(names, then_branch);
+            prune_expr(names, else_branch);
+        }
+        MirExpr::Index { array, index, .. } => {
+            prune_expr(names, array);
+            prune_expr(names, index);
+        }
+        MirExpr::Lambda { body, .. } => prune_expr(names, body),
+        MirExpr::Range { start, end, .. } => {
+            prune_expr(names, start);
+            prune_expr(names, end);
+        }
+        MirExpr::Slice {
+            array, start, end, ..
+        } => {
+            prune_expr(names, array);
+            prune_expr(names, start);
+            prune_expr(names, end);
+        }
+    }
+}
+
+/// Flip a condition in place so an `if` can run its `else` body instead
+fn negate(names: &FreshNames, condition: &mut MirExpr) {
+    let operand = std::mem::replace(condition, placeholder());
+    *condition = MirExpr::Unary {
+        op: UnaryOp::Not,
+        operand: Box::new(operand),
+        ty: nevermind_type_checker::Type::Bool,
+        id: names.node_id(),
+    };
+}
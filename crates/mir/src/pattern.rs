@@ -18,6 +18,12 @@ pub enum MirPattern {
         id: NodeId,
     },
 
+    /// Tuple pattern: (a, b)
+    Tuple {
+        patterns: Vec<MirPattern>,
+        id: NodeId,
+    },
+
     /// List pattern: [a, b, ...rest]
     List {
         patterns: Vec<MirPattern>,
@@ -1,6 +1,6 @@
 //! MIR statements (top-level)
 
-use super::{MirBlock, MirExpr, MirPattern, NodeId};
+use super::{ImportedSymbol, MirBlock, MirExpr, MirFunction, MirPattern, NodeId};
 use nevermind_type_checker::Type;
 
 /// Top-level MIR statements
@@ -8,6 +8,7 @@ use nevermind_type_checker::Type;
 pub enum MirStmt {
     /// Function definition
     Function {
+        is_async: bool,
         name: String,
         params: Vec<Param>,
         body: MirBlock,
@@ -15,6 +16,19 @@ pub enum MirStmt {
         id: NodeId,
     },
 
+    /// Class definition. Each method takes the instance as its first
+    /// parameter, `self`
+    Class {
+        name: String,
+        extends: Option<String>,
+        /// The required fields of the classes it extends, which its
+        /// constructor takes first and passes on to theirs
+        inherited: Vec<String>,
+        fields: Vec<MirField>,
+        methods: Vec<MirFunction>,
+        id: NodeId,
+    },
+
     /// Variable declaration
     Let {
         name: String,
@@ -43,7 +57,7 @@ pub enum MirStmt {
 
     /// For loop
     For {
-        variable: String,
+        pattern: MirPattern,
         iter: MirExpr,
         body: Vec<MirStmt>,
         id: NodeId,
@@ -52,6 +66,18 @@ pub enum MirStmt {
     /// Return statement
     Return { value: Option<MirExpr>, id: NodeId },
 
+    /// Try statement; `catch_var` is bound to the error's message
+    Try {
+        body: Vec<MirStmt>,
+        catch_var: Option<String>,
+        catch_body: Option<Vec<MirStmt>>,
+        finally_body: Option<Vec<MirStmt>>,
+        id: NodeId,
+    },
+
+    /// Raise an error with the given message
+    Raise { value: MirExpr, id: NodeId },
+
     /// Break statement
     Break { id: NodeId },
 
@@ -70,7 +96,9 @@ pub enum MirStmt {
         /// Module path (e.g. `"math/utils"`)
         module: String,
         /// Symbols to import; `None` means namespace import (`use "module"`)
-        symbols: Option<Vec<String>>,
+        symbols: Option<Vec<ImportedSymbol>>,
+        /// `as` name of a namespace import
+        alias: Option<String>,
         id: NodeId,
     },
 }
@@ -80,12 +108,15 @@ impl MirStmt {
     pub fn get_id(&self) -> NodeId {
         match self {
             MirStmt::Function { id, .. } => *id,
+            MirStmt::Class { id, .. } => *id,
             MirStmt::Let { id, .. } => *id,
             MirStmt::Expr(expr) => expr.get_id(),
             MirStmt::If { id, .. } => *id,
             MirStmt::While { id, .. } => *id,
             MirStmt::For { id, .. } => *id,
             MirStmt::Return { id, .. } => *id,
+            MirStmt::Try { id, .. } => *id,
+            MirStmt::Raise { id, .. } => *id,
             MirStmt::Break { id } => *id,
             MirStmt::Continue { id } => *id,
             MirStmt::Match { id, .. } => *id,
@@ -102,6 +133,15 @@ pub struct Param {
     pub id: NodeId,
 }
 
+/// A class field. Fields without a default are the constructor's
+/// parameters, in declaration order
+#[derive(Debug, Clone)]
+pub struct MirField {
+    pub name: String,
+    pub default: Option<MirExpr>,
+    pub ty: Type,
+}
+
 /// A match arm in a MIR match statement
 #[derive(Debug, Clone)]
 pub struct MirMatchArm {
@@ -0,0 +1,236 @@
+//! Stamping checker-inferred types onto lowered MIR
+
+use super::{MirBlock, MirExpr, MirExprStmt, MirProgram, MirStmt, MirStringPart, NodeId};
+use nevermind_type_checker::Type;
+use std::collections::HashMap;
+
+/// Overwrite the type of every MIR expression whose node id has an entry in
+/// `types`. Nodes synthesized during lowering keep the type lowering gave them.
+pub fn annotate_program(program: &mut MirProgram, types: &HashMap<NodeId, Type>) {
+    for stmt in &mut program.statements {
+        annotate_stmt(stmt, types);
+    }
+}
+
+fn annotate_stmt(stmt: &mut MirStmt, types: &HashMap<NodeId, Type>) {
+    match stmt {
+        MirStmt::Function { body, .. } => annotate_block(body, types),
+        MirStmt::Class {
+            fields, methods, ..
+        } => {
+            for default in fields.iter_mut().filter_map(|f| f.default.as_mut()) {
+                annotate_expr(default, types);
+            }
+            for method in methods {
+                annotate_block(&mut method.body, types);
+            }
+        }
+        MirStmt::Let { value, .. } => annotate_expr(value, types),
+        MirStmt::Expr(expr) => annotate_expr(expr, types),
+        MirStmt::If {
+            condition,
+            then_body,
+            else_body,
+            ..
+        } => {
+            annotate_expr(condition, types);
+            annotate_stmts(then_body, types);
+            if let Some(else_body) = else_body {
+                annotate_stmts(else_body, types);
+            }
+        }
+        MirStmt::While {
+            condition, body, ..
+        } => {
+            annotate_expr(condition, types);
+            annotate_stmts(body, types);
+        }
+        MirStmt::For { iter, body, .. } => {
+            annotate_expr(iter, types);
+            annotate_stmts(body, types);
+        }
+        MirStmt::Return { value, .. } => {
+            if let Some(value) = value {
+                annotate_expr(value, types);
+            }
+        }
+        MirStmt::Try {
+            body,
+            catch_body,
+            finally_body,
+            ..
+        } => {
+            annotate_stmts(body, types);
+            if let Some(catch_body) = catch_body {
+                annotate_stmts(catch_body, types);
+            }
+            if let Some(finally_body) = finally_body {
+                annotate_stmts(finally_body, types);
+            }
+        }
+        MirStmt::Raise { value, .. } => annotate_expr(value, types),
+        MirStmt::Match {
+            scrutinee, arms, ..
+        } => {
+            annotate_expr(scrutinee, types);
+            for arm in arms {
+                if let Some(guard) = &mut arm.guard {
+                    annotate_expr(guard, types);
+                }
+                annotate_stmts(&mut arm.body, types);
+            }
+        }
+        MirStmt::Break { .. } | MirStmt::Continue { .. } | MirStmt::Import { .. } => {}
+    }
+}
+
+fn annotate_stmts(stmts: &mut [MirStmt], types: &HashMap<NodeId, Type>) {
+    for stmt in stmts {
+        annotate_stmt(stmt, types);
+    }
+}
+
+fn annotate_block(block: &mut MirBlock, types: &HashMap<NodeId, Type>) {
+    annotate_expr_stmts(&mut block.statements, types);
+    if let Some(expr) = &mut block.expr {
+        annotate_expr(expr, types);
+    }
+}
+
+fn annotate_expr_stmts(stmts: &mut [MirExprStmt], types: &HashMap<NodeId, Type>) {
+    for stmt in stmts {
+        match stmt {
+            MirExprStmt::Let { value, .. } | MirExprStmt::Assign { value, .. } => {
+                annotate_expr(value, types)
+            }
+            MirExprStmt::IndexAssign { index, value, .. } => {
+                annotate_expr(index, types);
+                annotate_expr(value, types);
+            }
+            MirExprStmt::FieldAssign { object, value, .. } => {
+                annotate_expr(object, types);
+                annotate_expr(value, types);
+            }
+            MirExprStmt::Expr(expr) => annotate_expr(expr, types),
+            MirExprStmt::Return { value, .. } => {
+                if let Some(value) = value {
+                    annotate_expr(value, types);
+                }
+            }
+            MirExprStmt::Try {
+                body,
+                catch_body,
+                finally_body,
+                ..
+            } => {
+                annotate_expr_stmts(body, types);
+                if let Some(catch_body) = catch_body {
+                    annotate_expr_stmts(catch_body, types);
+                }
+                if let Some(finally_body) = finally_body {
+                    annotate_expr_stmts(finally_body, types);
+                }
+            }
+            MirExprStmt::Raise { value, .. } => annotate_expr(value, types),
+            MirExprStmt::If {
+                condition,
+                then_body,
+                else_body,
+                ..
+            } => {
+                annotate_expr(condition, types);
+                annotate_expr_stmts(then_body, types);
+                if let Some(else_body) = else_body {
+                    annotate_expr_stmts(else_body, types);
+                }
+            }
+            MirExprStmt::While {
+                condition, body, ..
+            } => {
+                annotate_expr(condition, types);
+                annotate_expr_stmts(body, types);
+            }
+            MirExprStmt::For { iter, body, .. } => {
+                annotate_expr(iter, types);
+                annotate_expr_stmts(body, types);
+            }
+            MirExprStmt::Break { .. } | MirExprStmt::Continue { .. } => {}
+        }
+    }
+}
+
+fn annotate_expr(expr: &mut MirExpr, types: &HashMap<NodeId, Type>) {
+    match expr {
+        MirExpr::Literal { .. } | MirExpr::Variable { .. } => {}
+        MirExpr::Binary { left, right, .. } => {
+            annotate_expr(left, types);
+            annotate_expr(right, types);
+        }
+        MirExpr::Unary { operand, .. } | MirExpr::Await { expr: operand, .. } => {
+            annotate_expr(operand, types)
+        }
+        MirExpr::Call { callee, args, .. } => {
+            annotate_expr(callee, types);
+            for arg in args {
+                annotate_expr(arg, types);
+            }
+        }
+        MirExpr::Block {
+            statements, expr, ..
+        } => {
+            annotate_expr_stmts(statements, types);
+            if let Some(expr) = expr {
+                annotate_expr(expr, types);
+            }
+        }
+        MirExpr::StringInterp { parts, .. } => {
+            for part in parts {
+                if let MirStringPart::Expr(expr) = part {
+                    annotate_expr(expr, types);
+                }
+            }
+        }
+        MirExpr::List { elements, .. } | MirExpr::Tuple { elements, .. } => {
+            for element in elements {
+                annotate_expr(element, types);
+            }
+        }
+        MirExpr::FieldAccess { object, .. } => annotate_expr(object, types),
+        MirExpr::Map { entries, .. } => {
+            for (key, value) in entries {
+                annotate_expr(key, types);
+                annotate_expr(value, types);
+            }
+        }
+        MirExpr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            annotate_expr(condition, types);
+            annotate_expr(then_branch, types);
+            annotate_expr(else_branch, types);
+        }
+        MirExpr::Index { array, index, .. } => {
+            annotate_expr(array, types);
+            annotate_expr(index, types);
+        }
+        MirExpr::Lambda { body, .. } => annotate_expr(body, types),
+        MirExpr::Range { start, end, .. } => {
+            annotate_expr(start, types);
+            annotate_expr(end, types);
+        }
+        MirExpr::Slice {
+            array, start, end, ..
+        } => {
+            annotate_expr(array, types);
+            annotate_expr(start, types);
+            annotate_expr(end, types);
+        }
+    }
+
+    if let Some(ty) = types.get(&expr.get_id()) {
+        *expr.ty_mut() = ty.clone();
+    }
+}
@@ -4,7 +4,7 @@ use std::fmt;
 
 use thiserror::Error;
 
-use nevermind_common::Span;
+use nevermind_common::{Diagnostic, Severity, Span};
 
 /// Kinds of name resolution errors
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,6 +12,10 @@ pub enum NameErrorKind {
     /// Undefined variable or function
     UndefinedVariable(String),
 
+    /// A `let` initializer that uses the name it declares, with no outer
+    /// binding of that name in scope
+    SelfReference(String),
+
     /// Missing export from an imported module
     UndefinedImport { module: String, symbol: String },
 
@@ -30,11 +34,57 @@ pub enum NameErrorKind {
     /// Continue statement outside of loop
     InvalidContinue,
 
+    /// Await expression outside of an async function
+    InvalidAwait,
+
     /// Failed to load a local module during import resolution
     ModuleLoadFailed(String),
 
     /// Incorrect number of arguments
     ArgumentCountMismatch { expected: usize, found: usize },
+
+    /// Access to a member a class does not declare
+    UnknownMember { class: String, member: String },
+
+    /// No overload of a function takes the given number of arguments
+    NoMatchingOverload { name: String, arity: usize },
+
+    /// Assignment to a binding declared with `let`
+    ImmutableAssignment(String),
+
+    /// A local variable or parameter that is never read
+    UnusedVariable(String),
+
+    /// A statement after a `return`, `break` or `continue` in its block
+    UnreachableCode,
+
+    /// A local binding that hides a variable, parameter or function of an
+    /// enclosing scope
+    ShadowedBinding { name: String, shadowed: String },
+}
+
+impl NameErrorKind {
+    /// The code `nevermind explain` describes this kind of error under
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            NameErrorKind::UndefinedVariable(_) => Some("E0001"),
+            NameErrorKind::UndefinedImport { .. } => Some("E0002"),
+            NameErrorKind::DuplicateDefinition(_) => Some("E0003"),
+            NameErrorKind::InvalidReturn => Some("E0004"),
+            NameErrorKind::InvalidBreak | NameErrorKind::InvalidContinue => Some("E0005"),
+            NameErrorKind::ModuleLoadFailed(_) => Some("E0006"),
+            NameErrorKind::ArgumentCountMismatch { .. } => Some("E0007"),
+            NameErrorKind::UnknownMember { .. } => Some("E0008"),
+            NameErrorKind::NoMatchingOverload { .. } => Some("E0009"),
+            NameErrorKind::ImmutableAssignment(_) => Some("E0010"),
+            NameErrorKind::InvalidAwait => Some("E0022"),
+            NameErrorKind::SelfReference(_) => Some("E0023"),
+            NameErrorKind::InvalidScope
+            | NameErrorKind::UnusedVariable(_)
+            | NameErrorKind::UnreachableCode
+            | NameErrorKind::ShadowedBinding { .. } => None,
+        }
+    }
 }
 
 impl fmt::Display for NameErrorKind {
@@ -43,6 +93,9 @@ impl fmt::Display for NameErrorKind {
             NameErrorKind::UndefinedVariable(name) => {
                 write!(f, "undefined variable or function '{}'", name)
             }
+            NameErrorKind::SelfReference(name) => {
+                write!(f, "binding '{}' used in its own initializer", name)
+            }
             NameErrorKind::UndefinedImport { module, symbol } => {
                 write!(f, "module '{}' does not export '{}'", module, symbol)
             }
@@ -61,12 +114,33 @@ impl fmt::Display for NameErrorKind {
             NameErrorKind::InvalidContinue => {
                 write!(f, "continue statement outside of loop")
             }
+            NameErrorKind::InvalidAwait => {
+                write!(f, "await outside of async function")
+            }
             NameErrorKind::ModuleLoadFailed(module) => {
                 write!(f, "failed to load local module '{}'", module)
             }
             NameErrorKind::ArgumentCountMismatch { expected, found } => {
                 write!(f, "expected {} argument(s), found {}", expected, found)
             }
+            NameErrorKind::UnknownMember { class, member } => {
+                write!(f, "class '{}' has no member '{}'", class, member)
+            }
+            NameErrorKind::NoMatchingOverload { name, arity } => {
+                write!(f, "no overload of '{}' takes {} argument(s)", name, arity)
+            }
+            NameErrorKind::ImmutableAssignment(name) => {
+                write!(f, "assignment to immutable binding '{}'", name)
+            }
+            NameErrorKind::UnusedVariable(name) => {
+                write!(f, "unused variable '{}'", name)
+            }
+            NameErrorKind::UnreachableCode => {
+                write!(f, "unreachable code")
+            }
+            NameErrorKind::ShadowedBinding { name, shadowed } => {
+                write!(f, "'{}' shadows a {} of the same name", name, shadowed)
+            }
         }
     }
 }
@@ -126,6 +200,18 @@ impl NameError {
         )
     }
 
+    /// Create an error for a binding whose initializer reads the binding
+    pub fn self_reference(name: String, span: Span) -> Self {
+        Self::new(
+            NameErrorKind::SelfReference(name.clone()),
+            format!(
+                "binding `{}` cannot reference itself in its own initializer",
+                name
+            ),
+            span,
+        )
+    }
+
     /// Create a missing import error
     pub fn undefined_import(module: String, symbol: String, span: Span) -> Self {
         Self::new(
@@ -179,6 +265,15 @@ impl NameError {
         )
     }
 
+    /// Create an invalid await error
+    pub fn invalid_await(span: Span) -> Self {
+        Self::new(
+            NameErrorKind::InvalidAwait,
+            "await can only be used inside an async function".to_string(),
+            span,
+        )
+    }
+
     /// Create a local module load failure
     pub fn module_load_failed(module: String, detail: String, span: Span) -> Self {
         Self::new(
@@ -197,63 +292,113 @@ impl NameError {
         )
     }
 
-    /// Format the error for display
-    pub fn display(&self, source: Option<&str>) -> String {
-        let mut output = String::new();
+    /// Create an error for accessing a member a class does not declare
+    pub fn unknown_member(class: String, member: String, span: Span) -> Self {
+        Self::new(
+            NameErrorKind::UnknownMember {
+                class: class.clone(),
+                member: member.clone(),
+            },
+            format!("Class '{}' has no member '{}'", class, member),
+            span,
+        )
+    }
 
-        // Error header
-        output.push_str(&format!("{}: error: {}\n", self.span, self.message));
+    /// Create an error for a call that matches no overload's arity
+    pub fn no_matching_overload(name: String, arity: usize, span: Span) -> Self {
+        Self::new(
+            NameErrorKind::NoMatchingOverload {
+                name: name.clone(),
+                arity,
+            },
+            format!("No overload of '{}' takes {} argument(s)", name, arity),
+            span,
+        )
+    }
 
-        // Show source code snippet if available
-        if let Some(source) = source {
-            if let Some(snippet) = self.format_source_snippet(source) {
-                output.push_str(&snippet);
-                output.push('\n');
-            }
-        }
+    /// Create an error for assigning to a binding declared with `let`
+    pub fn immutable_assignment(name: String, span: Span) -> Self {
+        Self::new(
+            NameErrorKind::ImmutableAssignment(name.clone()),
+            format!("Cannot assign to immutable binding '{}'", name),
+            span,
+        )
+    }
 
-        // Show context
-        for ctx in &self.context {
-            if let Some(span) = &ctx.span {
-                output.push_str(&format!("{}: note: {}\n", span, ctx.message));
-            } else {
-                output.push_str(&format!("note: {}\n", ctx.message));
-            }
-        }
+    /// Create a warning for a local binding that is never read
+    pub fn unused_variable(name: String, span: Span) -> Self {
+        Self::new(
+            NameErrorKind::UnusedVariable(name.clone()),
+            format!("Variable '{}' is never used", name),
+            span,
+        )
+        .with_context(
+            format!("prefix it with an underscore to silence this: '_{}'", name),
+            None,
+        )
+    }
 
-        output
+    /// Create a warning for a statement that follows `terminator`, a
+    /// `return`, `break` or `continue`, in the same block
+    pub fn unreachable_code(span: Span, terminator: Span) -> Self {
+        Self::new(
+            NameErrorKind::UnreachableCode,
+            "Unreachable code".to_string(),
+            span,
+        )
+        .with_context("any code after this is never run", Some(terminator))
     }
 
-    /// Format a source code snippet for this error
-    fn format_source_snippet(&self, source: &str) -> Option<String> {
-        let lines: Vec<&str> = source.lines().collect();
+    /// Create a warning for a local binding that hides a `shadowed` binding,
+    /// such as a parameter, declared at `shadowed_span`
+    pub fn shadowed_binding(name: String, shadowed: &str, span: Span, shadowed_span: Span) -> Self {
+        Self::new(
+            NameErrorKind::ShadowedBinding {
+                name: name.clone(),
+                shadowed: shadowed.to_string(),
+            },
+            format!("'{}' shadows a {} of the same name", name, shadowed),
+            span,
+        )
+        .with_context(
+            format!("the {} is declared here", shadowed),
+            Some(shadowed_span),
+        )
+    }
 
-        let line_num = self.span.start.line;
-        if line_num == 0 || line_num > lines.len() {
-            return None;
+    /// Convert this error into a diagnostic, with spanned context as
+    /// secondary labels
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let mut diagnostic = Diagnostic::error(self.message.clone(), self.span.clone());
+        if let Some(code) = self.kind.code() {
+            diagnostic = diagnostic.with_code(code);
         }
 
-        let line = lines[line_num - 1];
-
-        let mut output = String::new();
+        if let NameErrorKind::DuplicateDefinition(_) = self.kind {
+            diagnostic = diagnostic.with_primary_label("then redefined here");
+        }
 
-        // Show the line
-        output.push_str(&format!("  {} | {}\n", line_num, line));
+        for ctx in &self.context {
+            diagnostic = match &ctx.span {
+                Some(span) => diagnostic.with_secondary(span.clone(), ctx.message.clone()),
+                None => diagnostic.with_note(ctx.message.clone()),
+            };
+        }
 
-        // Show the error marker
-        let col_start = self.span.start.column;
-        let col_end = if self.span.start.line == self.span.end.line {
-            self.span.end.column
-        } else {
-            col_start + 1
-        };
+        diagnostic
+    }
 
-        output.push_str(&format!("  {} | ", " ".repeat(line_num.to_string().len())));
-        output.push_str(&" ".repeat(col_start - 1));
-        output.push_str(&"^".repeat(col_end.saturating_sub(col_start)));
-        output.push('\n');
+    /// Convert this error into a warning diagnostic, for problems reported
+    /// without failing resolution
+    pub fn to_warning(&self) -> Diagnostic {
+        let mut diagnostic = self.to_diagnostic();
+        diagnostic.severity = Severity::Warning;
+        diagnostic
+    }
 
-        Some(output)
+    /// Format the error for display
+    pub fn display(&self, source: Option<&str>) -> String {
+        self.to_diagnostic().render(source)
     }
 }
 
@@ -298,4 +443,13 @@ mod tests {
         let display = error.display(None);
         assert!(display.contains("Cannot find value 'my_var'"));
     }
+
+    #[test]
+    fn test_diagnostic_carries_a_registered_code() {
+        let error = NameError::undefined_variable("x".to_string(), Span::dummy());
+
+        let code = error.to_diagnostic().code.unwrap();
+        assert_eq!(code, "E0001");
+        assert!(nevermind_common::codes::lookup(code).is_some());
+    }
 }
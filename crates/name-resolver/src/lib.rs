@@ -10,6 +10,7 @@
 //! - Detection of undefined variables
 //! - Detection of duplicate definitions
 //! - Support for variable shadowing
+//! - Warnings for local variables that are never read
 //! - Validation of control flow (return, break, continue)
 //!
 //! ## Example
@@ -27,7 +28,11 @@
 //! ];
 //!
 //! match resolver.resolve(&stmts) {
-//!     Ok(()) => println!("Name resolution successful"),
+//!     Ok(warnings) => {
+//!         for warning in warnings {
+//!             eprintln!("Warning: {}", warning);
+//!         }
+//!     }
 //!     Err(errors) => {
 //!         for error in errors {
 //!             eprintln!("Error: {}", error);
@@ -1,12 +1,15 @@
 //! Name resolution for Nevermind AST
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
-use nevermind_ast::{Expr, Pattern, Stmt};
+use nevermind_ast::stmt::{namespace_binding, ClassMember};
+use nevermind_ast::{
+    Expr, ImportedSymbol, NodeId, Pattern, Stmt, StringPart, Type, TypeAnnotation,
+};
 
-use crate::error::{NameError, Result};
-use crate::symbol::Symbol;
+use crate::error::{NameError, NameErrorKind, Result};
+use crate::symbol::{Symbol, SymbolKind};
 use crate::symbol_table::SymbolTable;
 
 #[derive(Debug, Clone)]
@@ -15,7 +18,15 @@ struct ModuleExports {
     symbols: HashMap<String, Symbol>,
 }
 
+/// The members a class declares, and the class it extends
+#[derive(Debug, Clone)]
+struct ClassInfo {
+    extends: Option<String>,
+    members: HashMap<String, Symbol>,
+}
+
 /// The name resolver
+#[derive(Clone)]
 pub struct NameResolver {
     /// The symbol table
     symbol_table: SymbolTable,
@@ -23,12 +34,27 @@ pub struct NameResolver {
     /// Collected errors
     errors: Vec<NameError>,
 
+    /// Problems that don't stop resolution, such as unreachable code
+    warnings: Vec<NameError>,
+
     /// Base directory used to locate imported `.nm` files.
     /// `None` in REPL / in-memory compilation contexts.
     base_dir: Option<PathBuf>,
 
     /// Cached exports for local modules that have already been loaded.
     module_exports: HashMap<String, ModuleExports>,
+
+    /// Top-level items already declared by the hoisting pass
+    hoisted: HashSet<NodeId>,
+
+    /// Declared classes, for checking member accesses on their instances
+    classes: HashMap<String, ClassInfo>,
+
+    /// Whether the innermost enclosing function is `async`, so may `await`
+    in_async: bool,
+
+    /// Whether to warn about local bindings that shadow outer ones
+    warn_shadowing: bool,
 }
 
 impl NameResolver {
@@ -37,8 +63,13 @@ impl NameResolver {
         let mut resolver = Self {
             symbol_table: SymbolTable::new(),
             errors: Vec::new(),
+            warnings: Vec::new(),
             base_dir: None,
             module_exports: HashMap::new(),
+            hoisted: HashSet::new(),
+            classes: HashMap::new(),
+            in_async: false,
+            warn_shadowing: false,
         };
         resolver.register_builtins();
         resolver
@@ -52,13 +83,25 @@ impl NameResolver {
         let mut resolver = Self {
             symbol_table: SymbolTable::new(),
             errors: Vec::new(),
+            warnings: Vec::new(),
             base_dir: Some(base_dir),
             module_exports: HashMap::new(),
+            hoisted: HashSet::new(),
+            classes: HashMap::new(),
+            in_async: false,
+            warn_shadowing: false,
         };
         resolver.register_builtins();
         resolver
     }
 
+    /// Warn when a `let` or parameter shadows a variable, parameter or
+    /// function from an enclosing scope
+    pub fn with_shadow_warnings(mut self, warn_shadowing: bool) -> Self {
+        self.warn_shadowing = warn_shadowing;
+        self
+    }
+
     /// Register built-in functions in the global scope
     fn register_builtins(&mut self) {
         let builtins = [
@@ -71,10 +114,22 @@ impl NameResolver {
             ("bool", 1),
             ("type", 1),
             ("input", 1),
+            ("ord", 1),
+            ("chr", 1),
+            ("range", 1),
             ("range", 2),
+            ("range", 3),
             ("abs", 1),
+            ("min", 1),
             ("min", 2),
+            ("max", 1),
             ("max", 2),
+            ("sum", 1),
+            ("product", 1),
+            ("map", 2),
+            ("filter", 2),
+            ("fold", 3),
+            ("assert", 2),
         ];
         for (name, param_count) in builtins {
             let symbol = Symbol::function(
@@ -84,10 +139,30 @@ impl NameResolver {
             );
             let _ = self.symbol_table.declare(name.to_string(), symbol);
         }
+
+        // Option and Result constructors
+        for name in ["Some", "Ok", "Error"] {
+            let symbol = Symbol::function(name.to_string(), 1, nevermind_common::Span::dummy());
+            let _ = self.symbol_table.declare(name.to_string(), symbol);
+        }
+        let none = Symbol::variable("None".to_string(), false, nevermind_common::Span::dummy());
+        let _ = self.symbol_table.declare("None".to_string(), none);
     }
 
     /// Resolve a list of statements
-    pub fn resolve(&mut self, stmts: &[Stmt]) -> Result<(), Vec<NameError>> {
+    ///
+    /// On success, returns warnings for local variables and parameters that
+    /// are never read, for unreachable statements and, when enabled, for
+    /// shadowed bindings.
+    pub fn resolve(&mut self, stmts: &[Stmt]) -> Result<Vec<NameError>, Vec<NameError>> {
+        // Functions, type aliases and classes are visible to the whole module,
+        // so declare them before resolving any bodies
+        for stmt in stmts {
+            if let Err(err) = self.hoist_item(stmt) {
+                self.errors.push(err);
+            }
+        }
+
         for stmt in stmts {
             if let Err(err) = self.resolve_statement(stmt) {
                 self.errors.push(err);
@@ -97,13 +172,113 @@ impl NameResolver {
         // After resolving all statements, check for undefined variables
         self.check_undefined();
 
+        let mut warnings = std::mem::take(&mut self.warnings);
+        warnings.extend(
+            self.symbol_table
+                .take_unread()
+                .into_iter()
+                .map(|symbol| NameError::unused_variable(symbol.name, symbol.span)),
+        );
+        warnings.sort_by_key(|warning| (warning.span.start.line, warning.span.start.column));
         if self.errors.is_empty() {
-            Ok(())
+            Ok(warnings)
         } else {
             Err(self.errors.clone())
         }
     }
 
+    /// Declare a top-level function, type alias or class ahead of its body
+    fn hoist_item(&mut self, stmt: &Stmt) -> Result<()> {
+        let (id, name, symbol) = match stmt {
+            Stmt::Export { stmt, .. } => return self.hoist_item(stmt),
+            Stmt::Function {
+                id,
+                name,
+                params,
+                span,
+                ..
+            } => (
+                *id,
+                name,
+                Symbol::function(name.clone(), params.len(), span.clone()),
+            ),
+            Stmt::Class {
+                id,
+                name,
+                extends,
+                members,
+                span,
+            } => {
+                self.record_class(name, extends.as_ref(), members);
+                (*id, name, Symbol::type_(name.clone(), span.clone()))
+            }
+            Stmt::TypeAlias { id, name, span, .. } => {
+                (*id, name, Symbol::type_(name.clone(), span.clone()))
+            }
+            _ => return Ok(()),
+        };
+
+        self.hoisted.insert(id);
+        self.symbol_table.declare(name.clone(), symbol)
+    }
+
+    /// Warn if declaring `name` in the current scope would hide a variable,
+    /// parameter or function of an enclosing one.
+    ///
+    /// Names starting with `_` are never reported, and neither are built-ins
+    /// or types.
+    fn check_shadowing(&mut self, name: &str, span: &nevermind_common::Span) {
+        if !self.warn_shadowing || name.starts_with('_') {
+            return;
+        }
+        let Some(outer) = self.symbol_table.resolve_enclosing(name) else {
+            return;
+        };
+        if outer.span == nevermind_common::Span::dummy() {
+            return;
+        }
+        let shadowed = match outer.kind {
+            SymbolKind::Variable { .. } | SymbolKind::LoopVariable => "variable",
+            SymbolKind::Parameter { .. } => "parameter",
+            SymbolKind::Function { .. } => "function",
+            SymbolKind::Type => return,
+        };
+        let warning = NameError::shadowed_binding(
+            name.to_string(),
+            shadowed,
+            span.clone(),
+            outer.span.clone(),
+        );
+        self.warnings.push(warning);
+    }
+
+    /// Resolve the statements of a block, warning about any that follow a
+    /// `return`, `raise`, `break` or `continue` and so can never run
+    fn resolve_block(&mut self, stmts: &[Stmt]) -> Result<()> {
+        let terminator = stmts.iter().position(|stmt| {
+            matches!(
+                stmt,
+                Stmt::Return { .. }
+                    | Stmt::Raise { .. }
+                    | Stmt::Break { .. }
+                    | Stmt::Continue { .. }
+            )
+        });
+        if let Some(index) = terminator {
+            if let Some(dead) = stmts.get(index + 1) {
+                self.warnings.push(NameError::unreachable_code(
+                    dead.span().clone(),
+                    stmts[index].span().clone(),
+                ));
+            }
+        }
+
+        for stmt in stmts {
+            self.resolve_statement(stmt)?;
+        }
+        Ok(())
+    }
+
     /// Resolve a statement
     fn resolve_statement(&mut self, stmt: &Stmt) -> Result<()> {
         match stmt {
@@ -111,51 +286,77 @@ impl NameResolver {
             Stmt::Let {
                 name,
                 is_mutable,
+                type_annotation,
                 value,
                 span,
                 ..
             } => {
-                // First resolve the initializer expression
-                self.resolve_expression(value)?;
+                // First resolve the initializer expression. The name isn't
+                // bound yet, so it can only mean an outer binding
+                self.resolve_expression(value)
+                    .map_err(|error| match &error.kind {
+                        NameErrorKind::UndefinedVariable(undefined) if undefined == name => {
+                            NameError::self_reference(name.clone(), error.span)
+                        }
+                        _ => error,
+                    })?;
 
                 // Then declare the variable
-                let symbol = Symbol::variable(name.clone(), *is_mutable, span.clone());
+                self.check_shadowing(name, span);
+                let mut symbol = Symbol::variable(name.clone(), *is_mutable, span.clone());
+                symbol.type_ = self
+                    .instance_class(type_annotation.as_ref(), Some(value))
+                    .map(Type::Identifier);
                 self.symbol_table.declare(name.clone(), symbol)?;
                 Ok(())
             }
 
             Stmt::Function {
+                id,
+                is_async,
                 name,
                 params,
                 body,
                 span,
                 ..
             } => {
-                // Declare the function in the current scope
-                let func_symbol = Symbol::function(name.clone(), params.len(), span.clone());
-                self.symbol_table.declare(name.clone(), func_symbol)?;
+                // Declare the function in the current scope, unless it was hoisted
+                if !self.hoisted.contains(id) {
+                    let func_symbol = Symbol::function(name.clone(), params.len(), span.clone());
+                    self.symbol_table.declare(name.clone(), func_symbol)?;
+                }
 
                 // Enter a new function scope
                 self.symbol_table.enter_function();
 
                 // Declare parameters
                 for (i, param) in params.iter().enumerate() {
-                    let param_symbol =
-                        Symbol::parameter(param.name.clone(), i, nevermind_common::Span::dummy());
+                    self.check_shadowing(&param.name, &param.span);
+                    let mut param_symbol =
+                        Symbol::parameter(param.name.clone(), i, param.span.clone());
+                    param_symbol.type_ = self
+                        .instance_class(param.type_annotation.as_ref(), None)
+                        .map(Type::Identifier);
                     self.symbol_table
                         .declare(param.name.clone(), param_symbol)?;
                 }
 
                 // Resolve the function body
-                self.resolve_expression(body)?;
+                let enclosing_async = std::mem::replace(&mut self.in_async, *is_async);
+                let result = self.resolve_expression(body);
+                self.in_async = enclosing_async;
+                result?;
 
                 // Exit function scope
                 self.symbol_table.exit_function()?;
                 Ok(())
             }
 
-            Stmt::TypeAlias { name, span, .. } => {
-                // Declare the type
+            Stmt::TypeAlias { id, name, span, .. } => {
+                // Declare the type, unless it was hoisted
+                if self.hoisted.contains(id) {
+                    return Ok(());
+                }
                 let type_symbol = Symbol::type_(name.clone(), span.clone());
                 self.symbol_table.declare(name.clone(), type_symbol)
             }
@@ -171,17 +372,13 @@ impl NameResolver {
 
                 // Resolve then branch
                 self.symbol_table.enter_scope();
-                for stmt in then_branch {
-                    self.resolve_statement(stmt)?;
-                }
+                self.resolve_block(then_branch)?;
                 self.symbol_table.exit_scope()?;
 
                 // Resolve else branch if present
                 if let Some(else_branch) = else_branch {
                     self.symbol_table.enter_scope();
-                    for stmt in else_branch {
-                        self.resolve_statement(stmt)?;
-                    }
+                    self.resolve_block(else_branch)?;
                     self.symbol_table.exit_scope()?;
                 }
 
@@ -196,9 +393,7 @@ impl NameResolver {
 
                 // Resolve body in a loop scope
                 self.symbol_table.enter_loop();
-                for stmt in body {
-                    self.resolve_statement(stmt)?;
-                }
+                self.resolve_block(body)?;
                 self.symbol_table.exit_loop()?;
                 Ok(())
             }
@@ -235,9 +430,7 @@ impl NameResolver {
                 }
 
                 // Resolve body
-                for stmt in body {
-                    self.resolve_statement(stmt)?;
-                }
+                self.resolve_block(body)?;
 
                 self.symbol_table.exit_loop()?;
                 Ok(())
@@ -281,6 +474,40 @@ impl NameResolver {
                 Ok(())
             }
 
+            Stmt::Try {
+                body,
+                catch_var,
+                catch_body,
+                finally_body,
+                span,
+                ..
+            } => {
+                self.symbol_table.enter_scope();
+                self.resolve_block(body)?;
+                self.symbol_table.exit_scope()?;
+
+                // The catch variable is only visible in the catch body
+                if let Some(catch_body) = catch_body {
+                    self.symbol_table.enter_scope();
+                    if let Some(name) = catch_var {
+                        let symbol = Symbol::variable(name.clone(), false, span.clone());
+                        self.symbol_table.declare(name.clone(), symbol)?;
+                    }
+                    self.resolve_block(catch_body)?;
+                    self.symbol_table.exit_scope()?;
+                }
+
+                if let Some(finally_body) = finally_body {
+                    self.symbol_table.enter_scope();
+                    self.resolve_block(finally_body)?;
+                    self.symbol_table.exit_scope()?;
+                }
+
+                Ok(())
+            }
+
+            Stmt::Raise { value, .. } => self.resolve_expression(value),
+
             Stmt::Break { span, .. } => {
                 if !self.symbol_table.in_loop() {
                     return Err(NameError::invalid_break(span.clone()));
@@ -300,14 +527,31 @@ impl NameResolver {
             Stmt::Import {
                 module,
                 symbols,
+                alias,
                 span,
                 ..
-            } => self.resolve_import(module, symbols.as_deref(), span),
+            } => self.resolve_import(module, symbols.as_deref(), alias.as_deref(), span),
 
-            Stmt::Class { name, members, .. } => {
-                // Declare the class as a type
-                let class_symbol = Symbol::type_(name.clone(), nevermind_common::Span::dummy());
-                self.symbol_table.declare(name.clone(), class_symbol)?;
+            Stmt::Class {
+                id,
+                name,
+                extends,
+                members,
+                ..
+            } => {
+                // Declare the class as a type, unless it was hoisted
+                if !self.hoisted.contains(id) {
+                    self.record_class(name, extends.as_ref(), members);
+                    let class_symbol = Symbol::type_(name.clone(), nevermind_common::Span::dummy());
+                    self.symbol_table.declare(name.clone(), class_symbol)?;
+                }
+
+                // Members inherited from the classes it extends are visible
+                // in its methods, unless the class declares its own
+                self.symbol_table.enter_scope();
+                for (member, symbol) in self.inherited_members(extends.as_ref()) {
+                    self.symbol_table.declare(member, symbol)?;
+                }
 
                 // Enter class scope
                 self.symbol_table.enter_scope();
@@ -315,15 +559,17 @@ impl NameResolver {
                 // Resolve members
                 for member in members {
                     match member {
-                        nevermind_ast::stmt::ClassMember::Field { name, .. } => {
+                        ClassMember::Field {
+                            name, is_mutable, ..
+                        } => {
                             let field_symbol = Symbol::variable(
                                 name.clone(),
-                                false,
+                                *is_mutable,
                                 nevermind_common::Span::dummy(),
                             );
                             self.symbol_table.declare(name.clone(), field_symbol)?;
                         }
-                        nevermind_ast::stmt::ClassMember::Method {
+                        ClassMember::Method {
                             name, params, body, ..
                         } => {
                             let method_symbol = Symbol::function(
@@ -347,12 +593,16 @@ impl NameResolver {
                                     .declare(param.name.clone(), param_symbol)?;
                             }
 
-                            self.resolve_expression(body)?;
+                            let enclosing_async = std::mem::replace(&mut self.in_async, false);
+                            let result = self.resolve_expression(body);
+                            self.in_async = enclosing_async;
+                            result?;
                             self.symbol_table.exit_function()?;
                         }
                     }
                 }
 
+                self.symbol_table.exit_scope()?;
                 self.symbol_table.exit_scope()?;
                 Ok(())
             }
@@ -365,13 +615,15 @@ impl NameResolver {
 
     /// Resolve an import statement.
     ///
-    /// Two forms are supported:
-    /// - `from "module" import sym1, sym2` — selective import
-    /// - `use "module"`                    — namespace import
+    /// Two forms are supported, each taking an optional `as` alias that is
+    /// declared in place of the original name:
+    /// - `from "module" import sym1, sym2 as other` — selective import
+    /// - `use "module"` or `use "module" as m`      — namespace import
     fn resolve_import(
         &mut self,
         module: &str,
-        symbols: Option<&[String]>,
+        symbols: Option<&[ImportedSymbol]>,
+        alias: Option<&str>,
         span: &nevermind_common::Span,
     ) -> Result<()> {
         let local_module = self.load_module_exports(module, span)?;
@@ -379,7 +631,8 @@ impl NameResolver {
         match symbols {
             Some(syms) => {
                 if let Some(local_module) = local_module.as_ref() {
-                    for sym_name in syms {
+                    for symbol in syms {
+                        let sym_name = &symbol.name;
                         let export = match local_module.symbols.get(sym_name).cloned() {
                             Some(export) => export,
                             None => {
@@ -407,19 +660,20 @@ impl NameResolver {
                             }
                         };
 
-                        self.declare_imported_symbol(sym_name, &export, span)?;
+                        self.declare_imported_symbol(symbol.binding(), &export, span)?;
                     }
                 } else {
-                    for sym_name in syms {
-                        if !self.symbol_table.is_defined(sym_name) {
-                            let sym = Symbol::variable(sym_name.clone(), false, span.clone());
-                            self.symbol_table.declare(sym_name.clone(), sym)?;
+                    for symbol in syms {
+                        let binding = symbol.binding();
+                        if !self.symbol_table.is_defined(binding) {
+                            let sym = Symbol::variable(binding.to_string(), false, span.clone());
+                            self.symbol_table.declare(binding.to_string(), sym)?;
                         }
                     }
                 }
             }
             None => {
-                let namespace = module.split('/').next_back().unwrap_or(module);
+                let namespace = namespace_binding(module, alias);
                 if !self.symbol_table.is_defined(namespace) {
                     let sym = Symbol::variable(namespace.to_string(), false, span.clone());
                     self.symbol_table.declare(namespace.to_string(), sym)?;
@@ -558,10 +812,24 @@ impl NameResolver {
     /// Resolve an expression
     fn resolve_expression(&mut self, expr: &Expr) -> Result<()> {
         match expr {
-            Expr::Literal(_) => Ok(()),
+            Expr::Literal(nevermind_ast::Literal::String(text, _)) => {
+                // A literal with braces becomes a Python f-string, so the
+                // names inside them are read
+                for name in interpolated_names(text) {
+                    self.symbol_table.mark_read(name);
+                }
+                Ok(())
+            }
+
+            Expr::Literal(_) | Expr::Unit { .. } => Ok(()),
 
-            Expr::Variable { name, .. } => {
-                self.symbol_table.resolve(name)?;
+            Expr::Variable { name, span, .. } => {
+                // The symbol table doesn't know where the name was used
+                if let Err(mut error) = self.symbol_table.resolve(name) {
+                    error.span = span.clone();
+                    return Err(error);
+                }
+                self.symbol_table.mark_read(name);
                 Ok(())
             }
 
@@ -585,19 +853,33 @@ impl NameResolver {
 
             Expr::Unary { expr, .. } => self.resolve_expression(expr),
 
-            Expr::Call { callee, args, .. } => {
-                self.resolve_expression(callee)?;
-
-                for arg in args {
-                    self.resolve_expression(arg)?;
+            Expr::Await { expr, span, .. } => {
+                if !self.in_async {
+                    return Err(NameError::invalid_await(span.clone()));
                 }
-
-                Ok(())
+                self.resolve_expression(expr)
             }
 
+            Expr::Call {
+                callee, args, span, ..
+            } => self.resolve_call(callee, args, 0, span),
+
             Expr::Pipeline { stages, .. } => {
-                for stage in stages {
-                    self.resolve_expression(stage)?;
+                if let Some(first) = stages.first() {
+                    self.resolve_expression(first)?;
+                }
+                // Each later stage receives the piped value as its first argument
+                for stage in stages.iter().skip(1) {
+                    match stage {
+                        Expr::Call {
+                            callee, args, span, ..
+                        } => self.resolve_call(callee, args, 1, span)?,
+                        Expr::Variable { name, span, .. } => {
+                            self.resolve_expression(stage)?;
+                            self.check_overload_arity(name, 1, span)?;
+                        }
+                        _ => self.resolve_expression(stage)?,
+                    }
                 }
                 Ok(())
             }
@@ -608,14 +890,17 @@ impl NameResolver {
 
                 // Declare parameters
                 for (i, param) in params.iter().enumerate() {
-                    let param_symbol =
-                        Symbol::parameter(param.name.clone(), i, nevermind_common::Span::dummy());
+                    self.check_shadowing(&param.name, &param.span);
+                    let param_symbol = Symbol::parameter(param.name.clone(), i, param.span.clone());
                     self.symbol_table
                         .declare(param.name.clone(), param_symbol)?;
                 }
 
-                // Resolve body
-                self.resolve_expression(body)?;
+                // Resolve body; a lambda is never async
+                let enclosing_async = std::mem::replace(&mut self.in_async, false);
+                let result = self.resolve_expression(body);
+                self.in_async = enclosing_async;
+                result?;
 
                 // Exit scope
                 self.symbol_table.exit_scope()?;
@@ -636,16 +921,21 @@ impl NameResolver {
 
             Expr::Block { statements, .. } => {
                 self.symbol_table.enter_scope();
+                self.resolve_block(statements)?;
+                self.symbol_table.exit_scope()?;
+                Ok(())
+            }
 
-                for stmt in statements {
-                    self.resolve_statement(stmt)?;
+            Expr::StringInterp { parts, .. } => {
+                for part in parts {
+                    if let StringPart::Expr(expr) = part {
+                        self.resolve_expression(expr)?;
+                    }
                 }
-
-                self.symbol_table.exit_scope()?;
                 Ok(())
             }
 
-            Expr::List { elements, .. } => {
+            Expr::List { elements, .. } | Expr::Tuple { elements, .. } => {
                 for elem in elements {
                     self.resolve_expression(elem)?;
                 }
@@ -685,13 +975,223 @@ impl NameResolver {
                 self.resolve_expression(index)
             }
 
+            Expr::Range { start, end, .. } => {
+                self.resolve_expression(start)?;
+                self.resolve_expression(end)
+            }
+
+            Expr::Slice {
+                array, start, end, ..
+            } => {
+                self.resolve_expression(array)?;
+                self.resolve_expression(start)?;
+                self.resolve_expression(end)
+            }
+
             Expr::Assign { target, value, .. } => {
                 self.resolve_expression(target)?;
+                if let Expr::Variable { name, span, .. } = target.as_ref() {
+                    self.check_assignable(name, span)?;
+                }
                 self.resolve_expression(value)
             }
 
-            Expr::MemberAccess { object, .. } => self.resolve_expression(object),
+            Expr::MemberAccess {
+                object,
+                member,
+                span,
+                ..
+            } => {
+                self.resolve_expression(object)?;
+                self.check_member(object, member, span)
+            }
+        }
+    }
+
+    /// Remember the members `name` declares, for checking member accesses
+    fn record_class(&mut self, name: &str, extends: Option<&String>, members: &[ClassMember]) {
+        let members = members
+            .iter()
+            .map(|member| {
+                let symbol = match member {
+                    ClassMember::Field {
+                        name, is_mutable, ..
+                    } => {
+                        Symbol::variable(name.clone(), *is_mutable, nevermind_common::Span::dummy())
+                    }
+                    ClassMember::Method { name, params, .. } => Symbol::function(
+                        name.clone(),
+                        params.len(),
+                        nevermind_common::Span::dummy(),
+                    ),
+                };
+                (symbol.name.clone(), symbol)
+            })
+            .collect();
+        self.classes.insert(
+            name.to_string(),
+            ClassInfo {
+                extends: extends.cloned(),
+                members,
+            },
+        );
+    }
+
+    /// The members of the classes `extends` names and the ones it extends in
+    /// turn, the nearest declaration of each name winning
+    fn inherited_members(&self, extends: Option<&String>) -> Vec<(String, Symbol)> {
+        let mut inherited: HashMap<String, Symbol> = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut current = extends;
+        while let Some(name) = current.filter(|name| visited.insert(*name)) {
+            let Some(info) = self.classes.get(name) else {
+                break;
+            };
+            for (member, symbol) in &info.members {
+                inherited
+                    .entry(member.clone())
+                    .or_insert_with(|| symbol.clone());
+            }
+            current = info.extends.as_ref();
+        }
+        inherited.into_iter().collect()
+    }
+
+    /// The declared class a binding holds an instance of, going by its type
+    /// annotation or else a constructor call initializing it
+    fn instance_class(
+        &self,
+        annotation: Option<&TypeAnnotation>,
+        value: Option<&Expr>,
+    ) -> Option<String> {
+        let class = match (annotation, value) {
+            (Some(annotation), _) => match &annotation.kind {
+                Type::Identifier(name) => name,
+                _ => return None,
+            },
+            (None, Some(Expr::Call { callee, .. })) => match callee.as_ref() {
+                Expr::Variable { name, .. } => name,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        // A local binding may shadow the class's name
+        let symbol = self.symbol_table.resolve(class).ok()?;
+        (symbol.kind == SymbolKind::Type && self.classes.contains_key(class)).then(|| class.clone())
+    }
+
+    /// Check that `object.member` names a member of the object's class, when
+    /// the object is known to be a class instance
+    fn check_member(
+        &self,
+        object: &Expr,
+        member: &str,
+        span: &nevermind_common::Span,
+    ) -> Result<()> {
+        let Expr::Variable { name, .. } = object else {
+            return Ok(());
+        };
+        let Ok(Symbol {
+            type_: Some(Type::Identifier(class)),
+            ..
+        }) = self.symbol_table.resolve(name)
+        else {
+            return Ok(());
+        };
+
+        // Walk up the superclasses; one that isn't declared here may provide
+        // any member
+        let mut visited = HashSet::new();
+        let mut current = Some(class);
+        while let Some(name) = current.filter(|name| visited.insert(*name)) {
+            let Some(info) = self.classes.get(name) else {
+                return Ok(());
+            };
+            if info.members.contains_key(member) {
+                return Ok(());
+            }
+            current = info.extends.as_ref();
+        }
+
+        Err(NameError::unknown_member(
+            class.clone(),
+            member.to_string(),
+            span.clone(),
+        ))
+    }
+
+    /// Check that `name` may be assigned to: only a `let` binding may not
+    fn check_assignable(&self, name: &str, span: &nevermind_common::Span) -> Result<()> {
+        let symbol = self.symbol_table.resolve(name)?;
+        if symbol.kind != (SymbolKind::Variable { is_mutable: false }) {
+            return Ok(());
+        }
+
+        // Pattern bindings have no declaration span to point at
+        let declared_at =
+            Some(symbol.span.clone()).filter(|s| *s != nevermind_common::Span::dummy());
+        let hint = format!(
+            "'{}' is declared with `let`; use `var` to allow reassignment",
+            name
+        );
+        Err(
+            NameError::immutable_assignment(name.to_string(), span.clone())
+                .with_context(hint, declared_at),
+        )
+    }
+
+    /// Resolve a call whose callee also receives `piped` arguments ahead of
+    /// `args`, as a pipeline stage does
+    fn resolve_call(
+        &mut self,
+        callee: &Expr,
+        args: &[Expr],
+        piped: usize,
+        span: &nevermind_common::Span,
+    ) -> Result<()> {
+        self.resolve_expression(callee)?;
+
+        for arg in args {
+            self.resolve_expression(arg)?;
+        }
+
+        if let Expr::Variable { name, .. } = callee {
+            self.check_overload_arity(name, piped + args.len(), span)?;
+        }
+
+        Ok(())
+    }
+
+    /// Check that a call to an overloaded function matches one of its arities
+    fn check_overload_arity(
+        &self,
+        name: &str,
+        arity: usize,
+        span: &nevermind_common::Span,
+    ) -> Result<()> {
+        let Some(overloads) = self.symbol_table.resolve_overloads(name) else {
+            return Ok(());
+        };
+
+        if overloads
+            .iter()
+            .any(|overload| overload.param_count() == Some(arity))
+        {
+            return Ok(());
+        }
+
+        let mut error = NameError::no_matching_overload(name.to_string(), arity, span.clone());
+        for overload in overloads {
+            error = error.with_context(
+                format!(
+                    "candidate takes {} argument(s)",
+                    overload.param_count().unwrap_or(0)
+                ),
+                Some(overload.span.clone()),
+            );
         }
+        Err(error)
     }
 
     /// Resolve a pattern
@@ -763,6 +1263,15 @@ impl NameResolver {
     }
 }
 
+/// Identifiers inside the `{...}` segments of a string literal
+fn interpolated_names(text: &str) -> impl Iterator<Item = &str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(inside, _)| inside))
+        .flat_map(|inside| inside.split(|c: char| !(c.is_alphanumeric() || c == '_')))
+        .filter(|word| word.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+}
+
 impl Default for NameResolver {
     fn default() -> Self {
         Self::new()
@@ -776,6 +1285,7 @@ mod tests {
     use std::time::{SystemTime, UNIX_EPOCH};
 
     use super::*;
+    use crate::error::NameErrorKind;
     use nevermind_ast::Literal;
     use nevermind_ast::{Expr, Parameter, Stmt};
 
@@ -848,6 +1358,7 @@ mod tests {
         // Create a simple function declaration
         let stmt = Stmt::Function {
             id: 1,
+            is_async: false,
             name: "add".to_string(),
             params: vec![
                 Parameter {
@@ -855,12 +1366,14 @@ mod tests {
                     name: "a".to_string(),
                     type_annotation: None,
                     default_value: None,
+                    span: nevermind_common::Span::dummy(),
                 },
                 Parameter {
                     id: 3,
                     name: "b".to_string(),
                     type_annotation: None,
                     default_value: None,
+                    span: nevermind_common::Span::dummy(),
                 },
             ],
             return_type: None,
@@ -928,6 +1441,28 @@ mod tests {
         assert!(!resolver.symbol_table.is_defined("cube"));
     }
 
+    #[test]
+    fn test_import_aliases_are_declared_instead_of_original_names() {
+        let temp_dir = TestDir::new("nevermind_name_resolver_import_alias");
+        fs::write(
+            temp_dir.path.join("mathutils.nm"),
+            "export fn square(n) do n end\n",
+        )
+        .unwrap();
+
+        let statements = parse_statements(
+            "from \"mathutils\" import square as sq\nuse \"collections\" as coll\n",
+        );
+        let mut resolver = NameResolver::with_base_dir(temp_dir.path.clone());
+
+        resolver.resolve(&statements).unwrap();
+
+        assert!(resolver.symbol_table.is_defined("sq"));
+        assert!(!resolver.symbol_table.is_defined("square"));
+        assert!(resolver.symbol_table.is_defined("coll"));
+        assert!(!resolver.symbol_table.is_defined("collections"));
+    }
+
     #[test]
     fn test_missing_local_import_reports_error() {
         let temp_dir = TestDir::new("nevermind_name_resolver_missing_import");
@@ -991,4 +1526,322 @@ mod tests {
         assert!(resolver.symbol_table.is_defined("dumps"));
         assert!(resolver.symbol_table.is_defined("collections"));
     }
+
+    #[test]
+    fn test_duplicate_definition_renders_both_locations() {
+        let source = "let total = 1\nprint total\nlet total = 2\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+        let errors = resolver.resolve(&statements).unwrap_err();
+
+        let rendered = errors[0].display(Some(source));
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        let first = lines
+            .iter()
+            .position(|line| line.ends_with("first defined here"))
+            .expect("missing label for the first definition");
+        let second = lines
+            .iter()
+            .position(|line| line.ends_with("then redefined here"))
+            .expect("missing label for the redefinition");
+        assert!(first < second);
+
+        assert_eq!(lines[first - 1], " 1 | let total = 1");
+        assert_eq!(lines[first], "   | ------------- first defined here");
+        assert_eq!(lines[second - 2], "  ...");
+        assert_eq!(lines[second - 1], " 3 | let total = 2");
+        assert_eq!(lines[second], "   | ^^^^^^^^^^^^^ then redefined here");
+    }
+
+    #[test]
+    fn test_functions_overloaded_by_arity_resolve() {
+        let source = "fn area(r) do r * r end\nfn area(w, h) do w * h end\nprint area(2)\nprint area(2, 3)\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        resolver.resolve(&statements).unwrap();
+
+        let overloads = resolver.symbol_table.resolve_overloads("area").unwrap();
+        let arities: Vec<_> = overloads.iter().map(|o| o.param_count()).collect();
+        assert_eq!(arities, vec![Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_functions_can_be_called_before_their_definition() {
+        let source = "fn main() do\n  helper(2)\nend\nfn helper(x) do x * 2 end\nmain()\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        resolver.resolve(&statements).unwrap();
+    }
+
+    #[test]
+    fn test_top_level_let_is_not_visible_before_its_declaration() {
+        let source = "print total\nlet total = 1\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        let errors = resolver.resolve(&statements).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            NameErrorKind::UndefinedVariable(ref name) if name == "total"
+        ));
+    }
+
+    #[test]
+    fn test_let_initializer_cannot_reference_its_own_binding() {
+        let statements = parse_statements("let x = x + 1\n");
+        let mut resolver = NameResolver::new();
+
+        let errors = resolver.resolve(&statements).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind,
+            NameErrorKind::SelfReference("x".to_string())
+        );
+        assert!(errors[0]
+            .message
+            .contains("binding `x` cannot reference itself in its own initializer"));
+    }
+
+    #[test]
+    fn test_let_initializer_may_read_the_binding_it_shadows() {
+        let source = "let x = 1\nfn f() do\n  let x = x + 1\n  x\nend\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        resolver.resolve(&statements).unwrap();
+    }
+
+    #[test]
+    fn test_assigning_to_a_let_binding_is_an_error() {
+        let source = "let total = 1\ntotal = 2\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        let errors = resolver.resolve(&statements).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind,
+            NameErrorKind::ImmutableAssignment("total".to_string())
+        );
+        assert_eq!(errors[0].span.start.line, 2);
+        assert_eq!(errors[0].context[0].span.as_ref().unwrap().start.line, 1);
+    }
+
+    #[test]
+    fn test_var_bindings_and_parameters_can_be_assigned() {
+        let source = "var total = 1\ntotal = 2\nfn bump(n) do\n  n = n + 1\n  n\nend\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        resolver.resolve(&statements).unwrap();
+    }
+
+    #[test]
+    fn test_unused_local_variable_warns() {
+        let source = "fn main() do\n  let y = 1\n  0\nend\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        let warnings = resolver.resolve(&statements).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].kind,
+            NameErrorKind::UnusedVariable("y".to_string())
+        );
+        assert_eq!(warnings[0].span.start.line, 2);
+    }
+
+    #[test]
+    fn test_unused_parameter_warning_points_at_the_parameter() {
+        let source = "fn main(x,\n        unused) do\n  x\nend\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        let warnings = resolver.resolve(&statements).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].kind,
+            NameErrorKind::UnusedVariable("unused".to_string())
+        );
+        assert_eq!(warnings[0].span.start.line, 2);
+        assert_eq!(warnings[0].span.start.column, 9);
+    }
+
+    #[test]
+    fn test_read_variables_and_parameters_do_not_warn() {
+        let source = "fn main(x) do\n  let y = x\n  let z = 2\n  \"{z}\"\n  y\nend\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        let warnings = resolver.resolve(&statements).unwrap();
+        assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+    }
+
+    #[test]
+    fn test_underscore_variables_are_exempt_from_unused_warnings() {
+        let source = "fn main(_unused) do\n  let _tmp = 1\n  0\nend\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        let warnings = resolver.resolve(&statements).unwrap();
+        assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+    }
+
+    #[test]
+    fn test_range_takes_one_to_three_arguments() {
+        let source = "range(5)\nrange(1, 5)\nrange(1, 10, 2)\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+        resolver.resolve(&statements).unwrap();
+
+        let statements = parse_statements("range(1, 10, 2, 4)\n");
+        let mut resolver = NameResolver::new();
+        let errors = resolver.resolve(&statements).unwrap_err();
+        assert_eq!(
+            errors[0].kind,
+            NameErrorKind::NoMatchingOverload {
+                name: "range".to_string(),
+                arity: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_statement_after_return_is_unreachable() {
+        let source = "fn f(x) do\n  return x\n  print \"dead\"\nend\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        let warnings = resolver.resolve(&statements).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, NameErrorKind::UnreachableCode);
+        assert_eq!(warnings[0].span.start.line, 3);
+    }
+
+    #[test]
+    fn test_other_if_branch_is_not_unreachable() {
+        let source = "fn f(x) do if x > 1 do return 1 end else do print \"alive\" end end x end\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        let warnings = resolver.resolve(&statements).unwrap();
+        assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+    }
+
+    #[test]
+    fn test_shadowing_a_function_warns_when_enabled() {
+        let source = "fn total(xs) do sum(xs) end\nfn main() do\n  let total = 3\n  total\nend\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new().with_shadow_warnings(true);
+
+        let warnings = resolver.resolve(&statements).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].kind,
+            NameErrorKind::ShadowedBinding {
+                name: "total".to_string(),
+                shadowed: "function".to_string()
+            }
+        );
+        assert_eq!(warnings[0].span.start.line, 3);
+        assert_eq!(warnings[0].context[0].span.as_ref().unwrap().start.line, 1);
+
+        let mut resolver = NameResolver::new();
+        assert!(resolver.resolve(&statements).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_shadowing_a_parameter_names_its_kind() {
+        let source = "fn f(x) do\n  if x > 1 do\n    let x = 2\n    print x\n  end\n  x\nend\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new().with_shadow_warnings(true);
+
+        let warnings = resolver.resolve(&statements).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "'x' shadows a parameter of the same name"
+        );
+        // The parameter itself is pointed at, not the whole function
+        let declared = warnings[0].context[0].span.as_ref().unwrap();
+        assert_eq!((declared.start.line, declared.start.column), (1, 6));
+    }
+
+    #[test]
+    fn test_bindings_in_disjoint_scopes_do_not_shadow() {
+        let source = "fn f() do\n  let x = 1\n  x\nend\nfn g(x) do\n  let _total = 2\n  x\nend\n\
+                      fn h(_total) do\n  let _total = 3\n  0\nend\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new().with_shadow_warnings(true);
+
+        let warnings = resolver.resolve(&statements).unwrap();
+        assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+    }
+
+    #[test]
+    fn test_call_matching_no_overload_arity_is_an_error() {
+        let source = "fn area(r) do r * r end\nfn area(w, h) do w * h end\nprint area(1, 2, 3)\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        let errors = resolver.resolve(&statements).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind,
+            NameErrorKind::NoMatchingOverload {
+                name: "area".to_string(),
+                arity: 3
+            }
+        );
+        assert_eq!(errors[0].context.len(), 2);
+    }
+
+    #[test]
+    fn test_same_arity_function_redefinition_is_still_duplicate() {
+        let source = "fn area(r) do r end\nfn area(w, h) do w end\nfn area(x) do x end\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+
+        let errors = resolver.resolve(&statements).unwrap_err();
+        assert!(matches!(
+            errors[0].kind,
+            NameErrorKind::DuplicateDefinition(ref name) if name == "area"
+        ));
+    }
+
+    #[test]
+    fn test_user_function_replaces_builtin_of_the_same_name() {
+        let source = "fn print(a, b) do a end\nprint(1, 2)\nfn range(n) do n end\nrange(3)\n";
+        let statements = parse_statements(source);
+        let mut resolver = NameResolver::new();
+        resolver.resolve(&statements).unwrap();
+
+        // The user's range replaces every builtin arity
+        assert!(resolver.symbol_table.resolve_overloads("range").is_none());
+        let range = resolver.symbol_table.resolve("range").unwrap();
+        assert_eq!(range.param_count(), Some(1));
+    }
+
+    #[test]
+    fn test_member_access_is_checked_against_class_members() {
+        let class = "class Point {\n  let x: Int\n}\nlet obj = Point(1)\n";
+
+        let statements = parse_statements(&format!("{}println(obj.x)\n", class));
+        NameResolver::new().resolve(&statements).unwrap();
+
+        let statements = parse_statements(&format!("{}println(obj.y)\n", class));
+        let errors = NameResolver::new().resolve(&statements).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind,
+            NameErrorKind::UnknownMember {
+                class: "Point".to_string(),
+                member: "y".to_string(),
+            }
+        );
+    }
 }
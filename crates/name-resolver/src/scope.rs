@@ -17,6 +17,9 @@ pub struct Scope {
 
     /// Nesting level (0 = global)
     pub level: u32,
+
+    /// Every arity of a function name declared more than once in this scope
+    pub overloads: HashMap<String, Vec<Symbol>>,
 }
 
 impl Scope {
@@ -26,6 +29,7 @@ impl Scope {
             parent: parent.map(Box::new),
             symbols: HashMap::new(),
             level,
+            overloads: HashMap::new(),
         }
     }
 
@@ -37,26 +41,47 @@ impl Scope {
     /// Insert a symbol into this scope
     ///
     /// Returns an error if a symbol with the same name already exists
-    /// in this scope (shadowing is allowed in child scopes).
+    /// in this scope (shadowing is allowed in child scopes). Global
+    /// functions may share a name as long as their arities differ.
     pub fn insert(&mut self, name: String, symbol: Symbol) -> Result<(), NameError> {
         if self.symbols.contains_key(&name) {
             let existing = &self.symbols[&name];
-            // Allow user definitions to shadow built-in functions (which are functions with dummy spans)
+            // Allow user definitions to shadow built-in functions (which are
+            // functions with dummy spans). A built-in declared again with
+            // another arity is overloaded like any other global function.
             let is_builtin =
                 existing.is_function() && existing.span == nevermind_common::Span::dummy();
-            if is_builtin {
+            if is_builtin && symbol.span != nevermind_common::Span::dummy() {
+                self.overloads.remove(&name);
                 self.symbols.insert(name, symbol);
                 return Ok(());
             }
+
+            let existing = if self.is_global() && existing.is_function() && symbol.is_function() {
+                let overloads = self
+                    .overloads
+                    .entry(name.clone())
+                    .or_insert_with(|| vec![existing.clone()]);
+                match overloads
+                    .iter()
+                    .find(|overload| overload.param_count() == symbol.param_count())
+                {
+                    Some(same_arity) => same_arity,
+                    None => {
+                        overloads.push(symbol);
+                        return Ok(());
+                    }
+                }
+            } else {
+                existing
+            };
+
             return Err(NameError::new(
                 NameErrorKind::DuplicateDefinition(name.clone()),
                 format!("Cannot declare '{}', already defined in this scope", name),
                 symbol.span.clone(),
             )
-            .with_context(
-                format!("previous definition of '{}' is here", name),
-                Some(existing.span.clone()),
-            ));
+            .with_context("first defined here", Some(existing.span.clone())));
         }
 
         self.symbols.insert(name, symbol);
@@ -78,6 +103,17 @@ impl Scope {
         }
     }
 
+    /// Look up the arity overloads of a function name, if it has any
+    pub fn lookup_overloads(&self, name: &str) -> Option<&[Symbol]> {
+        if self.symbols.contains_key(name) {
+            return self.overloads.get(name).map(Vec::as_slice);
+        }
+
+        self.parent
+            .as_ref()
+            .and_then(|parent| parent.lookup_overloads(name))
+    }
+
     /// Look up a symbol in this scope only (not parent scopes)
     pub fn lookup_local(&self, name: &str) -> Option<&Symbol> {
         self.symbols.get(name)
@@ -126,6 +126,14 @@ impl Symbol {
     pub fn is_function(&self) -> bool {
         matches!(self.kind, SymbolKind::Function { .. })
     }
+
+    /// Get the number of parameters if this symbol is a function
+    pub fn param_count(&self) -> Option<usize> {
+        match self.kind {
+            SymbolKind::Function { param_count } => Some(param_count),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Debug for Symbol {
@@ -4,7 +4,14 @@ use std::fmt;
 
 use crate::error::{NameError, Result};
 use crate::scope::Scope;
-use crate::symbol::Symbol;
+use crate::symbol::{Symbol, SymbolKind};
+
+/// A variable or parameter, and whether its value has been read
+#[derive(Clone)]
+struct LocalBinding {
+    symbol: Symbol,
+    read: bool,
+}
 
 /// A symbol table managing nested scopes
 #[derive(Clone)]
@@ -12,6 +19,12 @@ pub struct SymbolTable {
     /// Stack of scopes
     scopes: Vec<Scope>,
 
+    /// Variables and parameters declared in each scope on the stack
+    bindings: Vec<Vec<LocalBinding>>,
+
+    /// Bindings whose scope has exited without them ever being read
+    unread: Vec<Symbol>,
+
     /// Loop depth (for break/continue validation)
     loop_depth: usize,
 
@@ -24,6 +37,8 @@ impl SymbolTable {
     pub fn new() -> Self {
         Self {
             scopes: vec![Scope::global()],
+            bindings: vec![Vec::new()],
+            unread: Vec::new(),
             loop_depth: 0,
             function_depth: 0,
         }
@@ -34,6 +49,7 @@ impl SymbolTable {
         let level = self.scopes.len() as u32;
         let parent = self.scopes.last().cloned();
         self.scopes.push(Scope::new(parent, level));
+        self.bindings.push(Vec::new());
     }
 
     /// Exit the current scope
@@ -46,6 +62,17 @@ impl SymbolTable {
         }
 
         self.scopes.pop();
+        let bindings = self.bindings.pop().unwrap_or_default();
+        self.unread.extend(
+            bindings
+                .into_iter()
+                .filter(|binding| !binding.read)
+                .map(|binding| binding.symbol)
+                // Symbols without a span, such as pattern bindings, can't be pointed at
+                .filter(|symbol| {
+                    !symbol.name.starts_with('_') && symbol.span != nevermind_common::Span::dummy()
+                }),
+        );
         Ok(())
     }
 
@@ -89,6 +116,15 @@ impl SymbolTable {
 
     /// Declare a symbol in the current scope
     pub fn declare(&mut self, name: String, symbol: Symbol) -> Result<()> {
+        if let SymbolKind::Variable { .. } | SymbolKind::Parameter { .. } = symbol.kind {
+            if let Some(bindings) = self.bindings.last_mut() {
+                bindings.push(LocalBinding {
+                    symbol: symbol.clone(),
+                    read: false,
+                });
+            }
+        }
+
         if let Some(scope) = self.scopes.last_mut() {
             scope.insert(name, symbol)
         } else {
@@ -113,6 +149,31 @@ impl SymbolTable {
         }
     }
 
+    /// Record that the innermost variable or parameter called `name` was read
+    pub fn mark_read(&mut self, name: &str) {
+        if let Some(binding) = self
+            .bindings
+            .iter_mut()
+            .rev()
+            .flat_map(|bindings| bindings.iter_mut().rev())
+            .find(|binding| binding.symbol.name == name)
+        {
+            binding.read = true;
+        }
+    }
+
+    /// Take the variables and parameters that went out of scope unread
+    pub fn take_unread(&mut self) -> Vec<Symbol> {
+        std::mem::take(&mut self.unread)
+    }
+
+    /// Get the arity overloads a name resolves to, if it is overloaded
+    pub fn resolve_overloads(&self, name: &str) -> Option<&[Symbol]> {
+        self.scopes
+            .last()
+            .and_then(|scope| scope.lookup_overloads(name))
+    }
+
     /// Resolve a symbol name in the current scope only (not parent scopes)
     pub fn resolve_local(&self, name: &str) -> Result<&Symbol> {
         if let Some(scope) = self.scopes.last() {
@@ -127,6 +188,15 @@ impl SymbolTable {
         }
     }
 
+    /// Resolve a symbol name in the scopes enclosing the current one, which
+    /// a declaration in the current scope would shadow
+    pub fn resolve_enclosing(&self, name: &str) -> Option<&Symbol> {
+        self.scopes
+            .last()
+            .and_then(|scope| scope.parent.as_ref())
+            .and_then(|parent| parent.lookup(name))
+    }
+
     /// Check if a name is defined in the current scope
     pub fn in_current_scope(&self, name: &str) -> bool {
         self.scopes
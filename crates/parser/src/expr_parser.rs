@@ -6,7 +6,7 @@ use nevermind_lexer::token::{Delimiter, Keyword, LiteralType, Operator};
 use nevermind_lexer::{Token, TokenType};
 
 use nevermind_ast::op::{BinaryOp, ComparisonOp, LogicalOp, UnaryOp};
-use nevermind_ast::{Expr, Literal, MatchArm, Parameter};
+use nevermind_ast::{Expr, Literal, MatchArm, Parameter, StringPart};
 
 use super::error::{ParseError, ParseResult};
 use super::Parser;
@@ -56,6 +56,16 @@ impl<'a> ExprParser<'a> {
         let start = self.parser.peek_span();
 
         let expr = match self.parser.peek_token_type() {
+            TokenType::Literal(LiteralType::String)
+                if self
+                    .parser
+                    .peek_text()
+                    .is_some_and(|text| text.contains("${")) =>
+            {
+                let token = self.parser.advance().unwrap();
+                self.parse_string_interpolation(&token.text, token.span)?
+            }
+
             TokenType::Literal(lit_type) => {
                 let token = self.parser.advance().unwrap();
                 let span = token.span.clone();
@@ -167,10 +177,24 @@ impl<'a> ExprParser<'a> {
 
             TokenType::Delimiter(Delimiter::LParen) => {
                 self.parser.advance();
-                let expr = self.parse_expression_bp(0)?;
-                self.parser
-                    .consume_delimiter(Delimiter::RParen, "expected ')' after expression")?;
-                expr
+                if self.parser.match_delimiter(Delimiter::RParen) {
+                    // `()` is the unit value, not an empty grouping
+                    Expr::Unit {
+                        id: nevermind_ast::new_node_id(),
+                        span: self.parser.span_from(start.clone()),
+                    }
+                } else {
+                    let expr = self.parse_expression_bp(0)?;
+                    if self.parser.match_delimiter(Delimiter::Comma) {
+                        self.parse_tuple(expr, start.clone())?
+                    } else {
+                        self.parser.consume_delimiter(
+                            Delimiter::RParen,
+                            "expected ')' after expression",
+                        )?;
+                        expr
+                    }
+                }
             }
 
             TokenType::Delimiter(Delimiter::LBracket) => {
@@ -187,6 +211,9 @@ impl<'a> ExprParser<'a> {
             | TokenType::Operator(Operator::BitNot)
             | TokenType::Operator(Operator::Sub) => {
                 let token = self.parser.advance().unwrap();
+                if let Some(literal) = self.parse_negative_literal(&token) {
+                    return Ok(literal);
+                }
                 let op = match token.kind {
                     TokenType::Operator(Operator::Not) => UnaryOp::Not,
                     TokenType::Operator(Operator::BitNot) => UnaryOp::BitNot,
@@ -194,7 +221,9 @@ impl<'a> ExprParser<'a> {
                     _ => unreachable!(),
                 };
 
-                let expr = self.parse_expression_bp(14)?; // Unary operators have high precedence
+                // Unary operators bind tighter than anything but `**`, so
+                // `-2 ** 2` is `-(2 ** 2)` as in Python
+                let expr = self.parse_expression_bp(17)?;
 
                 Expr::Unary {
                     id: nevermind_ast::new_node_id(),
@@ -204,6 +233,19 @@ impl<'a> ExprParser<'a> {
                 }
             }
 
+            TokenType::Keyword(Keyword::Await) => {
+                self.parser.advance();
+                // Binds like a unary operator: `await f() + 1` adds to the
+                // awaited result
+                let expr = self.parse_expression_bp(17)?;
+
+                Expr::Await {
+                    id: nevermind_ast::new_node_id(),
+                    expr: Box::new(expr),
+                    span: self.parser.span_from(start.clone()),
+                }
+            }
+
             TokenType::Operator(Operator::BitOr) => {
                 // Lambda expression: |param1, param2| -> expr
                 self.parser.advance();
@@ -247,11 +289,27 @@ impl<'a> ExprParser<'a> {
             self.parser
                 .consume_delimiter(Delimiter::RBracket, "expected ']' after index")?;
 
-            result = Expr::Index {
-                id: nevermind_ast::new_node_id(),
-                array: Box::new(result),
-                index: Box::new(index),
-                span: self.parser.span_from(start.clone()),
+            // Indexing with a range takes a slice: `xs[1..3]`
+            result = match index {
+                Expr::Range {
+                    start: from,
+                    end,
+                    inclusive,
+                    ..
+                } => Expr::Slice {
+                    id: nevermind_ast::new_node_id(),
+                    array: Box::new(result),
+                    start: from,
+                    end,
+                    inclusive,
+                    span: self.parser.span_from(start.clone()),
+                },
+                index => Expr::Index {
+                    id: nevermind_ast::new_node_id(),
+                    array: Box::new(result),
+                    index: Box::new(index),
+                    span: self.parser.span_from(start.clone()),
+                },
             };
         }
 
@@ -275,7 +333,11 @@ impl<'a> ExprParser<'a> {
                     | Operator::Div
                     | Operator::Mod
                     | Operator::Pow
-                    | Operator::Concat => {
+                    | Operator::Concat
+                    | Operator::BitAnd
+                    | Operator::BitXor
+                    | Operator::ShiftLeft
+                    | Operator::ShiftRight => {
                         let rhs = self.parse_expression_bp(right_bp)?;
                         let bin_op = match op {
                             Operator::Add => BinaryOp::Add,
@@ -285,6 +347,10 @@ impl<'a> ExprParser<'a> {
                             Operator::Mod => BinaryOp::Mod,
                             Operator::Pow => BinaryOp::Pow,
                             Operator::Concat => BinaryOp::Concat,
+                            Operator::BitAnd => BinaryOp::BitAnd,
+                            Operator::BitXor => BinaryOp::BitXor,
+                            Operator::ShiftLeft => BinaryOp::ShiftLeft,
+                            Operator::ShiftRight => BinaryOp::ShiftRight,
                             _ => unreachable!(),
                         };
 
@@ -303,22 +369,48 @@ impl<'a> ExprParser<'a> {
                     | Operator::Gt
                     | Operator::Le
                     | Operator::Ge => {
-                        let rhs = self.parse_expression_bp(right_bp)?;
-                        let cmp_op = match op {
-                            Operator::Eq => ComparisonOp::Eq,
-                            Operator::Ne => ComparisonOp::Ne,
-                            Operator::Lt => ComparisonOp::Lt,
-                            Operator::Gt => ComparisonOp::Gt,
-                            Operator::Le => ComparisonOp::Le,
-                            Operator::Ge => ComparisonOp::Ge,
-                            _ => unreachable!(),
-                        };
+                        let mut operands = vec![lhs, self.parse_expression_bp(right_bp)?];
+                        let mut ops = vec![comparison_op(op).unwrap()];
+
+                        // `a < b < c` means `a < b and b < c`, as in Python
+                        while let TokenType::Operator(next) = self.parser.peek_token_type() {
+                            let Some(cmp_op) = comparison_op(next) else {
+                                break;
+                            };
+                            self.parser.advance();
+                            ops.push(cmp_op);
+                            operands.push(self.parse_expression_bp(right_bp)?);
+                        }
 
-                        Expr::Comparison {
+                        let mut pairs =
+                            operands
+                                .windows(2)
+                                .zip(ops)
+                                .map(|(pair, cmp_op)| Expr::Comparison {
+                                    id: nevermind_ast::new_node_id(),
+                                    left: Box::new(pair[0].clone()),
+                                    op: cmp_op,
+                                    right: Box::new(pair[1].clone()),
+                                    span: pair[0].span().merge(pair[1].span()),
+                                });
+                        let first = pairs.next().unwrap();
+                        pairs.fold(first, |chain, pair| Expr::Logical {
                             id: nevermind_ast::new_node_id(),
-                            left: Box::new(lhs),
-                            op: cmp_op,
-                            right: Box::new(rhs),
+                            span: chain.span().merge(pair.span()),
+                            left: Box::new(chain),
+                            op: LogicalOp::And,
+                            right: Box::new(pair),
+                        })
+                    }
+
+                    Operator::DotDot | Operator::DotDotEq => {
+                        let end = self.parse_expression_bp(right_bp)?;
+
+                        Expr::Range {
+                            id: nevermind_ast::new_node_id(),
+                            start: Box::new(lhs),
+                            end: Box::new(end),
+                            inclusive: op == Operator::DotDotEq,
                             span: self.parser.span_from(start),
                         }
                     }
@@ -356,11 +448,25 @@ impl<'a> ExprParser<'a> {
                         }
                     }
 
-                    Operator::Assign => {
+                    Operator::Assign
+                    | Operator::AddAssign
+                    | Operator::SubAssign
+                    | Operator::MulAssign
+                    | Operator::DivAssign
+                    | Operator::ModAssign => {
                         let rhs = self.parse_expression_bp(right_bp)?;
+                        let compound_op = match op {
+                            Operator::AddAssign => Some(BinaryOp::Add),
+                            Operator::SubAssign => Some(BinaryOp::Sub),
+                            Operator::MulAssign => Some(BinaryOp::Mul),
+                            Operator::DivAssign => Some(BinaryOp::Div),
+                            Operator::ModAssign => Some(BinaryOp::Mod),
+                            _ => None,
+                        };
                         Expr::Assign {
                             id: nevermind_ast::new_node_id(),
                             target: Box::new(lhs),
+                            op: compound_op,
                             value: Box::new(rhs),
                             span: self.parser.span_from(start),
                         }
@@ -368,9 +474,26 @@ impl<'a> ExprParser<'a> {
 
                     Operator::Dot => {
                         // Field access or method call
-                        let field = self
+                        let field_line = self.parser.peek_span().start.line;
+                        // `t.0` reads a tuple element by position
+                        let field = if matches!(
+                            self.parser.peek_token_type(),
+                            TokenType::Literal(LiteralType::Integer)
+                        ) {
+                            self.parser.advance().unwrap().text
+                        } else {
+                            self.parser
+                                .consume_identifier("expected field name after '.'")?
+                        };
+
+                        // Command-style arguments must start on the member's
+                        // line, so `obj.field` ending a line stays an access
+                        let same_line = self
                             .parser
-                            .consume_identifier("expected field name after '.'")?;
+                            .current
+                            .as_ref()
+                            .map(|t| t.span.start.line == field_line)
+                            .unwrap_or(false);
 
                         let member_expr = Expr::MemberAccess {
                             id: nevermind_ast::new_node_id(),
@@ -383,7 +506,7 @@ impl<'a> ExprParser<'a> {
                         if self.parser.check_delimiter(Delimiter::LParen) {
                             // Will be handled by the normal call parsing via binding power
                             member_expr
-                        } else if self.parser.check_operator(Operator::BitOr) {
+                        } else if same_line && self.parser.check_operator(Operator::BitOr) {
                             // Method call with lambda argument: obj.method |params| body |
                             self.parser.advance(); // consume |
                             let lambda = self.parse_lambda()?;
@@ -393,10 +516,12 @@ impl<'a> ExprParser<'a> {
                                 args: vec![lambda],
                                 span: self.parser.span_from(start),
                             }
-                        } else if matches!(
-                            self.parser.peek_token_type(),
-                            TokenType::Identifier | TokenType::Literal(_)
-                        ) && !self.parser.check_keyword(Keyword::End)
+                        } else if same_line
+                            && matches!(
+                                self.parser.peek_token_type(),
+                                TokenType::Identifier | TokenType::Literal(_)
+                            )
+                            && !self.parser.check_keyword(Keyword::End)
                             && !self.parser.check_keyword(Keyword::Do)
                             && !self.parser.check_keyword(Keyword::Then)
                             && !self.parser.check_keyword(Keyword::Else)
@@ -473,9 +598,98 @@ impl<'a> ExprParser<'a> {
         Ok(expr)
     }
 
+    /// Split the text of a string literal into literal chunks and the
+    /// expressions inside its `${...}` placeholders
+    fn parse_string_interpolation(&mut self, text: &str, span: Span) -> ParseResult<Expr> {
+        let mut parts = Vec::new();
+        let mut rest = text;
+
+        while let Some(open) = rest.find("${") {
+            if open > 0 {
+                parts.push(StringPart::Literal(rest[..open].to_string()));
+            }
+
+            // Find the `}` closing the placeholder, skipping nested braces
+            let source = &rest[open + 2..];
+            let mut depth = 0;
+            let close = source
+                .char_indices()
+                .find(|&(_, c)| match c {
+                    '{' => {
+                        depth += 1;
+                        false
+                    }
+                    '}' if depth == 0 => true,
+                    '}' => {
+                        depth -= 1;
+                        false
+                    }
+                    _ => false,
+                })
+                .map(|(i, _)| i)
+                .ok_or_else(|| ParseError::new("unterminated '${' in string", span.clone()))?;
+
+            let source = &source[..close];
+            if source.trim().is_empty() {
+                return Err(ParseError::new("empty '${}' in string", span));
+            }
+            let mut parser =
+                Parser::new(source).map_err(|e| ParseError::new(e.message, span.clone()))?;
+            let expr = parser
+                .parse_expression()
+                .map_err(|e| ParseError::new(e.message, span.clone()))?;
+            if !parser.is_at_end() {
+                return Err(ParseError::new(
+                    format!("unexpected input after expression in '${{{}}}'", source),
+                    span,
+                ));
+            }
+            parts.push(StringPart::Expr(expr));
+
+            rest = &rest[open + 2 + close + 1..];
+        }
+
+        if !rest.is_empty() {
+            parts.push(StringPart::Literal(rest.to_string()));
+        }
+
+        Ok(Expr::StringInterp {
+            id: nevermind_ast::new_node_id(),
+            parts,
+            span,
+        })
+    }
+
+    /// Fold a `-` written directly before a number into a negative literal.
+    /// `-x` stays a negation, as does `-2 ** 2`, which is `-(2 ** 2)`.
+    fn parse_negative_literal(&mut self, minus: &Token) -> Option<Expr> {
+        if minus.kind != TokenType::Operator(Operator::Sub)
+            || self.parser.peek_span().start_offset() != minus.span.end_offset()
+            || self.parser.peek_next_token_type() == TokenType::Operator(Operator::Pow)
+        {
+            return None;
+        }
+
+        let literal = match self.parser.peek_token_type() {
+            TokenType::Literal(LiteralType::Integer) => {
+                let token = self.parser.advance().unwrap();
+                let value = format!("-{}", token.text).parse::<i64>().unwrap_or(0);
+                Literal::Integer(value, minus.span.merge(&token.span))
+            }
+            TokenType::Literal(LiteralType::Float) => {
+                let token = self.parser.advance().unwrap();
+                let value = format!("-{}", token.text).parse::<f64>().unwrap_or(0.0);
+                Literal::Float(value, minus.span.merge(&token.span))
+            }
+            _ => return None,
+        };
+        Some(Expr::Literal(literal))
+    }
+
     /// Parse a list literal
     fn parse_list(&mut self) -> ParseResult<Expr> {
-        let start = self.parser.peek_span();
+        // The opening token is already consumed
+        let start = self.parser.previous_span();
 
         let mut elements = Vec::new();
 
@@ -499,7 +713,8 @@ impl<'a> ExprParser<'a> {
 
     /// Parse a map literal
     fn parse_map(&mut self) -> ParseResult<Expr> {
-        let start = self.parser.peek_span();
+        // The opening token is already consumed
+        let start = self.parser.previous_span();
 
         let mut entries = Vec::new();
 
@@ -526,14 +741,38 @@ impl<'a> ExprParser<'a> {
         })
     }
 
+    /// Parse the rest of a tuple literal after its first element and comma
+    fn parse_tuple(&mut self, first: Expr, start: Span) -> ParseResult<Expr> {
+        let mut elements = vec![first];
+
+        while !self.parser.check_delimiter(Delimiter::RParen) && !self.parser.is_at_end() {
+            elements.push(self.parse_expression_bp(0)?);
+
+            if !self.parser.match_delimiter(Delimiter::Comma) {
+                break;
+            }
+        }
+
+        self.parser
+            .consume_delimiter(Delimiter::RParen, "expected ')' after tuple elements")?;
+
+        Ok(Expr::Tuple {
+            id: nevermind_ast::new_node_id(),
+            elements,
+            span: self.parser.span_from(start),
+        })
+    }
+
     /// Parse a lambda expression
     fn parse_lambda(&mut self) -> ParseResult<Expr> {
-        let start = self.parser.peek_span();
+        // The opening token is already consumed
+        let start = self.parser.previous_span();
 
         // Parse parameters
         let mut params = Vec::new();
 
         while !self.parser.check_operator(Operator::BitOr) && !self.parser.is_at_end() {
+            let name_span = self.parser.peek_span();
             let name = self.parser.consume_identifier("expected parameter name")?;
 
             let type_annotation = if self.parser.match_delimiter(Delimiter::Colon) {
@@ -547,6 +786,7 @@ impl<'a> ExprParser<'a> {
                 name,
                 type_annotation,
                 default_value: None,
+                span: name_span,
             });
 
             if !self.parser.match_delimiter(Delimiter::Comma) {
@@ -573,7 +813,8 @@ impl<'a> ExprParser<'a> {
 
     /// Parse an if expression
     fn parse_if_expression(&mut self) -> ParseResult<Expr> {
-        let start = self.parser.peek_span();
+        // The opening token is already consumed
+        let start = self.parser.previous_span();
 
         let condition = self.parse_expression_bp(0)?;
 
@@ -601,7 +842,8 @@ impl<'a> ExprParser<'a> {
 
     /// Parse a block expression
     fn parse_block(&mut self) -> ParseResult<Expr> {
-        let start = self.parser.peek_span();
+        // The opening token is already consumed
+        let start = self.parser.previous_span();
 
         let mut statements = Vec::new();
 
@@ -623,41 +865,34 @@ impl<'a> ExprParser<'a> {
 
     /// Parse a match expression
     fn parse_match_expression(&mut self) -> ParseResult<Expr> {
-        let start = self.parser.peek_span();
+        // The opening token is already consumed
+        let start = self.parser.previous_span();
 
         let scrutinee = self.parse_expression_bp(0)?;
 
-        self.parser
-            .consume_delimiter(Delimiter::LBrace, "expected '{' to start match arms")?;
-
         let mut arms = Vec::new();
 
-        while !self.parser.check_delimiter(Delimiter::RBrace) && !self.parser.is_at_end() {
-            let pattern = self.parser.parse_pattern()?;
-
-            let guard = if self.parser.match_delimiter(Delimiter::Colon) {
-                Some(Box::new(self.parse_expression_bp(0)?))
-            } else {
-                None
-            };
+        if self.parser.check_keyword(Keyword::Case) {
+            // Indentation form: one `case` per arm, ending at the dedent
+            while self.parser.match_keyword(Keyword::Case) {
+                arms.push(self.parse_match_arm()?);
+                self.parser.match_delimiter(Delimiter::Comma);
+            }
+        } else {
+            self.parser.consume_delimiter(
+                Delimiter::LBrace,
+                "expected '{' or 'case' to start match arms",
+            )?;
+
+            while !self.parser.check_delimiter(Delimiter::RBrace) && !self.parser.is_at_end() {
+                arms.push(self.parse_match_arm()?);
+                self.parser.match_delimiter(Delimiter::Comma);
+            }
 
             self.parser
-                .consume_operator(Operator::FatArrow, "expected '=>' after match pattern")?;
-
-            let body = self.parse_expression_bp(0)?;
-
-            arms.push(MatchArm {
-                pattern,
-                guard,
-                body: Box::new(body),
-            });
-
-            self.parser.match_delimiter(Delimiter::Comma);
+                .consume_delimiter(Delimiter::RBrace, "expected '}' to end match expression")?;
         }
 
-        self.parser
-            .consume_delimiter(Delimiter::RBrace, "expected '}' to end match expression")?;
-
         Ok(Expr::Match {
             id: nevermind_ast::new_node_id(),
             scrutinee: Box::new(scrutinee),
@@ -666,12 +901,39 @@ impl<'a> ExprParser<'a> {
         })
     }
 
+    /// Parse `pattern [: guard] => body`
+    fn parse_match_arm(&mut self) -> ParseResult<MatchArm> {
+        let pattern = self.parser.parse_pattern()?;
+
+        let guard = if self.parser.match_delimiter(Delimiter::Colon) {
+            Some(Box::new(self.parse_expression_bp(0)?))
+        } else {
+            None
+        };
+
+        self.parser
+            .consume_operator(Operator::FatArrow, "expected '=>' after match pattern")?;
+
+        let body = self.parse_expression_bp(0)?;
+
+        Ok(MatchArm {
+            pattern,
+            guard,
+            body: Box::new(body),
+        })
+    }
+
     /// Get the binding power (precedence) of an operator
     fn get_binding_power(&self, token: &Token) -> Option<(u8, u8)> {
         match &token.kind {
             TokenType::Operator(op) => {
                 let bp = match op {
-                    Operator::Assign => (2, 1),
+                    Operator::Assign
+                    | Operator::AddAssign
+                    | Operator::SubAssign
+                    | Operator::MulAssign
+                    | Operator::DivAssign
+                    | Operator::ModAssign => (2, 1),
                     Operator::Or => (4, 5),
                     Operator::And => (5, 6),
                     Operator::Eq
@@ -680,12 +942,17 @@ impl<'a> ExprParser<'a> {
                     | Operator::Gt
                     | Operator::Le
                     | Operator::Ge => (8, 9),
-                    Operator::Add | Operator::Sub => (10, 11),
-                    Operator::Mul | Operator::Div | Operator::Mod => (12, 13),
-                    Operator::Pow => (15, 14), // Right-associative
-                    Operator::Concat => (11, 11),
+                    // `|` delimits lambdas, so it has no infix form
+                    Operator::BitXor => (9, 10),
+                    Operator::BitAnd => (10, 11),
+                    Operator::ShiftLeft | Operator::ShiftRight => (11, 12),
+                    Operator::DotDot | Operator::DotDotEq => (12, 13),
+                    Operator::Add | Operator::Sub => (13, 14),
+                    Operator::Mul | Operator::Div | Operator::Mod => (15, 16),
+                    Operator::Pow => (18, 17), // Right-associative
+                    Operator::Concat => (14, 14),
                     Operator::Pipe => (6, 7),
-                    Operator::Dot => (22, 21),
+                    Operator::Dot => (25, 24),
                     _ => return None,
                 };
                 Some(bp)
@@ -693,15 +960,28 @@ impl<'a> ExprParser<'a> {
 
             TokenType::Delimiter(Delimiter::LParen) => {
                 // Function call has very high precedence
-                Some((20, 19))
+                Some((23, 22))
             }
 
             TokenType::Delimiter(Delimiter::LBracket) => {
                 // Indexing has high precedence
-                Some((21, 20))
+                Some((24, 23))
             }
 
             _ => None,
         }
     }
 }
+
+/// The comparison an operator token stands for, if any
+fn comparison_op(op: Operator) -> Option<ComparisonOp> {
+    match op {
+        Operator::Eq => Some(ComparisonOp::Eq),
+        Operator::Ne => Some(ComparisonOp::Ne),
+        Operator::Lt => Some(ComparisonOp::Lt),
+        Operator::Gt => Some(ComparisonOp::Gt),
+        Operator::Le => Some(ComparisonOp::Le),
+        Operator::Ge => Some(ComparisonOp::Ge),
+        _ => None,
+    }
+}
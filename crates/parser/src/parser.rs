@@ -8,9 +8,9 @@ use nevermind_common::Span;
 use nevermind_lexer::token::{Delimiter, Keyword, LiteralType, Operator};
 use nevermind_lexer::{Lexer, Token, TokenType};
 
-use nevermind_ast::stmt::MatchArm;
+use nevermind_ast::stmt::{ClassMember, MatchArm};
 use nevermind_ast::types::{PrimitiveType, Type};
-use nevermind_ast::{Expr, Parameter, Pattern, Stmt, TypeAnnotation};
+use nevermind_ast::{Expr, ImportedSymbol, Parameter, Pattern, Stmt, TypeAnnotation, TypeParam};
 // op module used indirectly through AST types
 
 use super::error::{ParseError, ParseResult};
@@ -104,7 +104,9 @@ impl Parser {
             TokenType::Keyword(Keyword::Let) | TokenType::Keyword(Keyword::Var) => {
                 self.parse_let_statement()?
             }
-            TokenType::Keyword(Keyword::Fn) => self.parse_function_statement()?,
+            TokenType::Keyword(Keyword::Fn) | TokenType::Keyword(Keyword::Async) => {
+                self.parse_function_statement()?
+            }
             TokenType::Keyword(Keyword::If) => {
                 // Check if this is an if-expression (then...else...end) or if-statement (do...end)
                 // We peek ahead to see what comes after the condition
@@ -115,6 +117,8 @@ impl Parser {
             TokenType::Keyword(Keyword::For) => self.parse_for_statement()?,
             TokenType::Keyword(Keyword::Match) => self.parse_match_statement()?,
             TokenType::Keyword(Keyword::Return) => self.parse_return_statement()?,
+            TokenType::Keyword(Keyword::Try) => self.parse_try_statement()?,
+            TokenType::Keyword(Keyword::Raise) => self.parse_raise_statement()?,
             TokenType::Keyword(Keyword::Break) => self.parse_break_statement()?,
             TokenType::Keyword(Keyword::Continue) => self.parse_continue_statement()?,
             TokenType::Keyword(Keyword::Type) => self.parse_type_alias_statement()?,
@@ -127,8 +131,8 @@ impl Parser {
                 let expr = self.parse_expression()?;
                 Some(Stmt::ExprStmt {
                     id: nevermind_ast::new_node_id(),
+                    span: expr.span().clone(),
                     expr,
-                    span: self.previous_span(),
                 })
             }
         };
@@ -145,7 +149,9 @@ impl Parser {
             TokenType::Keyword(Keyword::Let) | TokenType::Keyword(Keyword::Var) => {
                 self.parse_let_statement()?
             }
-            TokenType::Keyword(Keyword::Fn) => self.parse_function_statement()?,
+            TokenType::Keyword(Keyword::Fn) | TokenType::Keyword(Keyword::Async) => {
+                self.parse_function_statement()?
+            }
             TokenType::Keyword(Keyword::Type) => self.parse_type_alias_statement()?,
             TokenType::Keyword(Keyword::Class) => self.parse_class_statement()?,
             _ => {
@@ -290,10 +296,11 @@ impl Parser {
         }))
     }
 
-    /// Parse a function declaration
+    /// Parse a function declaration, `async` or not
     pub fn parse_function_statement(&mut self) -> ParseResult<Option<Stmt>> {
         let start = self.peek_span();
 
+        let is_async = self.match_keyword(Keyword::Async);
         self.consume_keyword(Keyword::Fn, "expected 'fn'")?;
 
         let name = self.consume_identifier("expected function name")?;
@@ -351,6 +358,7 @@ impl Parser {
 
         Ok(Some(Stmt::Function {
             id: nevermind_ast::new_node_id(),
+            is_async,
             name,
             params,
             return_type,
@@ -367,6 +375,7 @@ impl Parser {
 
         if !self.check_delimiter(Delimiter::RParen) {
             loop {
+                let name_span = self.peek_span();
                 let name = self.consume_identifier("expected parameter name")?;
 
                 let type_annotation = if self.match_delimiter(Delimiter::Colon) {
@@ -381,11 +390,25 @@ impl Parser {
                     None
                 };
 
+                // Python rejects a required parameter after a defaulted one
+                if default_value.is_none()
+                    && params.iter().any(|p: &Parameter| p.default_value.is_some())
+                {
+                    return Err(ParseError::new(
+                        format!(
+                            "required parameter '{}' cannot follow a parameter with a default value",
+                            name
+                        ),
+                        name_span,
+                    ));
+                }
+
                 params.push(Parameter {
                     id: nevermind_ast::new_node_id(),
                     name,
                     type_annotation,
                     default_value,
+                    span: name_span,
                 });
 
                 if !self.match_delimiter(Delimiter::Comma) {
@@ -548,33 +571,27 @@ impl Parser {
 
         let scrutinee = self.parse_expression()?;
 
-        self.consume_delimiter(Delimiter::LBrace, "expected '{' to start match arms")?;
-
         let mut arms = Vec::new();
-        while !self.check_delimiter(Delimiter::RBrace) && !self.is_at_end() {
-            let pattern = self.parse_pattern()?;
-
-            let guard = if self.match_delimiter(Delimiter::Colon) {
-                Some(self.parse_expression()?)
-            } else {
-                None
-            };
-
-            self.consume_operator(Operator::FatArrow, "expected '=>' after match pattern")?;
-
-            let body = self.parse_expression()?;
-
-            arms.push(MatchArm {
-                pattern,
-                guard,
-                body,
-            });
+        if self.check_keyword(Keyword::Case) {
+            // Indentation form: one `case` per arm, ending at the dedent
+            while self.match_keyword(Keyword::Case) {
+                arms.push(self.parse_match_arm()?);
+                self.match_delimiter(Delimiter::Comma);
+            }
+        } else {
+            self.consume_delimiter(
+                Delimiter::LBrace,
+                "expected '{' or 'case' to start match arms",
+            )?;
+
+            while !self.check_delimiter(Delimiter::RBrace) && !self.is_at_end() {
+                arms.push(self.parse_match_arm()?);
+                self.match_delimiter(Delimiter::Comma);
+            }
 
-            self.match_delimiter(Delimiter::Comma);
+            self.consume_delimiter(Delimiter::RBrace, "expected '}' to end match expression")?;
         }
 
-        self.consume_delimiter(Delimiter::RBrace, "expected '}' to end match expression")?;
-
         let span = self.span_from(start);
 
         Ok(Some(Stmt::Match {
@@ -585,6 +602,27 @@ impl Parser {
         }))
     }
 
+    /// Parse `pattern [: guard] => body`
+    fn parse_match_arm(&mut self) -> ParseResult<MatchArm> {
+        let pattern = self.parse_pattern()?;
+
+        let guard = if self.match_delimiter(Delimiter::Colon) {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+
+        self.consume_operator(Operator::FatArrow, "expected '=>' after match pattern")?;
+
+        let body = self.parse_expression()?;
+
+        Ok(MatchArm {
+            pattern,
+            guard,
+            body,
+        })
+    }
+
     /// Parse a return statement
     pub fn parse_return_statement(&mut self) -> ParseResult<Option<Stmt>> {
         let start = self.peek_span();
@@ -609,6 +647,93 @@ impl Parser {
         }))
     }
 
+    /// Parse a try statement
+    /// try do ... catch e do ... finally do ... end
+    pub fn parse_try_statement(&mut self) -> ParseResult<Option<Stmt>> {
+        let start = self.peek_span();
+
+        self.consume_keyword(Keyword::Try, "expected 'try'")?;
+        self.consume_keyword(Keyword::Do, "expected 'do' after 'try'")?;
+        let body = self.parse_try_clause()?;
+
+        let mut catch_var = None;
+        let mut catch_body = None;
+        if self.match_keyword(Keyword::Catch) {
+            if !self.check_keyword(Keyword::Do) {
+                catch_var = Some(self.consume_identifier("expected error name after 'catch'")?);
+            }
+            self.consume_keyword(Keyword::Do, "expected 'do' after catch clause")?;
+            catch_body = Some(self.parse_try_clause()?);
+        }
+
+        let mut finally_body = None;
+        if self.match_keyword(Keyword::Finally) {
+            self.consume_keyword(Keyword::Do, "expected 'do' after 'finally'")?;
+            finally_body = Some(self.parse_try_clause()?);
+        }
+
+        if catch_body.is_none() && finally_body.is_none() {
+            return Err(ParseError::new(
+                "expected 'catch' or 'finally' after try block",
+                self.peek_span(),
+            ));
+        }
+
+        self.consume_keyword(Keyword::End, "expected 'end' to close try statement")?;
+
+        let span = self.span_from(start);
+
+        Ok(Some(Stmt::Try {
+            id: nevermind_ast::new_node_id(),
+            body,
+            catch_var,
+            catch_body,
+            finally_body,
+            span,
+        }))
+    }
+
+    /// Parse the statements of one clause of a try statement, stopping
+    /// before the 'catch', 'finally' or 'end' that follows it
+    fn parse_try_clause(&mut self) -> ParseResult<Vec<Stmt>> {
+        let mut stmts = Vec::new();
+        loop {
+            while self.match_delimiter(Delimiter::Semicolon) {}
+
+            if self.is_at_end()
+                || self.check_keyword(Keyword::Catch)
+                || self.check_keyword(Keyword::Finally)
+                || self.check_keyword(Keyword::End)
+            {
+                break;
+            }
+
+            match self.parse_statement()? {
+                Some(stmt) => stmts.push(stmt),
+                // A stray 'else' or 'elif'; the caller reports the missing 'end'
+                None => break,
+            }
+        }
+        Ok(stmts)
+    }
+
+    /// Parse a raise statement
+    pub fn parse_raise_statement(&mut self) -> ParseResult<Option<Stmt>> {
+        let start = self.peek_span();
+
+        self.consume_keyword(Keyword::Raise, "expected 'raise'")?;
+
+        let value = self.parse_expression()?;
+
+        let span = self.span_from(start);
+
+        Ok(Some(Stmt::Raise {
+            id: nevermind_ast::new_node_id(),
+            value,
+            span,
+        }))
+    }
+
     /// Parse a break statement
     pub fn parse_break_statement(&mut self) -> ParseResult<Option<Stmt>> {
         let start = self.peek_span();
@@ -648,7 +773,20 @@ impl Parser {
         let type_params = if self.match_delimiter(Delimiter::LBracket) {
             let mut params = Vec::new();
             while !self.check_delimiter(Delimiter::RBracket) && !self.is_at_end() {
-                params.push(self.consume_identifier("expected type parameter name")?);
+                let param_start = self.peek_span();
+                let name = self.consume_identifier("expected type parameter name")?;
+                let default = if self.match_operator(Operator::Assign) {
+                    Some(self.parse_type_annotation()?)
+                } else {
+                    None
+                };
+                if default.is_none() && params.iter().any(|p: &TypeParam| p.default.is_some()) {
+                    return Err(ParseError::new(
+                        "type parameters with defaults must come last",
+                        param_start,
+                    ));
+                }
+                params.push(TypeParam { name, default });
                 if !self.match_delimiter(Delimiter::Comma) {
                     break;
                 }
@@ -678,24 +816,30 @@ impl Parser {
     pub fn parse_import_statement(&mut self) -> ParseResult<Option<Stmt>> {
         let start = self.peek_span();
 
-        let (module, symbols) = if self.match_keyword(Keyword::From) {
-            // from "module" import symbol1, symbol2
+        let (module, symbols, alias) = if self.match_keyword(Keyword::From) {
+            // from "module" import symbol1, symbol2 as alias
             let module = self.consume_string_literal("expected module name after 'from'")?;
             self.consume_keyword(Keyword::Import, "expected 'import' after module name")?;
 
             let mut symbols = Vec::new();
             if !self.is_at_end() {
-                symbols.push(self.consume_identifier("expected symbol name")?);
+                symbols.push(self.parse_imported_symbol()?);
                 while self.match_delimiter(Delimiter::Comma) {
-                    symbols.push(self.consume_identifier("expected symbol name")?);
+                    symbols.push(self.parse_imported_symbol()?);
                 }
             }
 
-            (module, Some(symbols))
+            (module, Some(symbols), None)
         } else {
-            // use "module"
+            // use "module" or use "module" as alias
             self.consume_keyword(Keyword::Use, "expected 'use' or 'from'")?;
-            (self.consume_string_literal("expected module name")?, None)
+            let module = self.consume_string_literal("expected module name")?;
+            let alias = if self.match_keyword(Keyword::As) {
+                Some(self.consume_identifier("expected alias after 'as'")?)
+            } else {
+                None
+            };
+            (module, None, alias)
         };
 
         let span = self.span_from(start);
@@ -704,13 +848,24 @@ impl Parser {
             id: nevermind_ast::new_node_id(),
             module,
             symbols,
+            alias,
             span,
         }))
     }
 
+    /// Parse one `name` or `name as alias` of a `from ... import` list
+    fn parse_imported_symbol(&mut self) -> ParseResult<ImportedSymbol> {
+        let name = self.consume_identifier("expected symbol name")?;
+        let alias = if self.match_keyword(Keyword::As) {
+            Some(self.consume_identifier("expected alias after 'as'")?)
+        } else {
+            None
+        };
+        Ok(ImportedSymbol { name, alias })
+    }
+
     /// Parse a class statement
     pub fn parse_class_statement(&mut self) -> ParseResult<Option<Stmt>> {
-        // TODO: Implement full class parsing
         let start = self.peek_span();
 
         self.consume_keyword(Keyword::Class, "expected 'class'")?;
@@ -725,7 +880,14 @@ impl Parser {
 
         self.consume_delimiter(Delimiter::LBrace, "expected '{' to start class body")?;
 
-        let members = Vec::new(); // TODO: Parse members
+        let mut members = Vec::new();
+        loop {
+            while self.match_delimiter(Delimiter::Semicolon) {}
+            if self.check_delimiter(Delimiter::RBrace) || self.is_at_end() {
+                break;
+            }
+            members.push(self.parse_class_member()?);
+        }
 
         self.consume_delimiter(Delimiter::RBrace, "expected '}' to end class body")?;
 
@@ -740,10 +902,89 @@ impl Parser {
         }))
     }
 
+    /// Parse a class field (`let`/`var` with an optional type and default)
+    /// or method (`fn` declaration)
+    fn parse_class_member(&mut self) -> ParseResult<ClassMember> {
+        let is_mutable = if self.match_keyword(Keyword::Var) {
+            true
+        } else if self.match_keyword(Keyword::Let) {
+            false
+        } else if self.check_keyword(Keyword::Fn) {
+            let Some(Stmt::Function {
+                name,
+                params,
+                return_type,
+                body,
+                ..
+            }) = self.parse_function_statement()?
+            else {
+                unreachable!("parse_function_statement always returns a function");
+            };
+            return Ok(ClassMember::Method {
+                name,
+                params,
+                return_type,
+                body,
+            });
+        } else {
+            return Err(ParseError::new(
+                "expected field or method declaration in class body",
+                self.peek_span(),
+            ));
+        };
+
+        let name = self.consume_identifier("expected field name")?;
+
+        let type_annotation = if self.match_delimiter(Delimiter::Colon) {
+            Some(self.parse_type_annotation()?)
+        } else {
+            None
+        };
+
+        let default_value = if self.match_operator(Operator::Assign) {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+
+        Ok(ClassMember::Field {
+            is_mutable,
+            name,
+            type_annotation,
+            default_value,
+        })
+    }
+
     /// Parse a type annotation
     pub fn parse_type_annotation(&mut self) -> ParseResult<TypeAnnotation> {
         let start = self.peek_span();
 
+        // Tuple type, e.g. (Int, String); `()` is Unit and `(T)` is just T
+        if self.match_delimiter(Delimiter::LParen) {
+            let mut elems = Vec::new();
+            let mut trailing_comma = false;
+            while !self.check_delimiter(Delimiter::RParen) && !self.is_at_end() {
+                elems.push(self.parse_type_annotation()?);
+                trailing_comma = self.match_delimiter(Delimiter::Comma);
+                if !trailing_comma {
+                    break;
+                }
+            }
+            self.consume_delimiter(Delimiter::RParen, "expected ')' after tuple type")?;
+
+            let kind = match elems.len() {
+                0 => Type::Primitive(PrimitiveType::Unit),
+                1 if !trailing_comma => return Ok(elems.pop().unwrap()),
+                _ => Type::Tuple(elems),
+            };
+
+            return Ok(TypeAnnotation {
+                id: nevermind_ast::new_node_id(),
+                span: self.span_from(start),
+                kind,
+            });
+        }
+
         // Simple identifier type
         let name = self.consume_identifier("expected type name")?;
 
@@ -751,13 +992,20 @@ impl Parser {
             "Int" | "UInt" | "Int64" | "UInt64" | "Int32" | "UInt32" | "Float" | "Float64"
             | "Float32" | "Bool" | "String" | "Char" | "Unit" | "Null" => {
                 let prim = match name.as_str() {
-                    "Int" => PrimitiveType::Int,
+                    "UInt" => PrimitiveType::UInt,
+                    "Int64" => PrimitiveType::Int64,
+                    "UInt64" => PrimitiveType::UInt64,
+                    "Int32" => PrimitiveType::Int32,
+                    "UInt32" => PrimitiveType::UInt32,
                     "Float" => PrimitiveType::Float,
+                    "Float64" => PrimitiveType::Float64,
+                    "Float32" => PrimitiveType::Float32,
                     "Bool" => PrimitiveType::Bool,
                     "String" => PrimitiveType::String,
+                    "Char" => PrimitiveType::Char,
                     "Unit" => PrimitiveType::Unit,
                     "Null" => PrimitiveType::Null,
-                    _ => PrimitiveType::Int, // TODO: Add all primitives
+                    _ => PrimitiveType::Int,
                 };
                 Type::Primitive(prim)
             }
@@ -795,6 +1043,17 @@ impl Parser {
                     error: Box::new(error),
                 }
             }
+            _ if self.match_delimiter(Delimiter::LBracket) => {
+                let mut params = Vec::new();
+                while !self.check_delimiter(Delimiter::RBracket) && !self.is_at_end() {
+                    params.push(self.parse_type_annotation()?);
+                    if !self.match_delimiter(Delimiter::Comma) {
+                        break;
+                    }
+                }
+                self.consume_delimiter(Delimiter::RBracket, "expected ']' after type arguments")?;
+                Type::Generic { name, params }
+            }
             _ => Type::Identifier(name),
         };
 
@@ -832,6 +1091,19 @@ impl Parser {
             .unwrap_or(TokenType::EOF)
     }
 
+    /// Get the type of the token after the current one
+    pub fn peek_next_token_type(&mut self) -> TokenType {
+        self.tokens
+            .peek()
+            .map(|t| t.kind.clone())
+            .unwrap_or(TokenType::EOF)
+    }
+
+    /// Get the text of the current token
+    pub fn peek_text(&self) -> Option<&str> {
+        self.current.as_ref().map(|t| t.text.as_str())
+    }
+
     /// Get the span of the current token
     pub fn peek_span(&self) -> Span {
         self.current
@@ -955,13 +1227,14 @@ impl Parser {
 
     /// Create a span from start to current position
     pub fn span_from(&self, start: Span) -> Span {
-        Span::new(start.start.clone(), self.peek_span().end)
+        start.merge(&self.peek_span())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nevermind_ast::Literal;
 
     #[test]
     fn test_parse_let_statement() {
@@ -976,6 +1249,94 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_import_symbol_aliases() {
+        let source = "from \"math\" import sqrt as root, floor";
+        let mut parser = Parser::new(source).unwrap();
+        let stmts = parser.parse().unwrap();
+
+        match &stmts[0] {
+            Stmt::Import { symbols, alias, .. } => {
+                assert_eq!(
+                    symbols.as_deref(),
+                    Some(
+                        &[
+                            ImportedSymbol {
+                                name: "sqrt".to_string(),
+                                alias: Some("root".to_string()),
+                            },
+                            ImportedSymbol::new("floor"),
+                        ][..]
+                    )
+                );
+                assert_eq!(alias, &None);
+            }
+            _ => panic!("expected Import statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_namespace_import_alias() {
+        let source = "use \"math/utils\" as mu";
+        let mut parser = Parser::new(source).unwrap();
+        let stmts = parser.parse().unwrap();
+
+        match &stmts[0] {
+            Stmt::Import {
+                module,
+                symbols,
+                alias,
+                ..
+            } => {
+                assert_eq!(module, "math/utils");
+                assert!(symbols.is_none());
+                assert_eq!(alias.as_deref(), Some("mu"));
+            }
+            _ => panic!("expected Import statement"),
+        }
+    }
+
+    #[test]
+    fn test_parse_case_match_arms_like_brace_arms() {
+        fn arms(source: &str) -> Vec<(String, bool, String)> {
+            let mut parser = Parser::new(source).unwrap();
+            let stmts = parser.parse().unwrap();
+            assert_eq!(stmts.len(), 2);
+
+            let Stmt::Match { arms, .. } = &stmts[0] else {
+                panic!("expected Match statement");
+            };
+            arms.iter()
+                .map(|arm| {
+                    let pattern = match &arm.pattern {
+                        Pattern::Literal {
+                            value: Literal::Integer(n, _),
+                            ..
+                        } => n.to_string(),
+                        Pattern::Variable { name, .. } => name.clone(),
+                        Pattern::Wildcard { .. } => "_".to_string(),
+                        other => panic!("unexpected pattern {:?}", other),
+                    };
+                    let Expr::Variable { name: body, .. } = &arm.body else {
+                        panic!("expected a variable arm body");
+                    };
+                    (pattern, arm.guard.is_some(), body.clone())
+                })
+                .collect()
+        }
+
+        let case_form = "match n\n  case 1 => one\n  case x: x > 5 => big\n  case _ => other\nn";
+        let brace_form = "match n {\n  1 => one,\n  x: x > 5 => big,\n  _ => other\n}\nn";
+
+        let expected = vec![
+            ("1".to_string(), false, "one".to_string()),
+            ("x".to_string(), true, "big".to_string()),
+            ("_".to_string(), false, "other".to_string()),
+        ];
+        assert_eq!(arms(case_form), expected);
+        assert_eq!(arms(brace_form), expected);
+    }
+
     #[test]
     fn test_parse_if_statement() {
         let source = "if x > 0 then x else 0 end";
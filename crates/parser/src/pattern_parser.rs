@@ -60,6 +60,16 @@ impl<'a> PatternParser<'a> {
                 });
             }
 
+            // `None` takes no arguments but is still a constructor, not a
+            // name to bind
+            if token.text == "None" {
+                return Ok(Pattern::Constructor {
+                    name: token.text,
+                    args: Vec::new(),
+                    span: self.parser.span_from(start),
+                });
+            }
+
             // Otherwise it's a variable pattern
             return Ok(Pattern::Variable {
                 name: token.text,
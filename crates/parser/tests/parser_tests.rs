@@ -6,7 +6,9 @@
 //! - Error cases (missing keywords, mismatched delimiters, invalid syntax)
 //! - Complex scenarios (nested functions, multiple statements, pattern matching)
 
-use nevermind_ast::{BinaryOp, ComparisonOp, Expr, Literal, LogicalOp, Pattern, Stmt, UnaryOp};
+use nevermind_ast::{
+    BinaryOp, ComparisonOp, Expr, Literal, LogicalOp, Pattern, Stmt, StringPart, UnaryOp,
+};
 use nevermind_parser::{ParseError, Parser};
 
 // ============================================================================
@@ -94,6 +96,105 @@ mod statement_tests {
         }
     }
 
+    #[test]
+    fn test_parameterized_type_alias() {
+        use nevermind_ast::types::Type;
+
+        let stmt = parse_first("type Pair[T] = (T, T)").unwrap();
+        match stmt {
+            Stmt::TypeAlias {
+                name,
+                type_params,
+                definition,
+                ..
+            } => {
+                assert_eq!(name, "Pair");
+                assert_eq!(type_params.len(), 1);
+                assert_eq!(type_params[0].name, "T");
+                assert!(type_params[0].default.is_none());
+                assert!(matches!(definition.kind, Type::Tuple(ref elems) if elems.len() == 2));
+            }
+            _ => panic!("Expected TypeAlias statement"),
+        }
+    }
+
+    #[test]
+    fn test_type_alias_parameter_defaults() {
+        let stmt = parse_first("type Pair[A, B = A] = (A, B)").unwrap();
+        match stmt {
+            Stmt::TypeAlias { type_params, .. } => {
+                assert_eq!(type_params.len(), 2);
+                assert!(type_params[0].default.is_none());
+                assert_eq!(
+                    type_params[1].default.as_ref().map(|d| d.kind.to_string()),
+                    Some("A".to_string())
+                );
+            }
+            _ => panic!("Expected TypeAlias statement"),
+        }
+
+        assert!(parse_first("type Pair[A = Int, B] = (A, B)").is_err());
+    }
+
+    #[test]
+    fn test_class_with_field_and_method() {
+        use nevermind_ast::stmt::ClassMember;
+
+        let source = "class Point extends Shape {\n    var x: Int = 0\n    fn shifted(dx) do\n        x + dx\n    end\n}\n";
+        let stmt = parse_first(source).unwrap();
+        match stmt {
+            Stmt::Class {
+                name,
+                extends,
+                members,
+                ..
+            } => {
+                assert_eq!(name, "Point");
+                assert_eq!(extends.as_deref(), Some("Shape"));
+                assert_eq!(members.len(), 2);
+                assert!(matches!(
+                    &members[0],
+                    ClassMember::Field {
+                        is_mutable: true,
+                        name,
+                        type_annotation: Some(_),
+                        default_value: Some(_),
+                    } if name == "x"
+                ));
+                assert!(matches!(
+                    &members[1],
+                    ClassMember::Method { name, params, .. } if name == "shifted" && params.len() == 1
+                ));
+            }
+            _ => panic!("Expected Class statement"),
+        }
+
+        let stmt = parse_first("class Point { let x; fn origin() do 0 end }").unwrap();
+        assert!(matches!(stmt, Stmt::Class { ref members, .. } if members.len() == 2));
+
+        assert!(parse_first("class Point { x }").is_err());
+    }
+
+    #[test]
+    fn test_let_statement_with_generic_type() {
+        use nevermind_ast::types::Type;
+
+        let stmt = parse_first("let p: Pair[Int] = point").unwrap();
+        match stmt {
+            Stmt::Let {
+                type_annotation: Some(ann),
+                ..
+            } => match ann.kind {
+                Type::Generic { name, params } => {
+                    assert_eq!(name, "Pair");
+                    assert_eq!(params.len(), 1);
+                }
+                other => panic!("Expected generic type, got {:?}", other),
+            },
+            _ => panic!("Expected Let statement with type annotation"),
+        }
+    }
+
     #[test]
     fn test_let_statement_complex_expr() {
         let stmt = parse_first("let x = 1 + 2 * 3").unwrap();
@@ -172,6 +273,23 @@ mod statement_tests {
         }
     }
 
+    #[test]
+    fn test_async_function_with_await() {
+        let stmt = parse_first("async fn f() do await g() end").unwrap();
+        match stmt {
+            Stmt::Function { is_async, body, .. } => {
+                assert!(is_async);
+                match body {
+                    Expr::Await { expr, .. } => {
+                        assert!(matches!(*expr, Expr::Call { .. }));
+                    }
+                    _ => panic!("Expected Await in function body"),
+                }
+            }
+            _ => panic!("Expected Function statement"),
+        }
+    }
+
     #[test]
     fn test_function_declaration_with_default_param() {
         let stmt = parse_first("fn greet(name = \"World\") do name end").unwrap();
@@ -184,6 +302,16 @@ mod statement_tests {
         }
     }
 
+    #[test]
+    fn test_required_parameter_after_default_is_an_error() {
+        let err = parse("fn f(a = 1, b) do b end").unwrap_err();
+        assert!(err.message.contains("'b'"), "{}", err.message);
+        assert_eq!(err.span.start.column, 13);
+
+        let stmt = parse_first("fn f(b, a = 1) do b end").unwrap();
+        assert!(matches!(stmt, Stmt::Function { ref params, .. } if params.len() == 2));
+    }
+
     #[test]
     fn test_function_declaration_complex_body() {
         let stmt = parse_first(
@@ -421,6 +549,28 @@ mod statement_tests {
         }
     }
 
+    #[test]
+    fn test_match_arm_with_block_body() {
+        let stmt = parse_first(
+            "match x { \
+                1 => do print \"one\"; let y = x + 1; y end, \
+                _ => 0 \
+             }",
+        )
+        .unwrap();
+        let Stmt::Match { arms, .. } = stmt else {
+            panic!("Expected Match statement");
+        };
+        match &arms[0].body {
+            Expr::Block { statements, .. } => {
+                assert_eq!(statements.len(), 3);
+                assert!(matches!(statements[2], Stmt::ExprStmt { .. }));
+            }
+            other => panic!("Expected Block arm body, got {:?}", other),
+        }
+        assert!(matches!(arms[1].body, Expr::Literal(_)));
+    }
+
     // ---------------------------------------------------------------------
     // Return Statements
     // ---------------------------------------------------------------------
@@ -472,6 +622,38 @@ mod statement_tests {
             _ => panic!("Expected Continue statement"),
         }
     }
+
+    // ---------------------------------------------------------------------
+    // Try and Raise Statements
+    // ---------------------------------------------------------------------
+
+    #[test]
+    fn test_try_catch_finally() {
+        let stmt = parse_first(
+            "try do\n  raise \"failed\"\ncatch e do\n  print(e)\nfinally do\n  print(\"done\")\nend",
+        )
+        .unwrap();
+        match stmt {
+            Stmt::Try {
+                body,
+                catch_var,
+                catch_body,
+                finally_body,
+                ..
+            } => {
+                assert!(matches!(body.as_slice(), [Stmt::Raise { .. }]));
+                assert_eq!(catch_var.as_deref(), Some("e"));
+                assert_eq!(catch_body.map(|body| body.len()), Some(1));
+                assert_eq!(finally_body.map(|body| body.len()), Some(1));
+            }
+            _ => panic!("Expected Try statement"),
+        }
+    }
+
+    #[test]
+    fn test_try_needs_catch_or_finally() {
+        assert!(parse("try do print(1) end").is_err());
+    }
 }
 
 // ============================================================================
@@ -518,6 +700,28 @@ mod expression_tests {
         }
     }
 
+    #[test]
+    fn test_string_interpolation() {
+        let expr = parse_expr(r#""hi ${name}, ${m["k"] + 1}!""#).unwrap();
+        match expr {
+            Expr::StringInterp { parts, .. } => {
+                assert_eq!(parts.len(), 5);
+                assert!(matches!(&parts[0], StringPart::Literal(s) if s == "hi "));
+                assert!(
+                    matches!(&parts[1], StringPart::Expr(Expr::Variable { name, .. }) if name == "name")
+                );
+                assert!(matches!(&parts[3], StringPart::Expr(Expr::Binary { .. })));
+                assert!(matches!(&parts[4], StringPart::Literal(s) if s == "!"));
+            }
+            _ => panic!("Expected interpolated string"),
+        }
+    }
+
+    #[test]
+    fn test_unterminated_interpolation_is_an_error() {
+        assert!(parse_expr(r#""hi ${name""#).is_err());
+    }
+
     #[test]
     fn test_boolean_true() {
         let expr = parse_expr("true").unwrap();
@@ -551,6 +755,28 @@ mod expression_tests {
         }
     }
 
+    #[test]
+    fn test_unit_literal() {
+        let expr = parse_expr("()").unwrap();
+        assert!(matches!(expr, Expr::Unit { .. }));
+
+        // Parentheses around a value still only group it
+        let expr = parse_expr("(1)").unwrap();
+        assert!(matches!(expr, Expr::Literal(Literal::Integer(1, _))));
+    }
+
+    #[test]
+    fn test_tuple_literal() {
+        let expr = parse_expr("(1, \"a\")").unwrap();
+        assert!(matches!(expr, Expr::Tuple { ref elements, .. } if elements.len() == 2));
+
+        let expr = parse_expr("(1,)").unwrap();
+        assert!(matches!(expr, Expr::Tuple { ref elements, .. } if elements.len() == 1));
+
+        let expr = parse_expr("t.1").unwrap();
+        assert!(matches!(expr, Expr::MemberAccess { ref member, .. } if member == "1"));
+    }
+
     // ---------------------------------------------------------------------
     // Variables
     // ---------------------------------------------------------------------
@@ -647,6 +873,18 @@ mod expression_tests {
         }
     }
 
+    #[test]
+    fn test_power_is_right_associative_and_binds_tighter_than_negation() {
+        let expr = parse_expr("2 ** 3 ** 2").unwrap();
+        assert_eq!(expr.to_string(), "(2 ** (3 ** 2))");
+
+        let expr = parse_expr("-2 ** 2").unwrap();
+        assert_eq!(expr.to_string(), "(-(2 ** 2))");
+
+        let expr = parse_expr("-2 * 3").unwrap();
+        assert_eq!(expr.to_string(), "(-2 * 3)");
+    }
+
     #[test]
     fn test_binary_concatenation() {
         let expr = parse_expr("\"hello\" ++ \"world\"").unwrap();
@@ -658,6 +896,47 @@ mod expression_tests {
         }
     }
 
+    #[test]
+    fn test_compound_index_assignment() {
+        let expr = parse_expr("xs[i] += 1").unwrap();
+        match expr {
+            Expr::Assign { target, op, .. } => {
+                assert!(matches!(*target, Expr::Index { .. }));
+                assert_eq!(op, Some(BinaryOp::Add));
+            }
+            _ => panic!("Expected Assign expression"),
+        }
+    }
+
+    #[test]
+    fn test_slice_with_range_index() {
+        let expr = parse_expr("xs[1..3]").unwrap();
+        match expr {
+            Expr::Slice {
+                array,
+                start,
+                end,
+                inclusive,
+                ..
+            } => {
+                assert!(matches!(*array, Expr::Variable { ref name, .. } if name == "xs"));
+                assert!(matches!(*start, Expr::Literal(Literal::Integer(1, _))));
+                assert!(matches!(*end, Expr::Literal(Literal::Integer(3, _))));
+                assert!(!inclusive);
+            }
+            _ => panic!("Expected Slice expression"),
+        }
+
+        let expr = parse_expr("xs[1..=3]").unwrap();
+        assert!(matches!(
+            expr,
+            Expr::Slice {
+                inclusive: true,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_operator_precedence() {
         let expr = parse_expr("1 + 2 * 3").unwrap();
@@ -692,6 +971,114 @@ mod expression_tests {
         }
     }
 
+    #[test]
+    fn test_bitwise_operator_precedence() {
+        let expr = parse_expr("a ^ b & c << 1").unwrap();
+        match expr {
+            Expr::Binary { op, right, .. } => {
+                assert_eq!(op, BinaryOp::BitXor);
+                match *right {
+                    Expr::Binary {
+                        op: inner_op,
+                        right: shift,
+                        ..
+                    } => {
+                        assert_eq!(inner_op, BinaryOp::BitAnd);
+                        assert!(matches!(
+                            *shift,
+                            Expr::Binary {
+                                op: BinaryOp::ShiftLeft,
+                                ..
+                            }
+                        ));
+                    }
+                    _ => panic!("Expected bitwise and on right side"),
+                }
+            }
+            _ => panic!("Expected Binary expression"),
+        }
+    }
+
+    #[test]
+    fn test_expression_span_covers_its_source() {
+        let sources = [
+            "42",
+            "()",
+            "name",
+            "a + b",
+            "a < b",
+            "a and b",
+            "not a",
+            "f(1, 2)",
+            "xs |> f",
+            "|x| x + 1",
+            "if a then 1 else 2 end",
+            "\"hi ${name}\"",
+            "[1, 2]",
+            "(1, 2)",
+            "{\"a\": 1}",
+            "match x { 1 => 2 }",
+            "xs[0]",
+            "x = 1",
+            "obj.field",
+            "1..3",
+            "xs[1..3]",
+            "do 1 end",
+        ];
+        for source in sources {
+            let binding = format!("let v = {}", source);
+            let Stmt::Let { value, .. } = parse_first(&binding).unwrap() else {
+                panic!("Expected Let statement for {:?}", source);
+            };
+            let span = value.span();
+            assert_eq!(
+                &binding[span.start_offset()..span.end_offset()],
+                source,
+                "span of {:?}",
+                value
+            );
+        }
+
+        // An expression statement spans its whole expression
+        let stmt = parse_first("f(1, 2)").unwrap();
+        assert_eq!(
+            (stmt.span().start_offset(), stmt.span().end_offset()),
+            (0, 7)
+        );
+    }
+
+    #[test]
+    fn test_member_access_at_end_of_line_does_not_take_next_line() {
+        let stmts = parse("obj.field\nnext_value").unwrap();
+        assert_eq!(stmts.len(), 2);
+        match &stmts[0] {
+            Stmt::ExprStmt {
+                expr: Expr::MemberAccess { member, .. },
+                ..
+            } => assert_eq!(member, "field"),
+            other => panic!("Expected member access statement, got {:?}", other),
+        }
+        assert!(matches!(
+            &stmts[1],
+            Stmt::ExprStmt {
+                expr: Expr::Variable { .. },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_command_style_method_call_on_one_line() {
+        let expr = parse_expr("obj.method arg").unwrap();
+        match expr {
+            Expr::Call { callee, args, .. } => {
+                assert!(matches!(*callee, Expr::MemberAccess { .. }));
+                assert_eq!(args.len(), 1);
+            }
+            _ => panic!("Expected method call"),
+        }
+    }
+
     // ---------------------------------------------------------------------
     // Comparison Operations
     // ---------------------------------------------------------------------
@@ -762,6 +1149,42 @@ mod expression_tests {
         }
     }
 
+    #[test]
+    fn test_chained_comparison() {
+        let expr = parse_expr("1 < 2 < 3").unwrap();
+        let Expr::Logical {
+            left,
+            op: LogicalOp::And,
+            right,
+            ..
+        } = expr
+        else {
+            panic!("Expected Logical and, got {:?}", expr);
+        };
+        match (*left, *right) {
+            (
+                Expr::Comparison {
+                    left: a,
+                    op: ComparisonOp::Lt,
+                    right: b,
+                    ..
+                },
+                Expr::Comparison {
+                    left: b2,
+                    op: ComparisonOp::Lt,
+                    right: c,
+                    ..
+                },
+            ) => {
+                assert!(matches!(*a, Expr::Literal(Literal::Integer(1, _))));
+                assert!(matches!(*b, Expr::Literal(Literal::Integer(2, _))));
+                assert!(matches!(*b2, Expr::Literal(Literal::Integer(2, _))));
+                assert!(matches!(*c, Expr::Literal(Literal::Integer(3, _))));
+            }
+            (left, right) => panic!("Expected two comparisons, got {} and {}", left, right),
+        }
+    }
+
     // ---------------------------------------------------------------------
     // Logical Operations
     // ---------------------------------------------------------------------
@@ -794,13 +1217,37 @@ mod expression_tests {
 
     #[test]
     fn test_unary_negation() {
-        let expr = parse_expr("-42").unwrap();
+        let expr = parse_expr("-x").unwrap();
         match expr {
             Expr::Unary { op, .. } => {
                 assert_eq!(op, UnaryOp::Neg);
             }
             _ => panic!("Expected Unary expression"),
         }
+
+        // Spaced out, the minus is still an operator
+        let expr = parse_expr("- 42").unwrap();
+        assert!(matches!(expr, Expr::Unary { .. }));
+    }
+
+    #[test]
+    fn test_negative_numeric_literal() {
+        let expr = parse_expr("-5").unwrap();
+        assert!(matches!(expr, Expr::Literal(Literal::Integer(-5, _))));
+
+        let expr = parse_expr("-2.5").unwrap();
+        assert!(matches!(expr, Expr::Literal(Literal::Float(f, _)) if f == -2.5));
+
+        let Stmt::Let { value, .. } = parse_first("let r = -3..3").unwrap() else {
+            panic!("Expected Let statement");
+        };
+        match value {
+            Expr::Range { start, end, .. } => {
+                assert!(matches!(*start, Expr::Literal(Literal::Integer(-3, _))));
+                assert!(matches!(*end, Expr::Literal(Literal::Integer(3, _))));
+            }
+            other => panic!("Expected Range, got {:?}", other),
+        }
     }
 
     #[test]
@@ -2,15 +2,21 @@
 
 use crate::environment::TypeEnvironment;
 use crate::error::{Result, TypeError};
-use crate::ty::TypeScheme;
+use crate::incremental::TopLevelEntry;
+use crate::ty::{Constraint, TypeScheme};
 use crate::types::Type;
 use crate::unification::Unifier;
 use crate::TypeContext;
-use nevermind_ast::Expr;
+use nevermind_ast::stmt::{namespace_binding, ClassMember};
 use nevermind_ast::Literal;
+use nevermind_ast::NodeId;
 use nevermind_ast::Pattern;
 use nevermind_ast::Stmt;
+use nevermind_ast::StringPart;
+use nevermind_ast::UnaryOp;
+use nevermind_ast::{BinaryOp, Expr};
 use nevermind_common::Span;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Clone)]
 struct FlowInfo {
@@ -44,7 +50,23 @@ struct FunctionContext {
     return_annotation_span: Option<Span>,
 }
 
+/// Predefined functions bound per arity under `name/arity` keys, as
+/// overloaded global functions are.
+const BUILTIN_OVERLOADS: &[(&str, &[usize])] =
+    &[("range", &[1, 2, 3]), ("min", &[1, 2]), ("max", &[1, 2])];
+
+/// Constructor families whose variants make up the whole of their type.
+const KNOWN_ENUMS: &[&[&str]] = &[&["Some", "None"], &["Ok", "Error"]];
+
+/// A `type Name[Params] = definition` declaration, expanded at each use.
+#[derive(Clone)]
+struct TypeAlias {
+    params: Vec<nevermind_ast::TypeParam>,
+    definition: nevermind_ast::TypeAnnotation,
+}
+
 /// The main type checker
+#[derive(Clone)]
 pub struct TypeChecker {
     /// Type environment
     env: TypeEnvironment,
@@ -57,88 +79,546 @@ pub struct TypeChecker {
 
     /// Stack of functions currently being checked.
     function_contexts: Vec<FunctionContext>,
+
+    /// Type aliases declared so far, keyed by alias name.
+    type_aliases: HashMap<String, TypeAlias>,
+
+    /// Arities of the global functions declared under each name. A name with
+    /// more than one arity is overloaded, and each overload is bound in the
+    /// environment under its `name/arity` key instead of the bare name.
+    function_arities: HashMap<String, Vec<usize>>,
+
+    /// Type inferred for each expression node, before substitution.
+    node_types: HashMap<NodeId, Type>,
+
+    /// Errors recorded while recovering; `None` unless running `check_all`.
+    recovered_errors: Option<Vec<TypeError>>,
+
+    /// Top-level statements of the latest check, for incremental re-checking.
+    program: Vec<TopLevelEntry>,
+
+    /// Number of top-level statements checked, including re-checks.
+    top_level_checks: usize,
+
+    /// Whether a match that isn't provably exhaustive is an error rather
+    /// than a warning.
+    strict_match: bool,
+
+    /// Problems that don't stop checking, such as non-exhaustive matches.
+    warnings: Vec<TypeError>,
+
+    /// Parameter and return types given to top-level functions declared
+    /// ahead of their bodies, keyed by the function's statement id.
+    hoisted_signatures: HashMap<NodeId, (Vec<Type>, Type)>,
+
+    /// Predefined functions not yet replaced by a user definition. A global
+    /// function of the same name drops every predefined signature, so the
+    /// user's definition wins as it does in the resolver and the output.
+    builtins: HashSet<String>,
+
+    /// Types of the fields and methods of each class declared so far, for
+    /// member accesses on its instances.
+    classes: HashMap<String, HashMap<String, TypeScheme>>,
+
+    /// Parameter types of the constructor of each class declared so far
+    constructors: HashMap<String, Vec<Type>>,
 }
 
 impl TypeChecker {
     /// Create a new type checker
     pub fn new() -> Self {
+        let env = TypeEnvironment::with_predefined();
+        let builtins = env
+            .current_names()
+            .filter(|name| !name.contains('/'))
+            .chain(BUILTIN_OVERLOADS.iter().map(|(name, _)| *name))
+            .map(str::to_string)
+            .collect();
         Self {
-            env: TypeEnvironment::with_predefined(),
+            env,
             ctx: TypeContext::new(),
             unifier: Unifier::new(),
             function_contexts: Vec::new(),
+            type_aliases: HashMap::new(),
+            function_arities: BUILTIN_OVERLOADS
+                .iter()
+                .map(|(name, arities)| (name.to_string(), arities.to_vec()))
+                .collect(),
+            node_types: HashMap::new(),
+            recovered_errors: None,
+            program: Vec::new(),
+            top_level_checks: 0,
+            strict_match: false,
+            warnings: Vec::new(),
+            hoisted_signatures: HashMap::new(),
+            builtins,
+            classes: HashMap::new(),
+            constructors: HashMap::new(),
         }
     }
 
+    /// Require every match to be provably exhaustive or to end in a
+    /// catch-all arm, instead of only warning when it isn't
+    pub fn with_strict_match(mut self, strict_match: bool) -> Self {
+        self.strict_match = strict_match;
+        self
+    }
+
+    /// Warnings found so far
+    pub fn warnings(&self) -> &[TypeError] {
+        &self.warnings
+    }
+
     /// Get the current environment
     pub fn env(&self) -> &TypeEnvironment {
         &self.env
     }
 
+    /// The type of a top-level binding, resolved through the current
+    /// substitution. `arity` picks out one overload of a function name.
+    pub fn global_type(&self, name: &str, arity: Option<usize>) -> Option<Type> {
+        let scheme = self
+            .env
+            .lookup(name)
+            .or_else(|| arity.and_then(|arity| self.env.lookup(&overload_key(name, arity))))?;
+        Some(self.unifier.apply(&scheme.ty))
+    }
+
     /// Get the current context
     pub fn ctx(&mut self) -> &mut TypeContext {
         &mut self.ctx
     }
 
+    /// Get the inferred type of every expression node checked so far
+    ///
+    /// Types are resolved through the current substitution, so later lowering
+    /// passes see concrete types wherever inference settled on one.
+    pub fn node_types(&self) -> HashMap<NodeId, Type> {
+        self.node_types
+            .iter()
+            .map(|(id, ty)| (*id, self.unifier.apply(ty)))
+            .collect()
+    }
+
     /// Type check a list of statements
+    ///
+    /// The statements replace the top-level program remembered from any
+    /// earlier check, so that a later change to one of them can be re-checked
+    /// with [`TypeChecker::recheck_statement`].
     pub fn check(&mut self, stmts: &[Stmt]) -> Result<Type> {
         let mut last_type = Type::Unit;
+        self.program.clear();
+        self.hoist_functions(stmts);
 
         for stmt in stmts {
-            last_type = self.check_statement(stmt)?;
+            last_type = self.check_top_level(stmt)?;
+            self.program
+                .push(TopLevelEntry::new(stmt.clone(), last_type.clone()));
         }
 
         Ok(last_type)
     }
 
+    /// Replace the top-level statement at `index` and re-check only it and
+    /// the statements that depend on what it defines.
+    ///
+    /// Functions are hoisted, so a dependent statement may come before the
+    /// one replaced; dependents are re-checked in program order, after the
+    /// changed functions' signatures are bound again. If the re-check fails,
+    /// the checker is left as it was before the call.
+    ///
+    /// Returns the type of the last top-level statement, as `check` does.
+    pub fn recheck_statement(&mut self, index: usize, stmt: Stmt) -> Result<Type> {
+        if index >= self.program.len() {
+            return Err(TypeError::no_such_statement(
+                index,
+                self.program.len(),
+                stmt.span().clone(),
+            ));
+        }
+
+        let saved = self.clone();
+        let result = self.recheck_replaced(index, stmt);
+        if result.is_err() {
+            // The statements were still checked, so they still count
+            let checks = self.top_level_checks;
+            *self = saved;
+            self.top_level_checks = checks;
+        }
+        result
+    }
+
+    fn recheck_replaced(&mut self, index: usize, stmt: Stmt) -> Result<Type> {
+        let stale_names = self.program[index].defines.clone();
+        let stale = std::mem::replace(
+            &mut self.program[index],
+            TopLevelEntry::new(stmt, Type::Unit),
+        );
+
+        let mut dirty_names: HashSet<String> = stale_names.into_iter().collect();
+        dirty_names.extend(self.program[index].defines.iter().cloned());
+        let mut is_dirty = vec![false; self.program.len()];
+        is_dirty[index] = true;
+        // A newly dirty statement can make earlier ones dirty too, so repeat
+        // until nothing changes
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, entry) in self.program.iter().enumerate() {
+                if !is_dirty[i]
+                    && entry
+                        .references
+                        .iter()
+                        .any(|name| dirty_names.contains(name))
+                {
+                    is_dirty[i] = true;
+                    dirty_names.extend(entry.defines.iter().cloned());
+                    changed = true;
+                }
+            }
+        }
+        let dirty: Vec<usize> = (0..self.program.len()).filter(|&i| is_dirty[i]).collect();
+
+        let dirty_stmts: Vec<Stmt> = dirty
+            .iter()
+            .map(|&i| self.program[i].stmt.clone())
+            .collect();
+        for stmt in std::iter::once(&stale.stmt).chain(&dirty_stmts) {
+            if let Stmt::Function { name, params, .. } = stmt {
+                self.forget_function(name, params.len());
+            }
+        }
+
+        for name in &dirty_names {
+            self.env.remove(name);
+            self.type_aliases.remove(name);
+        }
+        self.hoist_functions(&dirty_stmts);
+
+        for (i, stmt) in dirty.into_iter().zip(dirty_stmts) {
+            self.program[i].ty = self.check_top_level(&stmt)?;
+        }
+
+        Ok(self
+            .program
+            .last()
+            .map(|entry| entry.ty.clone())
+            .unwrap_or(Type::Unit))
+    }
+
+    /// Number of top-level statements checked so far, including re-checks
+    pub fn top_level_check_count(&self) -> usize {
+        self.top_level_checks
+    }
+
+    fn check_top_level(&mut self, stmt: &Stmt) -> Result<Type> {
+        self.top_level_checks += 1;
+        self.check_statement(stmt)
+    }
+
+    /// Bind every top-level function to a placeholder signature, so calls
+    /// that come before the definition check against it.
+    ///
+    /// Type aliases are registered first so signatures can name them. A
+    /// function whose annotations don't resolve is left to be declared in
+    /// order, where the error is reported.
+    fn hoist_functions(&mut self, stmts: &[Stmt]) {
+        let items: Vec<&Stmt> = stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Export { stmt, .. } => stmt.as_ref(),
+                stmt => stmt,
+            })
+            .collect();
+
+        for stmt in &items {
+            if let Stmt::TypeAlias {
+                name,
+                type_params,
+                definition,
+                ..
+            } = stmt
+            {
+                self.type_aliases.insert(
+                    name.clone(),
+                    TypeAlias {
+                        params: type_params.clone(),
+                        definition: definition.clone(),
+                    },
+                );
+            }
+        }
+
+        for stmt in items {
+            let Stmt::Function {
+                id,
+                name,
+                params,
+                return_type,
+                ..
+            } = stmt
+            else {
+                continue;
+            };
+            let Ok((param_types, declared_return)) =
+                self.function_signature(params, return_type.as_ref())
+            else {
+                continue;
+            };
+
+            let binding = self.function_binding_name(name, params.len());
+            let func_type = Type::Function(param_types.clone(), Box::new(declared_return.clone()));
+            self.env
+                .insert_or_update(binding, TypeScheme::monomorphic(func_type));
+            self.hoisted_signatures
+                .insert(*id, (param_types, declared_return));
+        }
+    }
+
+    /// Parameter and return types of a function, with a fresh type variable
+    /// for each one left unannotated
+    fn function_signature(
+        &mut self,
+        params: &[nevermind_ast::Parameter],
+        return_type: Option<&nevermind_ast::TypeAnnotation>,
+    ) -> Result<(Vec<Type>, Type)> {
+        let param_types: Vec<Type> = params
+            .iter()
+            .map(|p| {
+                if let Some(ann) = &p.type_annotation {
+                    self.resolve_type_annotation(ann)
+                } else {
+                    let var = self.ctx.fresh_var();
+                    Ok(Type::Var(crate::types::TypeVarRef::new(var.id())))
+                }
+            })
+            .collect::<Result<_>>()?;
+
+        let declared_return = if let Some(ann) = return_type {
+            self.resolve_type_annotation(ann)?
+        } else {
+            let var = self.ctx.fresh_var();
+            Type::Var(crate::types::TypeVarRef::new(var.id()))
+        };
+
+        Ok((param_types, declared_return))
+    }
+
+    /// Register a function's arity and get the name to bind it under.
+    ///
+    /// When a global function name gains a second arity, the binding already
+    /// made under the bare name moves to its `name/arity` key.
+    fn function_binding_name(&mut self, name: &str, arity: usize) -> String {
+        if self.env.depth() != 1 {
+            return name.to_string();
+        }
+        self.shadow_builtin(name);
+
+        let arities = self.function_arities.entry(name.to_string()).or_default();
+        if arities.contains(&arity) {
+            return if arities.len() == 1 {
+                name.to_string()
+            } else {
+                overload_key(name, arity)
+            };
+        }
+
+        arities.push(arity);
+        match arities.as_slice() {
+            [_] => name.to_string(),
+            [first, _] => {
+                let first = *first;
+                if let Some(scheme) = self.env.remove(name) {
+                    self.env.insert_or_update(overload_key(name, first), scheme);
+                }
+                overload_key(name, arity)
+            }
+            _ => overload_key(name, arity),
+        }
+    }
+
+    /// Forget the predefined signatures of `name` when it is a builtin
+    fn shadow_builtin(&mut self, name: &str) {
+        if !self.builtins.remove(name) {
+            return;
+        }
+        self.env.remove(name);
+        for arity in self.function_arities.remove(name).unwrap_or_default() {
+            self.env.remove(&overload_key(name, arity));
+        }
+    }
+
+    /// Drop one overload of a global function ahead of re-checking it
+    fn forget_function(&mut self, name: &str, arity: usize) {
+        let Some(arities) = self.function_arities.get_mut(name) else {
+            return;
+        };
+        if arities.len() > 1 {
+            self.env.remove(&overload_key(name, arity));
+        }
+        arities.retain(|&a| a != arity);
+
+        // A single remaining overload goes back to the bare name
+        if let [remaining] = arities.as_slice() {
+            let remaining = *remaining;
+            if let Some(scheme) = self.env.remove(&overload_key(name, remaining)) {
+                self.env.insert_or_update(name.to_string(), scheme);
+            }
+        }
+    }
+
+    /// Whether a name refers to a set of function overloads. A predefined
+    /// function's bare binding only stands in for it as a value, so calls
+    /// still pick an overload unless a local binding shadows it.
+    fn is_overloaded(&self, name: &str) -> bool {
+        let unshadowed = match self.env.lookup(name) {
+            None => true,
+            Some(_) => self.builtins.contains(name) && self.env.is_global(name),
+        };
+        unshadowed
+            && self
+                .function_arities
+                .get(name)
+                .is_some_and(|arities| arities.len() > 1)
+    }
+
+    /// Instantiate the overload of `name` that takes `arity` arguments
+    fn instantiate_overload(&mut self, name: &str, arity: usize, span: &Span) -> Result<Type> {
+        match self.env.lookup(&overload_key(name, arity)) {
+            Some(scheme) => Ok(scheme.instantiate(&mut self.ctx)),
+            None => Err(TypeError::no_matching_overload(
+                name.to_string(),
+                arity,
+                span.clone(),
+            )),
+        }
+    }
+
+    /// Type check a list of statements, collecting every error instead of
+    /// stopping at the first one.
+    ///
+    /// A node that fails to check is recorded and given a fresh type variable,
+    /// so its siblings are still checked without cascading errors.
+    pub fn check_all(&mut self, stmts: &[Stmt]) -> Vec<TypeError> {
+        self.recovered_errors = Some(Vec::new());
+        self.program.clear();
+        self.hoist_functions(stmts);
+
+        for stmt in stmts {
+            // Errors are recorded rather than returned while recovering
+            let ty = self.check_top_level(stmt).unwrap_or(Type::Unit);
+            self.program.push(TopLevelEntry::new(stmt.clone(), ty));
+        }
+
+        self.recovered_errors.take().unwrap_or_default()
+    }
+
+    /// Resolve type variable ids taken from the environment to the free
+    /// variables they currently stand for
+    fn resolve_free_vars(&self, ids: &HashSet<usize>) -> HashSet<usize> {
+        ids.iter()
+            .flat_map(|&id| self.unifier.free_vars(&Type::var(id)))
+            .collect()
+    }
+
     /// Type check a statement
     fn check_statement(&mut self, stmt: &Stmt) -> Result<Type> {
         Ok(self.check_statement_with_flow(stmt)?.ty)
     }
 
     fn check_statement_with_flow(&mut self, stmt: &Stmt) -> Result<FlowInfo> {
+        let scope_depth = self.env.depth();
+        let function_depth = self.function_contexts.len();
+        let result = self.check_statement_node(stmt);
+        let recovered = self.recover(result, scope_depth, function_depth)?;
+
+        // Keep a failed binding visible so later uses don't report it as undefined
+        if let Stmt::Let { name, .. } = stmt {
+            if !self.env.in_current_scope(name) {
+                let scheme = TypeScheme::monomorphic(recovered.ty.clone());
+                self.env.insert_or_update(name.clone(), scheme);
+            }
+        }
+
+        Ok(recovered)
+    }
+
+    /// When collecting errors, record a failed node's error, unwind any scopes
+    /// it left open, and stand in a fresh type variable for its type.
+    fn recover(
+        &mut self,
+        result: Result<FlowInfo>,
+        scope_depth: usize,
+        function_depth: usize,
+    ) -> Result<FlowInfo> {
+        let error = match result {
+            Err(error) if self.recovered_errors.is_some() => error,
+            other => return other,
+        };
+
+        while self.env.depth() > scope_depth {
+            self.env.exit_scope()?;
+        }
+        self.function_contexts.truncate(function_depth);
+        if let Some(errors) = self.recovered_errors.as_mut() {
+            errors.push(error);
+        }
+
+        let var = self.ctx.fresh_var();
+        Ok(FlowInfo::new(Type::Var(crate::types::TypeVarRef::new(
+            var.id(),
+        ))))
+    }
+
+    fn check_statement_node(&mut self, stmt: &Stmt) -> Result<FlowInfo> {
         match stmt {
             Stmt::Export { stmt, .. } => self.check_statement_with_flow(stmt),
 
-            Stmt::Let { name, value, .. } => {
+            Stmt::Let {
+                name,
+                type_annotation,
+                value,
+                ..
+            } => {
                 let ty = self.infer_expression(value)?;
-                let free_vars = self.env.free_vars();
+                if let Some(ann) = type_annotation {
+                    let declared = self.resolve_type_annotation(ann)?;
+                    self.unifier.unify(&declared, &ty, value.span())?;
+                }
+                // Generalize what the annotation pinned down, not the raw
+                // inferred type, so `let xs: List[Int] = []` stays `List[Int]`
+                let ty = self.unifier.apply(&ty);
+                let free_vars = self.resolve_free_vars(&self.env.free_vars());
                 let scheme = TypeScheme::generalize(ty, &free_vars);
                 self.env.insert(name.clone(), scheme)?;
                 Ok(FlowInfo::new(Type::Unit))
             }
 
             Stmt::Function {
+                id,
                 name,
                 params,
                 body,
                 return_type: ret_ann,
                 ..
             } => {
-                let param_types: Vec<Type> = params
-                    .iter()
-                    .map(|p| {
-                        if let Some(ann) = &p.type_annotation {
-                            self.resolve_type_annotation(ann)
-                        } else {
-                            let var = self.ctx.fresh_var();
-                            Type::Var(crate::types::TypeVarRef::new(var.id()))
-                        }
-                    })
-                    .collect();
-
-                let declared_return = if let Some(ann) = ret_ann {
-                    self.resolve_type_annotation(ann)
-                } else {
-                    let var = self.ctx.fresh_var();
-                    Type::Var(crate::types::TypeVarRef::new(var.id()))
+                let (param_types, declared_return) = match self.hoisted_signatures.remove(id) {
+                    Some(signature) => {
+                        // Drop the placeholder so its type variables can be
+                        // generalized once the body has been checked
+                        let binding = self.function_binding_name(name, params.len());
+                        self.env.remove(&binding);
+                        signature
+                    }
+                    None => self.function_signature(params, ret_ann.as_ref())?,
                 };
 
                 let free_vars_before = self.env.free_vars();
                 let func_type =
                     Type::Function(param_types.clone(), Box::new(declared_return.clone()));
                 let func_scheme = TypeScheme::monomorphic(func_type.clone());
-                self.env.insert_or_update(name.clone(), func_scheme);
+                let binding = self.function_binding_name(name, params.len());
+                self.env.insert_or_update(binding.clone(), func_scheme);
 
                 self.env.enter_scope();
                 for (i, param) in params.iter().enumerate() {
@@ -168,7 +648,7 @@ impl TypeChecker {
                         let mut error = TypeError::missing_return(
                             name.clone(),
                             expected_return.clone(),
-                            ast_helpers::get_span(body),
+                            body.span().clone(),
                         );
                         if let Some(span) = ret_ann.as_ref().map(|ann| ann.span.clone()) {
                             error = error.with_context(
@@ -190,7 +670,7 @@ impl TypeChecker {
                         let mut error = TypeError::missing_return(
                             name.clone(),
                             expected_return.clone(),
-                            ast_helpers::get_span(body),
+                            body.span().clone(),
                         );
                         if let Some(span) = ret_ann.as_ref().map(|ann| ann.span.clone()) {
                             error = error.with_context(
@@ -206,29 +686,58 @@ impl TypeChecker {
                     }
 
                     self.unifier
-                        .unify(
-                            &body_result.ty,
-                            &declared_return,
-                            &ast_helpers::get_span(body),
-                        )
+                        .unify(&body_result.ty, &declared_return, body.span())
                         .map_err(|_| {
                             self.return_type_mismatch_error(
                                 name,
                                 ret_ann.as_ref().map(|ann| ann.span.clone()),
                                 &declared_return,
                                 &body_result.ty,
-                                ast_helpers::get_span(body),
+                                body.span().clone(),
                             )
                         })?;
                 }
 
-                let scheme = TypeScheme::generalize(func_type, &free_vars_before);
-                self.env.insert_or_update(name.clone(), scheme);
+                // Generalize what checking the body inferred, so a variable
+                // it bound isn't quantified as if it were still free
+                let func_type = self.unifier.apply(&func_type);
+                let free_vars = self.resolve_free_vars(&free_vars_before);
+                let scheme = TypeScheme::generalize(func_type, &free_vars);
+                self.env.insert_or_update(binding, scheme);
 
                 Ok(FlowInfo::new(Type::Unit))
             }
 
-            Stmt::TypeAlias { .. } => Ok(FlowInfo::new(Type::Unit)),
+            Stmt::TypeAlias {
+                name,
+                type_params,
+                definition,
+                span,
+                ..
+            } => {
+                self.type_aliases.insert(
+                    name.clone(),
+                    TypeAlias {
+                        params: type_params.clone(),
+                        definition: definition.clone(),
+                    },
+                );
+
+                // Expand once with the parameters left abstract so cycles are
+                // reported at the declaration rather than at some later use.
+                let abstract_args: Vec<Type> = type_params
+                    .iter()
+                    .map(|p| Type::User(p.name.clone()))
+                    .collect();
+                if let Err(error) =
+                    self.expand_type_alias(name, abstract_args, span, &mut Vec::new())
+                {
+                    self.type_aliases.remove(name);
+                    return Err(error);
+                }
+
+                Ok(FlowInfo::new(Type::Unit))
+            }
 
             Stmt::If {
                 condition,
@@ -239,7 +748,7 @@ impl TypeChecker {
             } => {
                 let cond_ty = self.infer_expression(condition)?;
                 self.unifier
-                    .unify(&cond_ty, &Type::Bool, &ast_helpers::get_span(condition))?;
+                    .unify(&cond_ty, &Type::Bool, condition.span())?;
 
                 self.env.enter_scope();
                 let then_result = self.check_block_with_flow(then_branch)?;
@@ -267,7 +776,7 @@ impl TypeChecker {
             } => {
                 let cond_ty = self.infer_expression(condition)?;
                 self.unifier
-                    .unify(&cond_ty, &Type::Bool, &ast_helpers::get_span(condition))?;
+                    .unify(&cond_ty, &Type::Bool, condition.span())?;
 
                 self.env.enter_scope();
                 self.check_block_with_flow(body)?;
@@ -290,7 +799,7 @@ impl TypeChecker {
                     return Err(TypeError::type_mismatch(
                         Type::List(Box::new(Type::var(0))),
                         iter_ty,
-                        ast_helpers::get_span(iter),
+                        iter.span().clone(),
                     ));
                 };
 
@@ -317,11 +826,7 @@ impl TypeChecker {
 
                     if let Some(guard) = &arm.guard {
                         let guard_ty = self.infer_expression(guard)?;
-                        self.unifier.unify(
-                            &guard_ty,
-                            &Type::Bool,
-                            &ast_helpers::get_span(guard),
-                        )?;
+                        self.unifier.unify(&guard_ty, &Type::Bool, guard.span())?;
                     }
 
                     let arm_result = self.infer_expression_with_flow(&arm.body)?;
@@ -329,10 +834,14 @@ impl TypeChecker {
                     self.env.exit_scope()?;
                 }
 
+                let arm_patterns: Vec<(&Pattern, bool)> = arms
+                    .iter()
+                    .map(|arm| (&arm.pattern, arm.guard.is_some()))
+                    .collect();
+                let is_exhaustive =
+                    self.check_match_exhaustiveness(&arm_patterns, &scrutinee_ty, span)?;
+
                 if let Some(first_result) = arm_results.first() {
-                    let is_exhaustive = arms
-                        .iter()
-                        .any(|arm| arm.guard.is_none() && !arm.pattern.is_refutable());
                     for result in &arm_results[1..] {
                         self.unifier.unify(&first_result.ty, &result.ty, span)?;
                     }
@@ -382,12 +891,67 @@ impl TypeChecker {
                 }
             }
 
+            Stmt::Try {
+                body,
+                catch_var,
+                catch_body,
+                finally_body,
+                ..
+            } => {
+                self.env.enter_scope();
+                let body_result = self.check_block_with_flow(body)?;
+                self.env.exit_scope()?;
+
+                // An error is caught as its message
+                let mut catch_returns = true;
+                if let Some(catch_body) = catch_body {
+                    self.env.enter_scope();
+                    if let Some(name) = catch_var {
+                        self.env
+                            .insert_or_update(name.clone(), TypeScheme::monomorphic(Type::String));
+                    }
+                    catch_returns = self.check_block_with_flow(catch_body)?.always_returns;
+                    self.env.exit_scope()?;
+                }
+
+                let mut finally_returns = false;
+                if let Some(finally_body) = finally_body {
+                    self.env.enter_scope();
+                    finally_returns = self.check_block_with_flow(finally_body)?.always_returns;
+                    self.env.exit_scope()?;
+                }
+
+                if finally_returns || (body_result.always_returns && catch_returns) {
+                    let var = self.ctx.fresh_var();
+                    Ok(FlowInfo::returning(Type::Var(
+                        crate::types::TypeVarRef::new(var.id()),
+                    )))
+                } else {
+                    Ok(FlowInfo::new(Type::Unit))
+                }
+            }
+
+            Stmt::Raise { value, .. } => {
+                let value_ty = self.infer_expression(value)?;
+                self.unifier.unify(&Type::String, &value_ty, value.span())?;
+
+                // Like `return`, nothing after a raise runs, so it fits
+                // wherever a value of any type is expected
+                let var = self.ctx.fresh_var();
+                Ok(FlowInfo::returning(Type::Var(
+                    crate::types::TypeVarRef::new(var.id()),
+                )))
+            }
+
             Stmt::Break { .. } | Stmt::Continue { .. } => Ok(FlowInfo::new(Type::Unit)),
 
             Stmt::ExprStmt { expr, .. } => self.infer_expression_with_flow(expr),
 
             Stmt::Import {
-                module, symbols, ..
+                module,
+                symbols,
+                alias,
+                ..
             } => {
                 let register = |env: &mut TypeEnvironment, ctx: &mut TypeContext, name: &str| {
                     let var = ctx.fresh_var();
@@ -400,12 +964,12 @@ impl TypeChecker {
 
                 match symbols {
                     Some(syms) => {
-                        for sym_name in syms {
-                            register(&mut self.env, &mut self.ctx, sym_name);
+                        for symbol in syms {
+                            register(&mut self.env, &mut self.ctx, symbol.binding());
                         }
                     }
                     None => {
-                        let namespace = module.split('/').next_back().unwrap_or(module.as_str());
+                        let namespace = namespace_binding(module, alias.as_deref());
                         register(&mut self.env, &mut self.ctx, namespace);
                     }
                 }
@@ -413,29 +977,252 @@ impl TypeChecker {
                 Ok(FlowInfo::new(Type::Unit))
             }
 
-            Stmt::Class { .. } => Ok(FlowInfo::new(Type::Unit)),
+            Stmt::Class {
+                name,
+                extends,
+                members,
+                span,
+                ..
+            } => {
+                self.check_class(name, extends.as_deref(), members, span)?;
+                Ok(FlowInfo::new(Type::Unit))
+            }
         }
     }
 
+    /// Check a class declaration and bind its name to a constructor taking
+    /// the arguments of the constructor of the class it extends, then each
+    /// field without a default, in order.
+    ///
+    /// Methods are checked like nested functions, with the fields and every
+    /// method of the class in scope, along with the members it inherits.
+    fn check_class(
+        &mut self,
+        name: &str,
+        extends: Option<&str>,
+        members: &[ClassMember],
+        span: &Span,
+    ) -> Result<()> {
+        // A class extended without being declared here contributes nothing
+        let inherited = extends
+            .and_then(|base| self.classes.get(base))
+            .cloned()
+            .unwrap_or_default();
+        let mut constructor_params = extends
+            .and_then(|base| self.constructors.get(base))
+            .cloned()
+            .unwrap_or_default();
+        let mut member_types = HashMap::new();
+        for member in members {
+            let ClassMember::Field {
+                name: field,
+                type_annotation,
+                default_value,
+                ..
+            } = member
+            else {
+                continue;
+            };
+            let ty = match type_annotation {
+                Some(ann) => self.resolve_type_annotation(ann)?,
+                None => Type::Var(crate::types::TypeVarRef::new(self.ctx.fresh_var().id())),
+            };
+            match default_value {
+                Some(value) => {
+                    let value_ty = self.infer_expression(value)?;
+                    self.unifier.unify(&ty, &value_ty, value.span())?;
+                }
+                None => constructor_params.push(ty.clone()),
+            }
+            member_types.insert(field.clone(), ty);
+        }
+
+        self.constructors
+            .insert(name.to_string(), constructor_params.clone());
+        let instance = Type::User(name.to_string());
+        let constructor = Type::Function(constructor_params, Box::new(instance));
+        self.env
+            .insert_or_update(name.to_string(), TypeScheme::monomorphic(constructor));
+
+        let methods: Vec<Stmt> = members
+            .iter()
+            .filter_map(|member| match member {
+                ClassMember::Method {
+                    name,
+                    params,
+                    return_type,
+                    body,
+                } => Some(Stmt::Function {
+                    id: nevermind_ast::new_node_id(),
+                    is_async: false,
+                    name: name.clone(),
+                    params: params.clone(),
+                    return_type: return_type.clone(),
+                    body: body.clone(),
+                    span: span.clone(),
+                }),
+                ClassMember::Field { .. } => None,
+            })
+            .collect();
+
+        self.env.enter_scope();
+        for (member, scheme) in &inherited {
+            self.env.insert_or_update(member.clone(), scheme.clone());
+        }
+        for (field, ty) in &member_types {
+            self.env
+                .insert_or_update(field.clone(), TypeScheme::monomorphic(ty.clone()));
+        }
+        // Give every method a signature up front, so methods may call each
+        // other in any order
+        for method in &methods {
+            let Stmt::Function {
+                id,
+                name,
+                params,
+                return_type,
+                ..
+            } = method
+            else {
+                continue;
+            };
+            let (param_types, declared_return) =
+                self.function_signature(params, return_type.as_ref())?;
+            let func_type = Type::Function(param_types.clone(), Box::new(declared_return.clone()));
+            self.env
+                .insert_or_update(name.clone(), TypeScheme::monomorphic(func_type));
+            self.hoisted_signatures
+                .insert(*id, (param_types, declared_return));
+        }
+        let checked = methods
+            .iter()
+            .try_for_each(|method| self.check_statement(method).map(|_| ()));
+
+        let mut member_schemes = inherited;
+        member_schemes.extend(
+            member_types
+                .into_iter()
+                .map(|(field, ty)| (field, TypeScheme::monomorphic(self.unifier.apply(&ty)))),
+        );
+        for method in &methods {
+            if let Stmt::Function { name, .. } = method {
+                if let Some(scheme) = self.env.lookup(name) {
+                    member_schemes.insert(name.clone(), scheme.clone());
+                }
+            }
+        }
+        self.env.exit_scope()?;
+        checked?;
+
+        self.classes.insert(name.to_string(), member_schemes);
+        Ok(())
+    }
+
+    /// Infer the result type of calling `callee` with `args`, preceded by an
+    /// argument of type `piped` when the call is a pipeline stage
+    fn infer_call(
+        &mut self,
+        callee: &Expr,
+        piped: Option<Type>,
+        args: &[Expr],
+        span: &Span,
+    ) -> Result<Type> {
+        let leading = usize::from(piped.is_some());
+
+        // Infer the type of the callee, picking an overload by arity
+        let mut constrained = Vec::new();
+        let callee_ty = match callee {
+            Expr::Variable { name, .. } if self.is_overloaded(name) => {
+                self.instantiate_overload(name, leading + args.len(), span)?
+            }
+            Expr::Variable { name, id, .. } => match self.env.lookup(name) {
+                Some(scheme) if !scheme.constraints.is_empty() => {
+                    let (ty, vars) = scheme.instantiate_constrained(&mut self.ctx);
+                    constrained = vars;
+                    self.node_types.insert(*id, ty.clone());
+                    ty
+                }
+                _ => self.infer_expression(callee)?,
+            },
+            _ => self.infer_expression(callee)?,
+        };
+
+        // Create fresh type variables for arguments and return type
+        let var = self.ctx.fresh_var();
+        let return_var = Type::Var(crate::types::TypeVarRef::new(var.id()));
+
+        let mut arg_types = Vec::new();
+        for _ in 0..leading + args.len() {
+            let var = self.ctx.fresh_var();
+            arg_types.push(Type::Var(crate::types::TypeVarRef::new(var.id())));
+        }
+
+        // Expected function type
+        let expected_ty = Type::Function(arg_types.clone(), Box::new(return_var.clone()));
+
+        // Unify callee type with expected function type
+        self.unifier.unify(&callee_ty, &expected_ty, span)?;
+
+        // Type check arguments, the piped value first so that it informs the
+        // parameter types of any lambda argument
+        if let Some(piped) = piped {
+            self.unifier.unify(&piped, &arg_types[0], span)?;
+        }
+        for (i, arg) in args.iter().enumerate() {
+            let arg_ty = self.infer_expression(arg)?;
+            self.unifier.unify(&arg_ty, &arg_types[leading + i], span)?;
+        }
+
+        // Variables still unknown here are left unchecked
+        for (var, constraint) in constrained {
+            let ty = self.unifier.apply(&var);
+            if constraint.allows(&ty) || matches!(ty, Type::Var(_)) {
+                continue;
+            }
+            let Expr::Variable { name, .. } = callee else {
+                continue;
+            };
+            return Err(match constraint {
+                Constraint::Numeric => TypeError::not_numeric(name.clone(), ty, span.clone()),
+                Constraint::Sized => TypeError::not_sized(name.clone(), ty, span.clone()),
+            });
+        }
+
+        Ok(return_var)
+    }
+
     /// Infer the type of an expression
     fn infer_expression(&mut self, expr: &Expr) -> Result<Type> {
         Ok(self.infer_expression_with_flow(expr)?.ty)
     }
 
     fn infer_expression_with_flow(&mut self, expr: &Expr) -> Result<FlowInfo> {
+        let scope_depth = self.env.depth();
+        let function_depth = self.function_contexts.len();
+        let result = self.infer_expression_node(expr);
+        let flow = self.recover(result, scope_depth, function_depth)?;
+        if let Some(id) = expr.id() {
+            self.node_types.insert(id, flow.ty.clone());
+        }
+        Ok(flow)
+    }
+
+    fn infer_expression_node(&mut self, expr: &Expr) -> Result<FlowInfo> {
         match expr {
             Expr::Literal(lit) => {
                 let ty = match lit {
                     Literal::Integer(_, _) => Type::Int,
                     Literal::Float(_, _) => Type::Float,
                     Literal::String(_, _) => Type::String,
-                    Literal::Char(_, _) => Type::Int, // Characters are integers
+                    Literal::Char(_, _) => Type::Char,
                     Literal::Boolean(_, _) => Type::Bool,
                     Literal::Null(_) => Type::Null,
                 };
                 Ok(FlowInfo::new(ty))
             }
 
+            Expr::Unit { .. } => Ok(FlowInfo::new(Type::Unit)),
+
             Expr::Variable { name, span, .. } => {
                 // Look up the variable in the environment
                 if let Some(scheme) = self.env.lookup(name) {
@@ -448,18 +1235,16 @@ impl TypeChecker {
 
             Expr::Binary {
                 left,
+                op,
                 right,
                 span,
                 id: _,
-                ..
             } => {
                 let left_ty = self.infer_expression(left)?;
                 let right_ty = self.infer_expression(right)?;
-
-                // Type check based on operator
-                self.unifier.unify(&left_ty, &right_ty, span)?;
-                // Numeric operators return the same type as operands
-                Ok(FlowInfo::new(left_ty.clone()))
+                Ok(FlowInfo::new(
+                    self.binary_type(op, &left_ty, &right_ty, span)?,
+                ))
             }
 
             Expr::Comparison {
@@ -496,39 +1281,16 @@ impl TypeChecker {
                 Ok(FlowInfo::new(expr_ty))
             }
 
+            // Until there is a future type, an async call has the type of
+            // its result and awaiting it passes that type through
+            Expr::Await { expr, .. } => Ok(FlowInfo::new(self.infer_expression(expr)?)),
+
             Expr::Call {
                 callee,
                 args,
                 span,
                 id: _,
-            } => {
-                // Infer the type of the callee
-                let callee_ty = self.infer_expression(callee)?;
-
-                // Create fresh type variables for arguments and return type
-                let var = self.ctx.fresh_var();
-                let return_var = Type::Var(crate::types::TypeVarRef::new(var.id()));
-
-                let mut arg_types = Vec::new();
-                for _ in args {
-                    let var = self.ctx.fresh_var();
-                    arg_types.push(Type::Var(crate::types::TypeVarRef::new(var.id())));
-                }
-
-                // Expected function type
-                let expected_ty = Type::Function(arg_types.clone(), Box::new(return_var.clone()));
-
-                // Unify callee type with expected function type
-                self.unifier.unify(&callee_ty, &expected_ty, span)?;
-
-                // Type check arguments
-                for (i, arg) in args.iter().enumerate() {
-                    let arg_ty = self.infer_expression(arg)?;
-                    self.unifier.unify(&arg_ty, &arg_types[i], span)?;
-                }
-
-                Ok(FlowInfo::new(return_var))
-            }
+            } => Ok(FlowInfo::new(self.infer_call(callee, None, args, span)?)),
 
             Expr::Pipeline {
                 stages,
@@ -538,9 +1300,29 @@ impl TypeChecker {
                 // Type check each stage
                 let mut current_ty = self.infer_expression(&stages[0])?;
 
-                for stage in &stages[1..] {
+                for (index, stage) in stages.iter().enumerate().skip(1) {
+                    // A call stage receives the piped value as its first argument
+                    if let Expr::Call {
+                        callee, args, span, ..
+                    } = stage
+                    {
+                        current_ty = self.infer_call(callee, Some(current_ty), args, span)?;
+                        continue;
+                    }
+
                     let stage_ty = self.infer_expression(stage)?;
 
+                    // Reject stages that can never be called before unifying,
+                    // so the error names the stage rather than a type mismatch
+                    let resolved = self.unifier.apply(&stage_ty);
+                    if !matches!(resolved, Type::Function(..) | Type::Var(_)) {
+                        return Err(TypeError::pipeline_stage_not_callable(
+                            index + 1,
+                            resolved,
+                            stage.span().clone(),
+                        ));
+                    }
+
                     // Create a function type: current_ty -> ?
                     let var = self.ctx.fresh_var();
                     let expected_func = Type::Function(
@@ -597,18 +1379,15 @@ impl TypeChecker {
                 // Type check condition
                 let cond_ty = self.infer_expression(condition)?;
                 self.unifier
-                    .unify(&cond_ty, &Type::Bool, &ast_helpers::get_span(condition))?;
+                    .unify(&cond_ty, &Type::Bool, condition.span())?;
 
                 // Type check branches
                 let then_result = self.infer_expression_with_flow(then_branch)?;
                 let else_result = self.infer_expression_with_flow(else_branch)?;
 
                 // Unify branch types
-                self.unifier.unify(
-                    &then_result.ty,
-                    &else_result.ty,
-                    &ast_helpers::get_span(expr),
-                )?;
+                self.unifier
+                    .unify(&then_result.ty, &else_result.ty, expr.span())?;
 
                 Ok(FlowInfo {
                     ty: self.unifier.apply(&then_result.ty),
@@ -627,6 +1406,16 @@ impl TypeChecker {
                 Ok(result)
             }
 
+            Expr::StringInterp { parts, .. } => {
+                // Any value can be spliced into a string
+                for part in parts {
+                    if let StringPart::Expr(expr) = part {
+                        self.infer_expression(expr)?;
+                    }
+                }
+                Ok(FlowInfo::new(Type::String))
+            }
+
             Expr::List { elements, .. } => {
                 if elements.is_empty() {
                     // Empty list has a fresh type variable
@@ -641,14 +1430,21 @@ impl TypeChecker {
                     // All elements must have the same type
                     for elem in &elements[1..] {
                         let ty = self.infer_expression(elem)?;
-                        self.unifier
-                            .unify(&elem_ty, &ty, &ast_helpers::get_span(elem))?;
+                        self.unifier.unify(&elem_ty, &ty, elem.span())?;
                     }
 
                     Ok(FlowInfo::new(Type::List(Box::new(elem_ty))))
                 }
             }
 
+            Expr::Tuple { elements, .. } => {
+                let elem_tys = elements
+                    .iter()
+                    .map(|elem| self.infer_expression(elem))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(FlowInfo::new(Type::Tuple(elem_tys)))
+            }
+
             Expr::Map { entries, .. } => {
                 if entries.is_empty() {
                     // Empty map has a fresh type variable
@@ -663,12 +1459,10 @@ impl TypeChecker {
 
                     for (key, value) in entries {
                         let key_ty = self.infer_expression(key)?;
-                        self.unifier
-                            .unify(&key_ty, &Type::String, &ast_helpers::get_span(key))?;
+                        self.unifier.unify(&key_ty, &Type::String, key.span())?;
 
                         let ty = self.infer_expression(value)?;
-                        self.unifier
-                            .unify(&value_ty, &ty, &ast_helpers::get_span(value))?;
+                        self.unifier.unify(&value_ty, &ty, value.span())?;
                     }
 
                     Ok(FlowInfo::new(Type::Map(Box::new(value_ty))))
@@ -676,7 +1470,10 @@ impl TypeChecker {
             }
 
             Expr::Match {
-                scrutinee, arms, ..
+                scrutinee,
+                arms,
+                span,
+                ..
             } => {
                 // Type check scrutinee
                 let scrutinee_ty = self.infer_expression(scrutinee)?;
@@ -693,11 +1490,7 @@ impl TypeChecker {
                     // Type check guard if present
                     if let Some(guard) = &arm.guard {
                         let guard_ty = self.infer_expression(guard)?;
-                        self.unifier.unify(
-                            &guard_ty,
-                            &Type::Bool,
-                            &ast_helpers::get_span(guard),
-                        )?;
+                        self.unifier.unify(&guard_ty, &Type::Bool, guard.span())?;
                     }
 
                     // Type check body
@@ -707,17 +1500,18 @@ impl TypeChecker {
                     self.env.exit_scope()?;
                 }
 
+                let arm_patterns: Vec<(&Pattern, bool)> = arms
+                    .iter()
+                    .map(|arm| (&arm.pattern, arm.guard.is_some()))
+                    .collect();
+                let is_exhaustive =
+                    self.check_match_exhaustiveness(&arm_patterns, &scrutinee_ty, span)?;
+
                 // All arms must have the same type
                 if let Some(first_result) = arm_results.first() {
-                    let is_exhaustive = arms
-                        .iter()
-                        .any(|arm| arm.guard.is_none() && !arm.pattern.is_refutable());
                     for result in &arm_results[1..] {
-                        self.unifier.unify(
-                            &first_result.ty,
-                            &result.ty,
-                            &ast_helpers::get_span(expr),
-                        )?;
+                        self.unifier
+                            .unify(&first_result.ty, &result.ty, expr.span())?;
                     }
                     Ok(FlowInfo {
                         ty: self.unifier.apply(&first_result.ty),
@@ -733,15 +1527,43 @@ impl TypeChecker {
                 }
             }
 
-            Expr::Index { array, index, .. } => {
+            Expr::Index {
+                array, index, span, ..
+            } => {
                 // Infer array and index types
                 let array_ty = self.infer_expression(array)?;
-                let _index_ty = self.infer_expression(index)?;
+                let index_ty = self.infer_expression(index)?;
 
                 // Return the element type: if the array is a known List(T) return T,
                 // otherwise produce a fresh type variable (array type is still unknown).
-                match array_ty {
+                match self.unifier.apply(&array_ty) {
                     Type::List(elem_ty) => Ok(FlowInfo::new(*elem_ty)),
+                    Type::String => {
+                        self.unifier.unify(&index_ty, &Type::Int, span)?;
+                        // Negative indices count from the end, as in Python
+                        if let (Expr::Literal(Literal::String(text, _)), Some(position)) =
+                            (array.as_ref(), constant_index(index))
+                        {
+                            let len = text.chars().count();
+                            if position >= len as i64 || position < -(len as i64) {
+                                self.warnings.push(TypeError::string_index_out_of_range(
+                                    position,
+                                    len,
+                                    span.clone(),
+                                ));
+                            }
+                        }
+                        Ok(FlowInfo::new(Type::Char))
+                    }
+                    Type::Tuple(elem_tys) => match index.as_ref() {
+                        Expr::Literal(Literal::Integer(position, _)) => Ok(FlowInfo::new(
+                            tuple_element(elem_tys, *position as usize, span)?,
+                        )),
+                        _ => Err(TypeError::cannot_infer(
+                            "tuples can only be indexed by an integer literal".to_string(),
+                            span.clone(),
+                        )),
+                    },
                     _ => {
                         let var = self.ctx.fresh_var();
                         Ok(FlowInfo::new(Type::Var(crate::types::TypeVarRef::new(
@@ -751,20 +1573,86 @@ impl TypeChecker {
                 }
             }
 
-            Expr::Assign { target, value, .. } => {
-                let _target_ty = self.infer_expression(target)?;
+            Expr::Assign {
+                target,
+                op,
+                value,
+                span,
+                ..
+            } => {
+                // Assignment is evaluated for its effect; the expression itself is Unit.
+                let target_ty = self.infer_expression(target)?;
                 let value_ty = self.infer_expression(value)?;
-                Ok(FlowInfo::new(value_ty))
+                // A compound assignment stores `target op value`, which has
+                // to fit the target's type
+                let stored_ty = match op {
+                    Some(op) => self.binary_type(op, &target_ty, &value_ty, span)?,
+                    None => value_ty,
+                };
+                self.unifier.unify(&target_ty, &stored_ty, span)?;
+                Ok(FlowInfo::new(Type::Unit))
             }
 
-            Expr::MemberAccess { object, .. } => {
-                let _obj_ty = self.infer_expression(object)?;
+            Expr::MemberAccess {
+                object,
+                member,
+                span,
+                ..
+            } => {
+                let obj_ty = self.infer_expression(object)?;
+                if let (Type::Tuple(elem_tys), Ok(position)) =
+                    (self.unifier.apply(&obj_ty), member.parse::<usize>())
+                {
+                    return Ok(FlowInfo::new(tuple_element(elem_tys, position, span)?));
+                }
+                if let Type::User(class) = self.unifier.apply(&obj_ty) {
+                    if let Some(scheme) = self.classes.get(&class).and_then(|m| m.get(member)) {
+                        let scheme = scheme.clone();
+                        return Ok(FlowInfo::new(scheme.instantiate(&mut self.ctx)));
+                    }
+                }
                 // Return a fresh type variable since we don't know the member type
                 let var = self.ctx.fresh_var();
                 Ok(FlowInfo::new(Type::Var(crate::types::TypeVarRef::new(
                     var.id(),
                 ))))
             }
+
+            Expr::Range { start, end, .. } => {
+                // A range is iterated like a list of its integers
+                for bound in [start, end] {
+                    let bound_ty = self.infer_expression(bound)?;
+                    self.unifier.unify(&bound_ty, &Type::Int, bound.span())?;
+                }
+                Ok(FlowInfo::new(Type::List(Box::new(Type::Int))))
+            }
+
+            Expr::Slice {
+                array,
+                start,
+                end,
+                span,
+                ..
+            } => {
+                for bound in [start, end] {
+                    let bound_ty = self.infer_expression(bound)?;
+                    self.unifier.unify(&bound_ty, &Type::Int, bound.span())?;
+                }
+
+                // A slice has the type of what it was taken from
+                let array_ty = self.infer_expression(array)?;
+                match self.unifier.apply(&array_ty) {
+                    ty @ (Type::List(_) | Type::String) => Ok(FlowInfo::new(ty)),
+                    _ => {
+                        let var = self.ctx.fresh_var();
+                        let list_ty = Type::List(Box::new(Type::Var(
+                            crate::types::TypeVarRef::new(var.id()),
+                        )));
+                        self.unifier.unify(&array_ty, &list_ty, span)?;
+                        Ok(FlowInfo::new(list_ty))
+                    }
+                }
+            }
         }
     }
 
@@ -781,6 +1669,30 @@ impl TypeChecker {
         Ok(result)
     }
 
+    /// The type of applying an arithmetic or other binary operator
+    fn binary_type(
+        &mut self,
+        op: &BinaryOp,
+        left_ty: &Type,
+        right_ty: &Type,
+        span: &Span,
+    ) -> Result<Type> {
+        // Arithmetic mixing an Int with a Float promotes to Float
+        if op.is_arithmetic() {
+            let operands = (self.unifier.apply(left_ty), self.unifier.apply(right_ty));
+            if matches!(
+                operands,
+                (Type::Int, Type::Float) | (Type::Float, Type::Int)
+            ) {
+                return Ok(Type::Float);
+            }
+        }
+
+        // Numeric operators return the same type as operands
+        self.unifier.unify(left_ty, right_ty, span)?;
+        Ok(left_ty.clone())
+    }
+
     fn return_type_mismatch_error(
         &self,
         function: &str,
@@ -869,7 +1781,7 @@ impl TypeChecker {
                         return Err(TypeError::arity_mismatch(
                             elem_types.len(),
                             patterns.len(),
-                            ast_helpers::get_span_pattern(pattern),
+                            pattern.span().clone(),
                         ));
                     }
 
@@ -882,7 +1794,7 @@ impl TypeChecker {
                     Err(TypeError::type_mismatch(
                         Type::Tuple(vec![Type::var(0)]),
                         expected_ty.clone(),
-                        ast_helpers::get_span_pattern(pattern),
+                        pattern.span().clone(),
                     ))
                 }
             }
@@ -897,7 +1809,7 @@ impl TypeChecker {
                     Err(TypeError::type_mismatch(
                         Type::List(Box::new(Type::var(0))),
                         expected_ty.clone(),
-                        ast_helpers::get_span_pattern(pattern),
+                        pattern.span().clone(),
                     ))
                 }
             }
@@ -911,7 +1823,7 @@ impl TypeChecker {
                     Err(TypeError::type_mismatch(
                         Type::List(Box::new(Type::var(0))),
                         expected_ty.clone(),
-                        ast_helpers::get_span_pattern(pattern),
+                        pattern.span().clone(),
                     ))
                 }
             }
@@ -936,11 +1848,36 @@ impl TypeChecker {
                 Ok(())
             }
 
-            Pattern::Constructor { name: _, args, .. } => {
-                // Type check constructor arguments
-                for arg in args {
-                    let var = self.ctx.fresh_var();
-                    let arg_ty = Type::Var(crate::types::TypeVarRef::new(var.id()));
+            Pattern::Constructor { name, args, span } => {
+                // Known constructors give the subject's type and their fields'
+                let constructor_ty = self
+                    .env
+                    .lookup(name)
+                    .map(|scheme| scheme.instantiate(&mut self.ctx));
+                let (arg_types, result_ty) = match constructor_ty {
+                    Some(Type::Function(params, ret)) => (params, Some(*ret)),
+                    Some(ty) => (Vec::new(), Some(ty)),
+                    None => (Vec::new(), None),
+                };
+                if let Some(result_ty) = result_ty {
+                    if arg_types.len() != args.len() {
+                        return Err(TypeError::arity_mismatch(
+                            arg_types.len(),
+                            args.len(),
+                            span.clone(),
+                        ));
+                    }
+                    self.unifier.unify(&result_ty, expected_ty, span)?;
+                }
+
+                for (i, arg) in args.iter().enumerate() {
+                    let arg_ty = match arg_types.get(i) {
+                        Some(ty) => self.unifier.apply(ty),
+                        None => {
+                            let var = self.ctx.fresh_var();
+                            Type::Var(crate::types::TypeVarRef::new(var.id()))
+                        }
+                    };
                     self.check_pattern(arg, &arg_ty)?;
                 }
                 Ok(())
@@ -948,48 +1885,140 @@ impl TypeChecker {
         }
     }
 
+    /// Check that match arms (pattern, has guard) cover every case of a finite
+    /// scrutinee domain: booleans, `null`, and the known constructor families.
+    /// Matches over open domains such as Int are never exhaustive without a
+    /// catch-all. Returns whether the arms are exhaustive; a match that isn't
+    /// may fall through, and is a warning unless checking strictly.
+    fn check_match_exhaustiveness(
+        &mut self,
+        arms: &[(&Pattern, bool)],
+        scrutinee_ty: &Type,
+        span: &Span,
+    ) -> Result<bool> {
+        let unguarded: Vec<&Pattern> = arms
+            .iter()
+            .filter(|(_, guarded)| !guarded)
+            .map(|(pattern, _)| *pattern)
+            .collect();
+        if unguarded.iter().any(|pattern| !pattern.is_refutable()) {
+            return Ok(true);
+        }
+
+        let mut covered = Vec::new();
+        for pattern in &unguarded {
+            collect_covered_cases(pattern, &mut covered);
+        }
+
+        let domain: &[&str] = match self.unifier.apply(scrutinee_ty) {
+            Type::Bool => &["true", "false"],
+            Type::Null => &["null"],
+            Type::Option(_) => KNOWN_ENUMS[0],
+            Type::Result(_, _) => KNOWN_ENUMS[1],
+            _ => {
+                let mut mentioned = Vec::new();
+                for (pattern, _) in arms {
+                    collect_constructor_names(pattern, &mut mentioned);
+                }
+                match KNOWN_ENUMS
+                    .iter()
+                    .find(|family| mentioned.iter().any(|name| family.contains(&name.as_str())))
+                {
+                    Some(family) => family,
+                    None => &["_"],
+                }
+            }
+        };
+
+        let missing: Vec<String> = domain
+            .iter()
+            .filter(|case| !covered.iter().any(|c| c == *case))
+            .map(|case| case.to_string())
+            .collect();
+        if missing.is_empty() {
+            return Ok(true);
+        }
+
+        let error = TypeError::non_exhaustive_match(missing, span.clone());
+        if self.strict_match {
+            return Err(error);
+        }
+        self.warnings.push(error);
+        Ok(false)
+    }
+
     /// Resolve an AST type annotation to a type-checker type
-    fn resolve_type_annotation(&mut self, ann: &nevermind_ast::TypeAnnotation) -> Type {
+    fn resolve_type_annotation(&mut self, ann: &nevermind_ast::TypeAnnotation) -> Result<Type> {
+        self.resolve_annotation_with(ann, &HashMap::new(), &mut Vec::new())
+    }
+
+    /// Resolve an annotation with `bindings` for alias type parameters in scope.
+    /// `expanding` holds the aliases currently being expanded, for cycle detection.
+    fn resolve_annotation_with(
+        &mut self,
+        ann: &nevermind_ast::TypeAnnotation,
+        bindings: &HashMap<String, Type>,
+        expanding: &mut Vec<String>,
+    ) -> Result<Type> {
         use nevermind_ast::types::{PrimitiveType as AstPrim, Type as AstType};
-        match &ann.kind {
+        let ty = match &ann.kind {
             AstType::Primitive(prim) => match prim {
                 AstPrim::Int | AstPrim::Int32 | AstPrim::Int64 => Type::Int,
                 AstPrim::UInt | AstPrim::UInt32 | AstPrim::UInt64 => Type::Int,
                 AstPrim::Float | AstPrim::Float32 | AstPrim::Float64 => Type::Float,
                 AstPrim::Bool => Type::Bool,
                 AstPrim::String => Type::String,
-                AstPrim::Char => Type::String,
+                AstPrim::Char => Type::Char,
                 AstPrim::Unit => Type::Unit,
                 AstPrim::Null => Type::Null,
             },
             AstType::Identifier(name) => match name.as_str() {
+                _ if bindings.contains_key(name) => bindings[name].clone(),
+                _ if self.type_aliases.contains_key(name) => {
+                    self.expand_type_alias(name, Vec::new(), &ann.span, expanding)?
+                }
                 "Int" => Type::Int,
                 "Float" => Type::Float,
                 "Bool" => Type::Bool,
                 "String" => Type::String,
+                "Char" => Type::Char,
                 "Unit" | "Void" => Type::Unit,
                 _ => Type::User(name.clone()),
             },
+            AstType::Generic { name, params } if self.type_aliases.contains_key(name) => {
+                let args = params
+                    .iter()
+                    .map(|p| self.resolve_annotation_with(p, bindings, expanding))
+                    .collect::<Result<Vec<_>>>()?;
+                self.expand_type_alias(name, args, &ann.span, expanding)?
+            }
             AstType::List(elem) => {
-                let elem_ty = self.resolve_type_annotation(elem);
+                let elem_ty = self.resolve_annotation_with(elem, bindings, expanding)?;
                 Type::List(Box::new(elem_ty))
             }
             AstType::Tuple(elems) => {
                 let elem_tys: Vec<Type> = elems
                     .iter()
-                    .map(|e| self.resolve_type_annotation(e))
-                    .collect();
+                    .map(|e| self.resolve_annotation_with(e, bindings, expanding))
+                    .collect::<Result<_>>()?;
                 Type::Tuple(elem_tys)
             }
+            AstType::Option(inner) => {
+                Type::option(self.resolve_annotation_with(inner, bindings, expanding)?)
+            }
+            AstType::Result { ok, error } => Type::result(
+                self.resolve_annotation_with(ok, bindings, expanding)?,
+                self.resolve_annotation_with(error, bindings, expanding)?,
+            ),
             AstType::Function {
                 params,
                 return_type,
             } => {
                 let param_tys: Vec<Type> = params
                     .iter()
-                    .map(|p| self.resolve_type_annotation(p))
-                    .collect();
-                let ret_ty = self.resolve_type_annotation(return_type);
+                    .map(|p| self.resolve_annotation_with(p, bindings, expanding))
+                    .collect::<Result<_>>()?;
+                let ret_ty = self.resolve_annotation_with(return_type, bindings, expanding)?;
                 Type::Function(param_tys, Box::new(ret_ty))
             }
             _ => {
@@ -997,7 +2026,108 @@ impl TypeChecker {
                 let var = self.ctx.fresh_var();
                 Type::Var(crate::types::TypeVarRef::new(var.id()))
             }
+        };
+        Ok(ty)
+    }
+
+    /// Expand a use of the type alias `name` applied to `args`
+    fn expand_type_alias(
+        &mut self,
+        name: &str,
+        args: Vec<Type>,
+        span: &Span,
+        expanding: &mut Vec<String>,
+    ) -> Result<Type> {
+        if expanding.iter().any(|alias| alias == name) {
+            return Err(TypeError::recursive_type_alias(
+                name.to_string(),
+                span.clone(),
+            ));
+        }
+
+        let alias = self.type_aliases[name].clone();
+        let required = alias.params.iter().filter(|p| p.default.is_none()).count();
+        if args.len() < required || args.len() > alias.params.len() {
+            let expected = if args.len() < required {
+                required
+            } else {
+                alias.params.len()
+            };
+            return Err(TypeError::type_alias_arity_mismatch(
+                name.to_string(),
+                expected,
+                args.len(),
+                span.clone(),
+            ));
         }
+
+        expanding.push(name.to_string());
+        let expanded = self
+            .bind_alias_args(&alias.params, args, expanding)
+            .and_then(|bindings| {
+                self.resolve_annotation_with(&alias.definition, &bindings, expanding)
+            });
+        expanding.pop();
+        expanded
+    }
+
+    /// Bind alias parameters to `args`, filling omitted trailing arguments
+    /// from their defaults, which may mention the parameters before them
+    fn bind_alias_args(
+        &mut self,
+        params: &[nevermind_ast::TypeParam],
+        args: Vec<Type>,
+        expanding: &mut Vec<String>,
+    ) -> Result<HashMap<String, Type>> {
+        let mut bindings = HashMap::new();
+        let mut args = args.into_iter();
+        for param in params {
+            let ty = match (args.next(), &param.default) {
+                (Some(ty), _) => ty,
+                (None, Some(default)) => {
+                    self.resolve_annotation_with(default, &bindings, expanding)?
+                }
+                (None, None) => unreachable!("alias arity is checked before binding"),
+            };
+            bindings.insert(param.name.clone(), ty);
+        }
+        Ok(bindings)
+    }
+}
+
+/// Collect the finite-domain cases a pattern fully covers.
+fn collect_covered_cases(pattern: &Pattern, covered: &mut Vec<String>) {
+    match pattern {
+        Pattern::Literal {
+            value: Literal::Boolean(value, _),
+            ..
+        } => covered.push(value.to_string()),
+        Pattern::Literal {
+            value: Literal::Null(_),
+            ..
+        } => covered.push("null".to_string()),
+        Pattern::Constructor { name, args, .. } if args.iter().all(|a| !a.is_refutable()) => {
+            covered.push(name.clone())
+        }
+        Pattern::Or { patterns, .. } => {
+            for pattern in patterns {
+                collect_covered_cases(pattern, covered);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collect the constructor names a pattern mentions, covered or not.
+fn collect_constructor_names(pattern: &Pattern, names: &mut Vec<String>) {
+    match pattern {
+        Pattern::Constructor { name, .. } => names.push(name.clone()),
+        Pattern::Or { patterns, .. } => {
+            for pattern in patterns {
+                collect_constructor_names(pattern, names);
+            }
+        }
+        _ => {}
     }
 }
 
@@ -1007,57 +2137,35 @@ impl Default for TypeChecker {
     }
 }
 
-// Helper functions for getting spans from AST nodes
-mod ast_helpers {
-    use super::*;
-    use nevermind_common::Span;
-
-    pub fn get_span(expr: &Expr) -> Span {
-        match expr {
-            Expr::Literal(lit) => get_span_literal(lit),
-            Expr::Variable { span, .. } => span.clone(),
-            Expr::Binary { span, .. } => span.clone(),
-            Expr::Comparison { span, .. } => span.clone(),
-            Expr::Logical { span, .. } => span.clone(),
-            Expr::Unary { span, .. } => span.clone(),
-            Expr::Call { span, .. } => span.clone(),
-            Expr::Pipeline { span, .. } => span.clone(),
-            Expr::Lambda { span, .. } => span.clone(),
-            Expr::If { span, .. } => span.clone(),
-            Expr::Block { span, .. } => span.clone(),
-            Expr::List { span, .. } => span.clone(),
-            Expr::Map { span, .. } => span.clone(),
-            Expr::Match { span, .. } => span.clone(),
-            Expr::Index { span, .. } => span.clone(),
-            Expr::Assign { span, .. } => span.clone(),
-            Expr::MemberAccess { span, .. } => span.clone(),
-        }
-    }
+/// Environment key for the overload of `name` taking `arity` arguments.
+/// The `/` keeps it from colliding with any user-visible identifier.
+fn overload_key(name: &str, arity: usize) -> String {
+    format!("{}/{}", name, arity)
+}
 
-    pub fn get_span_pattern(pat: &Pattern) -> Span {
-        match pat {
-            Pattern::Literal { span, .. } => span.clone(),
-            Pattern::Variable { span, .. } => span.clone(),
-            Pattern::Wildcard { span, .. } => span.clone(),
-            Pattern::Tuple { span, .. } => span.clone(),
-            Pattern::List { span, .. } => span.clone(),
-            Pattern::ListCons { span, .. } => span.clone(),
-            Pattern::Struct { span, .. } => span.clone(),
-            Pattern::Or { span, .. } => span.clone(),
-            Pattern::Range { span, .. } => span.clone(),
-            Pattern::Constructor { span, .. } => span.clone(),
-        }
+/// The type of element `position` of a tuple, erroring when it is out of range
+fn tuple_element(mut elem_tys: Vec<Type>, position: usize, span: &Span) -> Result<Type> {
+    if position < elem_tys.len() {
+        Ok(elem_tys.swap_remove(position))
+    } else {
+        Err(TypeError::tuple_index_out_of_range(
+            position,
+            elem_tys.len(),
+            span.clone(),
+        ))
     }
+}
 
-    fn get_span_literal(lit: &Literal) -> Span {
-        match lit {
-            Literal::Integer(_, span)
-            | Literal::Float(_, span)
-            | Literal::String(_, span)
-            | Literal::Char(_, span)
-            | Literal::Boolean(_, span)
-            | Literal::Null(span) => span.clone(),
-        }
+/// The value of a constant integer index, which may be written negated
+fn constant_index(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Literal(Literal::Integer(value, _)) => Some(*value),
+        Expr::Unary {
+            op: UnaryOp::Neg,
+            expr,
+            ..
+        } => constant_index(expr).map(|value| -value),
+        _ => None,
     }
 }
 
@@ -1132,6 +2240,7 @@ mod tests {
     fn function_with_body(name: &str, return_type: Option<TypeAnnotation>, body: Expr) -> Stmt {
         Stmt::Function {
             id: 102,
+            is_async: false,
             name: name.to_string(),
             params: vec![],
             return_type,
@@ -1151,6 +2260,50 @@ mod tests {
         assert_eq!(checker.infer_expression(&bool_expr).unwrap(), Type::Bool);
     }
 
+    #[test]
+    fn test_unit_literal_has_unit_type() {
+        let mut checker = TypeChecker::new();
+
+        let unit_expr = Expr::Unit {
+            id: 1,
+            span: Span::dummy(),
+        };
+        assert_eq!(checker.infer_expression(&unit_expr).unwrap(), Type::Unit);
+    }
+
+    fn tuple_member(member: &str) -> Expr {
+        let tuple = Expr::Tuple {
+            id: 1,
+            elements: vec![
+                Expr::Literal(Literal::Integer(1, Span::dummy())),
+                Expr::Literal(Literal::String("a".to_string(), Span::dummy())),
+            ],
+            span: Span::dummy(),
+        };
+        Expr::MemberAccess {
+            id: 2,
+            object: Box::new(tuple),
+            member: member.to_string(),
+            span: Span::dummy(),
+        }
+    }
+
+    #[test]
+    fn test_tuple_member_has_element_type() {
+        let mut checker = TypeChecker::new();
+
+        assert_eq!(
+            checker.infer_expression(&tuple_member("1")).unwrap(),
+            Type::String
+        );
+
+        let err = checker.infer_expression(&tuple_member("2")).unwrap_err();
+        assert_eq!(
+            err.kind,
+            crate::error::TypeErrorKind::TupleIndexOutOfRange { index: 2, len: 2 }
+        );
+    }
+
     #[test]
     fn test_variable_lookup() {
         let mut checker = TypeChecker::new();
@@ -19,6 +19,10 @@ pub struct TypeEnvironment {
 struct Scope {
     /// Mapping from names to type schemes
     bindings: HashMap<String, TypeScheme>,
+
+    /// How many bindings mention each unquantified type variable, kept up to
+    /// date so generalization doesn't rescan every binding
+    free_vars: HashMap<usize, usize>,
 }
 
 impl TypeEnvironment {
@@ -31,7 +35,7 @@ impl TypeEnvironment {
 
     /// Create a new environment with predefined bindings
     pub fn with_predefined() -> Self {
-        use crate::ty::TypeVar;
+        use crate::ty::{Constraint, TypeVar};
 
         let mut env = Self::new();
 
@@ -58,7 +62,7 @@ impl TypeEnvironment {
             TypeScheme::new(vec![println_var.clone()], println_type),
         );
 
-        // len: forall a. (a) -> Int
+        // len: forall a: String | List | Map | tuple. (a) -> Int
         let len_var = TypeVar::new(9002);
         let len_type = Type::Function(
             vec![Type::Var(crate::types::TypeVarRef::new(len_var.id()))],
@@ -66,19 +70,39 @@ impl TypeEnvironment {
         );
         let _ = env.insert(
             "len".to_string(),
-            TypeScheme::new(vec![len_var.clone()], len_type),
+            TypeScheme::new(vec![len_var.clone()], len_type)
+                .with_constraint(Constraint::Sized, vec![len_var]),
         );
 
         // input: (String) -> String
         let input_type = Type::Function(vec![Type::String], Box::new(Type::String));
         let _ = env.insert("input".to_string(), TypeScheme::monomorphic(input_type));
 
-        // range: (Int, Int) -> List[Int]
-        let range_type = Type::Function(
-            vec![Type::Int, Type::Int],
-            Box::new(Type::List(Box::new(Type::Int))),
-        );
+        // assert: (Bool, String) -> Unit
+        let assert_type = Type::Function(vec![Type::Bool, Type::String], Box::new(Type::Unit));
+        let _ = env.insert("assert".to_string(), TypeScheme::monomorphic(assert_type));
+
+        // ord: (Char) -> Int and chr: (Int) -> Char
+        let ord_type = Type::Function(vec![Type::Char], Box::new(Type::Int));
+        let _ = env.insert("ord".to_string(), TypeScheme::monomorphic(ord_type));
+        let chr_type = Type::Function(vec![Type::Int], Box::new(Type::Char));
+        let _ = env.insert("chr".to_string(), TypeScheme::monomorphic(chr_type));
+
+        // range: (Int) -> List[Int], (Int, Int) -> List[Int] and
+        // (Int, Int, Int) -> List[Int], bound as overloads for calls, with
+        // the one-argument form also bound under the bare name as a value
+        let range_type = Type::Function(vec![Type::Int], Box::new(Type::List(Box::new(Type::Int))));
         let _ = env.insert("range".to_string(), TypeScheme::monomorphic(range_type));
+        for arity in 1..=3 {
+            let range_type = Type::Function(
+                vec![Type::Int; arity],
+                Box::new(Type::List(Box::new(Type::Int))),
+            );
+            let _ = env.insert(
+                format!("range/{}", arity),
+                TypeScheme::monomorphic(range_type),
+            );
+        }
 
         // str: forall a. (a) -> String
         let str_var = TypeVar::new(9003);
@@ -102,6 +126,144 @@ impl TypeEnvironment {
             TypeScheme::new(vec![int_var.clone()], int_type),
         );
 
+        // float, bool and type: forall a. (a) -> Float, Bool and String
+        for (offset, (name, result)) in [
+            ("float", Type::Float),
+            ("bool", Type::Bool),
+            ("type", Type::String),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let arg_var = TypeVar::new(9014 + offset);
+            let convert_type = Type::Function(
+                vec![Type::Var(crate::types::TypeVarRef::new(arg_var.id()))],
+                Box::new(result),
+            );
+            let _ = env.insert(
+                name.to_string(),
+                TypeScheme::new(vec![arg_var], convert_type),
+            );
+        }
+
+        // abs: forall a: Int | Float. (a) -> a
+        let abs_var = TypeVar::new(9017);
+        let a = Type::Var(crate::types::TypeVarRef::new(abs_var.id()));
+        let abs_type = Type::Function(vec![a.clone()], Box::new(a));
+        let _ = env.insert(
+            "abs".to_string(),
+            TypeScheme::new(vec![abs_var.clone()], abs_type)
+                .with_constraint(Constraint::Numeric, vec![abs_var]),
+        );
+
+        // sum, product, min, max: forall a. (List[a]) -> a
+        //
+        // There are no numeric constraints on type variables, so these accept
+        // any element type and return it; `sum([1, 2])` is Int and
+        // `sum([1.0])` is Float.
+        for (offset, name) in ["sum", "product", "min", "max"].into_iter().enumerate() {
+            let elem_var = TypeVar::new(9005 + offset);
+            let elem = Type::Var(crate::types::TypeVarRef::new(elem_var.id()));
+            let reduce_type =
+                Type::Function(vec![Type::List(Box::new(elem.clone()))], Box::new(elem));
+            let scheme = TypeScheme::new(vec![elem_var], reduce_type);
+            if matches!(name, "min" | "max") {
+                let _ = env.insert(format!("{}/1", name), scheme.clone());
+            }
+            let _ = env.insert(name.to_string(), scheme);
+        }
+
+        // min/2, max/2: forall a. (a, a) -> a, the smaller or larger of two
+        // values
+        for (offset, name) in ["min", "max"].into_iter().enumerate() {
+            let arg_var = TypeVar::new(9024 + offset);
+            let arg = Type::Var(crate::types::TypeVarRef::new(arg_var.id()));
+            let pick_type = Type::Function(vec![arg.clone(), arg.clone()], Box::new(arg));
+            let _ = env.insert(
+                format!("{}/2", name),
+                TypeScheme::new(vec![arg_var], pick_type),
+            );
+        }
+
+        // map: forall a b. (List[a], (a) -> b) -> List[b]
+        let (map_elem, map_result) = (TypeVar::new(9009), TypeVar::new(9010));
+        let a = Type::Var(crate::types::TypeVarRef::new(map_elem.id()));
+        let b = Type::Var(crate::types::TypeVarRef::new(map_result.id()));
+        let map_type = Type::Function(
+            vec![
+                Type::List(Box::new(a.clone())),
+                Type::Function(vec![a], Box::new(b.clone())),
+            ],
+            Box::new(Type::List(Box::new(b))),
+        );
+        let _ = env.insert(
+            "map".to_string(),
+            TypeScheme::new(vec![map_elem, map_result], map_type),
+        );
+
+        // filter: forall a. (List[a], (a) -> Bool) -> List[a]
+        let filter_elem = TypeVar::new(9011);
+        let a = Type::Var(crate::types::TypeVarRef::new(filter_elem.id()));
+        let filter_type = Type::Function(
+            vec![
+                Type::List(Box::new(a.clone())),
+                Type::Function(vec![a.clone()], Box::new(Type::Bool)),
+            ],
+            Box::new(Type::List(Box::new(a))),
+        );
+        let _ = env.insert(
+            "filter".to_string(),
+            TypeScheme::new(vec![filter_elem], filter_type),
+        );
+
+        // fold: forall a b. (List[a], b, (b, a) -> b) -> b
+        let (fold_elem, fold_acc) = (TypeVar::new(9012), TypeVar::new(9013));
+        let a = Type::Var(crate::types::TypeVarRef::new(fold_elem.id()));
+        let b = Type::Var(crate::types::TypeVarRef::new(fold_acc.id()));
+        let fold_type = Type::Function(
+            vec![
+                Type::List(Box::new(a.clone())),
+                b.clone(),
+                Type::Function(vec![b.clone(), a], Box::new(b.clone())),
+            ],
+            Box::new(b),
+        );
+        let _ = env.insert(
+            "fold".to_string(),
+            TypeScheme::new(vec![fold_elem, fold_acc], fold_type),
+        );
+
+        // Some: forall a. (a) -> Option[a], None: forall a. Option[a]
+        let some_var = TypeVar::new(9018);
+        let a = Type::Var(crate::types::TypeVarRef::new(some_var.id()));
+        let some_type = Type::Function(vec![a.clone()], Box::new(Type::option(a)));
+        let _ = env.insert(
+            "Some".to_string(),
+            TypeScheme::new(vec![some_var], some_type),
+        );
+        let none_var = TypeVar::new(9019);
+        let none_type = Type::option(Type::Var(crate::types::TypeVarRef::new(none_var.id())));
+        let _ = env.insert(
+            "None".to_string(),
+            TypeScheme::new(vec![none_var], none_type),
+        );
+
+        // Ok: forall a b. (a) -> Result[a, b], Error: forall a b. (b) -> Result[a, b]
+        for (offset, name) in ["Ok", "Error"].into_iter().enumerate() {
+            let (ok_var, error_var) = (
+                TypeVar::new(9020 + 2 * offset),
+                TypeVar::new(9021 + 2 * offset),
+            );
+            let a = Type::Var(crate::types::TypeVarRef::new(ok_var.id()));
+            let b = Type::Var(crate::types::TypeVarRef::new(error_var.id()));
+            let wrapped = if name == "Ok" { a.clone() } else { b.clone() };
+            let constructor_type = Type::Function(vec![wrapped], Box::new(Type::result(a, b)));
+            let _ = env.insert(
+                name.to_string(),
+                TypeScheme::new(vec![ok_var, error_var], constructor_type),
+            );
+        }
+
         env
     }
 
@@ -136,14 +298,20 @@ impl TypeEnvironment {
             ));
         }
 
-        current_scope.bindings.insert(name, scheme);
+        current_scope.bind(name, scheme);
         Ok(())
     }
 
     /// Insert or update a binding in the current scope (allows overwriting)
     pub fn insert_or_update(&mut self, name: String, scheme: TypeScheme) {
         let current_scope = self.scopes.last_mut().unwrap();
-        current_scope.bindings.insert(name, scheme);
+        current_scope.bind(name, scheme);
+    }
+
+    /// Remove a binding from the current scope, returning its scheme
+    pub fn remove(&mut self, name: &str) -> Option<TypeScheme> {
+        let current_scope = self.scopes.last_mut().unwrap();
+        current_scope.unbind(name)
     }
 
     /// Look up a name in the environment
@@ -157,6 +325,15 @@ impl TypeEnvironment {
         None
     }
 
+    /// Whether the innermost binding of a name is in the global scope
+    pub fn is_global(&self, name: &str) -> bool {
+        self.scopes
+            .iter()
+            .rev()
+            .find(|scope| scope.bindings.contains_key(name))
+            .is_some_and(|scope| std::ptr::eq(scope, &self.scopes[0]))
+    }
+
     /// Check if a name is defined in the current scope
     pub fn in_current_scope(&self, name: &str) -> bool {
         if let Some(scope) = self.scopes.last() {
@@ -166,22 +343,29 @@ impl TypeEnvironment {
         }
     }
 
+    /// Names bound in the current scope
+    pub fn current_names(&self) -> impl Iterator<Item = &str> {
+        self.scopes
+            .last()
+            .into_iter()
+            .flat_map(|scope| scope.bindings.keys().map(String::as_str))
+    }
+
     /// Get the depth of the environment
     pub fn depth(&self) -> usize {
         self.scopes.len()
     }
 
     /// Get the free type variables in the environment
+    ///
+    /// Variables quantified by a binding's scheme are not free. The ids are
+    /// as written in the schemes; the checker resolves them through its
+    /// unifier before generalizing.
     pub fn free_vars(&self) -> HashSet<usize> {
-        let mut free_vars = HashSet::new();
-
-        for scope in &self.scopes {
-            for scheme in scope.bindings.values() {
-                free_vars = &free_vars | &Type::free_vars(&scheme.ty);
-            }
-        }
-
-        free_vars
+        self.scopes
+            .iter()
+            .flat_map(|scope| scope.free_vars.keys().copied())
+            .collect()
     }
 }
 
@@ -189,8 +373,43 @@ impl Scope {
     fn new() -> Self {
         Self {
             bindings: HashMap::new(),
+            free_vars: HashMap::new(),
+        }
+    }
+
+    fn bind(&mut self, name: String, scheme: TypeScheme) {
+        for var in scheme_free_vars(&scheme) {
+            *self.free_vars.entry(var).or_insert(0) += 1;
+        }
+        if let Some(old) = self.bindings.insert(name, scheme) {
+            self.forget(&old);
         }
     }
+
+    fn unbind(&mut self, name: &str) -> Option<TypeScheme> {
+        let old = self.bindings.remove(name)?;
+        self.forget(&old);
+        Some(old)
+    }
+
+    fn forget(&mut self, scheme: &TypeScheme) {
+        for var in scheme_free_vars(scheme) {
+            if let Some(count) = self.free_vars.get_mut(&var) {
+                *count -= 1;
+                if *count == 0 {
+                    self.free_vars.remove(&var);
+                }
+            }
+        }
+    }
+}
+
+fn scheme_free_vars(scheme: &TypeScheme) -> HashSet<usize> {
+    let mut vars = Type::free_vars(&scheme.ty);
+    for quantified in &scheme.vars {
+        vars.remove(&quantified.id());
+    }
+    vars
 }
 
 impl Default for TypeEnvironment {
@@ -284,4 +503,29 @@ mod tests {
         assert!(free_vars.contains(&0));
         assert!(free_vars.contains(&1));
     }
+
+    #[test]
+    fn test_free_vars_track_removal_and_quantification() {
+        let mut env = TypeEnvironment::new();
+
+        env.insert("x".to_string(), TypeScheme::monomorphic(Type::var(0)))
+            .unwrap();
+        env.insert(
+            "id".to_string(),
+            TypeScheme::new(
+                vec![crate::ty::TypeVar::new(1)],
+                Type::function(vec![Type::var(1)], Type::var(1)),
+            ),
+        )
+        .unwrap();
+        assert_eq!(env.free_vars(), HashSet::from([0]));
+
+        env.insert_or_update("x".to_string(), TypeScheme::monomorphic(Type::Int));
+        assert!(env.free_vars().is_empty());
+
+        env.insert("y".to_string(), TypeScheme::monomorphic(Type::var(2)))
+            .unwrap();
+        env.remove("y");
+        assert!(env.free_vars().is_empty());
+    }
 }
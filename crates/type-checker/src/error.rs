@@ -1,7 +1,7 @@
 //! Error types for type checking
 
 use crate::types::Type;
-use nevermind_common::Span;
+use nevermind_common::{Diagnostic, Severity, Span};
 use std::fmt;
 use thiserror::Error;
 
@@ -47,6 +47,72 @@ pub enum TypeErrorKind {
 
     /// Occurs check failed (infinite type)
     OccursCheckFailed(usize),
+
+    /// Type alias whose expansion refers back to itself
+    RecursiveTypeAlias(String),
+
+    /// Match over a finite domain that leaves some cases uncovered
+    NonExhaustiveMatch { missing: Vec<String> },
+
+    /// Call to an overloaded function that matches none of its arities
+    NoMatchingOverload { name: String, arity: usize },
+
+    /// Pipeline stage (1-based position) whose type cannot be called
+    PipelineStageNotCallable { stage: usize, found: Type },
+
+    /// Type alias applied to the wrong number of type arguments
+    TypeAliasArityMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+
+    /// Constant tuple index past the last element
+    TupleIndexOutOfRange { index: usize, len: usize },
+
+    /// Constant index outside a string literal, counting negative indices
+    /// from the end
+    StringIndexOutOfRange { index: i64, len: usize },
+
+    /// Non-numeric argument to a function that only takes Int or Float
+    NotNumeric { function: String, found: Type },
+
+    /// Argument without a length to a function that takes a String, List,
+    /// Map or tuple
+    NotSized { function: String, found: Type },
+
+    /// Re-check of a top-level statement past the end of the program
+    NoSuchStatement { index: usize, len: usize },
+}
+
+impl TypeErrorKind {
+    /// The code `nevermind explain` describes this kind of error under
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            TypeErrorKind::UndefinedVariable(_) => Some("E0001"),
+            TypeErrorKind::DuplicateDefinition(_) => Some("E0003"),
+            TypeErrorKind::ArityMismatch { .. } => Some("E0007"),
+            TypeErrorKind::NoMatchingOverload { .. } => Some("E0009"),
+            TypeErrorKind::TypeMismatch { .. } => Some("E0011"),
+            TypeErrorKind::ReturnTypeMismatch { .. } => Some("E0012"),
+            TypeErrorKind::MissingReturnValue { .. } | TypeErrorKind::MissingReturn { .. } => {
+                Some("E0013")
+            }
+            TypeErrorKind::NotAFunction(_) | TypeErrorKind::PipelineStageNotCallable { .. } => {
+                Some("E0014")
+            }
+            TypeErrorKind::CannotInfer(_) => Some("E0015"),
+            TypeErrorKind::RecursiveType | TypeErrorKind::OccursCheckFailed(_) => Some("E0016"),
+            TypeErrorKind::RecursiveTypeAlias(_) => Some("E0017"),
+            TypeErrorKind::NonExhaustiveMatch { .. } => Some("E0018"),
+            TypeErrorKind::TypeAliasArityMismatch { .. } => Some("E0019"),
+            TypeErrorKind::TupleIndexOutOfRange { .. }
+            | TypeErrorKind::StringIndexOutOfRange { .. } => Some("E0020"),
+            TypeErrorKind::NotNumeric { .. } => Some("E0021"),
+            TypeErrorKind::NotSized { .. } => Some("E0024"),
+            TypeErrorKind::InvalidScope | TypeErrorKind::NoSuchStatement { .. } => None,
+        }
+    }
 }
 
 impl fmt::Display for TypeErrorKind {
@@ -114,6 +180,61 @@ impl fmt::Display for TypeErrorKind {
             TypeErrorKind::OccursCheckFailed(id) => {
                 write!(f, "infinite type: t{}", id)
             }
+            TypeErrorKind::NonExhaustiveMatch { missing } => {
+                write!(f, "non-exhaustive match: missing {}", missing.join(", "))
+            }
+            TypeErrorKind::NoMatchingOverload { name, arity } => {
+                write!(f, "no overload of '{}' takes {} argument(s)", name, arity)
+            }
+            TypeErrorKind::PipelineStageNotCallable { stage, found } => write!(
+                f,
+                "pipeline stage {} is not callable: found {}",
+                stage,
+                found.display_name()
+            ),
+            TypeErrorKind::RecursiveTypeAlias(name) => {
+                write!(f, "type alias '{}' refers to itself", name)
+            }
+            TypeErrorKind::TypeAliasArityMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "type alias '{}' takes {} type argument{} but {} {} supplied",
+                name,
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found,
+                if *found == 1 { "was" } else { "were" }
+            ),
+            TypeErrorKind::TupleIndexOutOfRange { index, len } => {
+                write!(f, "no element {} in a tuple of length {}", index, len)
+            }
+            TypeErrorKind::StringIndexOutOfRange { index, len } => {
+                write!(
+                    f,
+                    "index {} is out of range for a string of length {}",
+                    index, len
+                )
+            }
+            TypeErrorKind::NotNumeric { function, found } => write!(
+                f,
+                "'{}' expects Int or Float, found {}",
+                function,
+                found.display_name()
+            ),
+            TypeErrorKind::NotSized { function, found } => write!(
+                f,
+                "'{}' expects a String, List, Map or tuple, found {}",
+                function,
+                found.display_name()
+            ),
+            TypeErrorKind::NoSuchStatement { index, len } => write!(
+                f,
+                "no top-level statement at index {} in a program of {}",
+                index, len
+            ),
         }
     }
 }
@@ -282,87 +403,161 @@ impl TypeError {
         )
     }
 
-    /// Format the error for display
-    pub fn display(&self, source: Option<&str>) -> String {
-        let mut output = String::new();
-
-        // Error header
-        output.push_str(&format!("error: {}\n", self.message));
-
-        // Show location
-        if self.span.start.line > 0 {
-            let file = self
-                .span
-                .start
-                .file
-                .as_ref()
-                .and_then(|p| p.to_str())
-                .unwrap_or("<anon>");
-            output.push_str(&format!(
-                "  --> {}:{}:{}\n",
-                file, self.span.start.line, self.span.start.column
-            ));
-        }
+    /// Create a non-exhaustive match error listing the uncovered cases
+    pub fn non_exhaustive_match(missing: Vec<String>, span: Span) -> Self {
+        Self::new(
+            TypeErrorKind::NonExhaustiveMatch {
+                missing: missing.clone(),
+            },
+            format!("non-exhaustive match: missing {}", missing.join(", ")),
+            span,
+        )
+    }
 
-        // Show source code snippet if available
-        if let Some(source) = source {
-            if let Some(snippet) = self.format_source_snippet(source) {
-                output.push_str(&snippet);
-                output.push('\n');
-            }
-        }
+    /// Create an error for a call that matches no overload's arity
+    pub fn no_matching_overload(name: String, arity: usize, span: Span) -> Self {
+        Self::new(
+            TypeErrorKind::NoMatchingOverload {
+                name: name.clone(),
+                arity,
+            },
+            format!("no overload of '{}' takes {} argument(s)", name, arity),
+            span,
+        )
+    }
 
-        // Show context
-        for ctx in &self.context {
-            if let Some(span) = &ctx.span {
-                let file = span
-                    .start
-                    .file
-                    .as_ref()
-                    .and_then(|p| p.to_str())
-                    .unwrap_or("<anon>");
-                output.push_str(&format!(
-                    "  --> {}:{}:{}: note: {}\n",
-                    file, span.start.line, span.start.column, ctx.message
-                ));
-            } else {
-                output.push_str(&format!("  note: {}\n", ctx.message));
-            }
-        }
+    /// Create an error for a pipeline stage that is not a function
+    pub fn pipeline_stage_not_callable(stage: usize, found: Type, span: Span) -> Self {
+        Self::new(
+            TypeErrorKind::PipelineStageNotCallable {
+                stage,
+                found: found.clone(),
+            },
+            format!(
+                "pipeline stage {} is not callable: found {}",
+                stage,
+                found.display_name()
+            ),
+            span,
+        )
+    }
+
+    /// Create a recursive type alias error
+    pub fn recursive_type_alias(name: String, span: Span) -> Self {
+        Self::new(
+            TypeErrorKind::RecursiveTypeAlias(name.clone()),
+            format!("type alias '{}' refers to itself", name),
+            span,
+        )
+    }
+
+    /// Create a type alias arity mismatch error
+    pub fn type_alias_arity_mismatch(
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    ) -> Self {
+        Self::new(
+            TypeErrorKind::TypeAliasArityMismatch {
+                name: name.clone(),
+                expected,
+                found,
+            },
+            format!(
+                "type alias '{}' expects {} type argument{}, found {}",
+                name,
+                expected,
+                if expected == 1 { "" } else { "s" },
+                found
+            ),
+            span,
+        )
+    }
 
-        output
+    /// Create an error for a re-check of a statement the program doesn't have
+    pub fn no_such_statement(index: usize, len: usize, span: Span) -> Self {
+        Self::new(
+            TypeErrorKind::NoSuchStatement { index, len },
+            format!(
+                "no top-level statement at index {} in a program of {}",
+                index, len
+            ),
+            span,
+        )
     }
 
-    /// Format a source code snippet for this error
-    fn format_source_snippet(&self, source: &str) -> Option<String> {
-        let lines: Vec<&str> = source.lines().collect();
+    /// Create an error for a constant tuple index past the last element
+    pub fn tuple_index_out_of_range(index: usize, len: usize, span: Span) -> Self {
+        Self::new(
+            TypeErrorKind::TupleIndexOutOfRange { index, len },
+            format!("no element {} in a tuple of length {}", index, len),
+            span,
+        )
+    }
 
-        let line_num = self.span.start.line;
-        if line_num == 0 || line_num > lines.len() {
-            return None;
-        }
+    /// Create an error for a constant index outside a string literal
+    pub fn string_index_out_of_range(index: i64, len: usize, span: Span) -> Self {
+        Self::new(
+            TypeErrorKind::StringIndexOutOfRange { index, len },
+            format!(
+                "index {} is out of range for a string of length {}",
+                index, len
+            ),
+            span,
+        )
+    }
 
-        let line = lines[line_num - 1];
-        let mut output = String::new();
+    /// Create an error for a non-numeric argument to a numeric function
+    pub fn not_numeric(function: String, found: Type, span: Span) -> Self {
+        let message = format!(
+            "'{}' expects Int or Float, found {}",
+            function,
+            found.display_name()
+        );
+        Self::new(TypeErrorKind::NotNumeric { function, found }, message, span)
+    }
 
-        // Show the line
-        output.push_str(&format!("  {} |\n", line_num));
-        output.push_str(&format!("  {} | {}\n", line_num, line));
+    /// Create an error for an argument without a length to a function such
+    /// as `len`
+    pub fn not_sized(function: String, found: Type, span: Span) -> Self {
+        let message = format!(
+            "'{}' expects a String, List, Map or tuple, found {}",
+            function,
+            found.display_name()
+        );
+        Self::new(TypeErrorKind::NotSized { function, found }, message, span)
+    }
 
-        // Show the error marker
-        let col_start = self.span.start.column;
-        let col_end = if self.span.start.line == self.span.end.line {
-            self.span.end.column
-        } else {
-            col_start + 1
-        };
+    /// Convert this error into a diagnostic, with spanned context as
+    /// secondary labels
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let mut diagnostic = Diagnostic::error(self.message.clone(), self.span.clone());
+        if let Some(code) = self.kind.code() {
+            diagnostic = diagnostic.with_code(code);
+        }
 
-        output.push_str(&format!("  {} |", " ".repeat(line_num.to_string().len())));
-        output.push_str(&" ".repeat(col_start));
-        output.push_str(&"^".repeat(col_end.saturating_sub(col_start).max(1)));
-        output.push('\n');
+        for ctx in &self.context {
+            diagnostic = match &ctx.span {
+                Some(span) => diagnostic.with_secondary(span.clone(), ctx.message.clone()),
+                None => diagnostic.with_note(ctx.message.clone()),
+            };
+        }
+
+        diagnostic
+    }
 
-        Some(output)
+    /// Convert this error into a warning diagnostic, for problems reported
+    /// without failing the check
+    pub fn to_warning(&self) -> Diagnostic {
+        let mut diagnostic = self.to_diagnostic();
+        diagnostic.severity = Severity::Warning;
+        diagnostic
+    }
+
+    /// Format the error for display
+    pub fn display(&self, source: Option<&str>) -> String {
+        self.to_diagnostic().render(source)
     }
 }
 
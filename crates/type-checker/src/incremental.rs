@@ -0,0 +1,336 @@
+//! Dependency tracking for incremental re-checking of top-level statements
+//!
+//! Each checked top-level statement records the names it defines and the
+//! names it refers to. When one statement changes, only it and the statements
+//! that (transitively) refer to its definitions need to be checked again.
+
+use crate::types::Type;
+use nevermind_ast::stmt::{namespace_binding, ClassMember};
+use nevermind_ast::types::Type as AstType;
+use nevermind_ast::{Expr, Stmt, StringPart, TypeAnnotation};
+use std::collections::HashSet;
+
+/// A checked top-level statement and its dependency information
+#[derive(Clone)]
+pub(crate) struct TopLevelEntry {
+    pub stmt: Stmt,
+    pub defines: Vec<String>,
+    pub references: HashSet<String>,
+    pub ty: Type,
+}
+
+impl TopLevelEntry {
+    pub fn new(stmt: Stmt, ty: Type) -> Self {
+        Self {
+            defines: defined_names(&stmt),
+            references: referenced_names(&stmt),
+            stmt,
+            ty,
+        }
+    }
+}
+
+/// Names a top-level statement binds in the global scope
+pub(crate) fn defined_names(stmt: &Stmt) -> Vec<String> {
+    match stmt {
+        Stmt::Let { name, .. }
+        | Stmt::Function { name, .. }
+        | Stmt::TypeAlias { name, .. }
+        | Stmt::Class { name, .. } => vec![name.clone()],
+        Stmt::Import {
+            module,
+            symbols,
+            alias,
+            ..
+        } => match symbols {
+            Some(symbols) => symbols
+                .iter()
+                .map(|symbol| symbol.binding().to_string())
+                .collect(),
+            None => vec![namespace_binding(module, alias.as_deref()).to_string()],
+        },
+        Stmt::Export { stmt, .. } => defined_names(stmt),
+        _ => Vec::new(),
+    }
+}
+
+/// Every value or type name a statement refers to.
+///
+/// Local shadowing is ignored, so the result may over-approximate the real
+/// dependencies; that only costs an unnecessary re-check.
+pub(crate) fn referenced_names(stmt: &Stmt) -> HashSet<String> {
+    let mut names = HashSet::new();
+    collect_stmt(stmt, &mut names);
+    names
+}
+
+fn collect_stmt(stmt: &Stmt, names: &mut HashSet<String>) {
+    match stmt {
+        Stmt::Let {
+            type_annotation,
+            value,
+            ..
+        } => {
+            if let Some(ann) = type_annotation {
+                collect_annotation(ann, names);
+            }
+            collect_expr(value, names);
+        }
+        Stmt::Function {
+            params,
+            return_type,
+            body,
+            ..
+        } => {
+            for param in params {
+                collect_param(param, names);
+            }
+            if let Some(ann) = return_type {
+                collect_annotation(ann, names);
+            }
+            collect_expr(body, names);
+        }
+        Stmt::TypeAlias { definition, .. } => collect_annotation(definition, names),
+        Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            collect_expr(condition, names);
+            collect_stmts(then_branch, names);
+            if let Some(else_branch) = else_branch {
+                collect_stmts(else_branch, names);
+            }
+        }
+        Stmt::While {
+            condition, body, ..
+        } => {
+            collect_expr(condition, names);
+            collect_stmts(body, names);
+        }
+        Stmt::For { iter, body, .. } => {
+            collect_expr(iter, names);
+            collect_stmts(body, names);
+        }
+        Stmt::Match {
+            scrutinee, arms, ..
+        } => {
+            collect_expr(scrutinee, names);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    collect_expr(guard, names);
+                }
+                collect_expr(&arm.body, names);
+            }
+        }
+        Stmt::Return { value, .. } => {
+            if let Some(value) = value {
+                collect_expr(value, names);
+            }
+        }
+        Stmt::Try {
+            body,
+            catch_body,
+            finally_body,
+            ..
+        } => {
+            collect_stmts(body, names);
+            if let Some(catch_body) = catch_body {
+                collect_stmts(catch_body, names);
+            }
+            if let Some(finally_body) = finally_body {
+                collect_stmts(finally_body, names);
+            }
+        }
+        Stmt::Raise { value, .. } => collect_expr(value, names),
+        Stmt::ExprStmt { expr, .. } => collect_expr(expr, names),
+        Stmt::Export { stmt, .. } => collect_stmt(stmt, names),
+        Stmt::Class {
+            extends, members, ..
+        } => {
+            if let Some(parent) = extends {
+                names.insert(parent.clone());
+            }
+            for member in members {
+                match member {
+                    ClassMember::Field {
+                        type_annotation,
+                        default_value,
+                        ..
+                    } => {
+                        if let Some(ann) = type_annotation {
+                            collect_annotation(ann, names);
+                        }
+                        if let Some(value) = default_value {
+                            collect_expr(value, names);
+                        }
+                    }
+                    ClassMember::Method {
+                        params,
+                        return_type,
+                        body,
+                        ..
+                    } => {
+                        for param in params {
+                            collect_param(param, names);
+                        }
+                        if let Some(ann) = return_type {
+                            collect_annotation(ann, names);
+                        }
+                        collect_expr(body, names);
+                    }
+                }
+            }
+        }
+        Stmt::Break { .. } | Stmt::Continue { .. } | Stmt::Import { .. } => {}
+    }
+}
+
+fn collect_stmts(stmts: &[Stmt], names: &mut HashSet<String>) {
+    for stmt in stmts {
+        collect_stmt(stmt, names);
+    }
+}
+
+fn collect_param(param: &nevermind_ast::Parameter, names: &mut HashSet<String>) {
+    if let Some(ann) = &param.type_annotation {
+        collect_annotation(ann, names);
+    }
+    if let Some(default) = &param.default_value {
+        collect_expr(default, names);
+    }
+}
+
+fn collect_expr(expr: &Expr, names: &mut HashSet<String>) {
+    match expr {
+        Expr::Literal(_) | Expr::Unit { .. } => {}
+        Expr::Variable { name, .. } => {
+            names.insert(name.clone());
+        }
+        Expr::Binary { left, right, .. }
+        | Expr::Comparison { left, right, .. }
+        | Expr::Logical { left, right, .. } => {
+            collect_expr(left, names);
+            collect_expr(right, names);
+        }
+        Expr::Unary { expr, .. } | Expr::Await { expr, .. } => collect_expr(expr, names),
+        Expr::Call { callee, args, .. } => {
+            collect_expr(callee, names);
+            for arg in args {
+                collect_expr(arg, names);
+            }
+        }
+        Expr::Pipeline { stages, .. } => {
+            for stage in stages {
+                collect_expr(stage, names);
+            }
+        }
+        Expr::Lambda { params, body, .. } => {
+            for param in params {
+                collect_param(param, names);
+            }
+            collect_expr(body, names);
+        }
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            collect_expr(condition, names);
+            collect_expr(then_branch, names);
+            collect_expr(else_branch, names);
+        }
+        Expr::Block { statements, .. } => collect_stmts(statements, names),
+        Expr::StringInterp { parts, .. } => {
+            for part in parts {
+                if let StringPart::Expr(expr) = part {
+                    collect_expr(expr, names);
+                }
+            }
+        }
+        Expr::List { elements, .. } | Expr::Tuple { elements, .. } => {
+            for element in elements {
+                collect_expr(element, names);
+            }
+        }
+        Expr::Map { entries, .. } => {
+            for (key, value) in entries {
+                collect_expr(key, names);
+                collect_expr(value, names);
+            }
+        }
+        Expr::Match {
+            scrutinee, arms, ..
+        } => {
+            collect_expr(scrutinee, names);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    collect_expr(guard, names);
+                }
+                collect_expr(&arm.body, names);
+            }
+        }
+        Expr::Index { array, index, .. } => {
+            collect_expr(array, names);
+            collect_expr(index, names);
+        }
+        Expr::Range { start, end, .. } => {
+            collect_expr(start, names);
+            collect_expr(end, names);
+        }
+        Expr::Slice {
+            array, start, end, ..
+        } => {
+            collect_expr(array, names);
+            collect_expr(start, names);
+            collect_expr(end, names);
+        }
+        Expr::Assign { target, value, .. } => {
+            collect_expr(target, names);
+            collect_expr(value, names);
+        }
+        Expr::MemberAccess { object, .. } => collect_expr(object, names),
+    }
+}
+
+fn collect_annotation(ann: &TypeAnnotation, names: &mut HashSet<String>) {
+    match &ann.kind {
+        AstType::Primitive(_) => {}
+        AstType::Identifier(name) => {
+            names.insert(name.clone());
+        }
+        AstType::Generic { name, params } => {
+            names.insert(name.clone());
+            for param in params {
+                collect_annotation(param, names);
+            }
+        }
+        AstType::List(inner) | AstType::Set(inner) | AstType::Option(inner) => {
+            collect_annotation(inner, names)
+        }
+        AstType::Map { key, value } => {
+            collect_annotation(key, names);
+            collect_annotation(value, names);
+        }
+        AstType::Result { ok, error } => {
+            collect_annotation(ok, names);
+            collect_annotation(error, names);
+        }
+        AstType::Tuple(elems) | AstType::Union(elems) | AstType::Intersection(elems) => {
+            for elem in elems {
+                collect_annotation(elem, names);
+            }
+        }
+        AstType::Function {
+            params,
+            return_type,
+        } => {
+            for param in params {
+                collect_annotation(param, names);
+            }
+            collect_annotation(return_type, names);
+        }
+    }
+}
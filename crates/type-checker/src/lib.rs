@@ -11,6 +11,7 @@
 pub mod checker;
 pub mod environment;
 pub mod error;
+mod incremental;
 pub mod ty;
 pub mod types;
 pub mod unification;
@@ -18,11 +19,12 @@ pub mod unification;
 pub use checker::TypeChecker;
 pub use environment::TypeEnvironment;
 pub use error::{Result, TypeError, TypeErrorKind};
-pub use ty::{TypeScheme, TypeVar};
+pub use ty::{Constraint, TypeScheme, TypeVar};
 pub use types::{Type, TypeVarRef};
 pub use unification::Unifier;
 
 /// Type checking context
+#[derive(Clone)]
 pub struct TypeContext {
     /// Next type variable ID
     next_var_id: usize,
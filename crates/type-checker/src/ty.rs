@@ -27,6 +27,28 @@ impl std::fmt::Display for TypeVar {
 
 /// A type scheme: ∀α1...αn. type
 ///
+/// What a constrained type variable of a [`TypeScheme`] may stand for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constraint {
+    /// Int or Float
+    Numeric,
+    /// A value with a length: a String, List, Map or tuple
+    Sized,
+}
+
+impl Constraint {
+    /// Whether `ty` meets this constraint
+    pub fn allows(self, ty: &Type) -> bool {
+        match self {
+            Constraint::Numeric => ty.is_numeric(),
+            Constraint::Sized => matches!(
+                ty,
+                Type::String | Type::List(_) | Type::Map(_) | Type::Tuple(_)
+            ),
+        }
+    }
+}
+
 /// Type schemes represent polymorphic types. For example:
 /// - `∀a. a -> a` is the identity function type
 /// - `∀a b. a -> b -> a` is the const function type
@@ -37,17 +59,33 @@ pub struct TypeScheme {
 
     /// The type itself
     pub ty: Type,
+
+    /// Quantified variables restricted to some kinds of type, such as
+    /// Int or Float
+    pub constraints: Vec<(TypeVar, Constraint)>,
 }
 
 impl TypeScheme {
     /// Create a new type scheme
     pub fn new(vars: Vec<TypeVar>, ty: Type) -> Self {
-        Self { vars, ty }
+        Self {
+            vars,
+            ty,
+            constraints: Vec::new(),
+        }
     }
 
     /// Create a monomorphic type scheme (no quantified variables)
     pub fn monomorphic(ty: Type) -> Self {
-        Self { vars: vec![], ty }
+        Self::new(vec![], ty)
+    }
+
+    /// Restrict some of the quantified variables to the types
+    /// `constraint` allows
+    pub fn with_constraint(mut self, constraint: Constraint, vars: Vec<TypeVar>) -> Self {
+        self.constraints
+            .extend(vars.into_iter().map(|var| (var, constraint)));
+        self
     }
 
     /// Generalize a type with respect to a set of free variables
@@ -64,11 +102,20 @@ impl TypeScheme {
             .map(TypeVar::new)
             .collect();
 
-        Self { vars, ty }
+        Self::new(vars, ty)
     }
 
     /// Instantiate this type scheme by replacing quantified variables with fresh type variables
     pub fn instantiate(&self, ctx: &mut crate::TypeContext) -> Type {
+        self.instantiate_constrained(ctx).0
+    }
+
+    /// Instantiate this type scheme, also returning what each constrained
+    /// variable was replaced with
+    pub fn instantiate_constrained(
+        &self,
+        ctx: &mut crate::TypeContext,
+    ) -> (Type, Vec<(Type, Constraint)>) {
         // Create fresh type variables for each quantified variable
         let subst: std::collections::HashMap<usize, Type> = self
             .vars
@@ -82,8 +129,16 @@ impl TypeScheme {
             })
             .collect();
 
+        let constrained = self
+            .constraints
+            .iter()
+            .filter_map(|(var, constraint)| {
+                subst.get(&var.id()).map(|ty| (ty.clone(), *constraint))
+            })
+            .collect();
+
         // Apply substitution
-        self.substitute(&subst)
+        (self.substitute(&subst), constrained)
     }
 
     /// Apply a substitution to this type scheme
@@ -95,35 +150,42 @@ impl TypeScheme {
 impl Type {
     /// Get the free type variables in a type
     pub fn free_vars(ty: &Type) -> HashSet<usize> {
-        match ty {
+        let mut set = HashSet::new();
+        ty.collect_free_vars(&mut set);
+        set
+    }
+
+    fn collect_free_vars(&self, set: &mut HashSet<usize>) {
+        match self {
             Type::Var(var) => {
-                let mut set = HashSet::new();
                 set.insert(var.id());
-                set
             }
             Type::Function(params, ret) => {
-                let mut set = Type::free_vars(ret);
                 for param in params {
-                    set = &set | &Type::free_vars(param);
+                    param.collect_free_vars(set);
                 }
-                set
+                ret.collect_free_vars(set);
             }
-            Type::List(elem) => Type::free_vars(elem),
-            Type::Map(value) => Type::free_vars(value),
+            Type::List(elem) => elem.collect_free_vars(set),
+            Type::Map(value) => value.collect_free_vars(set),
             Type::Tuple(elems) => {
-                let mut set = HashSet::new();
                 for elem in elems {
-                    set = &set | &Type::free_vars(elem);
+                    elem.collect_free_vars(set);
                 }
-                set
+            }
+            Type::Option(inner) => inner.collect_free_vars(set),
+            Type::Result(ok, error) => {
+                ok.collect_free_vars(set);
+                error.collect_free_vars(set);
             }
             Type::Int
             | Type::Float
             | Type::String
+            | Type::Char
             | Type::Bool
             | Type::Null
             | Type::Unit
-            | Type::User(_) => HashSet::new(),
+            | Type::User(_) => {}
         }
     }
 
@@ -144,6 +206,11 @@ impl Type {
             Type::List(elem) => Type::List(Box::new(elem.substitute(subst))),
             Type::Map(value) => Type::Map(Box::new(value.substitute(subst))),
             Type::Tuple(elems) => Type::Tuple(elems.iter().map(|e| e.substitute(subst)).collect()),
+            Type::Option(inner) => Type::Option(Box::new(inner.substitute(subst))),
+            Type::Result(ok, error) => Type::Result(
+                Box::new(ok.substitute(subst)),
+                Box::new(error.substitute(subst)),
+            ),
             _ => self.clone(),
         }
     }
@@ -13,6 +13,7 @@ pub enum Type {
     Int,
     Float,
     String,
+    Char,
     Bool,
     Null,
     Unit,
@@ -29,6 +30,12 @@ pub enum Type {
     /// Tuple type (T1, T2, ..., Tn)
     Tuple(Vec<Type>),
 
+    /// Optional value: Some(T) or None
+    Option(Box<Type>),
+
+    /// Success or failure: Ok(T) or Error(E)
+    Result(Box<Type>, Box<Type>),
+
     /// User-defined type
     User(String),
 }
@@ -111,6 +118,16 @@ impl Type {
         Type::Tuple(elems)
     }
 
+    /// Create an option type
+    pub fn option(inner: Type) -> Self {
+        Type::Option(Box::new(inner))
+    }
+
+    /// Create a result type
+    pub fn result(ok: Type, error: Type) -> Self {
+        Type::Result(Box::new(ok), Box::new(error))
+    }
+
     /// Create a user-defined type
     pub fn user(name: String) -> Self {
         Type::User(name)
@@ -142,6 +159,7 @@ impl Type {
             Type::Int => "Int".to_string(),
             Type::Float => "Float".to_string(),
             Type::String => "String".to_string(),
+            Type::Char => "Char".to_string(),
             Type::Bool => "Bool".to_string(),
             Type::Null => "Null".to_string(),
             Type::Unit => "Unit".to_string(),
@@ -155,6 +173,10 @@ impl Type {
                 let elems: Vec<String> = elems.iter().map(|e| e.display_name()).collect();
                 format!("({})", elems.join(", "))
             }
+            Type::Option(inner) => format!("Option[{}]", inner.display_name()),
+            Type::Result(ok, error) => {
+                format!("Result[{}, {}]", ok.display_name(), error.display_name())
+            }
             Type::User(name) => name.clone(),
         }
     }
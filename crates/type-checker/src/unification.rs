@@ -3,14 +3,26 @@
 use crate::error::{Result, TypeError, TypeErrorKind};
 use crate::types::{Type, TypeVarRef};
 use nevermind_common::Span;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// A substitution mapping type variables to types
 pub type Substitution = HashMap<usize, Type>;
 
 /// A unifier for finding substitutions between types
+///
+/// Type variables are kept in a union-find forest: unifying two variables
+/// merges their classes, and only the representative of each class carries
+/// a binding. Resolving a variable is a near-constant-time walk to its root
+/// rather than a chase through a chain of variable-to-variable bindings.
+#[derive(Clone)]
 pub struct Unifier {
-    /// Current substitution
+    /// Parent links for merged variables; roots have no entry
+    parent: HashMap<usize, usize>,
+
+    /// Upper bound on the height of each root's tree
+    rank: HashMap<usize, u8>,
+
+    /// Bindings from class representatives to non-variable types
     subst: Substitution,
 }
 
@@ -18,6 +30,8 @@ impl Unifier {
     /// Create a new unifier
     pub fn new() -> Self {
         Self {
+            parent: HashMap::new(),
+            rank: HashMap::new(),
             subst: Substitution::new(),
         }
     }
@@ -32,10 +46,30 @@ impl Unifier {
         &mut self.subst
     }
 
+    /// Find the representative of a variable's class
+    pub fn find(&self, id: usize) -> usize {
+        let mut root = id;
+        while let Some(&next) = self.parent.get(&root) {
+            root = next;
+        }
+        root
+    }
+
+    /// Find the representative of a variable's class, compressing the path
+    fn find_mut(&mut self, id: usize) -> usize {
+        let root = self.find(id);
+        let mut current = id;
+        while current != root {
+            let next = self.parent.insert(current, root).unwrap_or(root);
+            current = next;
+        }
+        root
+    }
+
     /// Unify two types
     pub fn unify(&mut self, ty1: &Type, ty2: &Type, span: &Span) -> Result<()> {
-        let ty1 = self.apply(ty1);
-        let ty2 = self.apply(ty2);
+        let ty1 = self.shallow_resolve(ty1);
+        let ty2 = self.shallow_resolve(ty2);
 
         match (ty1, ty2) {
             // Unifying a type variable with a type
@@ -45,6 +79,7 @@ impl Unifier {
             (Type::Int, Type::Int)
             | (Type::Float, Type::Float)
             | (Type::String, Type::String)
+            | (Type::Char, Type::Char)
             | (Type::Bool, Type::Bool)
             | (Type::Null, Type::Null)
             | (Type::Unit, Type::Unit) => Ok(()),
@@ -77,12 +112,21 @@ impl Unifier {
             // Unifying two map types
             (Type::Map(value1), Type::Map(value2)) => self.unify(&value1, &value2, span),
 
+            // Unifying two option types
+            (Type::Option(inner1), Type::Option(inner2)) => self.unify(&inner1, &inner2, span),
+
+            // Unifying two result types
+            (Type::Result(ok1, error1), Type::Result(ok2, error2)) => {
+                self.unify(&ok1, &ok2, span)?;
+                self.unify(&error1, &error2, span)
+            }
+
             // Unifying two tuple types
             (Type::Tuple(elems1), Type::Tuple(elems2)) => {
                 if elems1.len() != elems2.len() {
                     return Err(TypeError::type_mismatch(
-                        Type::Tuple(elems1.clone()),
-                        Type::Tuple(elems2.clone()),
+                        self.apply(&Type::Tuple(elems1)),
+                        self.apply(&Type::Tuple(elems2)),
                         span.clone(),
                     ));
                 }
@@ -95,21 +139,44 @@ impl Unifier {
             }
 
             // Type mismatch
-            (ty1, ty2) => Err(TypeError::type_mismatch(ty1, ty2, span.clone())),
+            (ty1, ty2) => Err(TypeError::type_mismatch(
+                self.apply(&ty1),
+                self.apply(&ty2),
+                span.clone(),
+            )),
+        }
+    }
+
+    /// Resolve the outermost variable of a type, leaving its children as-is
+    fn shallow_resolve(&mut self, ty: &Type) -> Type {
+        match ty {
+            Type::Var(var) => {
+                let root = self.find_mut(var.id());
+                match self.subst.get(&root) {
+                    Some(bound) => bound.clone(),
+                    None if root == var.id() => ty.clone(),
+                    None => Type::Var(TypeVarRef::new(root)),
+                }
+            }
+            _ => ty.clone(),
         }
     }
 
     /// Bind a type variable to a type
     fn bind_var(&mut self, var: TypeVarRef, ty: Type, span: &Span) -> Result<()> {
-        // If the type is the same variable, nothing to do
+        let root = self.find_mut(var.id());
+
+        // Merging two unbound classes
         if let Type::Var(ref v) = ty {
-            if v.id() == var.id() {
-                return Ok(());
+            let other = self.find_mut(v.id());
+            if other != root {
+                self.union(root, other);
             }
+            return Ok(());
         }
 
         // Occurs check: ensure the variable doesn't occur in the type
-        if self.occurs(&var, &ty) {
+        if self.occurs(root, &ty) {
             return Err(TypeError::new(
                 TypeErrorKind::OccursCheckFailed(var.id()),
                 format!("infinite type: t{}", var.id()),
@@ -118,22 +185,47 @@ impl Unifier {
         }
 
         // Add the binding
-        self.subst.insert(var.id(), ty);
+        self.subst.insert(root, ty);
         Ok(())
     }
 
-    /// Check if a type variable occurs in a type (occurs check)
-    fn occurs(&self, var: &TypeVarRef, ty: &Type) -> bool {
-        let ty = self.apply(ty);
+    /// Merge two unbound classes by rank
+    fn union(&mut self, a: usize, b: usize) {
+        let rank_a = self.rank.get(&a).copied().unwrap_or(0);
+        let rank_b = self.rank.get(&b).copied().unwrap_or(0);
+
+        let (child, root) = if rank_a < rank_b { (a, b) } else { (b, a) };
+        self.parent.insert(child, root);
+        self.rank.remove(&child);
+        if rank_a == rank_b {
+            self.rank.insert(root, rank_a + 1);
+        }
+
+        // A binding on the absorbed class moves to the new representative
+        if let Some(bound) = self.subst.remove(&child) {
+            self.subst.insert(root, bound);
+        }
+    }
 
+    /// Check if a variable class occurs in a type (occurs check)
+    fn occurs(&self, root: usize, ty: &Type) -> bool {
         match ty {
-            Type::Var(v) => v.id() == var.id(),
+            Type::Var(v) => {
+                let other = self.find(v.id());
+                other == root
+                    || self
+                        .subst
+                        .get(&other)
+                        .is_some_and(|bound| self.occurs(root, bound))
+            }
             Type::Function(params, ret) => {
-                self.occurs(var, &ret) || params.iter().any(|p| self.occurs(var, p))
+                self.occurs(root, ret) || params.iter().any(|p| self.occurs(root, p))
             }
-            Type::List(elem) => self.occurs(var, &elem),
-            Type::Map(value) => self.occurs(var, &value),
-            Type::Tuple(elems) => elems.iter().any(|e| self.occurs(var, e)),
+            Type::List(elem) => self.occurs(root, elem),
+            Type::Map(value) => self.occurs(root, value),
+            Type::Tuple(elems) => elems.iter().any(|e| self.occurs(root, e)),
+            Type::Option(inner) => self.occurs(root, inner),
+            Type::Result(ok, error) => self.occurs(root, ok) || self.occurs(root, error),
             _ => false,
         }
     }
@@ -142,10 +234,11 @@ impl Unifier {
     pub fn apply(&self, ty: &Type) -> Type {
         match ty {
             Type::Var(var) => {
-                if let Some(replacement) = self.subst.get(&var.id()) {
-                    self.apply(replacement)
-                } else {
-                    ty.clone()
+                let root = self.find(var.id());
+                match self.subst.get(&root) {
+                    Some(bound) => self.apply(bound),
+                    None if root == var.id() => ty.clone(),
+                    None => Type::Var(TypeVarRef::new(root)),
                 }
             }
             Type::Function(params, ret) => Type::Function(
@@ -155,55 +248,63 @@ impl Unifier {
             Type::List(elem) => Type::List(Box::new(self.apply(elem))),
             Type::Map(value) => Type::Map(Box::new(self.apply(value))),
             Type::Tuple(elems) => Type::Tuple(elems.iter().map(|e| self.apply(e)).collect()),
+            Type::Option(inner) => Type::Option(Box::new(self.apply(inner))),
+            Type::Result(ok, error) => {
+                Type::Result(Box::new(self.apply(ok)), Box::new(self.apply(error)))
+            }
             _ => ty.clone(),
         }
     }
 
-    /// Compose two substitutions
-    pub fn compose(&mut self, other: Substitution) {
-        // Collect current substitutions
-        let current: Vec<(usize, Type)> = self.subst.drain().collect();
-
-        // Apply the new substitution to each current substitution
-        for (var, ty) in current {
-            let new_ty = self.apply_from_subst(&ty, &other);
-            self.subst.insert(var, new_ty);
-        }
-
-        // Add the new bindings
-        for (var, ty) in other {
-            self.subst.entry(var).or_insert(ty);
-        }
+    /// Get the unbound variables of a type after applying the substitution
+    ///
+    /// Each variable is reported by its class representative.
+    pub fn free_vars(&self, ty: &Type) -> HashSet<usize> {
+        let mut vars = HashSet::new();
+        self.collect_free_vars(ty, &mut vars);
+        vars
     }
 
-    /// Apply a substitution to a type
-    fn apply_from_subst(&self, ty: &Type, subst: &Substitution) -> Type {
+    fn collect_free_vars(&self, ty: &Type, vars: &mut HashSet<usize>) {
         match ty {
             Type::Var(var) => {
-                if let Some(replacement) = subst.get(&var.id()) {
-                    self.apply(replacement)
-                } else if let Some(replacement) = self.subst.get(&var.id()) {
-                    self.apply(replacement)
-                } else {
-                    ty.clone()
+                let root = self.find(var.id());
+                match self.subst.get(&root) {
+                    Some(bound) => self.collect_free_vars(bound, vars),
+                    None => {
+                        vars.insert(root);
+                    }
                 }
             }
-            Type::Function(params, ret) => Type::Function(
-                params
-                    .iter()
-                    .map(|p| self.apply_from_subst(p, subst))
-                    .collect(),
-                Box::new(self.apply_from_subst(ret, subst)),
-            ),
-            Type::List(elem) => Type::List(Box::new(self.apply_from_subst(elem, subst))),
-            Type::Map(value) => Type::Map(Box::new(self.apply_from_subst(value, subst))),
-            Type::Tuple(elems) => Type::Tuple(
-                elems
-                    .iter()
-                    .map(|e| self.apply_from_subst(e, subst))
-                    .collect(),
-            ),
-            _ => ty.clone(),
+            Type::Function(params, ret) => {
+                for param in params {
+                    self.collect_free_vars(param, vars);
+                }
+                self.collect_free_vars(ret, vars);
+            }
+            Type::List(elem) => self.collect_free_vars(elem, vars),
+            Type::Map(value) => self.collect_free_vars(value, vars),
+            Type::Tuple(elems) => {
+                for elem in elems {
+                    self.collect_free_vars(elem, vars);
+                }
+            }
+            Type::Option(inner) => self.collect_free_vars(inner, vars),
+            Type::Result(ok, error) => {
+                self.collect_free_vars(ok, vars);
+                self.collect_free_vars(error, vars);
+            }
+            _ => {}
+        }
+    }
+
+    /// Compose two substitutions
+    ///
+    /// Bindings in `other` take effect for any variable class not already bound.
+    pub fn compose(&mut self, other: Substitution) {
+        for (var, ty) in other {
+            let root = self.find_mut(var);
+            self.subst.entry(root).or_insert(ty);
         }
     }
 }
@@ -301,4 +402,34 @@ mod tests {
         let result = unifier.unify(&ty1, &ty2, &span);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_variable_chain_shares_one_binding() {
+        let mut unifier = Unifier::new();
+        let span = Span::dummy();
+
+        // t0 ~ t1 ~ ... ~ t99, then bind one end of the chain
+        for id in 0..99 {
+            unifier
+                .unify(&Type::var(id), &Type::var(id + 1), &span)
+                .unwrap();
+        }
+        assert_eq!(unifier.free_vars(&Type::var(0)).len(), 1);
+        assert_eq!(unifier.find(0), unifier.find(99));
+
+        unifier.unify(&Type::var(99), &Type::Int, &span).unwrap();
+        assert_eq!(unifier.apply(&Type::var(0)), Type::Int);
+        assert!(unifier.free_vars(&Type::list(Type::var(42))).is_empty());
+        assert!(unifier.unify(&Type::var(7), &Type::Bool, &span).is_err());
+    }
+
+    #[test]
+    fn test_occurs_check_through_merged_variables() {
+        let mut unifier = Unifier::new();
+        let span = Span::dummy();
+
+        unifier.unify(&Type::var(0), &Type::var(1), &span).unwrap();
+        let result = unifier.unify(&Type::var(0), &Type::list(Type::var(1)), &span);
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,159 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use nevermind::CompileOptions;
+
+/// The directory, beside the compiled source, that cached output goes in
+pub const CACHE_DIR: &str = ".nevermind-cache";
+
+/// How many entries the cache keeps before dropping the least recently
+/// used ones
+const MAX_ENTRIES: usize = 64;
+
+/// Whether a compile may reuse cached output, and whether it saves its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Reuse output cached by an earlier compile, and cache new output
+    Reuse,
+    /// Compile from scratch, replacing whatever was cached
+    Refresh,
+    /// Neither read nor write the cache
+    Off,
+}
+
+/// Python generated by an earlier compile, stored under a hash of
+/// everything that went into it: the source, the sources of the local
+/// modules it imports, the compile options and the compiler's version.
+///
+/// Changing any of those changes the key, so an entry is never stale; it
+/// just stops being looked up, and is dropped once enough newer entries
+/// have been stored.
+pub struct Cache {
+    path: PathBuf,
+}
+
+impl Cache {
+    pub fn new(
+        base_dir: &Path,
+        source: &str,
+        dependencies: &[String],
+        options: &CompileOptions,
+    ) -> Self {
+        let mut key = format!("{}\0{:?}\0{}", env!("CARGO_PKG_VERSION"), options, source);
+        for dependency in dependencies {
+            key.push('\0');
+            key.push_str(dependency);
+        }
+        let file_name = format!("{:016x}.py", fnv1a(key.as_bytes()));
+        Self {
+            path: base_dir.join(CACHE_DIR).join(file_name),
+        }
+    }
+
+    /// The cached Python, if this source was compiled before
+    pub fn load(&self) -> Option<String> {
+        let python = fs::read_to_string(&self.path).ok()?;
+        // Mark the entry as recently used, so eviction keeps it
+        if let Ok(file) = fs::File::options().append(true).open(&self.path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(python)
+    }
+
+    /// Save freshly generated Python for the next compile, dropping the
+    /// least recently used entries beyond [`MAX_ENTRIES`]
+    ///
+    /// The cache only saves time, so failing to write it isn't an error.
+    pub fn store(&self, python: &str) {
+        let Some(dir) = self.path.parent() else {
+            return;
+        };
+        if fs::create_dir_all(dir)
+            .and_then(|()| fs::write(&self.path, python))
+            .is_ok()
+        {
+            evict(dir, MAX_ENTRIES);
+        }
+    }
+}
+
+/// Remove all but the `keep` most recently used entries in `dir`
+fn evict(dir: &Path, keep: usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .filter(|(_, path)| path.extension().is_some_and(|ext| ext == "py"))
+        .collect();
+    if entries.len() <= keep {
+        return;
+    }
+    entries.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in entries.drain(keep..) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `std`'s hasher gives the same hash on every
+/// run and Rust release
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_changes_with_source_and_options() {
+        let dir = Path::new("project");
+        let options = CompileOptions::default();
+        let key = |source, options| Cache::new(dir, source, &[], options).path;
+
+        assert_eq!(key("print(1)\n", &options), key("print(1)\n", &options));
+        assert_ne!(key("print(1)\n", &options), key("print(2)\n", &options));
+        let optimized = CompileOptions {
+            optimize: true,
+            ..CompileOptions::default()
+        };
+        assert_ne!(key("print(1)\n", &options), key("print(1)\n", &optimized));
+        assert!(key("print(1)\n", &options).starts_with("project/.nevermind-cache"));
+    }
+
+    #[test]
+    fn test_key_changes_with_imported_modules() {
+        let dir = Path::new("project");
+        let options = CompileOptions::default();
+        let source = "from \"util\" import helper\nprint helper()\n";
+        let key = |util: &str| Cache::new(dir, source, &[util.to_string()], &options).path;
+        let (util, changed) = ("export fn helper() do 1 end\n", "export let other = 1\n");
+
+        assert_eq!(key(util), key(util));
+        assert_ne!(key(util), key(changed));
+    }
+
+    #[test]
+    fn test_store_evicts_least_recently_used_entries() {
+        let unique = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("nevermind_cache_evict_{}", unique));
+        let options = CompileOptions::default();
+        for n in 0..MAX_ENTRIES + 3 {
+            Cache::new(&dir, &format!("print({})\n", n), &[], &options).store("pass\n");
+        }
+
+        let entries = fs::read_dir(dir.join(CACHE_DIR)).unwrap().count();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(entries, MAX_ENTRIES);
+    }
+}
@@ -0,0 +1,115 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use nevermind::PythonTarget;
+use serde::Deserialize;
+
+/// The name of the project configuration file
+pub const CONFIG_FILE: &str = "nevermind.toml";
+
+/// Project defaults from `nevermind.toml`; flags on the command line win
+/// over anything set here.
+///
+/// ```toml
+/// [compile]
+/// target = "py310"
+/// output-dir = "build"
+/// warn-shadowing = true
+///
+/// [format]
+/// line-width = 120
+///
+/// [lint]
+/// disable = ["todo-marker"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub compile: CompileConfig,
+    pub format: FormatConfig,
+    pub lint: LintConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CompileConfig {
+    /// Python version to generate code for, as `--target` takes it
+    target: Option<String>,
+
+    /// Directory compiled files are written to when no `--output` is given
+    pub output_dir: Option<PathBuf>,
+
+    /// Warn when a local binding shadows an outer one, as
+    /// `--warn-shadowing` does
+    pub warn_shadowing: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FormatConfig {
+    /// Longest line, in characters, that `fmt` aims for and the `long-line`
+    /// lint allows
+    pub line_width: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    /// Codes of the lint rules not to run
+    pub disable: Vec<String>,
+}
+
+impl Config {
+    /// Read `nevermind.toml` from `dir`, or the defaults when there is none
+    pub fn load(dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = dir.join(CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(&path)?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", CONFIG_FILE, e).into())
+    }
+
+    fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let config: Config = toml::from_str(text)?;
+        if let Some(target) = &config.compile.target {
+            target.parse::<PythonTarget>()?;
+        }
+        for rule in &config.lint.disable {
+            crate::linting::check_rule_name(rule)?;
+        }
+        Ok(config)
+    }
+
+    /// The configured Python target, if any
+    pub fn target(&self) -> Option<PythonTarget> {
+        self.compile.target.as_deref()?.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reads_every_section() {
+        let config = Config::parse(
+            "[compile]\ntarget = \"py310\"\noutput-dir = \"build\"\nwarn-shadowing = true\n\n\
+             [format]\nline-width = 80\n\n[lint]\ndisable = [\"todo-marker\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.target(), Some(PythonTarget::Py310));
+        assert_eq!(config.compile.output_dir, Some(PathBuf::from("build")));
+        assert!(config.compile.warn_shadowing);
+        assert_eq!(config.format.line_width, Some(80));
+        assert_eq!(config.lint.disable, vec!["todo-marker".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_settings() {
+        assert!(Config::parse("[compile]\ntarget = \"py2\"\n").is_err());
+        assert!(Config::parse("[lint]\ndisable = [\"no-such-rule\"]\n").is_err());
+        assert!(Config::parse("[format]\nindent = 2\n").is_err());
+    }
+}
@@ -1,9 +1,25 @@
 use std::error::Error;
 use std::fs;
-use std::path::PathBuf;
-
-/// Format one or more Nevermind files.
-pub fn format_paths(inputs: Vec<PathBuf>, write: bool, check: bool) -> Result<(), Box<dyn Error>> {
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use nevermind_ast::expr::{Literal, StringPart};
+use nevermind_ast::op::{BinaryOp, UnaryOp};
+use nevermind_ast::Expr;
+use nevermind_lexer::token::{Keyword, TokenType};
+
+/// The line width used when neither `--line-width` nor `nevermind.toml`
+/// sets one
+pub const DEFAULT_LINE_WIDTH: usize = 100;
+
+/// Format one or more Nevermind files, wrapping lines longer than
+/// `line_width` where the formatter knows how.
+pub fn format_paths(
+    inputs: Vec<PathBuf>,
+    write: bool,
+    check: bool,
+    line_width: usize,
+) -> Result<(), Box<dyn Error>> {
     if inputs.is_empty() {
         return Err("no input files provided".into());
     }
@@ -12,8 +28,23 @@ pub fn format_paths(inputs: Vec<PathBuf>, write: bool, check: bool) -> Result<()
     let mut needs_formatting = Vec::new();
 
     for (index, input) in inputs.iter().enumerate() {
-        let source = fs::read_to_string(input)?;
-        let formatted = format_source(&source)?;
+        let from_stdin = crate::is_stdin(input);
+        let source = if from_stdin {
+            if write {
+                return Err("cannot write formatted standard input back".into());
+            }
+            let mut source = String::new();
+            io::stdin().read_to_string(&mut source)?;
+            source
+        } else {
+            fs::read_to_string(input)?
+        };
+        let input = if from_stdin {
+            Path::new(crate::STDIN_NAME)
+        } else {
+            input.as_path()
+        };
+        let formatted = format_source(&source, line_width)?;
         let is_changed = source != formatted;
 
         if check {
@@ -54,9 +85,74 @@ pub fn format_paths(inputs: Vec<PathBuf>, write: bool, check: bool) -> Result<()
 }
 
 /// Format a Nevermind source string while preserving comments.
-pub fn format_source(source: &str) -> Result<String, Box<dyn Error>> {
+///
+/// A `let`, `var` or `return` line longer than `line_width` whose value is
+/// a call or a list has its arguments or elements put one per line.
+pub fn format_source(source: &str, line_width: usize) -> Result<String, Box<dyn Error>> {
     validate_syntax(source)?;
-    Ok(reindent_source(source))
+    Ok(reindent_source(source, line_width))
+}
+
+/// Pretty-print a single expression, keeping only the parentheses that
+/// change how it parses.
+///
+/// Block and match expressions span several lines and are left to the
+/// line-based reindenter, so they are rejected here.
+pub fn format_expression(source: &str) -> Result<String, Box<dyn Error>> {
+    Ok(format_expr(&parse_line_expression(source)?))
+}
+
+fn parse_line_expression(source: &str) -> Result<Expr, Box<dyn Error>> {
+    let tokens = nevermind_lexer::Lexer::new(source).tokenize()?;
+    if tokens
+        .iter()
+        .any(|token| matches!(token.kind, TokenType::Keyword(Keyword::Do | Keyword::Match)))
+    {
+        return Err("only single-line expressions can be formatted".into());
+    }
+
+    let mut parser = nevermind_parser::Parser::from_tokens(tokens);
+    let expr = parser.parse_expression()?;
+    if !parser.is_at_end() {
+        return Err("unexpected input after expression".into());
+    }
+    Ok(expr)
+}
+
+/// Pretty-print an expression that follows `prefix` on a line indented to
+/// `indent_level`, putting the arguments of a call or the elements of a list
+/// one per line when the line would otherwise be longer than `line_width`.
+///
+/// Wrapped items are indented one level deeper than the line, which is
+/// where the reindenter puts lines inside an open bracket.
+fn format_wrapped(expr: &Expr, prefix: &str, indent_level: usize, line_width: usize) -> String {
+    let flat = format!("{}{}", prefix, format_expr(expr));
+    if indent(indent_level).len() + flat.chars().count() <= line_width {
+        return flat;
+    }
+
+    let (open, items) = match expr {
+        Expr::Call { callee, args, .. } if !args.is_empty() => (
+            format!(
+                "{}(",
+                format_operand(Operand::Prefix(precedence(expr)), callee)
+            ),
+            args,
+        ),
+        Expr::List { elements, .. } if !elements.is_empty() => ("[".to_string(), elements),
+        _ => return flat,
+    };
+    let close = if open.ends_with('(') { ")" } else { "]" };
+    let mut code = format!("{}{}", prefix, open);
+    for item in items {
+        code.push_str(&format!(
+            "\n{}{},",
+            indent(indent_level + 1),
+            format_expr(item)
+        ));
+    }
+    code.push_str(&format!("\n{}{}", indent(indent_level), close));
+    code
 }
 
 fn validate_syntax(source: &str) -> Result<(), Box<dyn Error>> {
@@ -67,7 +163,252 @@ fn validate_syntax(source: &str) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn reindent_source(source: &str) -> String {
+/// How tightly an expression binds, mirroring the parser's binding powers
+/// The precedence of `==`, `<` and the other comparisons
+const COMPARISON_PRECEDENCE: u8 = 8;
+
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Lambda { .. } => 1,
+        Expr::Assign { .. } => 2,
+        Expr::Logical { op, .. } => op.precedence(),
+        Expr::Pipeline { .. } => 6,
+        Expr::Comparison { .. } => COMPARISON_PRECEDENCE,
+        Expr::Binary { op, .. } => binary_precedence(*op),
+        Expr::Range { .. } => 12,
+        Expr::Unary { .. } | Expr::Await { .. } => 17,
+        // A negative literal is printed with its sign, so it binds as a
+        // unary minus would
+        Expr::Literal(Literal::Integer(value, _)) if *value < 0 => 17,
+        Expr::Literal(Literal::Float(value, _)) if value.is_sign_negative() => 17,
+        Expr::Call { .. } | Expr::Index { .. } | Expr::Slice { .. } | Expr::MemberAccess { .. } => {
+            22
+        }
+        _ => u8::MAX,
+    }
+}
+
+fn binary_precedence(op: BinaryOp) -> u8 {
+    match op {
+        BinaryOp::BitOr => 7,
+        BinaryOp::BitXor => 9,
+        BinaryOp::BitAnd => 10,
+        BinaryOp::ShiftLeft | BinaryOp::ShiftRight => 11,
+        BinaryOp::Add | BinaryOp::Sub => 13,
+        BinaryOp::Concat => 14,
+        BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 15,
+        BinaryOp::Pow => 18,
+    }
+}
+
+/// The operator an operand sits under, and which side of it
+#[derive(Clone, Copy)]
+enum Operand {
+    Left(u8, bool),
+    Right(u8, bool),
+    Prefix(u8),
+}
+
+/// Whether `child` must be parenthesized to stay an operand of `parent_op`
+fn needs_parens(parent_op: Operand, child: &Expr) -> bool {
+    let child_prec = precedence(child);
+    // Comparisons chain, so `(a < b) == c` would become `a < b and b == c`
+    if let Operand::Left(COMPARISON_PRECEDENCE, _) | Operand::Right(COMPARISON_PRECEDENCE, _) =
+        parent_op
+    {
+        if matches!(child, Expr::Comparison { .. }) {
+            return true;
+        }
+    }
+    match parent_op {
+        Operand::Left(prec, right_assoc) if right_assoc => child_prec <= prec,
+        Operand::Right(prec, right_assoc) if !right_assoc => child_prec <= prec,
+        Operand::Left(prec, _) | Operand::Right(prec, _) | Operand::Prefix(prec) => {
+            child_prec < prec
+        }
+    }
+}
+
+fn format_operand(parent_op: Operand, child: &Expr) -> String {
+    let code = format_expr(child);
+    if needs_parens(parent_op, child) {
+        format!("({})", code)
+    } else {
+        code
+    }
+}
+
+fn format_infix(prec: u8, right_assoc: bool, left: &Expr, symbol: &str, right: &Expr) -> String {
+    format!(
+        "{} {} {}",
+        format_operand(Operand::Left(prec, right_assoc), left),
+        symbol,
+        format_operand(Operand::Right(prec, right_assoc), right)
+    )
+}
+
+fn format_list(exprs: &[Expr]) -> String {
+    exprs.iter().map(format_expr).collect::<Vec<_>>().join(", ")
+}
+
+pub(crate) fn format_expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Literal(Literal::String(text, _)) => format!("\"{}\"", escape_string(text)),
+        Expr::Literal(Literal::Float(value, _)) => format!("{:?}", value),
+        Expr::Literal(literal) => literal.to_string(),
+        Expr::Unit { .. } => "()".to_string(),
+        Expr::Variable { name, .. } => name.clone(),
+        Expr::Binary {
+            left, op, right, ..
+        } => format_infix(
+            binary_precedence(*op),
+            !op.is_left_associative() || *op == BinaryOp::Concat,
+            left,
+            op.symbol(),
+            right,
+        ),
+        Expr::Comparison {
+            left, op, right, ..
+        } => format_infix(precedence(expr), false, left, op.symbol(), right),
+        Expr::Logical {
+            left, op, right, ..
+        } => format_infix(precedence(expr), false, left, op.symbol(), right),
+        Expr::Assign {
+            target, op, value, ..
+        } => {
+            let symbol = format!("{}=", op.map(|op| op.symbol()).unwrap_or(""));
+            format_infix(precedence(expr), true, target, &symbol, value)
+        }
+        Expr::Unary {
+            op, expr: operand, ..
+        } => {
+            let code = format_operand(Operand::Prefix(precedence(expr)), operand);
+            // Keep `- -x` from lexing as a single `--` token
+            if *op == UnaryOp::Not {
+                format!("not {}", code)
+            } else if code.starts_with(op.symbol()) {
+                format!("{} {}", op.symbol(), code)
+            } else {
+                format!("{}{}", op.symbol(), code)
+            }
+        }
+        Expr::Await { expr: operand, .. } => format!(
+            "await {}",
+            format_operand(Operand::Prefix(precedence(expr)), operand)
+        ),
+        Expr::Pipeline { stages, .. } => stages
+            .iter()
+            .map(|stage| format_operand(Operand::Right(precedence(expr), false), stage))
+            .collect::<Vec<_>>()
+            .join(" |> "),
+        Expr::Call { callee, args, .. } => format!(
+            "{}({})",
+            format_operand(Operand::Prefix(precedence(expr)), callee),
+            format_list(args)
+        ),
+        Expr::Range {
+            start,
+            end,
+            inclusive,
+            ..
+        } => format!(
+            "{}{}{}",
+            format_operand(Operand::Left(precedence(expr), false), start),
+            if *inclusive { "..=" } else { ".." },
+            format_operand(Operand::Right(precedence(expr), false), end)
+        ),
+        Expr::Index { array, index, .. } => format!(
+            "{}[{}]",
+            format_operand(Operand::Prefix(precedence(expr)), array),
+            format_expr(index)
+        ),
+        Expr::Slice {
+            array,
+            start,
+            end,
+            inclusive,
+            ..
+        } => format!(
+            "{}[{}{}{}]",
+            format_operand(Operand::Prefix(precedence(expr)), array),
+            format_operand(Operand::Left(12, false), start),
+            if *inclusive { "..=" } else { ".." },
+            format_operand(Operand::Right(12, false), end)
+        ),
+        Expr::MemberAccess { object, member, .. } => format!(
+            "{}.{}",
+            format_operand(Operand::Prefix(precedence(expr)), object),
+            member
+        ),
+        Expr::List { elements, .. } => format!("[{}]", format_list(elements)),
+        Expr::Tuple { elements, .. } if elements.len() == 1 => {
+            format!("({},)", format_expr(&elements[0]))
+        }
+        Expr::Tuple { elements, .. } => format!("({})", format_list(elements)),
+        Expr::Map { entries, .. } => {
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|(key, value)| format!("{}: {}", format_expr(key), format_expr(value)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Expr::Lambda { params, body, .. } => {
+            let params: Vec<String> = params
+                .iter()
+                .map(|param| match &param.type_annotation {
+                    Some(annotation) => format!("{}: {}", param.name, annotation.kind),
+                    None => param.name.clone(),
+                })
+                .collect();
+            format!("|{}| {}", params.join(", "), format_expr(body))
+        }
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => format!(
+            "if {} then {} else {} end",
+            format_expr(condition),
+            format_expr(then_branch),
+            format_expr(else_branch)
+        ),
+        Expr::StringInterp { parts, .. } => {
+            let mut code = String::from("\"");
+            for part in parts {
+                match part {
+                    StringPart::Literal(text) => code.push_str(&escape_string(text)),
+                    StringPart::Expr(expr) => {
+                        code.push_str("${");
+                        code.push_str(&format_expr(expr));
+                        code.push('}');
+                    }
+                }
+            }
+            code.push('"');
+            code
+        }
+        // Rejected up front by `format_expression`
+        Expr::Block { .. } | Expr::Match { .. } => expr.to_string(),
+    }
+}
+
+fn escape_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn reindent_source(source: &str, line_width: usize) -> String {
     let normalized = source.replace("\r\n", "\n").replace('\r', "\n");
     let mut lines = Vec::new();
     let mut indent_level = 0usize;
@@ -90,7 +431,11 @@ fn reindent_source(source: &str) -> String {
         let (dedent_before, remaining) = strip_leading_dedent(&structural);
         indent_level = indent_level.saturating_sub(dedent_before);
 
-        lines.push(format!("{}{}", indent(indent_level), trimmed_start));
+        lines.push(format!(
+            "{}{}",
+            indent(indent_level),
+            format_line_expression(trimmed_start, &structural, indent_level, line_width)
+        ));
         saw_content = true;
         previous_blank = false;
 
@@ -109,6 +454,68 @@ fn reindent_source(source: &str) -> String {
     }
 }
 
+/// Re-print a one-line `let`, `var` or `return`, or the condition of an
+/// `if ... do` / `while ... do` header, with canonical spacing. Any other
+/// line, or one carrying a comment, is left as written.
+fn format_line_expression(
+    line: &str,
+    structural: &str,
+    indent_level: usize,
+    line_width: usize,
+) -> String {
+    if structural.chars().count() != line.chars().count() {
+        return line.to_string();
+    }
+    // Structural text blanks out strings char by char, so char positions in
+    // it line up with `line`
+    let byte_at = |chars: usize| line.char_indices().nth(chars).map(|(byte, _)| byte);
+
+    let formatted = if starts_with_keyword(structural, "return") {
+        parse_line_expression(&line["return".len()..])
+            .ok()
+            .map(|expr| format_wrapped(&expr, "return ", indent_level, line_width))
+    } else if starts_with_keyword(structural, "let") || starts_with_keyword(structural, "var") {
+        binding_equals(structural)
+            .and_then(|index| byte_at(structural[..index].chars().count()))
+            .and_then(|equals| {
+                let head = line[..equals].replace(':', ": ");
+                let head = head.split_whitespace().collect::<Vec<_>>();
+                let expr = parse_line_expression(&line[equals + 1..]).ok()?;
+                let prefix = format!("{} = ", head.join(" "));
+                Some(format_wrapped(&expr, &prefix, indent_level, line_width))
+            })
+    } else if let Some(keyword) = ["if", "while"]
+        .into_iter()
+        .find(|keyword| starts_with_keyword(structural, keyword))
+    {
+        structural
+            .trim_end()
+            .strip_suffix(" do")
+            .and_then(|head| byte_at(head.chars().count()))
+            .and_then(|end| {
+                let condition = format_expression(&line[keyword.len()..end]).ok()?;
+                Some(format!("{} {} do", keyword, condition))
+            })
+    } else {
+        None
+    };
+
+    formatted.unwrap_or_else(|| line.to_string())
+}
+
+/// Byte index of the `=` that separates a binding from its value
+fn binding_equals(structural: &str) -> Option<usize> {
+    let bytes = structural.as_bytes();
+    (0..bytes.len()).find(|&index| {
+        bytes[index] == b'='
+            && bytes.get(index + 1) != Some(&b'=')
+            && !matches!(
+                index.checked_sub(1).map(|before| bytes[before]),
+                Some(b'=' | b'!' | b'<' | b'>')
+            )
+    })
+}
+
 fn indent(level: usize) -> String {
     "  ".repeat(level)
 }
@@ -116,11 +523,11 @@ fn indent(level: usize) -> String {
 fn strip_leading_dedent(code: &str) -> (usize, &str) {
     let code = code.trim_start();
 
-    if let Some(rest) = code.strip_prefix('}') {
+    if let Some(rest) = code.strip_prefix(['}', ')', ']']) {
         return (1, rest.trim_start());
     }
 
-    for keyword in ["end", "else", "elif"] {
+    for keyword in ["end", "else", "elif", "catch", "finally"] {
         if starts_with_keyword(code, keyword) {
             return (1, code[keyword.len()..].trim_start());
         }
@@ -132,7 +539,7 @@ fn strip_leading_dedent(code: &str) -> (usize, &str) {
 fn indentation_delta(code: &str) -> isize {
     count_keyword(code, "do") as isize + count_keyword(code, "then") as isize
         - count_keyword(code, "end") as isize
-        + brace_delta(code)
+        + bracket_delta(code)
 }
 
 fn starts_with_keyword(code: &str, keyword: &str) -> bool {
@@ -179,12 +586,14 @@ fn count_keyword(code: &str, keyword: &str) -> usize {
     count
 }
 
-fn brace_delta(code: &str) -> isize {
+/// How many more brackets of any kind `code` opens than it closes, so that
+/// lines inside an open one are indented a level deeper
+fn bracket_delta(code: &str) -> isize {
     let mut delta = 0isize;
     for ch in code.chars() {
         match ch {
-            '{' => delta += 1,
-            '}' => delta -= 1,
+            '{' | '(' | '[' => delta += 1,
+            '}' | ')' | ']' => delta -= 1,
             _ => {}
         }
     }
@@ -255,12 +664,12 @@ fn sanitize_for_structure(line: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::format_source;
+    use super::{format_expression, format_source, parse_line_expression, DEFAULT_LINE_WIDTH};
 
     #[test]
     fn formatter_normalizes_indentation_and_whitespace() {
         let source = "fn main() do\n    print \"hi\"   \n\n\n  # note\n    if true do\n      print \"nested\"\n    end\nend\n";
-        let formatted = format_source(source).unwrap();
+        let formatted = format_source(source, DEFAULT_LINE_WIDTH).unwrap();
 
         assert_eq!(
             formatted,
@@ -271,11 +680,135 @@ mod tests {
     #[test]
     fn formatter_preserves_comments_and_match_blocks() {
         let source = "fn describe(x) do\nmatch x {\nSome(v) => print \"ok\",\n# fallback\n_ => print \"nope\"\n}\nend\n";
-        let formatted = format_source(source).unwrap();
+        let formatted = format_source(source, DEFAULT_LINE_WIDTH).unwrap();
 
         assert_eq!(
             formatted,
             "fn describe(x) do\n  match x {\n    Some(v) => print \"ok\",\n    # fallback\n    _ => print \"nope\"\n  }\nend\n"
         );
     }
+
+    #[test]
+    fn formatter_keeps_parentheses_that_change_precedence() {
+        let formatted = format_source(
+            "let x = (1 + 2) * 3\nlet y = 1 + (2 * 3)\n",
+            DEFAULT_LINE_WIDTH,
+        )
+        .unwrap();
+
+        assert_eq!(formatted, "let x = (1 + 2) * 3\nlet y = 1 + 2 * 3\n");
+    }
+
+    #[test]
+    fn formatter_keeps_parentheses_around_negative_bases_and_nested_comparisons() {
+        let formatted = format_source(
+            "let a = (-2) ** 2\nlet f = (-1.5) ** 2\nlet b = (1 < 2) == true\nlet c = x == (y != z)\n",
+            DEFAULT_LINE_WIDTH,
+        )
+        .unwrap();
+
+        assert_eq!(
+            formatted,
+            "let a = (-2) ** 2\nlet f = (-1.5) ** 2\nlet b = (1 < 2) == true\nlet c = x == (y != z)\n"
+        );
+    }
+
+    #[test]
+    fn formatted_expressions_parse_to_the_same_tree() {
+        let sources = [
+            "(-2) ** 2",
+            "-(2 ** 2)",
+            "(1 < 2) == true",
+            "1 < 2 == true",
+            "(a - b) - (c - d)",
+            "-x ** 2",
+            "not (a and b) or c",
+            "xs[(i + 1)] * f(-1, (a + b) * c)",
+        ];
+
+        for source in sources {
+            let formatted = format_expression(source).unwrap();
+            let before = parse_line_expression(source).unwrap().to_string();
+            let after = parse_line_expression(&formatted).unwrap().to_string();
+            assert_eq!(before, after, "{} was formatted as {}", source, formatted);
+        }
+    }
+
+    #[test]
+    fn expression_printer_respects_associativity() {
+        let cases = [
+            ("(a - b) - c", "a - b - c"),
+            ("a - (b - c)", "a - (b - c)"),
+            ("a ** (b ** c)", "a ** b ** c"),
+            ("(a ** b) ** c", "(a ** b) ** c"),
+            ("-(a + b)", "-(a + b)"),
+            ("(-a) ** 2", "(-a) ** 2"),
+            ("(a  or b) and  c", "(a or b) and c"),
+            ("f((x))", "f(x)"),
+            ("xs[(i + 1)]", "xs[i + 1]"),
+            ("xs[(a)..=(n - 1)]", "xs[a..=n - 1]"),
+            ("(a)..(n + 1)", "a..n + 1"),
+            ("a..=(b..c)", "a..=(b..c)"),
+        ];
+
+        for (source, expected) in cases {
+            assert_eq!(format_expression(source).unwrap(), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn formatter_canonicalizes_statement_spacing() {
+        let source = "fn f(x) do\nlet  y=x*(2+1)\nvar z:Int =  3\nif y>1  and not  false do\nreturn  y-1\nend\nwhile z<10 do\nz = z + 1\nend\nreturn z\nend\n";
+        let formatted = format_source(source, DEFAULT_LINE_WIDTH).unwrap();
+
+        assert_eq!(
+            formatted,
+            "fn f(x) do\n  let y = x * (2 + 1)\n  var z: Int = 3\n  if y > 1 and not false do\n    return y - 1\n  end\n  while z < 10 do\n    z = z + 1\n  end\n  return z\nend\n"
+        );
+    }
+
+    #[test]
+    fn formatter_wraps_calls_longer_than_the_line_width() {
+        let source = "fn main() do\nlet total = add(100000, 200000, 300000)\nreturn [1, 2]\nend\n";
+        let formatted = format_source(source, 30).unwrap();
+
+        assert_eq!(
+            formatted,
+            "fn main() do\n  let total = add(\n    100000,\n    200000,\n    300000,\n  )\n  return [1, 2]\nend\n"
+        );
+        assert_eq!(format_source(&formatted, 30).unwrap(), formatted);
+        assert_eq!(
+            format_source(source, DEFAULT_LINE_WIDTH).unwrap(),
+            "fn main() do\n  let total = add(100000, 200000, 300000)\n  return [1, 2]\nend\n"
+        );
+    }
+
+    #[test]
+    fn formatter_is_idempotent_on_examples() {
+        // Writes function signatures without `do`, so it doesn't parse
+        const UNPARSED: &[&str] = &["brainfuck_simple.nm"];
+
+        let examples = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+        let mut checked = 0;
+
+        for entry in std::fs::read_dir(examples).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "nm")
+                || path
+                    .file_name()
+                    .is_some_and(|name| UNPARSED.iter().any(|unparsed| name == *unparsed))
+            {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            let once = format_source(&source, DEFAULT_LINE_WIDTH)
+                .unwrap_or_else(|err| panic!("{} doesn't format: {}", path.display(), err));
+
+            let twice = format_source(&once, DEFAULT_LINE_WIDTH).unwrap();
+            assert_eq!(once, twice, "{} is not stable", path.display());
+            checked += 1;
+        }
+
+        assert!(checked > 0);
+    }
 }
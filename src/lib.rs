@@ -0,0 +1,157 @@
+//! The Nevermind compiler as a library
+//!
+//! `compile_str` and `check_str` are the entry points for embedding the
+//! compiler; `compile_source` and `diagnostics` take options for the rest.
+//! The `nevermind` binary drives the same stages one at a time so it can
+//! report progress and stop early.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use nevermind_codegen::PythonModuleContext;
+use nevermind_common::{Severity, Span};
+
+pub use nevermind_common::{CompileOptions, Diagnostic, PythonTarget};
+
+/// The problems that stopped a compilation
+#[derive(Debug, Clone)]
+pub struct CompileError {
+    /// Every problem found by the stage that failed
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+impl From<Vec<Diagnostic>> for CompileError {
+    fn from(diagnostics: Vec<Diagnostic>) -> Self {
+        Self { diagnostics }
+    }
+}
+
+/// Compile a program to Python with the default options
+pub fn compile_str(source: &str) -> Result<String, CompileError> {
+    compile_source(source, &CompileOptions::default()).map_err(CompileError::from)
+}
+
+/// Check a program for errors without generating any code
+///
+/// Warnings don't fail the check and are left out; see `diagnostics` for
+/// those.
+pub fn check_str(source: &str) -> Result<(), Vec<Diagnostic>> {
+    let errors: Vec<Diagnostic> = diagnostics(source, None, &CompileOptions::default())
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Run every checking phase over a program, returning each problem found,
+/// errors first
+///
+/// Local imports are looked up in `base_dir` when one is given.
+pub fn diagnostics(
+    source: &str,
+    base_dir: Option<PathBuf>,
+    options: &CompileOptions,
+) -> Vec<Diagnostic> {
+    let tokens = match nevermind_lexer::Lexer::new(source).tokenize() {
+        Ok(tokens) => tokens,
+        Err(err) => return vec![Diagnostic::error(err.message, err.span)],
+    };
+
+    let statements = match nevermind_parser::Parser::from_tokens(tokens).parse() {
+        Ok(statements) => statements,
+        Err(err) => return vec![Diagnostic::error(err.message, err.span)],
+    };
+
+    let mut resolver = match base_dir {
+        Some(base_dir) => nevermind_name_resolver::NameResolver::with_base_dir(base_dir),
+        None => nevermind_name_resolver::NameResolver::new(),
+    }
+    .with_shadow_warnings(options.warn_shadowing);
+    let unused = match resolver.resolve(&statements) {
+        Ok(unused) => unused,
+        Err(errors) => return errors.iter().map(|error| error.to_diagnostic()).collect(),
+    };
+
+    let mut checker =
+        nevermind_type_checker::TypeChecker::new().with_strict_match(options.strict_match);
+    let errors = checker.check_all(&statements);
+    errors
+        .iter()
+        .map(|error| error.to_diagnostic())
+        .chain(unused.iter().map(|warning| warning.to_warning()))
+        .chain(
+            checker
+                .warnings()
+                .iter()
+                .map(|warning| warning.to_warning()),
+        )
+        .collect()
+}
+
+/// Compile a program to Python
+///
+/// Imports are left for Python to find at runtime. Every problem that
+/// stopped the compilation is returned as a diagnostic.
+pub fn compile_source(source: &str, options: &CompileOptions) -> Result<String, Vec<Diagnostic>> {
+    compile_module(source, None, options)
+}
+
+/// Compile a program to Python, resolving its local imports through
+/// `module_context` when one is given
+pub fn compile_module(
+    source: &str,
+    module_context: Option<PythonModuleContext>,
+    options: &CompileOptions,
+) -> Result<String, Vec<Diagnostic>> {
+    let tokens = nevermind_lexer::Lexer::new(source)
+        .tokenize()
+        .map_err(|err| vec![Diagnostic::error(err.message, err.span)])?;
+
+    let statements = nevermind_parser::Parser::from_tokens(tokens)
+        .parse()
+        .map_err(|err| vec![Diagnostic::error(err.message, err.span)])?;
+
+    let mut resolver = match &module_context {
+        Some(context) => {
+            nevermind_name_resolver::NameResolver::with_base_dir(context.base_dir.clone())
+        }
+        None => nevermind_name_resolver::NameResolver::new(),
+    };
+    if let Err(errors) = resolver.resolve(&statements) {
+        return Err(errors.iter().map(|error| error.to_diagnostic()).collect());
+    }
+
+    let mut checker =
+        nevermind_type_checker::TypeChecker::new().with_strict_match(options.strict_match);
+    checker
+        .check(&statements)
+        .map_err(|error| vec![error.to_diagnostic()])?;
+
+    let mut mir_program =
+        nevermind_mir::lower_program_with_types(&statements, &checker.node_types(), options)
+            .map_err(|err| vec![Diagnostic::error(err.to_string(), Span::dummy())])?;
+    nevermind_mir::optimize::remove_dead_code(&mut mir_program);
+
+    match module_context {
+        Some(context) => nevermind_codegen::generate_with_context(&mir_program, context, options),
+        None => nevermind_codegen::generate(&mir_program, options),
+    }
+    .map_err(|err| vec![Diagnostic::error(err.to_string(), Span::dummy())])
+}
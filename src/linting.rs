@@ -1,16 +1,106 @@
 use std::error::Error;
 use std::path::{Path, PathBuf};
 
+use nevermind_ast::expr::StringPart;
+use nevermind_ast::stmt::ClassMember;
+use nevermind_ast::visit::{walk_expr, Visitor};
+use nevermind_ast::{ComparisonOp, Expr, Parameter, Pattern, Stmt};
+use nevermind_common::{SourceLocation, Span};
+use nevermind_name_resolver::{NameError, NameErrorKind};
+
 use crate::formatting;
 
+/// A problem found by one lint rule
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct LintWarning {
-    line: usize,
-    message: String,
+pub struct LintWarning {
+    /// The code of the rule that produced this warning
+    pub code: &'static str,
+    pub span: Span,
+    pub message: String,
+}
+
+/// A named check over a parsed program.
+///
+/// Rules see the raw source as well as the statements, so both text-level
+/// style checks and checks over the syntax tree fit the same shape.
+pub struct LintRule {
+    pub code: &'static str,
+    pub check: fn(&str, &[Stmt], &LintOptions) -> Vec<Finding>,
+}
+
+/// Which rules run, and the limits they check against
+#[derive(Debug, Clone)]
+pub struct LintOptions {
+    /// Codes of the rules to skip
+    pub disabled: Vec<String>,
+
+    /// Longest line, in characters, the `long-line` rule allows
+    pub line_width: usize,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            disabled: Vec::new(),
+            line_width: formatting::DEFAULT_LINE_WIDTH,
+        }
+    }
+}
+
+/// Where a rule fired and what it has to say
+pub type Finding = (Span, String);
+
+/// Every rule `nevermind lint` runs; add new rules here
+pub const RULES: &[LintRule] = &[
+    LintRule {
+        code: "unformatted",
+        check: check_formatting,
+    },
+    LintRule {
+        code: "tab-indent",
+        check: check_tab_indent,
+    },
+    LintRule {
+        code: "trailing-whitespace",
+        check: check_trailing_whitespace,
+    },
+    LintRule {
+        code: "long-line",
+        check: check_long_lines,
+    },
+    LintRule {
+        code: "todo-marker",
+        check: check_todo_markers,
+    },
+    LintRule {
+        code: "unused-binding",
+        check: check_unused_bindings,
+    },
+    LintRule {
+        code: "shadowed-binding",
+        check: check_shadowed_bindings,
+    },
+    LintRule {
+        code: "unreachable-code",
+        check: check_unreachable_code,
+    },
+    LintRule {
+        code: "self-comparison",
+        check: check_self_comparisons,
+    },
+];
+
+/// Fail unless `code` names one of the [`RULES`]
+pub fn check_rule_name(code: &str) -> Result<(), String> {
+    if RULES.iter().any(|rule| rule.code == code) {
+        Ok(())
+    } else {
+        Err(format!("unknown lint rule '{}'", code))
+    }
 }
 
 /// Lint one or more Nevermind files.
-pub fn lint_paths(inputs: Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+pub fn lint_paths(inputs: Vec<PathBuf>, options: &LintOptions) -> Result<(), Box<dyn Error>> {
     if inputs.is_empty() {
         return Err("no input files provided".into());
     }
@@ -19,15 +109,27 @@ pub fn lint_paths(inputs: Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
 
     for input in &inputs {
         let source = std::fs::read_to_string(input)?;
-        let warnings = lint_source(input, &source)?;
+        let warnings = lint_source(input, &source, options)?;
 
         if warnings.is_empty() {
             println!("clean: {}", input.display());
             continue;
         }
 
+        let lines: Vec<&str> = source.lines().collect();
         for warning in &warnings {
-            println!("{}:{}: {}", input.display(), warning.line, warning.message);
+            let start = &warning.span.start;
+            println!(
+                "{}:{}:{}: warning[{}]: {}",
+                input.display(),
+                start.line,
+                start.column,
+                warning.code,
+                warning.message
+            );
+            if let Some(line) = lines.get(start.line.saturating_sub(1)) {
+                println!("{:>5} | {}", start.line, line);
+            }
         }
         total_warnings += warnings.len();
     }
@@ -39,7 +141,11 @@ pub fn lint_paths(inputs: Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn lint_source(path: &Path, source: &str) -> Result<Vec<LintWarning>, Box<dyn Error>> {
+fn lint_source(
+    path: &Path,
+    source: &str,
+    options: &LintOptions,
+) -> Result<Vec<LintWarning>, Box<dyn Error>> {
     let mut lexer = nevermind_lexer::Lexer::new(source);
     let tokens = lexer.tokenize()?;
 
@@ -66,64 +172,560 @@ fn lint_source(path: &Path, source: &str) -> Result<Vec<LintWarning>, Box<dyn Er
     let mut checker = nevermind_type_checker::TypeChecker::new();
     checker.check(&statements)?;
 
-    let mut warnings = Vec::new();
+    let mut warnings: Vec<LintWarning> = RULES
+        .iter()
+        .filter(|rule| !options.disabled.iter().any(|code| code == rule.code))
+        .flat_map(|rule| {
+            (rule.check)(source, &statements, options)
+                .into_iter()
+                .map(|(span, message)| LintWarning {
+                    code: rule.code,
+                    span,
+                    message,
+                })
+        })
+        .collect();
+    warnings.sort_by_key(|warning| (warning.span.start.line, warning.span.start.column));
 
-    if source != formatting::format_source(source)? {
-        warnings.push(LintWarning {
-            line: 1,
-            message: "file is not formatted; run `nevermind fmt --write <file>`".to_string(),
-        });
+    Ok(warnings)
+}
+
+/// A finding about a whole source line
+fn line_finding(line: usize, message: &str) -> Finding {
+    (
+        Span::point(SourceLocation::new(None, line, 1, 0)),
+        message.to_string(),
+    )
+}
+
+/// Run `flag` over every line and warn with `message` where it holds
+fn check_lines(source: &str, message: &str, flag: impl Fn(&str) -> bool) -> Vec<Finding> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| flag(line))
+        .map(|(index, _)| line_finding(index + 1, message))
+        .collect()
+}
+
+fn check_formatting(source: &str, _: &[Stmt], options: &LintOptions) -> Vec<Finding> {
+    match formatting::format_source(source, options.line_width) {
+        Ok(formatted) if formatted != source => vec![line_finding(
+            1,
+            "file is not formatted; run `nevermind fmt --write <file>`",
+        )],
+        _ => Vec::new(),
     }
+}
 
-    for (index, line) in source.lines().enumerate() {
-        let line_number = index + 1;
-        let leading = line
-            .chars()
-            .take_while(|ch| ch.is_whitespace())
-            .collect::<String>();
+fn check_tab_indent(source: &str, _: &[Stmt], _: &LintOptions) -> Vec<Finding> {
+    check_lines(
+        source,
+        "tabs are not allowed for indentation; use spaces",
+        |line| {
+            line.chars()
+                .take_while(|ch| ch.is_whitespace())
+                .any(|ch| ch == '\t')
+        },
+    )
+}
 
-        if leading.contains('\t') {
-            warnings.push(LintWarning {
-                line: line_number,
-                message: "tabs are not allowed for indentation; use spaces".to_string(),
-            });
+fn check_trailing_whitespace(source: &str, _: &[Stmt], _: &LintOptions) -> Vec<Finding> {
+    check_lines(source, "trailing whitespace", |line| {
+        line.ends_with(' ') || line.ends_with('\t')
+    })
+}
+
+fn check_long_lines(source: &str, _: &[Stmt], options: &LintOptions) -> Vec<Finding> {
+    let message = format!("line exceeds {} characters", options.line_width);
+    check_lines(source, &message, |line| {
+        line.chars().count() > options.line_width
+    })
+}
+
+fn check_todo_markers(source: &str, _: &[Stmt], _: &LintOptions) -> Vec<Finding> {
+    check_lines(source, "leftover TODO/FIXME marker", |line| {
+        line.contains("TODO") || line.contains("FIXME")
+    })
+}
+
+fn check_unused_bindings(_: &str, program: &[Stmt], _: &LintOptions) -> Vec<Finding> {
+    let mut scopes = ScopeWalker::default();
+    scopes.walk_program(program);
+    scopes.unused
+}
+
+/// The resolver's warnings for `program`, shadowing included, or none if it
+/// doesn't resolve
+fn resolver_warnings(program: &[Stmt]) -> Vec<NameError> {
+    let mut resolver = nevermind_name_resolver::NameResolver::new().with_shadow_warnings(true);
+    resolver.resolve(program).unwrap_or_default()
+}
+
+/// Report the resolver's shadowing warnings, the ones `--warn-shadowing`
+/// turns on when compiling
+fn check_shadowed_bindings(_: &str, program: &[Stmt], _: &LintOptions) -> Vec<Finding> {
+    resolver_warnings(program)
+        .into_iter()
+        .filter_map(|warning| {
+            let NameErrorKind::ShadowedBinding { name, shadowed } = &warning.kind else {
+                return None;
+            };
+            let declared = warning
+                .context
+                .iter()
+                .find_map(|context| context.span.as_ref())?;
+            let message = format!(
+                "`{}` shadows the {} at line {}",
+                name, shadowed, declared.start.line
+            );
+            Some((warning.span, message))
+        })
+        .collect()
+}
+
+/// Report the resolver's unreachable code warnings, naming the statement
+/// that leaves the block
+fn check_unreachable_code(source: &str, program: &[Stmt], _: &LintOptions) -> Vec<Finding> {
+    resolver_warnings(program)
+        .into_iter()
+        .filter(|warning| warning.kind == NameErrorKind::UnreachableCode)
+        .filter_map(|warning| {
+            let exit = warning
+                .context
+                .iter()
+                .find_map(|context| context.span.as_ref())?;
+            let keyword: String = source
+                .lines()
+                .nth(exit.start.line.checked_sub(1)?)?
+                .chars()
+                .skip(exit.start.column.saturating_sub(1))
+                .take_while(|c| c.is_alphabetic())
+                .collect();
+            Some((
+                warning.span,
+                format!("unreachable code after `{}`", keyword),
+            ))
+        })
+        .collect()
+}
+
+fn check_self_comparisons(_: &str, program: &[Stmt], _: &LintOptions) -> Vec<Finding> {
+    let mut finder = SelfComparisons(Vec::new());
+    finder.visit_block(program);
+    finder.0
+}
+
+/// Finds comparisons whose two sides are the same pure expression
+struct SelfComparisons(Vec<Finding>);
+
+impl SelfComparisons {
+    fn check(&mut self, expr: &Expr) {
+        let Expr::Comparison {
+            left,
+            op,
+            right,
+            span,
+            ..
+        } = expr
+        else {
+            return;
+        };
+        let text = formatting::format_expr(left);
+        if !is_pure(left) || text != formatting::format_expr(right) {
+            return;
         }
+        let outcome = match op {
+            ComparisonOp::Eq | ComparisonOp::Le | ComparisonOp::Ge => "true",
+            ComparisonOp::Ne | ComparisonOp::Lt | ComparisonOp::Gt => "false",
+        };
+        self.0.push((
+            span.clone(),
+            format!(
+                "`{} {} {}` compares a value with itself and is always {}",
+                text,
+                op.symbol(),
+                text,
+                outcome
+            ),
+        ));
+    }
+}
 
-        if line.ends_with(' ') || line.ends_with('\t') {
-            warnings.push(LintWarning {
-                line: line_number,
-                message: "trailing whitespace".to_string(),
-            });
+impl Visitor for SelfComparisons {
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.check(expr);
+        walk_expr(self, expr);
+    }
+}
+
+/// Whether evaluating `expr` twice is guaranteed to give the same value
+fn is_pure(expr: &Expr) -> bool {
+    match expr {
+        Expr::Variable { .. } | Expr::Literal(_) => true,
+        Expr::MemberAccess { object, .. } => is_pure(object),
+        Expr::Index { array, index, .. } => is_pure(array) && is_pure(index),
+        _ => false,
+    }
+}
+
+/// A name bound in one scope, with whether anything has read it yet
+struct Binding {
+    name: String,
+    span: Span,
+    used: bool,
+    /// Whether an unread binding is worth a warning: only local `let` and
+    /// `var` bindings are, since parameters and globals are read elsewhere
+    report_unused: bool,
+}
+
+/// Tracks lexical scopes to find unread local bindings.
+///
+/// The outermost scope holds the module's globals; everything pushed after
+/// it is local to a function, block or loop.
+#[derive(Default)]
+struct ScopeWalker {
+    scopes: Vec<Vec<Binding>>,
+    unused: Vec<Finding>,
+}
+
+impl ScopeWalker {
+    fn walk_program(&mut self, program: &[Stmt]) {
+        self.scopes.push(Vec::new());
+        for stmt in program {
+            self.walk_stmt(stmt);
         }
+        self.scopes.pop();
+    }
 
-        if line.chars().count() > 100 {
-            warnings.push(LintWarning {
-                line: line_number,
-                message: "line exceeds 100 characters".to_string(),
-            });
+    fn push(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    fn pop(&mut self) {
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+        for binding in scope {
+            if binding.report_unused && !binding.used {
+                self.unused.push((
+                    binding.span,
+                    format!(
+                        "`{}` is never read; prefix it with `_` if that is intended",
+                        binding.name
+                    ),
+                ));
+            }
         }
+    }
+
+    fn is_local(&self) -> bool {
+        self.scopes.len() > 1
+    }
 
-        if line.contains("TODO") || line.contains("FIXME") {
-            warnings.push(LintWarning {
-                line: line_number,
-                message: "leftover TODO/FIXME marker".to_string(),
+    /// Bind a name that is not a local `let`, such as a parameter
+    fn bind(&mut self, name: &str, span: &Span) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Binding {
+                name: name.to_string(),
+                span: span.clone(),
+                used: false,
+                report_unused: false,
             });
         }
     }
 
-    Ok(warnings)
+    /// Bind a `let` or `var`
+    fn bind_let(&mut self, name: &str, span: &Span) {
+        let report_unused = self.is_local() && !name.starts_with('_');
+        self.bind(name, span);
+        if let Some(binding) = self.scopes.last_mut().and_then(|scope| scope.last_mut()) {
+            binding.report_unused = report_unused;
+        }
+    }
+
+    fn bind_pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Variable { name, span } => self.bind(name, span),
+            Pattern::Wildcard { .. } | Pattern::Literal { .. } => {}
+            Pattern::Or { patterns, .. }
+            | Pattern::Tuple { patterns, .. }
+            | Pattern::List { patterns, .. }
+            | Pattern::Constructor { args: patterns, .. } => {
+                for pattern in patterns {
+                    self.bind_pattern(pattern);
+                }
+            }
+            Pattern::ListCons { head, tail, .. } => {
+                self.bind_pattern(head);
+                self.bind_pattern(tail);
+            }
+            Pattern::Struct { fields, .. } => {
+                for field in fields {
+                    self.bind_pattern(&field.pattern);
+                }
+            }
+            Pattern::Range { start, end, .. } => {
+                self.bind_pattern(start);
+                self.bind_pattern(end);
+            }
+        }
+    }
+
+    fn mark_used(&mut self, name: &str) {
+        let binding = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|binding| binding.name == name);
+        if let Some(binding) = binding {
+            binding.used = true;
+        }
+    }
+
+    fn walk_block(&mut self, stmts: &[Stmt]) {
+        self.push();
+        for stmt in stmts {
+            self.walk_stmt(stmt);
+        }
+        self.pop();
+    }
+
+    fn walk_function(&mut self, params: &[Parameter], body: &Expr, span: &Span) {
+        self.push();
+        for param in params {
+            if let Some(default) = &param.default_value {
+                self.walk_expr(default);
+            }
+            self.bind(&param.name, span);
+        }
+        self.walk_expr(body);
+        self.pop();
+    }
+
+    fn walk_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Export { stmt, .. } => self.walk_stmt(stmt),
+            Stmt::Let {
+                name, value, span, ..
+            } => {
+                self.walk_expr(value);
+                self.bind_let(name, span);
+            }
+            Stmt::Function {
+                name,
+                params,
+                body,
+                span,
+                ..
+            } => {
+                self.bind(name, span);
+                self.walk_function(params, body, span);
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.walk_expr(condition);
+                self.walk_block(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.walk_block(else_branch);
+                }
+            }
+            Stmt::While {
+                condition, body, ..
+            } => {
+                self.walk_expr(condition);
+                self.walk_block(body);
+            }
+            Stmt::For {
+                variable,
+                iter,
+                body,
+                ..
+            } => {
+                self.walk_expr(iter);
+                self.push();
+                self.bind_pattern(variable);
+                self.walk_block(body);
+                self.pop();
+            }
+            Stmt::Match {
+                scrutinee, arms, ..
+            } => {
+                self.walk_expr(scrutinee);
+                for arm in arms {
+                    self.walk_arm(&arm.pattern, arm.guard.as_ref(), &arm.body);
+                }
+            }
+            Stmt::Return { value, .. } => {
+                if let Some(value) = value {
+                    self.walk_expr(value);
+                }
+            }
+            Stmt::Try {
+                body,
+                catch_var,
+                catch_body,
+                finally_body,
+                span,
+                ..
+            } => {
+                self.walk_block(body);
+                if let Some(catch_body) = catch_body {
+                    self.push();
+                    if let Some(name) = catch_var {
+                        self.bind(name, span);
+                    }
+                    self.walk_block(catch_body);
+                    self.pop();
+                }
+                if let Some(finally_body) = finally_body {
+                    self.walk_block(finally_body);
+                }
+            }
+            Stmt::Raise { value, .. } => self.walk_expr(value),
+            Stmt::ExprStmt { expr, .. } => self.walk_expr(expr),
+            Stmt::Class { members, span, .. } => {
+                for member in members {
+                    match member {
+                        ClassMember::Field { default_value, .. } => {
+                            if let Some(default) = default_value {
+                                self.walk_expr(default);
+                            }
+                        }
+                        ClassMember::Method { params, body, .. } => {
+                            self.walk_function(params, body, span);
+                        }
+                    }
+                }
+            }
+            Stmt::TypeAlias { .. }
+            | Stmt::Break { .. }
+            | Stmt::Continue { .. }
+            | Stmt::Import { .. } => {}
+        }
+    }
+
+    fn walk_arm(&mut self, pattern: &Pattern, guard: Option<&Expr>, body: &Expr) {
+        self.push();
+        self.bind_pattern(pattern);
+        if let Some(guard) = guard {
+            self.walk_expr(guard);
+        }
+        self.walk_expr(body);
+        self.pop();
+    }
+
+    fn walk_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Variable { name, .. } => self.mark_used(name),
+            // A plain assignment writes its target without reading it
+            Expr::Assign {
+                target,
+                op: None,
+                value,
+                ..
+            } if matches!(**target, Expr::Variable { .. }) => self.walk_expr(value),
+            Expr::Lambda {
+                params, body, span, ..
+            } => self.walk_function(params, body, span),
+            Expr::Block { statements, .. } => self.walk_block(statements),
+            Expr::Match {
+                scrutinee, arms, ..
+            } => {
+                self.walk_expr(scrutinee);
+                for arm in arms {
+                    self.walk_arm(&arm.pattern, arm.guard.as_deref(), &arm.body);
+                }
+            }
+            _ => walk_children(expr, &mut |child| self.walk_expr(child)),
+        }
+    }
+}
+
+/// Call `visit` on each direct subexpression of a block-free `expr`
+fn walk_children<'a>(expr: &'a Expr, visit: &mut dyn FnMut(&'a Expr)) {
+    match expr {
+        Expr::Literal(_) | Expr::Unit { .. } | Expr::Variable { .. } => {}
+        Expr::Binary { left, right, .. }
+        | Expr::Comparison { left, right, .. }
+        | Expr::Logical { left, right, .. } => {
+            visit(left);
+            visit(right);
+        }
+        Expr::Unary { expr, .. } | Expr::Await { expr, .. } => visit(expr),
+        Expr::Call { callee, args, .. } => {
+            visit(callee);
+            args.iter().for_each(&mut *visit);
+        }
+        Expr::Pipeline {
+            stages: elements, ..
+        }
+        | Expr::List { elements, .. }
+        | Expr::Tuple { elements, .. } => elements.iter().for_each(&mut *visit),
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            visit(condition);
+            visit(then_branch);
+            visit(else_branch);
+        }
+        Expr::StringInterp { parts, .. } => {
+            for part in parts {
+                if let StringPart::Expr(expr) = part {
+                    visit(expr);
+                }
+            }
+        }
+        Expr::Map { entries, .. } => {
+            for (key, value) in entries {
+                visit(key);
+                visit(value);
+            }
+        }
+        Expr::Index { array, index, .. } => {
+            visit(array);
+            visit(index);
+        }
+        Expr::Range { start, end, .. } => {
+            visit(start);
+            visit(end);
+        }
+        Expr::Slice {
+            array, start, end, ..
+        } => {
+            visit(array);
+            visit(start);
+            visit(end);
+        }
+        Expr::Assign { target, value, .. } => {
+            visit(target);
+            visit(value);
+        }
+        Expr::MemberAccess { object, .. } => visit(object),
+        Expr::Lambda { body, .. } => visit(body),
+        Expr::Block { .. } | Expr::Match { .. } => {}
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
-    use super::lint_source;
+    use super::{lint_source, LintOptions, LintWarning};
 
     #[test]
     fn lint_reports_style_issues() {
         let source = "fn main() do\n    print \"hi\"  # TODO cleanup   \nend\n";
-        let warnings = lint_source(Path::new("sample.nm"), source).unwrap();
+        let warnings =
+            lint_source(Path::new("sample.nm"), source, &LintOptions::default()).unwrap();
 
         assert!(warnings
             .iter()
@@ -139,7 +741,65 @@ mod tests {
     #[test]
     fn lint_accepts_clean_source() {
         let source = "fn main() do\n  print \"hi\"\nend\n";
-        let warnings = lint_source(Path::new("sample.nm"), source).unwrap();
+        let warnings =
+            lint_source(Path::new("sample.nm"), source, &LintOptions::default()).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    fn codes(warnings: &[LintWarning]) -> Vec<&'static str> {
+        warnings.iter().map(|warning| warning.code).collect()
+    }
+
+    fn lint_body(body: &str) -> Vec<LintWarning> {
+        let source = format!("fn main() do\n{}end\n", body);
+        lint_source(Path::new("sample.nm"), &source, &LintOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn unused_binding_is_reported_at_its_let() {
+        let warnings = lint_body("  let total = 1\n  let _skipped = 2\n  print \"hi\"\n");
+        assert_eq!(codes(&warnings), vec!["unused-binding"]);
+        assert_eq!(warnings[0].span.start.line, 2);
+        assert!(warnings[0].message.contains("`total`"));
+
+        let warnings = lint_body("  var total = 1\n  total = 2\n");
+        assert_eq!(codes(&warnings), vec!["unused-binding"]);
+    }
+
+    #[test]
+    fn shadowing_an_outer_local_is_reported() {
+        let body = "  let x = 1\n  if x > 0 do\n    let x = 2\n    print x\n  end\n  print x\n";
+        let warnings = lint_body(body);
+        assert_eq!(codes(&warnings), vec!["shadowed-binding"]);
+        assert_eq!(warnings[0].span.start.line, 4);
+        assert!(warnings[0].message.contains("the variable at line 2"));
+
+        let body =
+            "  let _x = 1\n  if _x > 0 do\n    let _x = 2\n    print _x\n  end\n  print _x\n";
+        assert!(lint_body(body).is_empty());
+    }
+
+    #[test]
+    fn statements_after_return_are_unreachable() {
+        let warnings = lint_body("  return 1\n  print \"never\"\n");
+        assert_eq!(codes(&warnings), vec!["unreachable-code"]);
+        assert_eq!(warnings[0].span.start.line, 3);
+        assert_eq!(warnings[0].message, "unreachable code after `return`");
+    }
+
+    #[test]
+    fn comparing_a_value_with_itself_is_reported() {
+        let warnings = lint_body("  let xs = [1, 2]\n  print xs[0] != xs[0]\n");
+        assert_eq!(codes(&warnings), vec!["self-comparison"]);
+        assert!(warnings[0].message.contains("always false"));
+
+        let warnings = lint_body("  let xs = [1, 2]\n  print xs[0] == xs[1]\n");
         assert!(warnings.is_empty());
     }
+
+    #[test]
+    fn semantic_rules_accept_clean_code() {
+        let body = "  let x = 1\n  var total = x\n  total = total + 1\n  print total\n";
+        assert!(lint_body(body).is_empty());
+    }
 }
@@ -1,13 +1,24 @@
 //! Nevermind CLI - Command-line interface for the Nevermind language
 
+mod cache;
+mod config;
 mod formatting;
 mod linting;
+mod preprocess;
+mod session;
+mod testing;
 
 use std::fs;
 use std::io::{self, BufRead, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use nevermind::{CompileOptions, PythonTarget};
+use nevermind_common::{ColorChoice, Diagnostic, Severity};
+
+use cache::CacheMode;
+use config::Config;
 
 #[derive(Parser)]
 #[command(name = "nevermind")]
@@ -16,22 +27,87 @@ use clap::{Parser, Subcommand};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// When to color diagnostics (auto, always or never)
+    #[arg(long, global = true, default_value = "auto")]
+    color: ColorChoice,
+}
+
+/// Whether diagnostics are rendered in color, settled once from `--color`
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// A compiler stage whose output `compile --emit` prints, or bytecode to
+/// write in place of Python source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum EmitStage {
+    /// The lexer's token stream
+    Tokens,
+    /// The parsed syntax tree
+    Ast,
+    /// Top-level bindings with their checked types
+    Resolved,
+    /// The lowered MIR program
+    Mir,
+    /// The generated Python code
+    Py,
+    /// A `.pyc` file, compiled from the generated Python by Python itself
+    Pyc,
+}
+
+impl EmitStage {
+    /// Whether the stage is printed to stdout rather than written to a file
+    fn is_printed(self) -> bool {
+        self != EmitStage::Pyc
+    }
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Compile a Nevermind file
+    /// Compile Nevermind files
     Compile {
-        /// Input file
-        input: PathBuf,
+        /// Input file(s) (`-` reads standard input)
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
 
-        /// Output file (default: input with .py extension)
+        /// Output file for a single input (default: input with .py
+        /// extension, or stdout for `-`)
         #[arg(short, long)]
         output: Option<PathBuf>,
 
         /// Parse only (don't compile)
         #[arg(long)]
         parse_only: bool,
+
+        /// Fold constant expressions before generating code
+        #[arg(long)]
+        opt: bool,
+
+        /// Directory to write compiled files to (default: next to each
+        /// input)
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+
+        /// Python version to generate code for (py3 or py310; default py3)
+        #[arg(long)]
+        target: Option<PythonTarget>,
+
+        /// Stop after a stage and print its output instead of writing a file
+        #[arg(long, value_enum)]
+        emit: Option<EmitStage>,
+
+        /// Make a match that isn't provably exhaustive an error
+        #[arg(long)]
+        strict_match: bool,
+
+        /// Warn when a `let` or parameter shadows an outer binding; prefix
+        /// a name with `_` to silence it
+        #[arg(long)]
+        warn_shadowing: bool,
+
+        /// Recompile even when the source is unchanged since the last
+        /// compile
+        #[arg(long)]
+        force: bool,
     },
 
     /// Run a Nevermind file
@@ -39,6 +115,11 @@ enum Commands {
         /// Input file
         input: PathBuf,
 
+        /// Keep the compiled Python next to the input instead of a
+        /// temporary file
+        #[arg(long)]
+        keep: bool,
+
         /// Arguments to pass to the program
         #[arg(trailing_var_arg = true)]
         args: Vec<String>,
@@ -47,15 +128,25 @@ enum Commands {
     /// Start the REPL
     Repl,
 
-    /// Check a file for errors (without compiling)
+    /// Check files for errors (without compiling)
     Check {
-        /// Input file
-        input: PathBuf,
+        /// Input file(s) (`-` reads standard input)
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+
+        /// Make a match that isn't provably exhaustive an error
+        #[arg(long)]
+        strict_match: bool,
+
+        /// Warn when a `let` or parameter shadows an outer binding; prefix
+        /// a name with `_` to silence it
+        #[arg(long)]
+        warn_shadowing: bool,
     },
 
     /// Format a Nevermind file
     Fmt {
-        /// Input file(s)
+        /// Input file(s) (`-` reads standard input)
         inputs: Vec<PathBuf>,
 
         /// Write to file instead of stdout
@@ -65,33 +156,130 @@ enum Commands {
         /// Check if files are formatted without modifying them
         #[arg(long)]
         check: bool,
+
+        /// Longest line before calls and lists are wrapped (default 100)
+        #[arg(long)]
+        line_width: Option<usize>,
     },
 
     /// Lint a Nevermind file
     Lint {
         /// Input file(s)
         inputs: Vec<PathBuf>,
+
+        /// Run a rule that nevermind.toml disables
+        #[arg(long, value_name = "RULE")]
+        enable: Vec<String>,
+
+        /// Skip a rule
+        #[arg(long, value_name = "RULE")]
+        disable: Vec<String>,
+
+        /// Longest line the long-line rule allows (default 100)
+        #[arg(long)]
+        line_width: Option<usize>,
+    },
+
+    /// Run Nevermind files and report failed assertions
+    Test {
+        /// Input file(s)
+        inputs: Vec<PathBuf>,
+    },
+
+    /// Explain an error code, such as E0001
+    Explain {
+        /// The code shown in the diagnostic
+        code: String,
     },
 }
 
 fn main() {
     let cli = Cli::parse();
+    COLOR.store(cli.color.use_color(), Ordering::Relaxed);
+
+    // Flags override the defaults in nevermind.toml. A broken file
+    // shouldn't stop commands that don't need it, such as `explain`.
+    let config = Config::load(Path::new(".")).unwrap_or_else(|e| {
+        eprintln!("Warning: {}; using the default settings", e);
+        Config::default()
+    });
 
     let result = match cli.command {
         Commands::Compile {
-            input,
+            inputs,
             output,
+            out_dir,
             parse_only,
-        } => compile(input, output, parse_only),
-        Commands::Run { input, args } => run(input, args),
+            opt,
+            target,
+            emit,
+            strict_match,
+            warn_shadowing,
+            force,
+        } => {
+            let options = CompileOptions {
+                target: target.or(config.target()).unwrap_or_default(),
+                optimize: opt,
+                strict_match,
+                warn_shadowing: warn_shadowing || config.compile.warn_shadowing,
+            };
+            let out_dir = out_dir.or(config.compile.output_dir);
+            if output.is_some() && inputs.len() > 1 {
+                Err("--output needs a single input file".into())
+            } else {
+                for_each_input(inputs, "compiled", |input| {
+                    let output = output.clone();
+                    compile(
+                        input,
+                        output,
+                        out_dir.as_deref(),
+                        parse_only,
+                        &options,
+                        emit,
+                        if force {
+                            CacheMode::Refresh
+                        } else {
+                            CacheMode::Reuse
+                        },
+                    )
+                })
+            }
+        }
+        Commands::Run { input, keep, args } => {
+            let options = CompileOptions {
+                target: config.target().unwrap_or_default(),
+                warn_shadowing: config.compile.warn_shadowing,
+                ..CompileOptions::default()
+            };
+            run(input, keep, args, &options)
+        }
         Commands::Repl => repl(),
-        Commands::Check { input } => check(input),
+        Commands::Check {
+            inputs,
+            strict_match,
+            warn_shadowing,
+        } => {
+            let options = CompileOptions {
+                strict_match,
+                warn_shadowing: warn_shadowing || config.compile.warn_shadowing,
+                ..CompileOptions::default()
+            };
+            for_each_input(inputs, "checked", |input| check(input, &options))
+        }
         Commands::Fmt {
             inputs,
             write,
             check,
-        } => fmt(inputs, write, check),
-        Commands::Lint { inputs } => lint(inputs),
+            line_width,
+        } => fmt(inputs, write, check, line_width, &config),
+        Commands::Lint {
+            inputs,
+            enable,
+            disable,
+            line_width,
+        } => lint(inputs, enable, disable, line_width, &config),
+        Commands::Test { inputs } => test(inputs),
+        Commands::Explain { code } => explain(&code),
     };
 
     if let Err(e) = result {
@@ -100,31 +288,112 @@ fn main() {
     }
 }
 
+/// Run a command on each of its inputs, carrying on past the ones that fail.
+///
+/// A single input fails with its own error; with several, each failure is
+/// reported as it happens and a summary counts the inputs that succeeded.
+fn for_each_input(
+    inputs: Vec<PathBuf>,
+    done: &str,
+    mut action: impl FnMut(PathBuf) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if inputs.len() == 1 {
+        return action(inputs.into_iter().next().unwrap());
+    }
+
+    let total = inputs.len();
+    let mut failed = 0;
+    for input in inputs {
+        let name = input.display().to_string();
+        if let Err(e) = action(input) {
+            eprintln!("Error: {}: {}", name, e);
+            failed += 1;
+        }
+    }
+
+    println!(
+        "{} of {} files {} successfully",
+        total - failed,
+        total,
+        done
+    );
+    if failed > 0 {
+        return Err(format!("{} of {} files failed", failed, total).into());
+    }
+    Ok(())
+}
+
 /// Compile a Nevermind file
 fn compile(
     input: PathBuf,
     output: Option<PathBuf>,
+    out_dir: Option<&Path>,
     parse_only: bool,
+    options: &CompileOptions,
+    emit: Option<EmitStage>,
+    cache_mode: CacheMode,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Compiling: {:?}", input);
+    // Output is derived from the input path, so without an explicit one the
+    // Python compiled from stdin goes to stdout
+    let emit = if is_stdin(&input) && output.is_none() {
+        if emit == Some(EmitStage::Pyc) {
+            return Err("compiling standard input to bytecode needs an --output file".into());
+        }
+        emit.or(Some(EmitStage::Py))
+    } else {
+        emit
+    };
+    let prints_stage = emit.is_some_and(EmitStage::is_printed);
+
+    // Progress lines would get in the way of an emitted stage on stdout
+    let progress = |line: String| {
+        if !prints_stage {
+            println!("{}", line);
+        }
+    };
 
-    // Read the source file
-    let source = fs::read_to_string(&input)?;
+    // Read the source file, splicing in any included files
+    let (program, name) = read_input(&input)?;
+    let source = &program.source;
+    progress(format!("Compiling: {:?}", name));
 
     // Lex the source
-    let mut lexer = nevermind_lexer::Lexer::new(&source);
-    let tokens = lexer.tokenize()?;
+    let mut lexer = nevermind_lexer::Lexer::new(source);
+    let tokens = lexer.tokenize().map_err(|err| {
+        let diagnostic = Diagnostic::error(err.message, err.span);
+        stage_failed("Lexical analysis", vec![diagnostic], &name, &program)
+    })?;
+
+    if emit == Some(EmitStage::Tokens) {
+        for token in &tokens {
+            println!("{}\t{:?}\t{:?}", token.span, token.kind, token.text);
+        }
+        return Ok(());
+    }
 
-    println!("  ✓ Lexical analysis passed ({} tokens)", tokens.len());
+    progress(format!(
+        "  ✓ Lexical analysis passed ({} tokens)",
+        tokens.len()
+    ));
 
     // Parse the AST
     let mut parser = nevermind_parser::Parser::from_tokens(tokens);
-    let statements = parser.parse()?;
+    let statements = parser.parse().map_err(|err| {
+        let diagnostic = Diagnostic::error(err.message, err.span);
+        stage_failed("Syntax analysis", vec![diagnostic], &name, &program)
+    })?;
+
+    if emit == Some(EmitStage::Ast) {
+        for stmt in &statements {
+            println!("{:#?}", stmt);
+        }
+        return Ok(());
+    }
 
-    println!(
+    progress(format!(
         "  ✓ Syntax analysis passed ({} statements)",
         statements.len()
-    );
+    ));
 
     if parse_only {
         // Just show AST
@@ -142,50 +411,127 @@ fn compile(
         .unwrap_or_else(|| std::path::PathBuf::from("."));
 
     // Compile any local .nm modules that this file imports (transitive).
-    // Each imported module is compiled to a .py file in the same directory
-    // so Python can find it at runtime.
-    compile_module_deps(&statements, &base_dir)?;
-    let mut resolver = nevermind_name_resolver::NameResolver::with_base_dir(base_dir.clone());
-    match resolver.resolve(&statements) {
-        Ok(()) => {}
-        Err(errors) => {
-            eprintln!("  Name resolution errors: {}", errors.len());
-            for error in &errors {
-                eprintln!("    - {}: {}", error.span, error.message);
-            }
-            return Err(format!("Name resolution failed with {} errors", errors.len()).into());
-        }
+    // Each imported module is compiled to a .py file in the same directory,
+    // or in the output directory along with this file, so Python can find
+    // it at runtime.
+    let dependencies = if prints_stage {
+        Vec::new()
+    } else {
+        compile_module_deps(&statements, &base_dir, out_dir)?
     };
 
-    println!("  ✓ Name resolution passed");
+    let mut resolver = nevermind_name_resolver::NameResolver::with_base_dir(base_dir.clone())
+        .with_shadow_warnings(options.warn_shadowing);
+    let unused = resolver.resolve(&statements).map_err(|errors| {
+        let diagnostics = errors.iter().map(|error| error.to_diagnostic()).collect();
+        stage_failed("Name resolution", diagnostics, &name, &program)
+    })?;
+    for warning in unused {
+        eprint!(
+            "{}",
+            render_diagnostic(warning.to_warning(), &name, &program)
+        );
+    }
+
+    progress("  ✓ Name resolution passed".to_string());
 
     // Type checking
-    let mut checker = nevermind_type_checker::TypeChecker::new();
-    checker.check(&statements)?;
+    let mut checker =
+        nevermind_type_checker::TypeChecker::new().with_strict_match(options.strict_match);
+    checker.check(&statements).map_err(|error| {
+        stage_failed(
+            "Type checking",
+            vec![error.to_diagnostic()],
+            &name,
+            &program,
+        )
+    })?;
+    for warning in checker.warnings() {
+        eprint!(
+            "{}",
+            render_diagnostic(warning.to_warning(), &name, &program)
+        );
+    }
+
+    if emit == Some(EmitStage::Resolved) {
+        print_resolved_bindings(&statements, &checker);
+        return Ok(());
+    }
 
-    println!("  ✓ Type checking passed");
+    progress("  ✓ Type checking passed".to_string());
+
+    // Python written to a file is cached, and reused while the source, the
+    // modules it imports and the options stay the same. Checking still runs
+    // on every compile, so errors and warnings are never skipped.
+    let cache = (emit.is_none() && cache_mode != CacheMode::Off)
+        .then(|| cache::Cache::new(&base_dir, source, &dependencies, options));
+    if let Some(python_code) = cache
+        .as_ref()
+        .filter(|_| cache_mode == CacheMode::Reuse)
+        .and_then(cache::Cache::load)
+    {
+        progress("  ✓ Source unchanged; reusing cached output".to_string());
+        return write_python(&python_code, &input, output, out_dir);
+    }
 
     // Lower to MIR
-    let mir_program = nevermind_mir::lower_program(&statements)?;
+    let mut mir_program =
+        nevermind_mir::lower_program_with_types(&statements, &checker.node_types(), options)?;
 
-    println!("  ✓ MIR lowering passed");
+    progress("  ✓ MIR lowering passed".to_string());
+    if options.optimize {
+        progress("  ✓ Constant folding passed".to_string());
+    }
+
+    nevermind_mir::optimize::remove_dead_code(&mut mir_program);
+
+    if emit == Some(EmitStage::Mir) {
+        println!("{:#?}", mir_program);
+        return Ok(());
+    }
 
     // Code generation
     let python_code = nevermind_codegen::generate_with_context(
         &mir_program,
         nevermind_codegen::PythonModuleContext::new(base_dir.clone(), None),
+        options,
     )?;
 
+    if emit == Some(EmitStage::Py) {
+        print!("{}", python_code);
+        return Ok(());
+    }
+
     println!("  ✓ Code generation passed");
 
-    // Determine output file
-    let output = output.unwrap_or_else(|| {
-        let mut out = input.clone();
-        out.set_extension("py");
-        out
-    });
+    if emit == Some(EmitStage::Pyc) {
+        let output = match output {
+            Some(output) => output,
+            None => default_output(&input, out_dir, "pyc")?,
+        };
+        write_bytecode(&python_code, &output)?;
+        println!("  ✓ Bytecode written to: {:?}", output);
+        return Ok(());
+    }
+
+    if let Some(cache) = &cache {
+        cache.store(&python_code);
+    }
+
+    write_python(&python_code, &input, output, out_dir)
+}
 
-    // Write output
+/// Write compiled Python to `output`, or where [`default_output`] puts it
+fn write_python(
+    python_code: &str,
+    input: &Path,
+    output: Option<PathBuf>,
+    out_dir: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = match output {
+        Some(output) => output,
+        None => default_output(input, out_dir, "py")?,
+    };
     fs::write(&output, python_code)?;
 
     println!("  ✓ Output written to: {:?}", output);
@@ -193,24 +539,66 @@ fn compile(
     Ok(())
 }
 
-/// Run a Nevermind file
-fn run(input: PathBuf, args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Running: {:?}", input);
-    println!("Args: {:?}", args);
+/// Where a compiled file goes without `--output`: next to its input, or in
+/// `out_dir`, created if need be, when there is one
+fn default_output(input: &Path, out_dir: Option<&Path>, extension: &str) -> io::Result<PathBuf> {
+    let output = input.with_extension(extension);
+    match (out_dir, output.file_name()) {
+        (Some(dir), Some(file_name)) => {
+            fs::create_dir_all(dir)?;
+            Ok(dir.join(file_name))
+        }
+        _ => Ok(output),
+    }
+}
 
-    // Compile to Python
-    let py_output = {
-        let mut out = input.clone();
-        out.set_extension("py");
-        out
-    };
+/// Compile Python source to a `.pyc` file with Python's `py_compile`, and
+/// check that the result starts with a bytecode header
+fn write_bytecode(python_code: &str, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let source = temp_script("nevermind_pyc")?;
+    fs::write(&source, python_code)?;
+
+    let result = python_output([
+        "-c".as_ref(),
+        "import py_compile, sys; py_compile.compile(sys.argv[1], cfile=sys.argv[2], doraise=True)"
+            .as_ref(),
+        source.as_os_str(),
+        output.as_os_str(),
+    ]);
+    let _ = fs::remove_file(&source);
+
+    let result = result?;
+    if !result.status.success() {
+        return Err(format!(
+            "Python failed to compile to bytecode: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        )
+        .into());
+    }
 
-    compile(input.clone(), Some(py_output.clone()), false)?;
+    // A .pyc starts with a magic number whose last two bytes are "\r\n"
+    let bytecode = fs::read(output)?;
+    if bytecode.get(2..4) != Some(b"\r\n".as_slice()) {
+        return Err(format!("{:?} is not a valid .pyc file", output).into());
+    }
+    Ok(())
+}
 
-    // Run with Python
-    println!("\nExecuting with Python...");
+/// Run Python with `args`, trying each interpreter name in turn, and
+/// capture its output
+fn python_output<I, S>(args: I) -> Result<std::process::Output, Box<dyn std::error::Error>>
+where
+    I: IntoIterator<Item = S> + Clone,
+    S: AsRef<std::ffi::OsStr>,
+{
+    with_python(|command| command.args(args.clone()).output())
+}
 
-    // Try python interpreters in order: python3, python, py (Windows launcher)
+/// Start Python with `start`, trying each interpreter name in turn until
+/// one is found
+fn with_python<T>(
+    mut start: impl FnMut(&mut std::process::Command) -> io::Result<T>,
+) -> Result<T, Box<dyn std::error::Error>> {
     let python_cmds = if cfg!(windows) {
         vec!["python", "python3", "py"]
     } else {
@@ -218,35 +606,118 @@ fn run(input: PathBuf, args: Vec<String>) -> Result<(), Box<dyn std::error::Erro
     };
 
     let mut last_err = None;
-    let mut status = None;
     for python_cmd in &python_cmds {
-        let result = std::process::Command::new(python_cmd)
-            .arg(&py_output)
-            .args(&args)
-            .spawn();
-        match result {
-            Ok(mut child) => {
-                status = Some(child.wait()?);
-                last_err = None;
-                break;
-            }
-            Err(e) => {
-                last_err = Some(e);
-                continue;
-            }
+        match start(&mut std::process::Command::new(python_cmd)) {
+            Ok(result) => return Ok(result),
+            Err(e) => last_err = Some(e),
         }
     }
 
-    if let Some(e) = last_err {
-        return Err(format!(
-            "Could not find Python interpreter. Tried: {}. Error: {}",
-            python_cmds.join(", "),
-            e
-        )
-        .into());
+    Err(format!(
+        "Could not find Python interpreter. Tried: {}. Error: {}",
+        python_cmds.join(", "),
+        last_err.unwrap()
+    )
+    .into())
+}
+
+/// A fresh path in the temporary directory for a generated script
+fn temp_script(prefix: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let unique = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_nanos();
+    Ok(std::env::temp_dir().join(format!("{}_{}_{}.py", prefix, std::process::id(), unique)))
+}
+
+/// `PYTHONPATH` with `base_dir` in front, so a script outside it can still
+/// import the local modules compiled there
+fn python_path_with(base_dir: PathBuf) -> Result<std::ffi::OsString, Box<dyn std::error::Error>> {
+    Ok(std::env::join_paths(
+        std::iter::once(base_dir).chain(
+            std::env::var_os("PYTHONPATH")
+                .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+                .unwrap_or_default(),
+        ),
+    )?)
+}
+
+/// Print each top-level binding of a checked program with its type
+fn print_resolved_bindings(
+    statements: &[nevermind_ast::Stmt],
+    checker: &nevermind_type_checker::TypeChecker,
+) {
+    use nevermind_ast::Stmt;
+
+    for stmt in statements {
+        let stmt = match stmt {
+            Stmt::Export { stmt, .. } => stmt,
+            stmt => stmt,
+        };
+        let (keyword, name, arity) = match stmt {
+            Stmt::Let {
+                is_mutable, name, ..
+            } => (if *is_mutable { "var" } else { "let" }, name, None),
+            Stmt::Function { name, params, .. } => ("fn", name, Some(params.len())),
+            _ => continue,
+        };
+        match checker.global_type(name, arity) {
+            Some(ty) => println!("{} {}: {}", keyword, name, ty),
+            None => println!("{} {}", keyword, name),
+        }
     }
+}
 
-    let status = status.unwrap();
+/// Run a Nevermind file
+fn run(
+    input: PathBuf,
+    keep: bool,
+    args: Vec<String>,
+    options: &CompileOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Running: {:?}", input);
+    println!("Args: {:?}", args);
+
+    // Compile to Python, in a temporary file unless asked to keep it
+    let py_output = if keep {
+        input.with_extension("py")
+    } else {
+        temp_script("nevermind_run")?
+    };
+
+    compile(
+        input.clone(),
+        Some(py_output.clone()),
+        None,
+        false,
+        options,
+        None,
+        CacheMode::Off,
+    )?;
+
+    // A temporary script isn't beside the input, so Python needs telling
+    // where the input's local modules were compiled to
+    let base_dir = input
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.parent().map(|d| d.to_path_buf()))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let python_path = python_path_with(base_dir)?;
+
+    // Run with Python
+    println!("\nExecuting with Python...");
+    let status = with_python(|command| {
+        command
+            .arg(&py_output)
+            .args(&args)
+            .env("PYTHONPATH", &python_path)
+            .status()
+    });
+
+    if !keep {
+        let _ = fs::remove_file(&py_output);
+    }
+
+    let status = status?;
 
     if !status.success() {
         return Err(format!("Python execution failed with status: {}", status).into());
@@ -261,7 +732,8 @@ fn repl() -> Result<(), Box<dyn std::error::Error>> {
     println!("Type :help for help, exit or Ctrl-D to quit\n");
 
     let repl_base_dir = std::env::current_dir()?;
-    let mut definitions: Vec<String> = Vec::new();
+    let mut definitions = ReplDefinitions::new(repl_base_dir.clone());
+    let mut session: Option<session::Session> = None;
     let mut input_buffer = String::new();
 
     let stdin = io::stdin();
@@ -300,23 +772,41 @@ fn repl() -> Result<(), Box<dyn std::error::Error>> {
                 ":help" => {
                     println!("Commands:");
                     println!("  :help   Show this help message");
-                    println!("  :clear  Clear all definitions");
+                    println!("  :reset  Forget all definitions and values (or :clear)");
                     println!("  :defs   Show current definitions");
+                    println!("  :load   Load the definitions in a file (:load path.nm)");
+                    println!("  :type   Show the type of an expression (:type 1 + 2, or :t)");
                     println!("  exit    Exit the REPL");
                 }
-                ":clear" => {
-                    definitions.clear();
-                    println!("Definitions cleared.");
+                ":reset" | ":clear" => {
+                    definitions = ReplDefinitions::new(repl_base_dir.clone());
+                    session = None;
+                    println!("Session reset.");
                 }
                 ":defs" => {
-                    if definitions.is_empty() {
+                    if definitions.sources.is_empty() {
                         println!("No definitions.");
                     } else {
-                        for def in &definitions {
+                        for def in &definitions.sources {
                             println!("{}", def);
                         }
                     }
                 }
+                _ if trimmed.starts_with(":load ") => {
+                    let path = Path::new(trimmed[":load ".len()..].trim());
+                    match repl_load(&mut definitions, &mut session, path) {
+                        Ok(count) => {
+                            println!("Loaded {} definition(s) from {}.", count, path.display())
+                        }
+                        Err(e) => eprintln!("Error: {}", e),
+                    }
+                }
+                _ if trimmed.starts_with(":type ") || trimmed.starts_with(":t ") => {
+                    let expr = trimmed.split_once(' ').map_or("", |(_, expr)| expr);
+                    if let Some(ty) = repl_type(&definitions, expr) {
+                        println!("{}", ty);
+                    }
+                }
                 _ => {
                     eprintln!("Unknown command: {}. Type :help for help.", trimmed);
                 }
@@ -348,64 +838,253 @@ fn repl() -> Result<(), Box<dyn std::error::Error>> {
             continue;
         }
 
-        // Classify: definition or expression
+        // Definitions are kept so later inputs check against them; the
+        // values everything computes stay in the Python session
         let is_def = is_repl_definition(&complete_input);
-
-        if is_def {
-            // Validate by compiling all defs + new def
-            let mut source = String::new();
-            for def in &definitions {
-                source.push_str(def);
-                source.push('\n');
-            }
-            source.push_str(&complete_input);
-            source.push('\n');
-            // Add a dummy main so codegen succeeds
-            source.push_str("fn main() do 0 end\n");
-
-            match compile_repl_source(&source, &repl_base_dir) {
-                Ok(_) => {
-                    definitions.push(complete_input);
-                }
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                }
-            }
+        let source = if is_def {
+            complete_input.clone()
         } else {
-            // Expression: compile all defs + expression, execute
-            let mut source = String::new();
-            for def in &definitions {
-                source.push_str(def);
-                source.push('\n');
+            repl_echo(&complete_input)
+        };
+        let (python, checked) = match repl_python(&definitions, &source) {
+            Ok(Some(compiled)) => compiled,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                continue;
             }
-            source.push_str(&complete_input);
-            source.push('\n');
-
-            match compile_repl_source(&source, &repl_base_dir) {
-                Ok(python_code) => {
-                    let code = strip_main_guard(&python_code);
-                    match execute_python_code(&code, Some(&repl_base_dir)) {
-                        Ok(output) => {
-                            let output = output.trim_end();
-                            if !output.is_empty() {
-                                println!("{}", output);
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Runtime error: {}", e);
-                        }
-                    }
+        };
+        match repl_run(&mut session, &python, &repl_base_dir) {
+            Ok(output) => {
+                let output = output.trim_end();
+                if !output.is_empty() {
+                    println!("{}", output);
                 }
-                Err(e) => {
-                    eprintln!("Error: {}", e);
+                if is_def {
+                    definitions.add([complete_input], checked);
                 }
             }
+            Err(e) => eprintln!("Runtime error: {}", e),
         }
     }
 
     Ok(())
 }
 
+/// Add the top-level definitions in the file at `path` to the REPL session,
+/// returning how many there were
+///
+/// Other top-level statements in the file are skipped, so they don't run
+/// again with every later input. Nothing is added if the definitions don't
+/// check against the session.
+fn repl_load(
+    definitions: &mut ReplDefinitions,
+    session: &mut Option<session::Session>,
+    path: &Path,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let source = preprocess::expand_includes(path)?.source;
+    let statements = nevermind_parser::Parser::new(&source)
+        .and_then(|mut parser| parser.parse())
+        .map_err(|err| format!("{}: {}", path.display(), err.message))?;
+
+    // Each statement runs from its first line up to the next statement's
+    let lines: Vec<&str> = source.lines().collect();
+    let starts: Vec<usize> = statements
+        .iter()
+        .map(|stmt| stmt.span().start.line.saturating_sub(1))
+        .chain(std::iter::once(lines.len()))
+        .collect();
+    let loaded: Vec<String> = statements
+        .iter()
+        .zip(starts.windows(2))
+        .filter(|(stmt, _)| {
+            matches!(
+                stmt,
+                nevermind_ast::Stmt::Function { .. }
+                    | nevermind_ast::Stmt::Let { .. }
+                    | nevermind_ast::Stmt::TypeAlias { .. }
+                    | nevermind_ast::Stmt::Class { .. }
+                    | nevermind_ast::Stmt::Import { .. }
+                    | nevermind_ast::Stmt::Export { .. }
+            )
+        })
+        .map(|(_, range)| lines[range[0]..range[1]].join("\n").trim_end().to_string())
+        .collect();
+
+    let (python, checked) = repl_python(definitions, &loaded.join("\n"))?
+        .ok_or_else(|| format!("{} was not loaded", path.display()))?;
+    repl_run(session, &python, &definitions.base_dir)?;
+
+    let count = loaded.len();
+    definitions.add(loaded, checked);
+    Ok(count)
+}
+
+/// What the REPL has defined so far, with the name resolver and type
+/// checker that have seen it, so each input is checked and compiled on its
+/// own instead of along with every earlier definition
+struct ReplDefinitions {
+    /// Where local modules are looked up and compiled to
+    base_dir: PathBuf,
+    /// The source of each definition, as `:defs` shows them
+    sources: Vec<String>,
+    /// The definitions' statements, whose functions and classes later
+    /// inputs can use
+    statements: Vec<nevermind_ast::Stmt>,
+    resolver: nevermind_name_resolver::NameResolver,
+    checker: nevermind_type_checker::TypeChecker,
+}
+
+/// An input checked against the REPL's definitions, ready to join them once
+/// it has run
+struct ReplInput {
+    statements: Vec<nevermind_ast::Stmt>,
+    resolver: nevermind_name_resolver::NameResolver,
+    checker: nevermind_type_checker::TypeChecker,
+}
+
+impl ReplDefinitions {
+    fn new(base_dir: PathBuf) -> Self {
+        Self {
+            resolver: nevermind_name_resolver::NameResolver::with_base_dir(base_dir.clone()),
+            checker: nevermind_type_checker::TypeChecker::new(),
+            base_dir,
+            sources: Vec::new(),
+            statements: Vec::new(),
+        }
+    }
+
+    /// Check `input` against the definitions, without changing them
+    ///
+    /// Returns `None` after printing the errors if it doesn't check.
+    /// Warnings are left out, as what a definition binds is often only used
+    /// by later inputs.
+    fn check(&self, input: &str) -> Option<ReplInput> {
+        let statements = match nevermind_parser::Parser::new(input).and_then(|mut p| p.parse()) {
+            Ok(statements) => statements,
+            Err(err) => {
+                report_repl_errors(&[Diagnostic::error(err.message, err.span)], input);
+                return None;
+            }
+        };
+
+        let mut resolver = self.resolver.clone();
+        let mut checker = self.checker.clone();
+        let errors: Vec<Diagnostic> = match resolver.resolve(&statements) {
+            Ok(_) => checker
+                .check_all(&statements)
+                .iter()
+                .map(|error| error.to_diagnostic())
+                .collect(),
+            Err(errors) => errors.iter().map(|error| error.to_diagnostic()).collect(),
+        };
+        if !errors.is_empty() {
+            report_repl_errors(&errors, input);
+            return None;
+        }
+
+        Some(ReplInput {
+            statements,
+            resolver,
+            checker,
+        })
+    }
+
+    /// Add an input that has run, made of the definitions in `sources`
+    fn add(&mut self, sources: impl IntoIterator<Item = String>, input: ReplInput) {
+        self.sources.extend(sources);
+        self.statements.extend(input.statements);
+        self.resolver = input.resolver;
+        self.checker = input.checker;
+    }
+}
+
+/// The Python that runs `input` in a session where `definitions` have
+/// already run, along with the checked input to add to them once it has
+///
+/// Only the input is compiled, after being checked against the
+/// definitions. Returns `None` after printing the errors if it doesn't
+/// check.
+fn repl_python(
+    definitions: &ReplDefinitions,
+    input: &str,
+) -> Result<Option<(String, ReplInput)>, Box<dyn std::error::Error>> {
+    let Some(checked) = definitions.check(input) else {
+        return Ok(None);
+    };
+    compile_module_deps(&checked.statements, &definitions.base_dir, None)?;
+
+    let options = CompileOptions::default();
+    let mut mir_program = nevermind_mir::lower_continuation_with_types(
+        &definitions.statements,
+        &checked.statements,
+        &checked.checker.node_types(),
+        &options,
+    )?;
+    nevermind_mir::optimize::remove_dead_code(&mut mir_program);
+    let context = nevermind_codegen::PythonModuleContext::new(definitions.base_dir.clone(), None);
+    let python = nevermind_codegen::generate_with_context(&mir_program, context, &options)?;
+    Ok(Some((python, checked)))
+}
+
+/// Bind an input that is a single expression to the name the session
+/// prints, so the REPL echoes its value
+fn repl_echo(input: &str) -> String {
+    let is_expression = nevermind_parser::Parser::new(input)
+        .and_then(|mut parser| parser.parse())
+        .is_ok_and(|statements| {
+            matches!(
+                statements.as_slice(),
+                [nevermind_ast::Stmt::ExprStmt { expr, .. }]
+                    if !matches!(expr, nevermind_ast::Expr::Assign { .. })
+            )
+        });
+    if is_expression {
+        format!("let {} = {}", session::VALUE_BINDING, input)
+    } else {
+        input.to_string()
+    }
+}
+
+/// Run Python in the REPL's session, starting one if there isn't one yet
+fn repl_run(
+    session: &mut Option<session::Session>,
+    python: &str,
+    base_dir: &Path,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let session = match session {
+        Some(session) => session,
+        None => session.insert(session::Session::start(base_dir)?),
+    };
+    session.run(python)
+}
+
+/// The static type of `expr` checked against the session's definitions,
+/// without running it
+///
+/// Returns `None` after printing the errors if the expression doesn't check.
+fn repl_type(definitions: &ReplDefinitions, expr: &str) -> Option<String> {
+    let source = format!("let {} = {}\n", REPL_TYPE_BINDING, expr.trim());
+    definitions
+        .check(&source)?
+        .checker
+        .global_type(REPL_TYPE_BINDING, None)
+        .map(|ty| ty.display_name())
+}
+
+/// The name `:type` binds its expression to while checking it
+const REPL_TYPE_BINDING: &str = "__repl_type";
+
+/// Print the errors in a REPL input
+fn report_repl_errors(errors: &[Diagnostic], input: &str) {
+    for error in errors {
+        eprint!(
+            "{}",
+            render(&error.clone().with_source_name("<repl>"), input)
+        );
+    }
+}
+
 fn is_repl_definition(input: &str) -> bool {
     let input = input.trim_start();
     input.starts_with("fn ")
@@ -415,19 +1094,6 @@ fn is_repl_definition(input: &str) -> bool {
         || input.starts_with("from ")
 }
 
-fn compile_repl_source(
-    source: &str,
-    base_dir: &Path,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let mut lexer = nevermind_lexer::Lexer::new(source);
-    let tokens = lexer.tokenize()?;
-    let mut parser = nevermind_parser::Parser::from_tokens(tokens);
-    let statements = parser.parse()?;
-
-    compile_module_deps(&statements, base_dir)?;
-    compile_source_silent_with_base(source, Some(base_dir.to_path_buf()), None)
-}
-
 fn qualify_local_module(current_module: Option<&str>, module: &str) -> String {
     match current_module
         .and_then(|current| current.rsplit_once('/').map(|(dir, _)| dir))
@@ -441,22 +1107,30 @@ fn qualify_local_module(current_module: Option<&str>, module: &str) -> String {
 /// Recursively compile all local `.nm` module dependencies referenced by
 /// `stmts`.  For each `from "mod" import …` or `use "mod"` where `mod.nm`
 /// exists inside `base_dir`, the module is compiled to a `.py` file alongside
-/// it so that Python can `import` it at runtime.
+/// it, or at the same relative path in `out_dir` when there is one, so that
+/// Python can `import` it at runtime.
 ///
 /// `visited` prevents re-compiling (and infinite loops on circular imports).
+///
+/// Returns the sources of the modules compiled, dependencies first.
 fn compile_module_deps(
     stmts: &[nevermind_ast::Stmt],
     base_dir: &std::path::Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+    out_dir: Option<&Path>,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
     let mut visited = std::collections::HashSet::new();
-    compile_module_deps_inner(stmts, base_dir, None, &mut visited)
+    let mut sources = Vec::new();
+    compile_module_deps_inner(stmts, base_dir, out_dir, None, &mut visited, &mut sources)?;
+    Ok(sources)
 }
 
 fn compile_module_deps_inner(
     stmts: &[nevermind_ast::Stmt],
     base_dir: &std::path::Path,
+    out_dir: Option<&Path>,
     current_module: Option<&str>,
     visited: &mut std::collections::HashSet<String>,
+    sources: &mut Vec<String>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     for stmt in stmts {
         let module = match stmt {
@@ -483,7 +1157,7 @@ fn compile_module_deps_inner(
         // Mark before recursing to guard against cycles.
         visited.insert(resolved_module.clone());
 
-        let source = fs::read_to_string(&source_path)?;
+        let source = preprocess::expand_includes(&source_path)?.source;
 
         // Parse the module to discover its own dependencies.
         let mut lexer = nevermind_lexer::Lexer::new(&source);
@@ -499,8 +1173,10 @@ fn compile_module_deps_inner(
         compile_module_deps_inner(
             &sub_stmts,
             &sub_base,
+            out_dir,
             Some(resolved_module.as_str()),
             visited,
+            sources,
         )?;
 
         // Now compile this module to Python.
@@ -511,8 +1187,18 @@ fn compile_module_deps_inner(
         )?;
 
         // Output path mirrors the source path: .nm → .py
-        let out_path = source_path.with_extension("py");
+        let out_path = match out_dir {
+            Some(dir) => {
+                let out_path = dir.join(&resolved_module).with_extension("py");
+                if let Some(parent) = out_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                out_path
+            }
+            None => source_path.with_extension("py"),
+        };
         fs::write(&out_path, python)?;
+        sources.push(source);
     }
     Ok(())
 }
@@ -524,176 +1210,73 @@ fn compile_source_silent_with_base(
     base_dir: Option<std::path::PathBuf>,
     current_module: Option<String>,
 ) -> Result<String, Box<dyn std::error::Error>> {
-    let mut lexer = nevermind_lexer::Lexer::new(source);
-    let tokens = lexer.tokenize()?;
-
-    let mut parser = nevermind_parser::Parser::from_tokens(tokens);
-    let statements = parser.parse()?;
-
-    let mut resolver = match &base_dir {
-        Some(dir) => nevermind_name_resolver::NameResolver::with_base_dir(dir.clone()),
-        None => nevermind_name_resolver::NameResolver::new(),
-    };
-    if let Err(errors) = resolver.resolve(&statements) {
-        let msgs: Vec<String> = errors.iter().map(|e| e.message.clone()).collect();
-        return Err(msgs.join("; ").into());
-    }
-
-    let mut checker = nevermind_type_checker::TypeChecker::new();
-    checker.check(&statements)?;
-
-    let mir_program = nevermind_mir::lower_program(&statements)?;
-    let python_code = match base_dir {
-        Some(dir) => nevermind_codegen::generate_with_context(
-            &mir_program,
-            nevermind_codegen::PythonModuleContext::new(dir, current_module),
-        )?,
-        None => nevermind_codegen::generate(&mir_program)?,
-    };
-
-    Ok(python_code)
+    let module_context =
+        base_dir.map(|dir| nevermind_codegen::PythonModuleContext::new(dir, current_module));
+    nevermind::compile_module(source, module_context, &CompileOptions::default())
+        .map_err(|diagnostics| nevermind::CompileError::from(diagnostics).into())
 }
 
-/// Execute Python code and return its stdout output.
-fn execute_python_code(
-    code: &str,
-    base_dir: Option<&Path>,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let python_cmds = if cfg!(windows) {
-        vec!["python", "python3", "py"]
-    } else {
-        vec!["python3", "python"]
+/// Check if the input buffer needs more lines (multi-line input).
+///
+/// The input is lexed so that keywords and brackets inside string and char
+/// literals don't count. Input that doesn't lex is treated as complete and
+/// left for the compiler to report.
+fn needs_more_input(input: &str) -> bool {
+    use nevermind_lexer::token::{Delimiter, Keyword};
+    use nevermind_lexer::TokenType;
+
+    let Ok(tokens) = nevermind_lexer::Lexer::new(input).tokenize() else {
+        return false;
     };
 
-    let mut last_err = None;
-    for python_cmd in &python_cmds {
-        let mut command = std::process::Command::new(python_cmd);
-        command.arg("-c").arg(code);
-        if let Some(base_dir) = base_dir {
-            command.current_dir(base_dir);
-        }
-        let result = command.output();
-        match result {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                if !output.status.success() {
-                    return Err(friendly_runtime_error(&stderr).into());
-                }
-                return Ok(stdout);
+    let mut depth: i32 = 0;
+    // A `while` loop's `do` shares the loop's `end`
+    let mut in_while_header = false;
+    let mut previous = None;
+    for token in &tokens {
+        match &token.kind {
+            TokenType::Keyword(Keyword::While) => {
+                depth += 1;
+                in_while_header = true;
             }
-            Err(e) => {
-                last_err = Some(e);
-                continue;
+            TokenType::Keyword(Keyword::Do) if in_while_header => in_while_header = false,
+            // A `for` loop closes its `do` block and then itself
+            TokenType::Keyword(Keyword::For | Keyword::Do | Keyword::Then) => depth += 1,
+            // `if ... do ... end else ...` needs a closing `end` of its own
+            TokenType::Keyword(Keyword::Else)
+                if previous == Some(&TokenType::Keyword(Keyword::End)) =>
+            {
+                depth += 1
             }
+            // A try's clauses each open a `do` block, but share one `end`
+            TokenType::Keyword(Keyword::Catch | Keyword::Finally) => depth -= 1,
+            TokenType::Keyword(Keyword::End) => depth -= 1,
+            TokenType::Delimiter(Delimiter::LParen | Delimiter::LBracket | Delimiter::LBrace) => {
+                depth += 1
+            }
+            TokenType::Delimiter(Delimiter::RParen | Delimiter::RBracket | Delimiter::RBrace) => {
+                depth -= 1
+            }
+            _ => {}
         }
-    }
-
-    Err(format!(
-        "Could not find Python interpreter. Tried: {}. Error: {}",
-        python_cmds.join(", "),
-        last_err.unwrap()
-    )
-    .into())
-}
-
-/// Check if a word-boundary keyword exists in a string.
-fn has_keyword(s: &str, kw: &str) -> bool {
-    let kw_len = kw.len();
-    for (i, _) in s.match_indices(kw) {
-        let before_ok =
-            i == 0 || !s.as_bytes()[i - 1].is_ascii_alphanumeric() && s.as_bytes()[i - 1] != b'_';
-        let after = i + kw_len;
-        let after_ok = after >= s.len()
-            || !s.as_bytes()[after].is_ascii_alphanumeric() && s.as_bytes()[after] != b'_';
-        if before_ok && after_ok {
-            return true;
-        }
-    }
-    false
-}
-
-/// Count occurrences of a word-boundary keyword in a string.
-fn count_keyword(s: &str, kw: &str) -> i32 {
-    let kw_len = kw.len();
-    let mut count = 0;
-    for (i, _) in s.match_indices(kw) {
-        let before_ok =
-            i == 0 || !s.as_bytes()[i - 1].is_ascii_alphanumeric() && s.as_bytes()[i - 1] != b'_';
-        let after = i + kw_len;
-        let after_ok = after >= s.len()
-            || !s.as_bytes()[after].is_ascii_alphanumeric() && s.as_bytes()[after] != b'_';
-        if before_ok && after_ok {
-            count += 1;
-        }
-    }
-    count
-}
-
-/// Check if the input buffer needs more lines (multi-line input).
-fn needs_more_input(input: &str) -> bool {
-    let mut depth: i32 = 0;
-
-    for line in input.lines() {
-        let trimmed = line.trim();
-        // "do" and "then" each need a matching "end"
-        depth += count_keyword(trimmed, "do");
-        depth += count_keyword(trimmed, "then");
-        // "for" and "while" with "do" need an extra "end" beyond the do-block
-        if has_keyword(trimmed, "for") && has_keyword(trimmed, "do") {
-            depth += 1;
-        }
-        if has_keyword(trimmed, "while") && has_keyword(trimmed, "do") {
-            depth += 1;
-        }
-        if trimmed.starts_with("match ") || trimmed == "match" {
-            depth += 1;
+        if !matches!(token.kind, TokenType::Delimiter(Delimiter::Semicolon)) {
+            previous = Some(&token.kind);
         }
-        // Count block closers
-        depth -= count_keyword(trimmed, "end");
     }
 
     if depth > 0 {
         return true;
     }
 
-    // Check for fn signature without body (no "do" on same line)
-    let first_line = input.lines().next().unwrap_or("").trim();
-    if first_line.starts_with("fn ")
-        && !has_keyword(first_line, "do")
-        && !has_keyword(first_line, "end")
-    {
-        let line_count = input.lines().count();
-        if line_count == 1 {
-            return true;
-        }
-    }
-
-    false
-}
-
-/// Strip the `if __name__ == "__main__": main()` guard from generated Python.
-fn strip_main_guard(python: &str) -> String {
-    let mut lines: Vec<&str> = Vec::new();
-    let mut skip_next = false;
-
-    for line in python.lines() {
-        if skip_next {
-            // Skip indented line after __name__ guard (e.g., "    main()")
-            if line.starts_with(' ') || line.starts_with('\t') {
-                skip_next = false;
-                continue;
-            }
-            skip_next = false;
-        }
-        if line.contains("if __name__") && line.contains("__main__") {
-            skip_next = true;
-            continue;
-        }
-        lines.push(line);
-    }
-
-    lines.join("\n")
+    // A one-line fn signature whose body starts on the next line
+    let is_fn = matches!(
+        tokens.first().map(|token| &token.kind),
+        Some(TokenType::Keyword(Keyword::Fn))
+    );
+    let has_body = tokens
+        .iter()
+        .any(|token| matches!(token.kind, TokenType::Keyword(Keyword::Do | Keyword::End)));
+    is_fn && !has_body && input.lines().count() == 1
 }
 
 /// Extract a friendly error message from a Python traceback.
@@ -733,63 +1316,157 @@ fn friendly_runtime_error(stderr: &str) -> String {
 }
 
 /// Check a file for errors
-fn check(input: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Checking: {:?}", input);
+fn check(input: PathBuf, options: &CompileOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let (program, name) = read_input(&input)?;
+    println!("Checking: {:?}", name);
 
-    let source = fs::read_to_string(&input)?;
-
-    // Lex
-    let mut lexer = nevermind_lexer::Lexer::new(&source);
-    let tokens = lexer.tokenize()?;
-
-    println!("  ✓ Lexical analysis passed");
-
-    // Parse
-    let mut parser = nevermind_parser::Parser::from_tokens(tokens);
-    let statements = parser.parse()?;
-
-    println!("  ✓ Syntax analysis passed");
-    println!("  ✓ Parsed {} statements", statements.len());
-
-    // Name resolution
     let base_dir = input
         .canonicalize()
         .ok()
         .and_then(|p| p.parent().map(|d| d.to_path_buf()))
         .unwrap_or_else(|| std::path::PathBuf::from("."));
-    let mut resolver = nevermind_name_resolver::NameResolver::with_base_dir(base_dir);
-    match resolver.resolve(&statements) {
-        Ok(()) => {}
-        Err(errors) => {
-            eprintln!("  Name resolution errors: {}", errors.len());
-            for error in &errors {
-                eprintln!("    - {}: {}", error.span, error.message);
-            }
-            return Err(format!("Name resolution failed with {} errors", errors.len()).into());
-        }
-    };
 
-    println!("  ✓ Name resolution passed");
+    let diagnostics = source_diagnostics(&program.source, &name, base_dir, options);
+    for diagnostic in &diagnostics {
+        eprint!("{}", render_diagnostic(diagnostic.clone(), &name, &program));
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(format!("Checking failed with {} errors", errors).into());
+    }
 
-    // Type checking
-    let mut checker = nevermind_type_checker::TypeChecker::new();
-    checker.check(&statements)?;
+    println!("  ✓ No errors found!");
 
-    println!("  ✓ Type checking passed");
+    Ok(())
+}
 
-    println!("\n  No errors found!");
+/// Source name used in diagnostics for a program read from standard input
+const STDIN_NAME: &str = "<stdin>";
 
-    Ok(())
+/// Whether `input` is `-`, meaning the program comes from standard input
+fn is_stdin(input: &Path) -> bool {
+    input == Path::new("-")
+}
+
+/// Read a program with its includes spliced in, along with the name its
+/// diagnostics should be reported under.
+fn read_input(input: &Path) -> Result<(preprocess::Expanded, String), Box<dyn std::error::Error>> {
+    if is_stdin(input) {
+        Ok((preprocess::expand_stdin_includes()?, STDIN_NAME.to_string()))
+    } else {
+        Ok((
+            preprocess::expand_includes(input)?,
+            input.display().to_string(),
+        ))
+    }
+}
+
+/// Run every checking phase over an in-memory program, returning each
+/// problem found, errors first, named after the program's source.
+fn source_diagnostics(
+    source: &str,
+    name: &str,
+    base_dir: PathBuf,
+    options: &CompileOptions,
+) -> Vec<Diagnostic> {
+    nevermind::diagnostics(source, Some(base_dir), options)
+        .into_iter()
+        .map(|diagnostic| diagnostic.with_source_name(name))
+        .collect()
 }
 
-/// Format Nevermind files
-fn fmt(inputs: Vec<PathBuf>, write: bool, check: bool) -> Result<(), Box<dyn std::error::Error>> {
-    formatting::format_paths(inputs, write, check)
+/// Render a diagnostic under the name of the program it points into, or
+/// of the included file it does
+fn render_diagnostic(diagnostic: Diagnostic, name: &str, source: &preprocess::Expanded) -> String {
+    let (diagnostic, text) = source.locate(diagnostic.with_source_name(name));
+    render(&diagnostic, text)
 }
 
-/// Lint Nevermind files
-fn lint(inputs: Vec<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
-    linting::lint_paths(inputs)
+/// Render a diagnostic against its source, in color if `--color` asked
+fn render(diagnostic: &Diagnostic, source: &str) -> String {
+    diagnostic.render_with_color(Some(source), COLOR.load(Ordering::Relaxed))
+}
+
+/// Print the diagnostics that stopped the `stage` of a compilation and
+/// return the error that ends it
+fn stage_failed(
+    stage: &str,
+    diagnostics: Vec<Diagnostic>,
+    name: &str,
+    source: &preprocess::Expanded,
+) -> Box<dyn std::error::Error> {
+    let count = diagnostics.len();
+    for diagnostic in diagnostics {
+        eprint!("{}", render_diagnostic(diagnostic, name, source));
+    }
+    format!(
+        "{} failed with {} error{}",
+        stage,
+        count,
+        if count == 1 { "" } else { "s" }
+    )
+    .into()
+}
+
+/// Format Nevermind files, to the line width nevermind.toml sets unless
+/// `--line-width` overrides it
+fn fmt(
+    inputs: Vec<PathBuf>,
+    write: bool,
+    check: bool,
+    line_width: Option<usize>,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let line_width = line_width
+        .or(config.format.line_width)
+        .unwrap_or(formatting::DEFAULT_LINE_WIDTH);
+    formatting::format_paths(inputs, write, check, line_width)
+}
+
+/// Lint Nevermind files, skipping the rules nevermind.toml disables unless
+/// `--enable` names them
+fn lint(
+    inputs: Vec<PathBuf>,
+    enable: Vec<String>,
+    disable: Vec<String>,
+    line_width: Option<usize>,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for code in enable.iter().chain(&disable) {
+        linting::check_rule_name(code)?;
+    }
+    let disabled = config
+        .lint
+        .disable
+        .iter()
+        .filter(|code| !enable.contains(code))
+        .cloned()
+        .chain(disable)
+        .collect();
+    let options = linting::LintOptions {
+        disabled,
+        line_width: line_width
+            .or(config.format.line_width)
+            .unwrap_or(formatting::DEFAULT_LINE_WIDTH),
+    };
+    linting::lint_paths(inputs, &options)
+}
+
+/// Run Nevermind test files
+fn test(inputs: Vec<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    testing::test_paths(inputs)
+}
+
+/// Print the long-form explanation of an error code
+fn explain(code: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let entry = nevermind_common::codes::lookup(code)
+        .ok_or_else(|| format!("no such error code: {}", code))?;
+    println!("{}: {}\n", entry.code, entry.title);
+    print!("{}", entry.explanation);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -819,6 +1496,25 @@ mod tests {
         }
     }
 
+    /// Execute Python code and return its stdout output.
+    fn execute_python_code(
+        code: &str,
+        base_dir: Option<&Path>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let output = with_python(|command| {
+            command.arg("-c").arg(code);
+            if let Some(base_dir) = base_dir {
+                command.current_dir(base_dir);
+            }
+            command.output()
+        })?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(friendly_runtime_error(&stderr).into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
     #[test]
     fn repl_treats_imports_as_persistent_definitions() {
         assert!(is_repl_definition("use \"mathutils\""));
@@ -835,18 +1531,52 @@ mod tests {
         )
         .unwrap();
 
-        let python = compile_repl_source(
-            "from \"mathutils\" import square\nprint square(2)\n",
-            &temp_dir.path,
-        )
-        .unwrap();
-
+        let mut definitions = ReplDefinitions::new(temp_dir.path.clone());
+        let mut session = None;
+        let import = "from \"mathutils\" import square";
+        let (python, checked) = repl_python(&definitions, import).unwrap().unwrap();
         assert!(temp_dir.path.join("mathutils.py").exists());
+        repl_run(&mut session, &python, &temp_dir.path).unwrap();
+        definitions.add([import.to_string()], checked);
 
-        let output = execute_python_code(&strip_main_guard(&python), Some(&temp_dir.path)).unwrap();
+        let (python, _) = repl_python(&definitions, "print square(2)")
+            .unwrap()
+            .unwrap();
+        let output = repl_run(&mut session, &python, &temp_dir.path).unwrap();
         assert_eq!(output.trim(), "4");
     }
 
+    #[test]
+    fn repl_compiles_only_the_new_input() {
+        let temp_dir = TestDir::new("nevermind_repl_incremental");
+        let mut definitions = ReplDefinitions::new(temp_dir.path.clone());
+        let mut session = None;
+        for input in [
+            "fn inc(x: Int) -> Int do x + 1 end",
+            "class Shape {\n  let name: String\n}",
+            "class Square extends Shape {\n  let side: Int\n}",
+        ] {
+            let (python, checked) = repl_python(&definitions, input).unwrap().unwrap();
+            repl_run(&mut session, &python, &temp_dir.path).unwrap();
+            definitions.add([input.to_string()], checked);
+        }
+
+        let (python, _) = repl_python(&definitions, "print Square(\"sq\", inc(1)).name")
+            .unwrap()
+            .unwrap();
+        assert!(!python.contains("def inc"), "python:\n{}", python);
+        let output = repl_run(&mut session, &python, &temp_dir.path).unwrap();
+        assert_eq!(output.trim(), "sq");
+
+        // An input that doesn't check leaves the definitions as they were
+        assert!(repl_python(&definitions, "let bad = inc(\"a\")")
+            .unwrap()
+            .is_none());
+        assert!(repl_python(&definitions, "let bad = inc(2)")
+            .unwrap()
+            .is_some());
+    }
+
     #[test]
     fn compile_module_deps_qualifies_nested_local_imports() {
         let temp_dir = TestDir::new("nevermind_nested_module_codegen");
@@ -870,9 +1600,297 @@ mod tests {
         let mut parser = nevermind_parser::Parser::from_tokens(tokens);
         let statements = parser.parse().unwrap();
 
-        compile_module_deps(&statements, &temp_dir.path).unwrap();
+        compile_module_deps(&statements, &temp_dir.path, None).unwrap();
 
         let foo_python = fs::read_to_string(pkg_dir.join("foo.py")).unwrap();
         assert!(foo_python.contains("from pkg.bar import value"));
     }
+
+    #[test]
+    fn compile_module_deps_writes_into_the_output_dir() {
+        let temp_dir = TestDir::new("nevermind_module_out_dir");
+        let pkg_dir = temp_dir.path.join("pkg");
+        fs::create_dir_all(&pkg_dir).unwrap();
+        fs::write(pkg_dir.join("foo.nm"), "export let value = 42\n").unwrap();
+        fs::write(temp_dir.path.join("util.nm"), "export let other = 1\n").unwrap();
+        let statements = nevermind_parser::Parser::from_tokens(
+            nevermind_lexer::Lexer::new("use \"pkg/foo\"\nuse \"util\"\n")
+                .tokenize()
+                .unwrap(),
+        )
+        .parse()
+        .unwrap();
+
+        let out_dir = temp_dir.path.join("build");
+        compile_module_deps(&statements, &temp_dir.path, Some(&out_dir)).unwrap();
+
+        assert!(out_dir.join("pkg").join("foo.py").exists());
+        assert!(out_dir.join("util.py").exists());
+        assert!(!pkg_dir.join("foo.py").exists());
+    }
+
+    #[test]
+    fn repl_load_makes_file_functions_callable() {
+        let temp_dir = TestDir::new("nevermind_repl_load");
+        let path = temp_dir.path.join("defs.nm");
+        fs::write(
+            &path,
+            "# helpers\nfn double(x) do\n  x * 2\nend\n\nprint(double(1))\n",
+        )
+        .unwrap();
+
+        let mut definitions = ReplDefinitions::new(temp_dir.path.clone());
+        let mut session = None;
+        assert_eq!(repl_load(&mut definitions, &mut session, &path).unwrap(), 1);
+        assert_eq!(
+            definitions.sources,
+            vec!["fn double(x) do\n  x * 2\nend".to_string()]
+        );
+
+        let (python, _) = repl_python(&definitions, "print(double(21))")
+            .unwrap()
+            .unwrap();
+        let output = repl_run(&mut session, &python, &temp_dir.path).unwrap();
+        assert_eq!(output.trim(), "42");
+
+        fs::write(&path, "fn broken() do missing end\n").unwrap();
+        assert!(repl_load(&mut definitions, &mut session, &path).is_err());
+        assert_eq!(definitions.sources.len(), 1);
+    }
+
+    #[test]
+    fn repl_keeps_reading_until_blocks_and_brackets_close() {
+        assert!(needs_more_input("fn greet(name) do"));
+        assert!(needs_more_input("fn greet(name) do\n  print(name)"));
+        assert!(!needs_more_input("fn greet(name) do\n  print(name)\nend"));
+        assert!(needs_more_input("for x in xs do print(x) end"));
+        assert!(!needs_more_input("for x in xs do print(x) end end"));
+        assert!(needs_more_input("let xs = [1,"));
+        assert!(needs_more_input("fn f(x)"));
+    }
+
+    #[test]
+    fn repl_ignores_keywords_inside_string_literals() {
+        assert!(!needs_more_input(r#"print(" do ")"#));
+        assert!(!needs_more_input(r#"let s = "match { ( ""#));
+        assert!(!needs_more_input("fn id(x) do x end"));
+    }
+
+    #[test]
+    fn repl_type_shows_the_static_type_of_an_expression() {
+        let mut definitions = ReplDefinitions::new(PathBuf::from("."));
+        assert_eq!(repl_type(&definitions, "1 + 2").as_deref(), Some("Int"));
+
+        let identity = repl_type(&definitions, "|x| x").unwrap();
+        assert!(identity.contains("->"), "got: {}", identity);
+
+        let inc = "fn inc(x: Int) -> Int do x + 1 end";
+        let checked = definitions.check(inc).unwrap();
+        definitions.add([inc.to_string()], checked);
+        assert_eq!(
+            repl_type(&definitions, "inc").as_deref(),
+            Some("(Int) -> Int")
+        );
+        assert_eq!(repl_type(&definitions, "missing"), None);
+    }
+
+    #[test]
+    fn map_literals_and_subscripts_run_as_python() {
+        let python = compile_source_silent_with_base(
+            r#"let m = {"key": 1, "say \"hi\"": 2}
+print(m["key"] + m["say \"hi\""])
+"#,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert!(python.contains(r#"m = {"key": 1, "say \"hi\"": 2}"#));
+        assert!(python.contains(r#"m["key"]"#));
+
+        let output = execute_python_code(&python, None).unwrap();
+        assert_eq!(output.trim(), "3");
+    }
+
+    #[test]
+    fn logical_operators_short_circuit_at_runtime() {
+        let python = compile_source_silent_with_base(
+            r#"fn crash() -> Bool do
+  println("crashed")
+  true
+end
+println(false and crash())
+println(true or crash())
+"#,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let output = execute_python_code(&python, None).unwrap();
+        assert_eq!(output.trim(), "False\nTrue");
+    }
+
+    #[test]
+    fn for_loops_unpack_tuple_patterns() {
+        let python = compile_source_silent_with_base(
+            "let pairs = [(1, 2), (3, 4)]\nfor (x, y) in pairs do println(x * y) end end\n",
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(python.contains("for x, y in pairs:"));
+
+        let output = execute_python_code(&python, None).unwrap();
+        assert_eq!(output.trim(), "2\n12");
+    }
+
+    #[test]
+    fn range_for_loops_run_over_python_ranges() {
+        let python =
+            compile_source_silent_with_base("for i in 1..10 do println(i) end end\n", None, None)
+                .unwrap();
+        assert!(python.contains("for i in range(1, 10):"));
+
+        let output = execute_python_code(&python, None).unwrap();
+        let expected: Vec<String> = (1..10).map(|i| i.to_string()).collect();
+        assert_eq!(output.trim(), expected.join("\n"));
+    }
+
+    #[test]
+    fn print_leaves_the_line_open_and_println_ends_it() {
+        let run = |source: &str| {
+            let python = compile_source_silent_with_base(source, None, None).unwrap();
+            execute_python_code(&python, None).unwrap()
+        };
+
+        assert_eq!(run("print \"a\"; print \"b\"\n"), "ab");
+        assert_eq!(run("println \"a\"; println \"b\"\n"), "a\nb\n");
+    }
+
+    #[test]
+    fn python_keywords_are_renamed_where_declared_and_used() {
+        let python = compile_source_silent_with_base(
+            "let lambda = 1\nprintln lambda\nfn yield(global) do global + lambda end\nprintln(yield(2))\nprintln \"v={lambda + yield(1)}\"\n",
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert!(python.contains("lambda_ = 1"));
+        assert!(python.contains("def yield_(global_):"));
+        assert!(python.contains("f\"v={lambda_ + yield_(1)}\""));
+
+        let output = execute_python_code(&python, None).unwrap();
+        assert_eq!(output.trim(), "1\n3\nv=3");
+    }
+
+    #[test]
+    fn nested_bodies_are_indented_four_spaces_per_level() {
+        let python = compile_source_silent_with_base(
+            r#"fn count_evens(n: Int) -> Int do
+  var i = 0
+  var evens = 0
+  while i < n do
+    if i % 2 == 0 do
+      evens = evens + 1
+    end
+    i = i + 1
+  end
+  evens
+end
+print(count_evens(10))
+"#,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert!(
+            python.contains("\n    while (i < n):\n        if ((i % 2) == 0):\n            evens")
+        );
+        assert!(python.contains("\n        i = (i + 1)\n    return evens"));
+
+        let output = execute_python_code(&python, None).unwrap();
+        assert_eq!(output.trim(), "5");
+    }
+
+    #[test]
+    fn source_diagnostics_are_named_after_their_source() {
+        let source = "let x: Int = \"a\"\nprint missing\n";
+        let base_dir = std::env::temp_dir();
+        let rendered = |name: &str| -> Vec<String> {
+            source_diagnostics(source, name, base_dir.clone(), &CompileOptions::default())
+                .iter()
+                .map(|diagnostic| diagnostic.render(Some(source)))
+                .collect()
+        };
+
+        let from_file = rendered("prog.nm");
+        let from_stdin = rendered(STDIN_NAME);
+
+        assert!(!from_file.is_empty());
+        assert!(from_stdin.iter().all(|d| d.contains("--> <stdin>:")));
+        assert!(from_file[0].contains(" 2 | print missing\n   |       ^^^^^^^\n"));
+        let renamed: Vec<String> = from_file
+            .iter()
+            .map(|d| d.replacen("prog.nm", STDIN_NAME, 1))
+            .collect();
+        assert_eq!(renamed, from_stdin);
+
+        assert!(source_diagnostics(
+            "print 1\n",
+            STDIN_NAME,
+            std::env::temp_dir(),
+            &CompileOptions::default()
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn strict_match_turns_non_exhaustive_warnings_into_errors() {
+        let severities = |source: &str, strict_match: bool| -> Vec<Severity> {
+            let options = CompileOptions {
+                strict_match,
+                ..CompileOptions::default()
+            };
+            source_diagnostics(source, "prog.nm", std::env::temp_dir(), &options)
+                .iter()
+                .map(|diagnostic| diagnostic.severity)
+                .collect()
+        };
+
+        let partial = "let n = 2\nmatch n { 1 => print \"one\" }\n";
+        assert_eq!(severities(partial, false), vec![Severity::Warning]);
+        assert_eq!(severities(partial, true), vec![Severity::Error]);
+
+        for exhaustive in [
+            "let b = true\nmatch b { true => 1, false => 0 }\n",
+            "let n = 2\nmatch n { 1 => 1, _ => 0 }\n",
+        ] {
+            assert!(severities(exhaustive, false).is_empty());
+            assert!(severities(exhaustive, true).is_empty());
+        }
+    }
+
+    #[test]
+    fn warn_shadowing_reports_shadowed_bindings_only_when_asked() {
+        let source = "fn total() do 1 end\nfn main() do\n  let total = 2\n  print total\nend\n";
+        let messages = |warn_shadowing: bool| -> Vec<String> {
+            let options = CompileOptions {
+                warn_shadowing,
+                ..CompileOptions::default()
+            };
+            source_diagnostics(source, "prog.nm", std::env::temp_dir(), &options)
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect()
+        };
+
+        assert!(messages(false).is_empty());
+        assert_eq!(
+            messages(true),
+            vec!["'total' shadows a function of the same name".to_string()]
+        );
+    }
 }
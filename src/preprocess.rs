@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use nevermind_common::{Diagnostic, Label, SourceLocation, Span};
+
+/// A program with its includes spliced in, along with where each of its
+/// lines came from, so diagnostics can point into the file that has them
+pub struct Expanded {
+    pub source: String,
+    /// The program's own text first, then each included file's path and
+    /// text in the order they were first included
+    files: Vec<(PathBuf, String)>,
+    /// For each line of `source`, the index of the file it came from and
+    /// its line number there
+    origins: Vec<(usize, usize)>,
+}
+
+impl Expanded {
+    /// Point `diagnostic` at the files and lines its spans came from,
+    /// returning it with the text it should be rendered against
+    ///
+    /// Spans in the program itself keep no file, so they are shown under
+    /// the diagnostic's source name. A related label in a different file
+    /// than the primary one can't be underlined, so it becomes a note.
+    pub fn locate(&self, mut diagnostic: Diagnostic) -> (Diagnostic, &str) {
+        let file = self.file_of(&diagnostic.primary.span);
+        diagnostic.primary.span = self.locate_span(&diagnostic.primary.span);
+        let (shown, elsewhere): (Vec<Label>, Vec<Label>) = diagnostic
+            .secondary
+            .into_iter()
+            .partition(|label| self.file_of(&label.span) == file);
+        diagnostic.secondary = shown
+            .into_iter()
+            .map(|label| Label::new(self.locate_span(&label.span), label.message))
+            .collect();
+        for label in elsewhere {
+            let span = self.locate_span(&label.span);
+            let path = span.file().unwrap_or_else(|| Path::new(""));
+            diagnostic.notes.push(format!(
+                "{} ({}:{}:{})",
+                label.message,
+                path.display(),
+                span.start.line,
+                span.start.column
+            ));
+        }
+        (diagnostic, &self.files[file].1)
+    }
+
+    /// The index of the file a span starts in
+    fn file_of(&self, span: &Span) -> usize {
+        self.origin(span.start.line).map_or(0, |(file, _)| file)
+    }
+
+    /// The line of the expanded source numbered `line`, if it has one
+    fn origin(&self, line: usize) -> Option<(usize, usize)> {
+        line.checked_sub(1)
+            .and_then(|index| self.origins.get(index))
+            .copied()
+    }
+
+    fn locate_span(&self, span: &Span) -> Span {
+        let (Some(start), end) = (self.origin(span.start.line), self.origin(span.end.line)) else {
+            return span.clone();
+        };
+        let located = |(file, line): (usize, usize), at: &SourceLocation| SourceLocation {
+            file: (file > 0).then(|| self.files[file].0.clone()),
+            line,
+            ..at.clone()
+        };
+        let start_at = located(start, &span.start);
+        // A span can't run from one file into another, so one that would is
+        // cut short where it starts
+        let end_at = match end {
+            Some(end) if end.0 == start.0 => located(end, &span.end),
+            _ => start_at.clone(),
+        };
+        Span::new(start_at, end_at)
+    }
+}
+
+/// Read a Nevermind file and splice in the files it includes.
+///
+/// An `include "shared.nm"` directive must sit on a line of its own and is
+/// replaced by the contents of the named file, resolved relative to the
+/// directory of the file containing the directive. Included files may
+/// include others; a file that ends up including itself is an error, and
+/// one already included is skipped, so two files including a third don't
+/// define its contents twice.
+pub fn expand_includes(path: &Path) -> Result<Expanded, Box<dyn Error>> {
+    let source = fs::read_to_string(path)?;
+    let canonical = fs::canonicalize(path)?;
+    let mut expander = Expander::new(source.clone());
+    expander.chain.push((canonical, path.to_path_buf()));
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    expander.expand_source(&source, 0, base_dir)?;
+    Ok(expander.finish())
+}
+
+/// Read a program from standard input and splice in the files it includes,
+/// resolved relative to the current directory.
+pub fn expand_stdin_includes() -> Result<Expanded, Box<dyn Error>> {
+    let mut source = String::new();
+    io::stdin().read_to_string(&mut source)?;
+    let mut expander = Expander::new(source.clone());
+    expander.expand_source(&source, 0, Path::new(""))?;
+    Ok(expander.finish())
+}
+
+/// The state of one expansion
+struct Expander {
+    expanded: String,
+    files: Vec<(PathBuf, String)>,
+    origins: Vec<(usize, usize)>,
+    /// The canonical path of every file currently being expanded and the
+    /// path it was reached through
+    chain: Vec<(PathBuf, PathBuf)>,
+    /// The canonical path of every file already spliced in
+    included: HashSet<PathBuf>,
+}
+
+impl Expander {
+    fn new(source: String) -> Self {
+        Self {
+            expanded: String::with_capacity(source.len()),
+            files: vec![(PathBuf::new(), source)],
+            origins: Vec::new(),
+            chain: Vec::new(),
+            included: HashSet::new(),
+        }
+    }
+
+    fn finish(self) -> Expanded {
+        Expanded {
+            source: self.expanded,
+            files: self.files,
+            origins: self.origins,
+        }
+    }
+
+    /// Splice the file at `path` in, unless it already has been
+    fn expand_file(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let canonical = fs::canonicalize(path)
+            .map_err(|err| format!("cannot include {}: {}", path.display(), err))?;
+
+        if let Some(start) = self.chain.iter().position(|(seen, _)| *seen == canonical) {
+            let files: Vec<String> = self.chain[start..]
+                .iter()
+                .map(|(_, shown)| shown.display().to_string())
+                .chain(std::iter::once(path.display().to_string()))
+                .collect();
+            return Err(format!("include cycle: {}", files.join(" -> ")).into());
+        }
+        if !self.included.insert(canonical.clone()) {
+            return Ok(());
+        }
+
+        let source = fs::read_to_string(path)?;
+        let file = self.files.len();
+        self.files.push((path.to_path_buf(), source.clone()));
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        self.chain.push((canonical, path.to_path_buf()));
+        self.expand_source(&source, file, base_dir)?;
+        self.chain.pop();
+        Ok(())
+    }
+
+    /// Splice includes into `source`, the text of the file numbered `file`,
+    /// resolving them against `base_dir`
+    fn expand_source(
+        &mut self,
+        source: &str,
+        file: usize,
+        base_dir: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        for (index, line) in source.split_inclusive('\n').enumerate() {
+            match include_target(line) {
+                Some(target) => self.expand_file(&base_dir.join(target))?,
+                None => {
+                    self.expanded.push_str(line);
+                    if file > 0 && !line.ends_with('\n') {
+                        self.expanded.push('\n');
+                    }
+                    self.origins.push((file, index + 1));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The quoted path of an `include` directive, if `line` is one
+fn include_target(line: &str) -> Option<&str> {
+    let rest = line.trim().strip_prefix("include")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    rest.trim_start().strip_prefix('"')?.strip_suffix('"')
+}
@@ -0,0 +1,100 @@
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Stdio};
+
+/// A binding the driver prints and then forgets after running an input,
+/// which is how the REPL shows the value of an expression
+pub const VALUE_BINDING: &str = "__repl_value";
+
+/// Runs each chunk of code it reads in one namespace, so bindings outlive
+/// the input that made them.
+///
+/// A chunk arrives as its length in characters on a line of its own, then
+/// the code. Whatever the code prints is passed on, followed by `\0error`
+/// and the traceback if it raised, and `\0done` once it has finished.
+const DRIVER: &str = r#"
+import io, sys, traceback
+channel, sys.stdin = sys.stdin, io.StringIO()
+namespace = {"__name__": "__repl__"}
+while True:
+    header = channel.readline()
+    if not header:
+        break
+    code = channel.read(int(header))
+    error = None
+    try:
+        exec(compile(code, "<repl>", "exec"), namespace)
+        value = namespace.pop("__repl_value", None)
+        if value is not None:
+            print(value)
+    except SystemExit:
+        pass
+    except BaseException:
+        error = traceback.format_exc()
+    sys.stdout.write("\n")
+    if error is not None:
+        sys.stdout.write("\0error\n" + error)
+    sys.stdout.write("\0done\n")
+    sys.stdout.flush()
+"#;
+
+/// A Python interpreter that stays alive between REPL inputs
+pub struct Session {
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+}
+
+impl Session {
+    /// Start Python in `base_dir`, where local modules are compiled to
+    pub fn start(base_dir: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut child = crate::with_python(|command| {
+            command
+                .args(["-u", "-c", DRIVER])
+                .current_dir(base_dir)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+        })?;
+        let input = child.stdin.take().ok_or("Python has no stdin")?;
+        let output = child.stdout.take().ok_or("Python has no stdout")?;
+        Ok(Self {
+            child,
+            input,
+            output: BufReader::new(output),
+        })
+    }
+
+    /// Run Python in the session, returning what it printed
+    pub fn run(&mut self, code: &str) -> Result<String, Box<dyn Error>> {
+        write!(self.input, "{}\n{}", code.chars().count(), code)?;
+        self.input.flush()?;
+
+        let mut output = String::new();
+        let mut traceback: Option<String> = None;
+        loop {
+            let mut line = String::new();
+            if self.output.read_line(&mut line)? == 0 {
+                return Err("the Python session ended; use :reset to start a new one".into());
+            }
+            match line.as_str() {
+                "\0done\n" => break,
+                "\0error\n" => traceback = Some(String::new()),
+                _ => traceback.as_mut().unwrap_or(&mut output).push_str(&line),
+            }
+        }
+
+        match traceback {
+            Some(traceback) => Err(crate::friendly_runtime_error(&traceback).into()),
+            None => Ok(output),
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,146 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use nevermind::CompileOptions;
+
+/// How one test file went
+enum Outcome {
+    Passed,
+    /// An `assert` failed on the given line with its message
+    AssertionFailed(usize, String),
+    /// The file didn't compile or stopped with some other error
+    Error(String),
+}
+
+/// Run one or more Nevermind test files.
+///
+/// Each file is compiled and run; it passes when every `assert` it reaches
+/// holds and the program exits cleanly.
+pub fn test_paths(inputs: Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    if inputs.is_empty() {
+        return Err("no input files provided".into());
+    }
+
+    println!(
+        "running {} test file{}",
+        inputs.len(),
+        if inputs.len() == 1 { "" } else { "s" }
+    );
+
+    let mut failures = Vec::new();
+    for input in &inputs {
+        let outcome = test_file(input)?;
+        let status = if matches!(outcome, Outcome::Passed) {
+            "ok"
+        } else {
+            "FAILED"
+        };
+        println!("test {} ... {}", input.display(), status);
+        if !matches!(outcome, Outcome::Passed) {
+            failures.push((input, outcome));
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for (input, outcome) in &failures {
+            match outcome {
+                Outcome::AssertionFailed(line, message) => println!(
+                    "    {}:{}: assertion failed: {}",
+                    input.display(),
+                    line,
+                    message
+                ),
+                Outcome::Error(message) => println!("    {}: {}", input.display(), message),
+                Outcome::Passed => {}
+            }
+        }
+    }
+
+    let failed = failures.len();
+    println!(
+        "\ntest result: {}. {} passed; {} failed",
+        if failed == 0 { "ok" } else { "FAILED" },
+        inputs.len() - failed,
+        failed
+    );
+
+    if failed > 0 {
+        return Err(format!("{} test file(s) failed", failed).into());
+    }
+
+    Ok(())
+}
+
+/// Compile a test file to a temporary script and run it, with the local
+/// modules it imports compiled next to its source
+fn test_file(input: &Path) -> Result<Outcome, Box<dyn Error>> {
+    let (program, name) = crate::read_input(input)?;
+    let source = &program.source;
+    let base_dir = input
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.parent().map(|d| d.to_path_buf()))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let statements = match nevermind_lexer::Lexer::new(source)
+        .tokenize()
+        .map_err(|err| err.to_string())
+        .and_then(|tokens| {
+            nevermind_parser::Parser::from_tokens(tokens)
+                .parse()
+                .map_err(|err| err.to_string())
+        }) {
+        Ok(statements) => statements,
+        Err(message) => return Ok(Outcome::Error(message)),
+    };
+    crate::compile_module_deps(&statements, &base_dir, None)?;
+
+    let context = nevermind_codegen::PythonModuleContext::new(base_dir.clone(), None);
+    let python = match nevermind::compile_module(source, Some(context), &CompileOptions::default())
+    {
+        Ok(python) => python,
+        Err(diagnostics) => {
+            for diagnostic in &diagnostics {
+                eprint!(
+                    "{}",
+                    crate::render_diagnostic(diagnostic.clone(), &name, &program)
+                );
+            }
+            return Ok(Outcome::Error("compilation failed".to_string()));
+        }
+    };
+
+    let script = crate::temp_script("nevermind_test")?;
+    fs::write(&script, python)?;
+    let python_path = crate::python_path_with(base_dir)?;
+    let output = crate::with_python(|command| {
+        command
+            .arg(&script)
+            .env("PYTHONPATH", &python_path)
+            .output()
+    });
+    let _ = fs::remove_file(&script);
+    let output = output?;
+    if output.status.success() {
+        return Ok(Outcome::Passed);
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Ok(assertion_failure(&stderr)
+        .unwrap_or_else(|| Outcome::Error(crate::friendly_runtime_error(&stderr))))
+}
+
+/// The line and message of a failed `assert`, which the compiler writes as
+/// `line <n>: <message>`
+fn assertion_failure(stderr: &str) -> Option<Outcome> {
+    let last = stderr.trim().lines().last()?;
+    let (line, message) = last
+        .strip_prefix("AssertionError: line ")?
+        .split_once(": ")?;
+    Some(Outcome::AssertionFailed(
+        line.parse().ok()?,
+        message.to_string(),
+    ))
+}
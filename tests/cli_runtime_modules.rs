@@ -1,6 +1,7 @@
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 struct TestDir {
@@ -146,3 +147,640 @@ fn nested_local_imports_prefer_the_importing_directory() {
     let foo_python = fs::read_to_string(pkg_dir.join("foo.py")).unwrap();
     assert!(foo_python.contains("from pkg.bar import value"));
 }
+
+#[test]
+fn run_splices_included_files_relative_to_the_includer() {
+    let temp_dir = TestDir::new("nevermind_cli_include");
+    let lib_dir = temp_dir.path.join("lib");
+    fs::create_dir_all(&lib_dir).unwrap();
+
+    fs::write(
+        temp_dir.path.join("main.nm"),
+        "include \"lib/shapes.nm\"\n\nfn main() do\n  print area(6, 7)\nend\n",
+    )
+    .unwrap();
+    fs::write(
+        lib_dir.join("shapes.nm"),
+        "include \"units.nm\"\n\nfn area(w, h) do\n  w * h * scale\nend\n",
+    )
+    .unwrap();
+    fs::write(lib_dir.join("units.nm"), "let scale = 1\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+        .current_dir(&temp_dir.path)
+        .arg("run")
+        .arg("main.nm")
+        .output()
+        .unwrap();
+
+    if !output.status.success() {
+        panic!(
+            "stdout:\n{}\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("42"));
+}
+
+#[test]
+fn run_includes_a_file_reached_twice_only_once() {
+    let temp_dir = TestDir::new("nevermind_cli_include_diamond");
+
+    fs::write(
+        temp_dir.path.join("main.nm"),
+        "include \"circle.nm\"\ninclude \"square.nm\"\nprint circle(1) + square(2)\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path.join("circle.nm"),
+        "include \"units.nm\"\nfn circle(r) do\n  3 * r * r * scale()\nend\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path.join("square.nm"),
+        "include \"units.nm\"\nfn square(s) do\n  s * s * scale()\nend\n",
+    )
+    .unwrap();
+    fs::write(temp_dir.path.join("units.nm"), "fn scale() do\n  1\nend\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+        .current_dir(&temp_dir.path)
+        .arg("run")
+        .arg("main.nm")
+        .output()
+        .unwrap();
+
+    if !output.status.success() {
+        panic!(
+            "stdout:\n{}\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("7"));
+}
+
+#[test]
+fn errors_in_included_files_point_into_them() {
+    let temp_dir = TestDir::new("nevermind_cli_include_errors");
+    let lib_dir = temp_dir.path.join("lib");
+    fs::create_dir_all(&lib_dir).unwrap();
+    let shapes = "fn area(w, h) do\n  w * h\nend\n";
+    let main = "include \"lib/shapes.nm\"\n\nprint area(2, 3)\n";
+
+    let errors = |main: &str, shapes: &str| {
+        fs::write(temp_dir.path.join("main.nm"), main).unwrap();
+        fs::write(lib_dir.join("shapes.nm"), shapes).unwrap();
+        ["check", "compile"].map(|command| {
+            let output = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+                .current_dir(&temp_dir.path)
+                .arg(command)
+                .arg("main.nm")
+                .output()
+                .unwrap();
+            assert!(!output.status.success());
+            String::from_utf8_lossy(&output.stderr).into_owned()
+        })
+    };
+
+    for stderr in errors(&main.replace("area(2, 3)", "missing"), shapes) {
+        assert!(stderr.contains("--> main.nm:3:7"), "stderr:\n{}", stderr);
+        assert!(
+            stderr.contains(" 3 | print missing\n"),
+            "stderr:\n{}",
+            stderr
+        );
+    }
+    for stderr in errors(main, &shapes.replace("w * h", "w * missing")) {
+        assert!(
+            stderr.contains("--> lib/shapes.nm:2:7"),
+            "stderr:\n{}",
+            stderr
+        );
+        assert!(
+            stderr.contains(" 2 |   w * missing\n"),
+            "stderr:\n{}",
+            stderr
+        );
+    }
+}
+
+#[test]
+fn run_executes_top_level_expression_statements_in_order() {
+    let temp_dir = TestDir::new("nevermind_cli_top_level_exprs");
+    fs::write(
+        temp_dir.path.join("main.nm"),
+        "println \"hi\"\nfn twice(n) do\n  println n * 2\n  n * 2\nend\ntwice(21)\n1 + 2\nprintln \"bye\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+        .current_dir(&temp_dir.path)
+        .arg("run")
+        .arg("main.nm")
+        .output()
+        .unwrap();
+
+    if !output.status.success() {
+        panic!(
+            "stdout:\n{}\n\nstderr:\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let program_output = stdout.split("Executing with Python...").nth(1).unwrap();
+    assert_eq!(program_output.trim(), "hi\n42\nbye");
+}
+
+#[test]
+fn run_leaves_no_python_file_unless_asked_to_keep_it() {
+    let temp_dir = TestDir::new("nevermind_cli_run_temp");
+    fs::write(temp_dir.path.join("main.nm"), "println(\"hi\")\n").unwrap();
+
+    let run = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_nevermind"))
+            .current_dir(&temp_dir.path)
+            .arg("run")
+            .args(extra)
+            .arg("main.nm")
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&[]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("hi"));
+    assert!(!temp_dir.path.join("main.py").exists());
+
+    assert!(run(&["--keep"]).status.success());
+    assert!(temp_dir.path.join("main.py").exists());
+}
+
+#[test]
+fn include_cycle_is_reported_with_the_file_chain() {
+    let temp_dir = TestDir::new("nevermind_cli_include_cycle");
+
+    fs::write(temp_dir.path.join("a.nm"), "include \"b.nm\"\nprint 1\n").unwrap();
+    fs::write(temp_dir.path.join("b.nm"), "include \"a.nm\"\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+        .current_dir(&temp_dir.path)
+        .arg("compile")
+        .arg("a.nm")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("include cycle: a.nm -> b.nm -> a.nm"),
+        "stderr:\n{}",
+        stderr
+    );
+}
+
+/// Run the CLI with `args`, feeding `source` to its standard input
+fn pipe_into(args: &[&str], source: &str) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn repl_keeps_values_between_inputs_until_reset() {
+    let output = pipe_into(
+        &["repl"],
+        "let x = 5\nx + 1\nvar n = 0\nn = n + 1\nn = n + 1\nn\n:reset\nx\n",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let echoed: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.trim_start_matches(">>> "))
+        .filter(|line| !line.is_empty())
+        .collect();
+    assert!(
+        echoed.windows(2).any(|pair| pair == ["6", "2"]),
+        "stdout:\n{}",
+        stdout
+    );
+    assert!(stdout.contains("Session reset."), "stdout:\n{}", stdout);
+    assert!(
+        stderr.contains("Cannot find value 'x'"),
+        "stderr:\n{}",
+        stderr
+    );
+}
+
+#[test]
+fn check_reads_standard_input_when_given_a_dash() {
+    let temp_dir = TestDir::new("nevermind_cli_check_stdin");
+    let source = "let x: Int = \"a\"\nlet y = x + 1\n";
+    fs::write(temp_dir.path.join("bad.nm"), source).unwrap();
+
+    let from_file = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+        .current_dir(&temp_dir.path)
+        .args(["check", "bad.nm"])
+        .output()
+        .unwrap();
+
+    let from_stdin = pipe_into(&["check", "-"], source);
+
+    assert!(!from_file.status.success());
+    assert!(!from_stdin.status.success());
+    let file_stderr = String::from_utf8_lossy(&from_file.stderr);
+    let stdin_stderr = String::from_utf8_lossy(&from_stdin.stderr);
+    assert!(
+        stdin_stderr.contains("<stdin>:1:"),
+        "stderr:\n{}",
+        stdin_stderr
+    );
+    assert_eq!(file_stderr.replace("bad.nm", "<stdin>"), stdin_stderr);
+}
+
+#[test]
+fn check_and_compile_accept_a_program_piped_to_a_dash() {
+    let source = "let x = 1 + 2\nprintln(x)\n";
+
+    let checked = pipe_into(&["check", "-"], source);
+    assert!(
+        checked.status.success(),
+        "stderr:\n{}",
+        String::from_utf8_lossy(&checked.stderr)
+    );
+
+    let compiled = pipe_into(&["compile", "-"], source);
+    assert!(compiled.status.success());
+    let stdout = String::from_utf8_lossy(&compiled.stdout);
+    assert!(
+        stdout.starts_with("# Generated by Nevermind compiler"),
+        "stdout:\n{}",
+        stdout
+    );
+    assert!(stdout.contains("print(x)"), "stdout:\n{}", stdout);
+}
+
+#[test]
+fn color_flag_controls_escape_sequences_in_diagnostics() {
+    let source = "let x: Int = \"a\"\n";
+    let stderr = |color: &str| {
+        let output = pipe_into(&["check", "-", "--color", color], source);
+        assert!(!output.status.success());
+        String::from_utf8_lossy(&output.stderr).to_string()
+    };
+
+    let never = stderr("never");
+    assert!(never.contains("error[E0011]"), "stderr:\n{}", never);
+    assert!(!never.contains('\x1b'), "stderr:\n{}", never);
+
+    let always = stderr("always");
+    assert!(
+        always.contains("\x1b[1;31merror[E0011]"),
+        "stderr:\n{}",
+        always
+    );
+
+    // Piped output isn't a terminal, so the default is no color
+    assert!(!stderr("auto").contains('\x1b'));
+}
+
+#[test]
+fn compile_carries_on_past_a_file_with_errors() {
+    let temp_dir = TestDir::new("nevermind_cli_compile_many");
+    fs::write(temp_dir.path.join("bad.nm"), "let x: Int = \"a\"\n").unwrap();
+    fs::write(temp_dir.path.join("good.nm"), "println(1 + 2)\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+        .current_dir(&temp_dir.path)
+        .args(["compile", "bad.nm", "good.nm"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(!temp_dir.path.join("bad.py").exists());
+    assert!(temp_dir.path.join("good.py").exists());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("1 of 2 files compiled successfully"),
+        "stdout:\n{}",
+        stdout
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Error: bad.nm:"), "stderr:\n{}", stderr);
+}
+
+#[test]
+fn emit_pyc_writes_bytecode_python_can_load() {
+    let temp_dir = TestDir::new("nevermind_cli_emit_pyc");
+    fs::write(
+        temp_dir.path.join("greet.nm"),
+        "let greeting = \"hello\"\nprintln(greeting)\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+        .current_dir(&temp_dir.path)
+        .args(["compile", "greet.nm", "--emit", "pyc"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(temp_dir.path.join("greet.pyc").exists());
+    assert!(!temp_dir.path.join("greet.py").exists());
+
+    // Run directly, and imported as a module without any source beside it
+    let python = |args: &[&str]| {
+        let output = Command::new("python3")
+            .current_dir(&temp_dir.path)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "stderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    assert_eq!(python(&["greet.pyc"]), "hello\n");
+    assert_eq!(
+        python(&["-c", "import greet; print(greet.greeting)"]),
+        "hello\nhello\n"
+    );
+}
+
+#[test]
+fn fmt_check_fails_only_when_formatting_would_change_the_file() {
+    let temp_dir = TestDir::new("nevermind_cli_fmt_check");
+    fs::write(temp_dir.path.join("messy.nm"), "let  x=1+2\n").unwrap();
+    fs::write(temp_dir.path.join("tidy.nm"), "let x = 1 + 2\n").unwrap();
+
+    let check = |file: &str| {
+        Command::new(env!("CARGO_BIN_EXE_nevermind"))
+            .current_dir(&temp_dir.path)
+            .args(["fmt", "--check", file])
+            .output()
+            .unwrap()
+    };
+
+    assert!(!check("messy.nm").status.success());
+    assert!(check("tidy.nm").status.success());
+}
+
+#[test]
+fn test_reports_failed_assertions_with_their_line() {
+    let temp_dir = TestDir::new("nevermind_cli_test");
+    fs::write(
+        temp_dir.path.join("good.nm"),
+        "assert(1 + 1 == 2, \"addition\")\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path.join("bad.nm"),
+        "let x = 3\nassert(x > 1, \"x is positive\")\nassert(x > 5, \"x is big\")\n",
+    )
+    .unwrap();
+
+    let test = |files: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_nevermind"))
+            .current_dir(&temp_dir.path)
+            .arg("test")
+            .args(files)
+            .output()
+            .unwrap()
+    };
+
+    assert!(test(&["good.nm"]).status.success());
+    assert!(!temp_dir.path.join("good.py").exists());
+
+    let output = test(&["good.nm", "bad.nm"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("test good.nm ... ok"),
+        "stdout:\n{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("bad.nm:3: assertion failed: x is big"),
+        "stdout:\n{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("test result: FAILED. 1 passed; 1 failed"),
+        "stdout:\n{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_runs_files_that_import_local_modules() {
+    let temp_dir = TestDir::new("nevermind_cli_test_imports");
+    fs::write(
+        temp_dir.path.join("util.nm"),
+        "export fn double(x) do x * 2 end\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path.join("uses_util.nm"),
+        "from \"util\" import double\nassert(double(2) == 4, \"doubles\")\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+        .current_dir(&temp_dir.path)
+        .args(["test", "uses_util.nm"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stdout:\n{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    assert!(!temp_dir.path.join("uses_util.py").exists());
+}
+
+#[test]
+fn explain_describes_known_codes_and_rejects_unknown_ones() {
+    let explain = |code: &str| {
+        Command::new(env!("CARGO_BIN_EXE_nevermind"))
+            .args(["explain", code])
+            .output()
+            .unwrap()
+    };
+
+    let known = explain("E0012");
+    assert!(known.status.success());
+    let stdout = String::from_utf8_lossy(&known.stdout);
+    assert!(
+        stdout.starts_with("E0012: return type mismatch"),
+        "stdout:\n{}",
+        stdout
+    );
+    assert!(stdout.lines().count() > 3, "stdout:\n{}", stdout);
+
+    let unknown = explain("E9999");
+    assert!(!unknown.status.success());
+    let stderr = String::from_utf8_lossy(&unknown.stderr);
+    assert!(
+        stderr.contains("no such error code: E9999"),
+        "stderr:\n{}",
+        stderr
+    );
+}
+
+/// Run `compile --emit <stage>` on a small program and return its stdout,
+/// checking that no Python file was written
+fn emit_stage(stage: &str) -> String {
+    let temp_dir = TestDir::new(&format!("nevermind_cli_emit_{}", stage));
+    fs::write(
+        temp_dir.path.join("main.nm"),
+        "fn add(a: Int, b: Int) -> Int do\n  return a + b\nend\nlet total = add(1, 2)\nprint total\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+        .current_dir(&temp_dir.path)
+        .args(["compile", "main.nm", "--emit", stage])
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "stderr:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!temp_dir.path.join("main.py").exists());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn nevermind_toml_sets_defaults_that_flags_override() {
+    let temp_dir = TestDir::new("nevermind_cli_config");
+    fs::write(
+        temp_dir.path.join("nevermind.toml"),
+        "[compile]\noutput-dir = \"build\"\n\n[lint]\ndisable = [\"todo-marker\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path.join("notes.nm"),
+        "# TODO: more notes\nprint(1)\n",
+    )
+    .unwrap();
+
+    let nevermind = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_nevermind"))
+            .current_dir(&temp_dir.path)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let lint = nevermind(&["lint", "notes.nm"]);
+    assert!(
+        lint.status.success(),
+        "stdout:\n{}",
+        String::from_utf8_lossy(&lint.stdout)
+    );
+
+    let lint = nevermind(&["lint", "notes.nm", "--enable", "todo-marker"]);
+    assert!(!lint.status.success());
+    assert!(String::from_utf8_lossy(&lint.stdout).contains("warning[todo-marker]"));
+
+    assert!(nevermind(&["compile", "notes.nm"]).status.success());
+    assert!(temp_dir.path.join("build/notes.py").exists());
+    assert!(!temp_dir.path.join("notes.py").exists());
+
+    assert!(nevermind(&["compile", "notes.nm", "--out-dir", "."])
+        .status
+        .success());
+    assert!(temp_dir.path.join("notes.py").exists());
+}
+
+#[test]
+fn compile_reuses_cached_output_until_the_source_changes() {
+    let temp_dir = TestDir::new("nevermind_cli_cache");
+    let source = temp_dir.path.join("count.nm");
+    fs::write(&source, "print(1)\n").unwrap();
+
+    let compile = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_nevermind"))
+            .current_dir(&temp_dir.path)
+            .arg("compile")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "stderr:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).contains("reusing cached output")
+    };
+    let python = || fs::read_to_string(temp_dir.path.join("count.py")).unwrap();
+
+    assert!(!compile(&["count.nm"]));
+    assert!(compile(&["count.nm"]));
+    assert!(python().contains("print(1,"));
+    assert!(!compile(&["count.nm", "--force"]));
+
+    fs::write(&source, "print(2)\n").unwrap();
+    assert!(!compile(&["count.nm"]));
+    assert!(python().contains("print(2,"));
+    assert!(compile(&["count.nm"]));
+}
+
+#[test]
+fn emit_tokens_prints_the_token_stream() {
+    let tokens = emit_stage("tokens");
+    assert!(tokens.lines().next().unwrap().contains("Keyword(Fn)"));
+    assert!(tokens.contains("\"total\""));
+}
+
+#[test]
+fn emit_ast_prints_the_syntax_tree() {
+    let ast = emit_stage("ast");
+    assert!(ast.starts_with("Function {"));
+    assert!(ast.contains("name: \"add\""));
+}
+
+#[test]
+fn emit_resolved_prints_top_level_types() {
+    let resolved = emit_stage("resolved");
+    assert_eq!(resolved, "fn add: (Int, Int) -> Int\nlet total: Int\n");
+}
+
+#[test]
+fn emit_mir_prints_the_lowered_program() {
+    let mir = emit_stage("mir");
+    assert!(mir.starts_with("MirProgram {"));
+    assert!(mir.contains("name: \"add\""));
+}
+
+#[test]
+fn emit_py_prints_python_instead_of_writing_it() {
+    let python = emit_stage("py");
+    assert!(python.starts_with("# Generated by Nevermind compiler"));
+    assert!(python.contains("def add(a, b):"));
+}
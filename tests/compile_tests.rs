@@ -5,6 +5,8 @@
 
 use std::path::Path;
 
+use nevermind::CompileOptions;
+
 /// Helper: compile a source string through the full pipeline and return generated Python
 fn compile_to_python(source: &str) -> Result<String, Box<dyn std::error::Error>> {
     // Lex
@@ -34,10 +36,14 @@ fn compile_to_python(source: &str) -> Result<String, Box<dyn std::error::Error>>
     checker.check(&stmts)?;
 
     // MIR lowering
-    let mir_program = nevermind_mir::lower_program(&stmts)?;
+    let mir_program = nevermind_mir::lower_program_with_types(
+        &stmts,
+        &checker.node_types(),
+        &CompileOptions::default(),
+    )?;
 
     // Code generation
-    let python_code = nevermind_codegen::generate(&mir_program)?;
+    let python_code = nevermind_codegen::generate(&mir_program, &CompileOptions::default())?;
 
     Ok(python_code)
 }
@@ -73,8 +79,12 @@ fn compile_file_to_python(path: &Path) -> Result<String, Box<dyn std::error::Err
     let mut checker = nevermind_type_checker::TypeChecker::new();
     checker.check(&stmts)?;
 
-    let mir_program = nevermind_mir::lower_program(&stmts)?;
-    let python_code = nevermind_codegen::generate(&mir_program)?;
+    let mir_program = nevermind_mir::lower_program_with_types(
+        &stmts,
+        &checker.node_types(),
+        &CompileOptions::default(),
+    )?;
+    let python_code = nevermind_codegen::generate(&mir_program, &CompileOptions::default())?;
 
     Ok(python_code)
 }
@@ -83,7 +93,7 @@ fn compile_file_to_python(path: &Path) -> Result<String, Box<dyn std::error::Err
 fn test_hello_world() {
     let source = r#"
 fn main() do
-  print "Hello, World!"
+  println "Hello, World!"
 end
 "#;
     let python = compile_to_python(source).expect("compilation failed");
@@ -98,7 +108,7 @@ fn test_simple_arithmetic() {
 let x = 10
 let y = 20
 let z = x + y
-print z
+println z
 "#;
     let python = compile_to_python(source).expect("compilation failed");
     assert!(python.contains("x = 10"));
@@ -268,6 +278,24 @@ end
     assert!(!python.contains("if __name__"));
 }
 
+#[test]
+fn test_top_level_statements_get_no_entry_point() {
+    let python = compile_to_python("let x = 1\nprint(x)").expect("compilation failed");
+    assert!(!python.contains("if __name__"));
+}
+
+#[test]
+fn test_main_with_parameters_is_not_called_as_entry_point() {
+    let source = r#"
+fn main(args: List[String]) do
+  print(args)
+end
+"#;
+    let python = compile_to_python(source).expect("compilation failed");
+    assert!(python.contains("def main(args):"));
+    assert!(!python.contains("if __name__"));
+}
+
 #[test]
 fn test_nested_arithmetic() {
     let source = r#"
@@ -302,8 +330,8 @@ end
 "#;
     let python = compile_to_python(source).expect("compilation failed");
     assert!(python.contains("def greet():"));
-    assert!(python.contains("print(\"hi\")"));
-    assert!(python.contains("print(\"bye\")"));
+    assert!(python.contains("    print(\"hi\", end=\"\")\n"));
+    assert!(python.contains("    print(\"bye\", end=\"\")\n"));
 }
 
 #[test]
@@ -317,3 +345,875 @@ fn test_modules_example_compiles_with_explicit_exports() {
     assert!(python.contains("import mathutils"));
     assert!(python.contains("mathutils.abs_val"));
 }
+
+#[test]
+fn test_assignment_in_value_position_is_unit() {
+    let source = r#"
+var x = 0
+let y = (x = 5)
+print y
+"#;
+    let python = compile_to_python(source).expect("compilation failed");
+    assert!(python.contains("\nx = 5\n"));
+    assert!(python.contains("y = None"));
+    assert!(!python.contains("y = x = 5"));
+}
+
+#[test]
+fn test_bool_and_null_literals_use_python_keywords() {
+    let source = r#"
+let yes = true
+let no = false
+let empty = null
+"#;
+    let python = compile_to_python(source).expect("compilation failed");
+    assert!(python.contains("yes = True"));
+    assert!(python.contains("no = False"));
+    assert!(python.contains("empty = None"));
+    assert!(!python.contains("= true"));
+    assert!(!python.contains("= false"));
+    assert!(!python.contains("= null"));
+}
+
+#[test]
+fn test_compound_index_assignment_evaluates_index_once() {
+    let source = r#"
+fn pick() do
+  1
+end
+
+var xs = [1, 2, 3]
+xs[pick()] += 1
+xs[0] -= 1
+"#;
+    let python = compile_to_python(source).expect("compilation failed");
+    assert_eq!(python.matches("= pick()").count(), 1);
+    assert!(python.contains("= pick()\nxs[__index_"));
+    assert!(python.contains("] += 1"));
+    assert!(python.contains("xs[0] -= 1"));
+}
+
+#[test]
+fn test_char_literal_preserved_through_mir() {
+    let mut parser = nevermind_parser::Parser::new("let c = 'z'").expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mir_program =
+        nevermind_mir::lower_program(&stmts, &CompileOptions::default()).expect("lowering failed");
+
+    match &mir_program.statements[0] {
+        nevermind_mir::MirStmt::Let {
+            value: nevermind_mir::MirExpr::Literal { value, ty, .. },
+            ..
+        } => {
+            assert!(matches!(value, nevermind_mir::Literal::Char('z')));
+            assert_eq!(ty, &nevermind_type_checker::Type::Char);
+        }
+        other => panic!("expected let of a char literal, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_char_literal_emits_python_string() {
+    let source = r#"
+let c = 'a'
+let same = c == 'b'
+"#;
+    let python = compile_to_python(source).expect("compilation failed");
+    assert!(python.contains("c = 'a'"));
+    assert!(python.contains("(c == 'b')"));
+}
+
+#[test]
+fn test_concat_and_bitnot_lower_to_their_own_mir_ops() {
+    let mut parser =
+        nevermind_parser::Parser::new("let s = \"a\" ++ \"b\"\nlet n = ~5").expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mir_program =
+        nevermind_mir::lower_program(&stmts, &CompileOptions::default()).expect("lowering failed");
+
+    match &mir_program.statements[0] {
+        nevermind_mir::MirStmt::Let {
+            value: nevermind_mir::MirExpr::Binary { op, .. },
+            ..
+        } => assert_eq!(*op, nevermind_mir::BinOp::Concat),
+        other => panic!("expected let of a concatenation, got {:?}", other),
+    }
+
+    match &mir_program.statements[1] {
+        nevermind_mir::MirStmt::Let {
+            value: nevermind_mir::MirExpr::Unary { op, .. },
+            ..
+        } => assert_eq!(*op, nevermind_mir::UnaryOp::BitNot),
+        other => panic!("expected let of a bitwise not, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_compound_index_temporary_has_the_key_type() {
+    let source = "fn key() do\n  \"a\"\nend\nvar counts = {\"a\": 1}\ncounts[key()] += 1";
+    let mut parser = nevermind_parser::Parser::new(source).expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mut checker = nevermind_type_checker::TypeChecker::new();
+    checker.check(&stmts).expect("type check failed");
+    let mir_program = nevermind_mir::lower_program_with_types(
+        &stmts,
+        &checker.node_types(),
+        &CompileOptions::default(),
+    )
+    .expect("lowering failed");
+
+    match mir_program.statements.last() {
+        Some(nevermind_mir::MirStmt::Expr(nevermind_mir::MirExpr::Block {
+            statements, ..
+        })) => match &statements[0] {
+            nevermind_mir::MirExprStmt::Let { ty, value, .. } => {
+                assert_eq!(*ty, nevermind_type_checker::Type::String);
+                assert_eq!(*value.get_type(), nevermind_type_checker::Type::String);
+            }
+            other => panic!("expected the index temporary, got {:?}", other),
+        },
+        other => panic!("expected a compound index assignment, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_lowering_a_statement_sees_only_its_contexts_functions() {
+    let parse = |source: &str| {
+        nevermind_parser::Parser::new(source)
+            .expect("parser")
+            .parse()
+            .expect("parse failed")
+    };
+    let own_print = parse("fn print(x) do\n  x\nend\nfn show() do\n  print(1)\nend");
+    let builtin_print = parse("fn show() do\n  print(1)\nend");
+    nevermind_mir::lower_program(&own_print, &CompileOptions::default()).expect("lowering failed");
+
+    let show_returns = |program: &[nevermind_ast::Stmt]| {
+        let cx = nevermind_mir::lowering::Context::new(program);
+        match nevermind_mir::lowering::lower_statement(&cx, program.last().unwrap()) {
+            Ok(nevermind_mir::MirStmt::Function { body, .. }) => body.expr.is_some(),
+            other => panic!("expected a function, got {:?}", other),
+        }
+    };
+    assert!(show_returns(&own_print));
+    assert!(!show_returns(&builtin_print));
+}
+
+#[test]
+fn test_lowered_expressions_carry_checked_types() {
+    let mut parser = nevermind_parser::Parser::new("let n = 1 + 2\nlet s = \"hi\"\nlet m = n * 3")
+        .expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mut checker = nevermind_type_checker::TypeChecker::new();
+    checker.check(&stmts).expect("type check failed");
+    let mir_program = nevermind_mir::lower_program_with_types(
+        &stmts,
+        &checker.node_types(),
+        &CompileOptions::default(),
+    )
+    .expect("lowering failed");
+
+    let value_type = |index: usize| match &mir_program.statements[index] {
+        nevermind_mir::MirStmt::Let { value, .. } => value.get_type().clone(),
+        other => panic!("expected let statement, got {:?}", other),
+    };
+    assert_eq!(value_type(0), nevermind_type_checker::Type::Int);
+    assert_eq!(value_type(1), nevermind_type_checker::Type::String);
+    assert_eq!(value_type(2), nevermind_type_checker::Type::Int);
+
+    match &mir_program.statements[2] {
+        nevermind_mir::MirStmt::Let {
+            value: nevermind_mir::MirExpr::Binary { left, .. },
+            ..
+        } => assert_eq!(*left.get_type(), nevermind_type_checker::Type::Int),
+        other => panic!("expected let of a multiplication, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_bitwise_operators_emit_python_operators() {
+    let source = r#"
+let s = "a" ++ "b"
+let n = ~5
+let m = 6 & 3 ^ 8 << 2 >> 1
+"#;
+    let python = compile_to_python(source).expect("compilation failed");
+    assert!(python.contains("s = (\"a\" + \"b\")"));
+    assert!(python.contains("n = ~5"));
+    assert!(python.contains("m = ((6 & 3) ^ ((8 << 2) >> 1))"));
+}
+
+#[test]
+fn test_arity_overloads_emit_dispatcher() {
+    let source = r#"
+fn area(r) do
+  r * r
+end
+
+fn area(w, h) do
+  w * h
+end
+
+println(area(3, 4))
+"#;
+    let python = compile_to_python(source).expect("compilation failed");
+    assert!(python.contains("def area__1(r):"));
+    assert!(python.contains("def area__2(w, h):"));
+    assert!(python.contains("def area(*args):"));
+    assert!(python.contains("return area__2(*args)"));
+    assert!(python.contains("print(area(3, 4))"));
+
+    let dispatcher = python.find("def area(*args):").unwrap();
+    assert!(python.find("def area__2(w, h):").unwrap() < dispatcher);
+}
+
+#[test]
+fn test_list_reductions_emit_python_builtins() {
+    let python = compile_to_python("let total = sum([1, 2, 3])\nlet top = max([1, 2])")
+        .expect("compilation failed");
+    assert!(python.contains("total = sum([1, 2, 3])"));
+    assert!(python.contains("top = max([1, 2])"));
+    assert!(!python.contains("functools"));
+
+    let python = compile_to_python("let all = product([2, 3])").expect("compilation failed");
+    assert!(python.contains("def product(xs):"));
+    assert!(python.contains("return functools.reduce(operator.mul, xs, 1)"));
+    assert!(python.contains("all = product([2, 3])"));
+}
+
+#[test]
+fn test_map_lowers_to_map_node() {
+    let mut parser = nevermind_parser::Parser::new("let m = {\"a\": 1, \"b\": 2}").expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mir_program =
+        nevermind_mir::lower_program(&stmts, &CompileOptions::default()).expect("lowering failed");
+
+    match &mir_program.statements[0] {
+        nevermind_mir::MirStmt::Let {
+            value: nevermind_mir::MirExpr::Map { entries, .. },
+            ..
+        } => assert_eq!(entries.len(), 2),
+        other => panic!("expected let of a map, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_map_emits_python_dict_literal() {
+    let python = compile_to_python("let m = {\"a\": 1, \"b\": 2}").expect("compilation failed");
+    assert!(python.contains("m = {\"a\": 1, \"b\": 2}"));
+}
+
+#[test]
+fn test_block_expression_keeps_final_value() {
+    let mut parser =
+        nevermind_parser::Parser::new("let x = do\n  let y = 1\n  42\nend").expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mir_program =
+        nevermind_mir::lower_program(&stmts, &CompileOptions::default()).expect("lowering failed");
+
+    match &mir_program.statements[0] {
+        nevermind_mir::MirStmt::Let {
+            value:
+                nevermind_mir::MirExpr::Block {
+                    statements, expr, ..
+                },
+            ..
+        } => {
+            assert_eq!(statements.len(), 1);
+            assert!(matches!(
+                expr.as_deref(),
+                Some(nevermind_mir::MirExpr::Literal {
+                    value: nevermind_mir::Literal::Int(42),
+                    ..
+                })
+            ));
+        }
+        other => panic!("expected let of a block, got {:?}", other),
+    }
+
+    let python =
+        compile_to_python("let x = do\n  let y = 41\n  y + 1\nend").expect("compilation failed");
+    assert!(python.contains("y = 41\nx = (y + 1)"));
+}
+
+#[test]
+fn test_method_call_lowers_to_field_access_callee() {
+    let mut parser = nevermind_parser::Parser::new("lst.map(f)").expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mir_program =
+        nevermind_mir::lower_program(&stmts, &CompileOptions::default()).expect("lowering failed");
+
+    match &mir_program.statements[0] {
+        nevermind_mir::MirStmt::Expr(nevermind_mir::MirExpr::Call { callee, args, .. }) => {
+            match callee.as_ref() {
+                nevermind_mir::MirExpr::FieldAccess { object, field, .. } => {
+                    assert_eq!(field, "map");
+                    assert!(matches!(
+                        object.as_ref(),
+                        nevermind_mir::MirExpr::Variable { name, .. } if name == "lst"
+                    ));
+                }
+                other => panic!("expected a field-access callee, got {:?}", other),
+            }
+            assert_eq!(args.len(), 1);
+        }
+        other => panic!("expected a method call statement, got {:?}", other),
+    }
+
+    let python = nevermind_codegen::generate(&mir_program, &CompileOptions::default())
+        .expect("codegen failed");
+    assert!(python.contains("lst.map(f)"));
+}
+
+/// Helper: lower a source string and run an optimization pass over it
+fn optimize_source(
+    source: &str,
+    pass: fn(&mut nevermind_mir::MirProgram),
+) -> nevermind_mir::MirProgram {
+    let mut parser = nevermind_parser::Parser::new(source).expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mut mir_program =
+        nevermind_mir::lower_program(&stmts, &CompileOptions::default()).expect("lowering failed");
+    pass(&mut mir_program);
+    mir_program
+}
+
+/// Helper: lower a source string and run constant folding over it
+fn fold_source(source: &str) -> nevermind_mir::MirProgram {
+    optimize_source(source, nevermind_mir::optimize::fold_constants)
+}
+
+/// Helper: the value bound by a top-level `let`
+fn let_value(stmt: &nevermind_mir::MirStmt) -> &nevermind_mir::MirExpr {
+    match stmt {
+        nevermind_mir::MirStmt::Let { value, .. } => value,
+        other => panic!("expected let statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_constant_folding_evaluates_literal_arithmetic() {
+    let program = fold_source("let a = 1 + 2 * 3\nlet b = -7 / 2\nlet c = !true");
+
+    assert!(matches!(
+        let_value(&program.statements[0]),
+        nevermind_mir::MirExpr::Literal {
+            value: nevermind_mir::Literal::Int(7),
+            ..
+        }
+    ));
+    // Division rounds down, as Python's `//` does
+    assert!(matches!(
+        let_value(&program.statements[1]),
+        nevermind_mir::MirExpr::Literal {
+            value: nevermind_mir::Literal::Int(-4),
+            ..
+        }
+    ));
+    assert!(matches!(
+        let_value(&program.statements[2]),
+        nevermind_mir::MirExpr::Literal {
+            value: nevermind_mir::Literal::Bool(false),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_constant_folding_leaves_division_by_zero() {
+    let program = fold_source("let a = 1 / 0");
+
+    assert!(matches!(
+        let_value(&program.statements[0]),
+        nevermind_mir::MirExpr::Binary {
+            op: nevermind_mir::BinOp::Div,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_constant_folding_selects_literal_if_branch() {
+    let program = fold_source("let a = if 1 > 2 then \"big\" else \"small\" end");
+    let python =
+        nevermind_codegen::generate(&program, &CompileOptions::default()).expect("codegen failed");
+
+    assert!(python.contains("a = \"small\""));
+}
+
+#[test]
+fn test_constant_folding_keeps_concatenations_that_form_braces() {
+    let program = fold_source("let a = \"x{\" + \"y}\"\nlet b = \"x\" + \"y\"");
+    let python =
+        nevermind_codegen::generate(&program, &CompileOptions::default()).expect("codegen failed");
+
+    assert!(!python.contains("f\""));
+    assert!(python.contains("b = \"xy\""));
+}
+
+#[test]
+fn test_dead_code_after_return_is_removed() {
+    let source = "fn f(x) do\n  return x\n  print(\"unreachable\")\n  x + 1\nend";
+    let mir_program = optimize_source(source, nevermind_mir::optimize::remove_dead_code);
+
+    match &mir_program.statements[0] {
+        nevermind_mir::MirStmt::Function { body, .. } => {
+            assert_eq!(body.statements.len(), 1);
+            assert!(matches!(
+                body.statements[0],
+                nevermind_mir::MirExprStmt::Return { .. }
+            ));
+            assert!(body.expr.is_none());
+        }
+        other => panic!("expected function, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_dead_code_removal_shrinks_generated_python() {
+    let source = "fn f(x) do\n  while x > 0 do\n    break\n    print(x)\n  end\n  x\nend";
+    let mir_program = optimize_source(source, nevermind_mir::optimize::remove_dead_code);
+    let python = nevermind_codegen::generate(&mir_program, &CompileOptions::default())
+        .expect("codegen failed");
+
+    assert!(python.contains("break"));
+    assert!(!python.contains("print(x)"));
+}
+
+#[test]
+fn test_dead_code_removal_draws_ids_from_its_own_program() {
+    use nevermind_mir::{Literal, MirExpr, MirStmt, UnaryOp};
+
+    let lower = |source: &str| {
+        let stmts = nevermind_parser::Parser::new(source)
+            .expect("parser")
+            .parse()
+            .expect("parse failed");
+        nevermind_mir::lower_program(&stmts, &CompileOptions::default()).expect("lowering failed")
+    };
+    let mut program = lower("let flag = true");
+    let next = program.fresh_names.clone();
+    // Lowering another program in between must not affect this one's ids
+    lower("fn g(x) do\n  let y = [x, x]\n  y[x] += 1\nend");
+
+    let print = MirExpr::Call {
+        callee: Box::new(MirExpr::Variable {
+            name: "print".to_string(),
+            ty: nevermind_type_checker::Type::Unit,
+            id: 0,
+        }),
+        args: vec![MirExpr::Literal {
+            value: Literal::Int(1),
+            ty: nevermind_type_checker::Type::Int,
+            id: 0,
+        }],
+        ty: nevermind_type_checker::Type::Unit,
+        id: 0,
+    };
+    program.statements.push(MirStmt::If {
+        condition: MirExpr::Variable {
+            name: "flag".to_string(),
+            ty: nevermind_type_checker::Type::Bool,
+            id: 0,
+        },
+        then_body: Vec::new(),
+        else_body: Some(vec![MirStmt::Expr(print)]),
+        id: 0,
+    });
+    nevermind_mir::optimize::remove_dead_code(&mut program);
+
+    match program.statements.last() {
+        Some(MirStmt::If {
+            condition:
+                MirExpr::Unary {
+                    op: UnaryOp::Not,
+                    id,
+                    ..
+                },
+            else_body: None,
+            ..
+        }) => assert_eq!(*id, next.node_id()),
+        other => panic!("expected an if on the negated condition, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_logical_and_bitwise_operators_emit_distinct_python() {
+    let python = compile_to_python(
+        "let t = true\nlet f = false\nlet a = t and f\nlet b = t or f\nlet c = 6 & 3",
+    )
+    .expect("compile failed");
+
+    assert!(python.contains("a = (t and f)"));
+    assert!(python.contains("b = (t or f)"));
+    assert!(python.contains("c = (6 & 3)"));
+
+    // `|` has no infix form in the source language, so build it directly
+    use nevermind_mir::{BinOp, Literal, MirExpr, MirProgram, MirStmt};
+    use nevermind_type_checker::Type;
+    let int = |value| MirExpr::Literal {
+        value: Literal::Int(value),
+        ty: Type::Int,
+        id: 0,
+    };
+    let mut program = MirProgram::new();
+    program.add_statement(MirStmt::Let {
+        name: "d".to_string(),
+        value: MirExpr::Binary {
+            op: BinOp::BitOr,
+            left: Box::new(int(6)),
+            right: Box::new(int(3)),
+            ty: Type::Int,
+            id: 0,
+        },
+        ty: Type::Int,
+        id: 0,
+    });
+    let python =
+        nevermind_codegen::generate(&program, &CompileOptions::default()).expect("codegen failed");
+    assert!(python.contains("d = (6 | 3)"));
+}
+
+/// Helper: the control-flow graph of the first function in `source`
+fn function_cfg(source: &str) -> nevermind_mir::cfg::ControlFlowGraph {
+    let mut parser = nevermind_parser::Parser::new(source).expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mir_program =
+        nevermind_mir::lower_program(&stmts, &CompileOptions::default()).expect("lowering failed");
+    let function = mir_program
+        .iter_functions()
+        .next()
+        .expect("expected a function");
+    nevermind_mir::cfg::ControlFlowGraph::from_function(&function)
+}
+
+#[test]
+fn test_cfg_of_if_else_joins_both_branches() {
+    use nevermind_mir::cfg::Terminator;
+
+    let cfg = function_cfg(
+        "fn f(x) do\n  if x > 0 do print(1) end else do print(2) end end\n  print(3)\nend",
+    );
+
+    // entry -> then, else -> join
+    assert_eq!(cfg.blocks.len(), 4);
+    assert_eq!(cfg.edge_count(), 4);
+    assert!(matches!(
+        cfg.block(cfg.entry).terminator,
+        Terminator::Branch { .. }
+    ));
+    let join = cfg.blocks.len() - 1;
+    assert_eq!(cfg.predecessors(join).len(), 2);
+    assert_eq!(cfg.block(join).statements.len(), 1);
+    assert!(matches!(cfg.block(join).terminator, Terminator::Return(_)));
+}
+
+#[test]
+fn test_cfg_of_while_loop_has_a_back_edge() {
+    use nevermind_mir::cfg::Terminator;
+
+    let cfg =
+        function_cfg("fn f(n) do\n  var i = 0\n  while i < n do\n    i = i + 1\n  end\n  i\nend");
+
+    // entry -> header -> body -> header, header -> exit
+    assert_eq!(cfg.blocks.len(), 4);
+    assert_eq!(cfg.edge_count(), 4);
+    let header = cfg.block(cfg.entry).successors()[0];
+    assert!(matches!(
+        cfg.block(header).terminator,
+        Terminator::Branch { .. }
+    ));
+    let body = cfg.block(header).successors()[0];
+    assert_eq!(cfg.block(body).successors(), vec![header]);
+    assert_eq!(cfg.predecessors(header), vec![cfg.entry, body]);
+}
+
+#[test]
+fn test_cfg_leaves_out_code_after_break() {
+    let cfg =
+        function_cfg("fn f(x) do\n  while x > 0 do\n    break\n    print(x)\n  end\n  x\nend");
+
+    let header = cfg.block(cfg.entry).successors()[0];
+    let body = cfg.block(header).successors()[0];
+    let exit = cfg.block(header).successors()[1];
+    assert!(cfg.block(body).statements.is_empty());
+    assert_eq!(cfg.block(body).successors(), vec![exit]);
+}
+
+#[test]
+fn test_function_ending_in_loop_has_no_return() {
+    let source = r#"
+fn count(n: Int) do
+  var i = 0
+  while i < n do
+    println(i)
+    i = i + 1
+  end
+end
+
+fn report(x: Int) do
+  if x > 1 do
+    println("big")
+  end
+end
+"#;
+    let python = compile_to_python(source).expect("compile failed");
+
+    assert!(
+        !python.contains("return"),
+        "unexpected return in:\n{}",
+        python
+    );
+    assert!(python.contains("def count(n):\n    i = 0\n    while (i < n):"));
+    assert!(python.contains("        i = (i + 1)\n\ndef report(x):"));
+    assert!(python.contains("    if (x > 1):\n        print(\"big\")\n"));
+}
+
+#[test]
+fn test_range_forms_pass_through_to_python() {
+    let python =
+        compile_to_python("let a = range(5)\nlet b = range(1, 5)\nlet c = range(1, 10, 2)")
+            .expect("compile failed");
+
+    assert!(python.contains("a = range(5)"));
+    assert!(python.contains("b = range(1, 5)"));
+    assert!(python.contains("c = range(1, 10, 2)"));
+}
+
+#[test]
+fn test_range_expressions_lower_to_python_range() {
+    let python = compile_to_python(
+        "let n = 3\nfor i in 1..=10 do print(i) end end\nfor j in 0..=n do print(j) end end\nlet xs = 0..n",
+    )
+    .expect("compile failed");
+
+    assert!(python.contains("for i in range(1, 11):"));
+    assert!(python.contains("for j in range(0, n + 1):"));
+    assert!(python.contains("xs = list(range(0, n))"));
+}
+
+#[test]
+fn test_ord_and_chr_lower_to_python_builtins() {
+    let python =
+        compile_to_python("let code = ord('a')\nlet letter = chr(98)").expect("compile failed");
+
+    assert!(python.contains("code = ord('a')"));
+    assert!(python.contains("letter = chr(98)"));
+}
+
+#[test]
+fn test_match_statement_follows_python_target() {
+    let source =
+        "match n {\n  1 => println(\"one\"),\n  2 => println(\"two\"),\n  _ => println(\"many\")\n}";
+    let mut parser = nevermind_parser::Parser::new(source).expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mir_program =
+        nevermind_mir::lower_program(&stmts, &CompileOptions::default()).expect("lowering failed");
+
+    let py310 = nevermind_codegen::PythonGenerator::new()
+        .with_target(nevermind_codegen::PythonTarget::Py310)
+        .generate(&mir_program)
+        .expect("codegen failed");
+    assert!(py310.contains("match n:\n    case 1:\n        print(\"one\")\n    case 2:"));
+    assert!(py310.contains("    case _:\n        print(\"many\")"));
+
+    let default = nevermind_codegen::generate(&mir_program, &CompileOptions::default())
+        .expect("codegen failed");
+    assert!(!default.contains("match"));
+    assert!(default.contains("if n == 1:\n    print(\"one\")\nelif n == 2:"));
+    assert!(default.contains("else:\n    print(\"many\")"));
+}
+
+#[test]
+fn test_compile_source_generates_for_the_target_in_its_options() {
+    let source = "let n = 2\nmatch n {\n  1 => print(\"one\"),\n  _ => print(\"many\")\n}";
+    let options = CompileOptions {
+        target: nevermind::PythonTarget::Py310,
+        ..CompileOptions::default()
+    };
+
+    let py310 = nevermind::compile_source(source, &options).expect("compile failed");
+    assert!(py310.contains("match n:\n    case 1:"));
+
+    let default =
+        nevermind::compile_source(source, &CompileOptions::default()).expect("compile failed");
+    assert!(!default.contains("match"));
+    assert!(default.contains("if n == 1:"));
+}
+
+#[test]
+fn test_unit_literal_emits_none() {
+    let python = compile_to_python("fn done() do\n  ()\nend\nlet u = ()").expect("compile failed");
+
+    assert!(python.contains("def done():\n    return None"));
+    assert!(python.contains("u = None"));
+}
+
+#[test]
+fn test_string_interpolation_emits_f_strings() {
+    let source = r#"
+let name = "Ada"
+let a = 1
+let b = 2
+let greeting = "hi ${name}!"
+let total = "${a + b}"
+let braces = "{x}: ${a}"
+"#;
+    let python = compile_to_python(source).expect("compile failed");
+
+    assert!(python.contains(r#"greeting = f"hi {name}!""#));
+    assert!(python.contains(r#"total = f"{(a + b)}""#));
+    assert!(python.contains(r#"braces = f"{{x}}: {a}""#));
+}
+
+#[test]
+fn test_string_literals_are_escaped_for_python() {
+    let source = r#"
+let quoted = "a\"b"
+let path = "C:\\dir"
+let tabbed = "x\ty"
+let bell = "ding\x07"
+let unicode = "日本"
+"#;
+    let python = compile_to_python(source).expect("compile failed");
+
+    assert!(python.contains(r#"quoted = "a\"b""#));
+    assert!(python.contains(r#"path = "C:\\dir""#));
+    assert!(python.contains(r#"tabbed = "x\ty""#));
+    assert!(python.contains(r#"bell = "ding\x07""#));
+    assert!(python.contains(r#"unicode = "日本""#));
+}
+
+#[test]
+fn test_division_follows_operand_types() {
+    let python = compile_to_python("let a = 7 / 2\nlet b = 7.0 / 2\nvar c = 9.0\nc /= 2.0\n")
+        .expect("compile failed");
+
+    assert!(python.contains("a = (7 // 2)"));
+    assert!(python.contains("b = (7.0 / 2)"));
+    assert!(python.contains("c /= 2.0"));
+}
+
+#[test]
+fn test_tuple_access_emits_python_indexing() {
+    let python =
+        compile_to_python("let t = (1, \"a\")\nlet s = t.1\nlet n = t[0]\nlet one = (5,)\n")
+            .expect("compile failed");
+
+    assert!(python.contains("t = (1, \"a\")"));
+    assert!(python.contains("s = t[1]"));
+    assert!(python.contains("n = t[0]"));
+    assert!(python.contains("one = (5,)"));
+
+    let python =
+        compile_to_python("let t = ((1, 2), 3.5)\nlet n = t.0.1\n").expect("compile failed");
+    assert!(python.contains("n = t[0][1]"), "python:\n{}", python);
+}
+
+#[test]
+fn test_match_scrutinee_is_bound_once() {
+    let source = "let word = match next() {\n  1 => \"one\",\n  2 => \"two\",\n  _ => \"many\"\n}";
+    let mut parser = nevermind_parser::Parser::new(source).expect("parser");
+    let stmts = parser.parse().expect("parse failed");
+    let mir_program =
+        nevermind_mir::lower_program(&stmts, &CompileOptions::default()).expect("lowering failed");
+
+    let nevermind_mir::MirExpr::Block {
+        statements, expr, ..
+    } = let_value(&mir_program.statements[0])
+    else {
+        panic!("expected the match to bind its scrutinee first");
+    };
+    let [nevermind_mir::MirExprStmt::Let { name, value, .. }] = statements.as_slice() else {
+        panic!("expected a single let binding, got {:?}", statements);
+    };
+    assert!(matches!(value, nevermind_mir::MirExpr::Call { .. }));
+
+    // Every arm test compares against the temporary, not a fresh call
+    let mut chain = expr.as_deref();
+    let mut tests = 0;
+    while let Some(nevermind_mir::MirExpr::If {
+        condition,
+        else_branch,
+        ..
+    }) = chain
+    {
+        let nevermind_mir::MirExpr::Binary { left, .. } = condition.as_ref() else {
+            panic!("expected an equality test, got {:?}", condition);
+        };
+        assert!(matches!(
+            left.as_ref(),
+            nevermind_mir::MirExpr::Variable { name: subject, .. } if subject == name
+        ));
+        tests += 1;
+        chain = Some(else_branch.as_ref());
+    }
+    assert_eq!(tests, 2);
+
+    let python = compile_to_python(&format!("fn next() do\n  return 2\nend\n{}", source))
+        .expect("compile failed");
+    // One call besides the definition's own `def next():`
+    assert_eq!(python.matches("next()").count(), 2, "{}", python);
+}
+
+#[test]
+fn test_compiling_twice_gives_identical_output() {
+    let source = r#"
+fn pick() do
+  1
+end
+
+var xs = [1, 2, 3]
+xs[pick()] += 1
+let word = match pick() {
+  1 => "one",
+  _ => "many"
+}
+"#;
+    let first = compile_to_python(source).expect("compilation failed");
+    let second = compile_to_python(source).expect("compilation failed");
+    assert_eq!(first, second);
+    assert!(first.contains("__index_0"));
+    assert!(first.contains("__match_1"));
+}
+
+#[test]
+fn test_user_print_shadows_the_builtin() {
+    let source = r#"
+fn print(x) do
+  return x + 1
+end
+
+fn twice(x) do
+  print(print(x))
+end
+
+println(twice(1))
+"#;
+    let python = compile_to_python(source).expect("compilation failed");
+    assert!(python.contains("return print(print(x))"), "{}", python);
+    assert!(!python.contains("end=\"\""), "{}", python);
+    assert!(python.contains("import builtins"), "{}", python);
+    assert!(python.contains("builtins.print(twice(1))"), "{}", python);
+}
+
+#[test]
+fn test_async_functions_and_await() {
+    let source = r#"
+async fn fetch(x) do
+  x * 2
+end
+
+async fn main() do
+  println(await fetch(1) + 1)
+end
+"#;
+    let python = compile_to_python(source).expect("compilation failed");
+    assert!(python.contains("async def fetch(x):"), "{}", python);
+    assert!(
+        python.contains("print(((await fetch(1)) + 1))"),
+        "{}",
+        python
+    );
+    assert!(python.contains("asyncio.run(main())"), "{}", python);
+}
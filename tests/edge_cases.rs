@@ -44,10 +44,29 @@ fn pipeline_stage_must_be_function() {
     let result = type_check_program("let value = 10 |> 5");
     assert!(result.is_err());
     if let Err(err) = result {
-        assert!(matches!(err.kind, TypeErrorKind::TypeMismatch { .. }));
+        assert!(matches!(
+            err.kind,
+            TypeErrorKind::PipelineStageNotCallable { .. }
+        ));
     }
 }
 
+#[test]
+fn pipeline_reports_position_and_type_of_uncallable_stage() {
+    let err = type_check_program("let value = [1, 2, 3] |> 5").unwrap_err();
+    match err.kind {
+        TypeErrorKind::PipelineStageNotCallable { stage, found } => {
+            assert_eq!(stage, 2);
+            assert_eq!(found, Type::Int);
+        }
+        other => panic!("expected uncallable pipeline stage, got {:?}", other),
+    }
+    assert_eq!(err.message, "pipeline stage 2 is not callable: found Int");
+
+    let source = "fn count(xs) do len(xs) end\nlet value: Int = [1, 2, 3] |> count";
+    type_check_program(source).expect("function stage should type check");
+}
+
 #[test]
 fn pipeline_respects_generic_function_shape() {
     let source =
@@ -55,3 +74,590 @@ fn pipeline_respects_generic_function_shape() {
     let ty = type_check_program(source).expect("expected pipeline to type check");
     assert!(!matches!(ty, Type::Unit));
 }
+
+#[test]
+fn assignment_expression_has_unit_type() {
+    let ty = type_check_program("var x = 0\nx = 5").expect("assignment should type check");
+    assert_eq!(ty, Type::Unit);
+}
+
+#[test]
+fn assignment_value_cannot_be_used_as_operand() {
+    let result = type_check_program("var x = 0\nlet y = (x = 5)\ny + 1");
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(err.kind, TypeErrorKind::TypeMismatch { .. }));
+    }
+}
+
+#[test]
+fn type_alias_expands_in_let_annotation() {
+    type_check_program("type Name = String\nlet n: Name = \"x\"")
+        .expect("alias of String should accept a string");
+
+    let result = type_check_program("type Name = String\nlet n: Name = 5");
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(err.kind, TypeErrorKind::TypeMismatch { .. }));
+    }
+}
+
+#[test]
+fn parameterized_type_alias_substitutes_arguments() {
+    let ty = type_check_program("type Pair[T] = (T, T)\nfn swap(p: Pair[Int]) do p end\nswap")
+        .expect("parameterized alias should resolve");
+    match ty {
+        Type::Function(params, _) => {
+            assert_eq!(params, vec![Type::Tuple(vec![Type::Int, Type::Int])]);
+        }
+        other => panic!("expected function type, found {:?}", other),
+    }
+
+    type_check_program("type Items[T] = List[T]\nlet xs: Items[Int] = [1, 2]")
+        .expect("Items[Int] should accept a list of ints");
+
+    let result = type_check_program("type Items[T] = List[T]\nlet xs: Items[Int] = [\"a\"]");
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(err.kind, TypeErrorKind::TypeMismatch { .. }));
+    }
+}
+
+#[test]
+fn function_called_before_its_definition_is_checked_against_it() {
+    let stmts = parse_and_resolve("fn main() do helper(2) end\nfn helper(x) do x * 2 end");
+    let mut checker = TypeChecker::new();
+    checker
+        .check(&stmts)
+        .expect("main may call helper declared below it");
+    assert_eq!(
+        checker.global_type("main", None),
+        Some(Type::Function(vec![], Box::new(Type::Int)))
+    );
+
+    let result = type_check_program("fn main() do helper(\"s\") end\nfn helper(x) do x - 1 end");
+    assert!(result.is_err());
+}
+
+#[test]
+fn every_range_form_is_a_list_of_ints() {
+    for call in ["range(5)", "range(1, 5)", "range(1, 10, 2)"] {
+        let stmts = parse_and_resolve(&format!("let r = {}", call));
+        let mut checker = TypeChecker::new();
+        checker.check(&stmts).expect("range call should type-check");
+        assert_eq!(
+            checker.global_type("r", None),
+            Some(Type::List(Box::new(Type::Int))),
+            "{}",
+            call
+        );
+    }
+
+    assert!(type_check_program("range(1, \"10\")").is_err());
+}
+
+#[test]
+fn range_can_be_bound_as_a_value() {
+    let stmts = parse_and_resolve("let f = range\nlet r = f(3)\nlet s = range(1, 3)");
+    let mut checker = TypeChecker::new();
+    checker.check(&stmts).expect("range should bind as a value");
+    assert_eq!(
+        checker.global_type("r", None),
+        Some(Type::List(Box::new(Type::Int)))
+    );
+    assert_eq!(
+        checker.global_type("s", None),
+        Some(Type::List(Box::new(Type::Int)))
+    );
+
+    // A local binding named range shadows every form
+    assert!(type_check_program("fn g(range) do range + 1 end\nlet n = g(2)").is_ok());
+}
+
+#[test]
+fn ord_and_chr_convert_between_char_and_int() {
+    let stmts = parse_and_resolve("let code = ord('a')\nlet letter = chr(98)");
+    let mut checker = TypeChecker::new();
+    checker
+        .check(&stmts)
+        .expect("ord and chr should type-check");
+    assert_eq!(checker.global_type("code", None), Some(Type::Int));
+    assert_eq!(checker.global_type("letter", None), Some(Type::Char));
+
+    assert!(type_check_program("ord(97)").is_err());
+    assert!(type_check_program("chr('a')").is_err());
+}
+
+#[test]
+fn builtins_have_typed_signatures() {
+    let stmts = parse_and_resolve(
+        "let n = len([1, 2])\nlet i = abs(-3)\nlet f = abs(-2.5)\nlet s = str(1)\nlet x = float(2)\nlet u = print(1)",
+    );
+    let mut checker = TypeChecker::new();
+    checker
+        .check(&stmts)
+        .expect("builtin calls should type-check");
+    assert_eq!(checker.global_type("n", None), Some(Type::Int));
+    assert_eq!(checker.global_type("i", None), Some(Type::Int));
+    assert_eq!(checker.global_type("f", None), Some(Type::Float));
+    assert_eq!(checker.global_type("s", None), Some(Type::String));
+    assert_eq!(checker.global_type("x", None), Some(Type::Float));
+    assert_eq!(checker.global_type("u", None), Some(Type::Unit));
+
+    let err = type_check_program("abs(\"x\")").expect_err("abs takes a number");
+    assert!(matches!(
+        err.kind,
+        TypeErrorKind::NotNumeric { ref function, found: Type::String } if function == "abs"
+    ));
+    let err = type_check_program("len(5)").expect_err("len takes a sized value");
+    assert!(matches!(
+        err.kind,
+        TypeErrorKind::NotSized { ref function, found: Type::Int } if function == "len"
+    ));
+    assert!(type_check_program("range(\"a\", 3)").is_err());
+}
+
+#[test]
+fn user_functions_replace_builtins_of_the_same_name() {
+    let stmts = parse_and_resolve(
+        "fn print(x: Int) -> Int do\n  return x + 1\nend\nfn abs(s: String) -> String do\n  return s\nend\nfn range(n: String) -> List[String] do\n  return [n]\nend\nlet y = print(1)\nlet a = abs(\"x\")\nlet r = range(\"a\")",
+    );
+    let mut checker = TypeChecker::new();
+    checker
+        .check(&stmts)
+        .expect("user definitions should replace builtin signatures");
+    assert_eq!(checker.global_type("y", None), Some(Type::Int));
+    assert_eq!(checker.global_type("a", None), Some(Type::String));
+    assert_eq!(
+        checker.global_type("r", None),
+        Some(Type::List(Box::new(Type::String)))
+    );
+}
+
+#[test]
+fn len_accepts_strings_lists_and_maps() {
+    let stmts = parse_and_resolve(
+        "let a = len(\"hello\")\nlet b = len([1, 2])\nlet c = len({\"k\": 1})\nlet d = len((1, 2))",
+    );
+    let mut checker = TypeChecker::new();
+    checker
+        .check(&stmts)
+        .expect("len should take any sized value");
+    for name in ["a", "b", "c", "d"] {
+        assert_eq!(checker.global_type(name, None), Some(Type::Int));
+    }
+}
+
+#[test]
+fn type_alias_arity_is_checked() {
+    let result = type_check_program("type Pair[T] = (T, T)\nlet p: Pair[Int, String] = (1, \"a\")");
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(
+            err.kind,
+            TypeErrorKind::TypeAliasArityMismatch {
+                expected: 1,
+                found: 2,
+                ..
+            }
+        ));
+    }
+}
+
+#[test]
+fn omitted_type_alias_arguments_take_their_defaults() {
+    let ty =
+        type_check_program("type Pair[A, B = A] = (A, B)\nfn swap(p: Pair[Int]) do p end\nswap")
+            .expect("Pair[Int] should fill B from its default");
+    match ty {
+        Type::Function(params, _) => {
+            assert_eq!(params, vec![Type::Tuple(vec![Type::Int, Type::Int])]);
+        }
+        other => panic!("expected function type, found {:?}", other),
+    }
+
+    type_check_program("type Pair[A, B = A] = (A, B)\nlet p: Pair[Int, String] = (1, \"a\")")
+        .expect("an explicit argument should override the default");
+
+    let result = type_check_program("type Pair[A, B = A] = (A, B)\nlet p: Pair = (1, 2)");
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(
+            err.kind,
+            TypeErrorKind::TypeAliasArityMismatch {
+                expected: 1,
+                found: 0,
+                ..
+            }
+        ));
+    }
+}
+
+#[test]
+fn self_referential_type_alias_rejected() {
+    let result = type_check_program("type Tree = List[Tree]");
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(err.kind, TypeErrorKind::RecursiveTypeAlias(ref name) if name == "Tree"));
+    }
+
+    let result = type_check_program("type A = B\ntype B = (Int, A)");
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(err.kind, TypeErrorKind::RecursiveTypeAlias(_)));
+    }
+}
+
+#[test]
+fn check_all_reports_independent_errors() {
+    let stmts =
+        parse_and_resolve("let a = 1 + \"one\"\nlet b = a + 1\nlet c = true + 2\nlet d = 3");
+    let mut checker = TypeChecker::new();
+    let errors = checker.check_all(&stmts);
+
+    assert_eq!(errors.len(), 2, "unexpected errors: {:?}", errors);
+    assert!(errors
+        .iter()
+        .all(|err| matches!(err.kind, TypeErrorKind::TypeMismatch { .. })));
+    assert_eq!(errors[0].span.start.line, 1);
+    assert_eq!(errors[1].span.start.line, 3);
+}
+
+#[test]
+fn bool_match_missing_false_is_non_exhaustive() {
+    let stmts = parse_and_resolve("let b = true\nmatch b { true => 1 }");
+    let is_missing_false = |err: &TypeError| match &err.kind {
+        TypeErrorKind::NonExhaustiveMatch { missing } => *missing == ["false"],
+        _ => false,
+    };
+
+    let mut lenient = TypeChecker::new();
+    lenient
+        .check(&stmts)
+        .expect("a non-exhaustive match is only a warning by default");
+    assert_eq!(lenient.warnings().len(), 1);
+    assert!(is_missing_false(&lenient.warnings()[0]));
+
+    let mut strict = TypeChecker::new().with_strict_match(true);
+    let err = strict
+        .check(&stmts)
+        .expect_err("strict matching rejects a non-exhaustive match");
+    assert!(is_missing_false(&err));
+}
+
+#[test]
+fn wildcard_arm_makes_bool_match_exhaustive() {
+    type_check_program("let b = true\nmatch b { true => 1, _ => 0 }")
+        .expect("wildcard arm should satisfy exhaustiveness");
+    type_check_program("let b = true\nmatch b { true => 1, other => 0 }")
+        .expect("variable arm should satisfy exhaustiveness");
+}
+
+#[test]
+fn bool_match_covering_both_values_returns_from_function() {
+    let source = "fn flag(b: Bool) -> Int do\n  match b { true => 1, false => 0 }\nend";
+    type_check_program(source).expect("true and false arms are exhaustive");
+}
+
+#[test]
+fn recheck_statement_only_reprocesses_changed_code_and_dependents() {
+    let stmts = parse_and_resolve(
+        "fn double(x: Int) -> Int do x * 2 end\n\
+         fn greet(name) do \"hi \" + name end\n\
+         let n = double(2)",
+    );
+    let mut checker = TypeChecker::new();
+    checker.check(&stmts).expect("program should type check");
+    assert_eq!(checker.top_level_check_count(), 3);
+
+    // Nothing refers to greet, so only greet itself is re-inferred.
+    let greet = parse_and_resolve("fn greet(name) do \"hello \" + name end").remove(0);
+    checker
+        .recheck_statement(1, greet)
+        .expect("changed greet should type check");
+    assert_eq!(checker.top_level_check_count(), 4);
+
+    // `let n` calls double, so it is re-checked against the new signature.
+    let double = parse_and_resolve("fn double(s: String) -> String do s end").remove(0);
+    let result = checker.recheck_statement(0, double);
+    assert_eq!(checker.top_level_check_count(), 6);
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(err.kind, TypeErrorKind::TypeMismatch { .. }));
+    }
+}
+
+#[test]
+fn let_does_not_generalize_variables_bound_through_the_environment() {
+    // `g(x)` unifies the result with `x`'s parameter type, so `y` must stay
+    // monomorphic rather than being used as both an Int and a String
+    let result = type_check_program(
+        "fn f(x) do\n  let g = |a| a\n  let y = g(x)\n  let p = y + 1\n  let q = y ++ \"s\"\n  p\nend",
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn recheck_statement_rechecks_earlier_callers_of_a_hoisted_function() {
+    let stmts = parse_and_resolve(
+        "fn main() do\n  let s: String = helper(1)\n  s\nend\n\
+         fn helper(x: Int) -> String do \"a\" end\n\
+         let n = 1",
+    );
+    let mut checker = TypeChecker::new();
+    checker.check(&stmts).expect("program should type check");
+
+    let helper = parse_and_resolve("fn helper(x: Int) -> Int do x end").remove(0);
+    let err = checker
+        .recheck_statement(1, helper.clone())
+        .expect_err("main uses helper's result as a String");
+    assert!(matches!(err.kind, TypeErrorKind::TypeMismatch { .. }));
+    // main comes first, and fails before helper is reached
+    assert_eq!(checker.top_level_check_count(), 4);
+
+    // The failed re-check left the checker as it was, so the original
+    // helper still checks and main still sees its String result
+    let original = parse_and_resolve("fn helper(x: Int) -> String do \"b\" end").remove(0);
+    checker
+        .recheck_statement(1, original)
+        .expect("restoring helper's signature should type check");
+    assert_eq!(checker.top_level_check_count(), 6);
+
+    let mut full = stmts.clone();
+    full[1] = helper;
+    assert!(TypeChecker::new().check(&full).is_err());
+}
+
+#[test]
+fn recheck_statement_indexes_the_latest_check() {
+    let mut checker = TypeChecker::new();
+    let first = parse_and_resolve("let a = 1\nlet b = 2");
+    checker
+        .check(&first)
+        .expect("first program should type check");
+    let second = Parser::new("let c = a + b").unwrap().parse().unwrap();
+    checker
+        .check(&second)
+        .expect("second program should type check");
+
+    let replacement = parse_and_resolve("let c = \"text\"").remove(0);
+    checker
+        .recheck_statement(0, replacement.clone())
+        .expect("the latest check's only statement is at index 0");
+
+    let err = checker
+        .recheck_statement(1, replacement)
+        .expect_err("the latest check has one statement");
+    assert_eq!(
+        err.kind,
+        TypeErrorKind::NoSuchStatement { index: 1, len: 1 }
+    );
+}
+
+#[test]
+fn char_literal_has_char_type() {
+    let ty = type_check_program("let c = 'a'\nc").expect("char literal should type check");
+    assert_eq!(ty, Type::Char);
+
+    let ty = type_check_program("let c = 'a'\nc < 'b'").expect("chars should compare");
+    assert_eq!(ty, Type::Bool);
+
+    type_check_program("let c: Char = 'a'").expect("Char annotation should accept a char");
+}
+
+#[test]
+fn char_plus_int_is_type_error() {
+    let result = type_check_program("let c = 'a'\nc + 1");
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(err.kind, TypeErrorKind::TypeMismatch { .. }));
+    }
+}
+
+#[test]
+fn thousand_binding_program_type_checks_quickly() {
+    let mut source = String::from("fn identity(x) do x end\nlet v0 = 0\n");
+    for i in 1..1000 {
+        if i % 2 == 0 {
+            source.push_str(&format!("let v{} = identity(v{}) + {}\n", i, i - 1, i));
+        } else {
+            source.push_str(&format!(
+                "fn f{}(a, b) do\n  if a then b else v{} end\nend\n",
+                i,
+                i - 1
+            ));
+            source.push_str(&format!("let v{} = f{}(true, v{})\n", i, i, i - 1));
+        }
+    }
+    source.push_str("v999");
+
+    let stmts = parse_and_resolve(&source);
+    let started = std::time::Instant::now();
+    let ty = TypeChecker::new()
+        .check(&stmts)
+        .expect("expected large program to type check");
+    let elapsed = started.elapsed();
+
+    assert_eq!(ty, Type::Int);
+    assert!(
+        elapsed < std::time::Duration::from_secs(2),
+        "type checking 1000 bindings took {:?}",
+        elapsed
+    );
+}
+
+#[test]
+fn call_picks_overload_matching_its_arity() {
+    let overloads = "fn area(r: Int) -> Int do r * r end\nfn area(w: String, h: String) -> String do w ++ h end\n";
+
+    let source = format!(
+        "{}let one: Int = area(3)\nlet two: String = area(\"a\", \"b\")",
+        overloads
+    );
+    assert!(type_check_program(&source).is_ok());
+
+    let result = type_check_program(&format!("{}let wrong: String = area(3)", overloads));
+    assert!(result.is_err());
+}
+
+#[test]
+fn list_reductions_return_their_element_type() {
+    let source = "let total: Int = sum([1, 2, 3])\nlet real: Float = sum([1.0])\nlet most: Int = max([4, 9])\nlet least: Float = min([2.5])\nlet all: Int = product([2, 3])";
+    assert!(type_check_program(source).is_ok());
+
+    let result = type_check_program("let total: String = sum([1, 2, 3])");
+    assert!(result.is_err());
+}
+
+#[test]
+fn min_and_max_also_take_two_values() {
+    let source =
+        "let least: Int = min(3, 1)\nlet most: Float = max(2.5, 4.0)\nlet top: Int = max([4, 9])";
+    assert!(type_check_program(source).is_ok());
+
+    assert!(type_check_program("let least = min(3, \"a\")").is_err());
+}
+
+#[test]
+fn higher_order_builtins_infer_element_types_from_lambdas() {
+    let source = "let doubled: List[Int] = [1, 2, 3] |> map |x| x * 2 |";
+    assert!(type_check_program(source).is_ok());
+
+    let source = "let doubled: List[String] = [1, 2, 3] |> map |x| x * 2 |";
+    assert!(type_check_program(source).is_err());
+
+    let source = "let total: Int = fold([1, 2], 0, |acc, x| acc + x)";
+    assert!(type_check_program(source).is_ok());
+}
+
+#[test]
+fn filter_lambda_must_return_bool() {
+    let result = type_check_program("let kept = [1, 2, 3] |> filter |x| x + 1 |");
+    let err = result.expect_err("non-bool filter predicate should be rejected");
+    assert!(matches!(err.kind, TypeErrorKind::TypeMismatch { .. }));
+
+    let source = "let kept: List[Int] = [1, 2, 3] |> filter |x| x > 1 |";
+    assert!(type_check_program(source).is_ok());
+}
+
+#[test]
+fn empty_list_annotation_fixes_element_type() {
+    type_check_program("let xs: List[Int] = []\nlet ys: List[Int] = xs")
+        .expect("annotated empty list should be a List[Int]");
+
+    let result = type_check_program("let xs: List[Int] = []\nlet ys: List[String] = xs");
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(err.kind, TypeErrorKind::TypeMismatch { .. }));
+    }
+}
+
+#[test]
+fn list_annotation_rejects_mismatched_elements() {
+    let result = type_check_program("let xs: List[Int] = [\"a\"]");
+    assert!(result.is_err());
+    if let Err(err) = result {
+        assert!(matches!(err.kind, TypeErrorKind::TypeMismatch { .. }));
+    }
+}
+
+#[test]
+fn mixed_int_float_arithmetic_promotes_to_float() {
+    type_check_program("let x: Float = 7.0 / 2\nlet y: Float = 1 + 0.5")
+        .expect("Int and Float operands should promote to Float");
+
+    let result = type_check_program("let x: Int = 7.0 / 2");
+    assert!(result.is_err());
+}
+
+#[test]
+fn compound_assignment_must_fit_the_target() {
+    type_check_program("var total = 1.5\ntotal += 1\ntotal *= 2")
+        .expect("a Float target takes Int operands");
+
+    let result = type_check_program("var count = 1\ncount += 0.5");
+    assert!(matches!(
+        result.map_err(|err| err.kind),
+        Err(TypeErrorKind::TypeMismatch { .. })
+    ));
+}
+
+#[test]
+fn indexing_a_string_yields_a_char() {
+    assert_eq!(type_check_program("\"abc\"[0]").unwrap(), Type::Char);
+
+    let stmts = parse_and_resolve("let last = \"abc\"[-1]\n\"abc\"[-3]");
+    let mut checker = TypeChecker::new();
+    assert_eq!(checker.check(&stmts).unwrap(), Type::Char);
+    assert!(checker.warnings().is_empty());
+}
+
+#[test]
+fn constant_index_past_a_string_literal_warns() {
+    for (source, index) in [("\"abc\"[5]", 5), ("\"abc\"[-4]", -4)] {
+        let stmts = parse_and_resolve(source);
+        let mut checker = TypeChecker::new();
+        checker
+            .check(&stmts)
+            .expect("an out-of-range index is only a warning");
+        assert_eq!(checker.warnings().len(), 1);
+        assert_eq!(
+            checker.warnings()[0].kind,
+            TypeErrorKind::StringIndexOutOfRange { index, len: 3 }
+        );
+    }
+}
+
+#[test]
+fn option_and_result_constructors_are_typed() {
+    let stmts = parse_and_resolve(
+        "let s = Some(1)\nlet n: Option[String] = None\nlet r = Ok(\"x\")\nlet e: Result[Int, String] = Error(\"bad\")\nlet v = match s {\n  Some(x) => x + 1,\n  None => 0\n}",
+    );
+    let mut checker = TypeChecker::new();
+    checker
+        .check(&stmts)
+        .expect("constructors should type-check");
+    assert_eq!(
+        checker.global_type("s", None),
+        Some(Type::option(Type::Int))
+    );
+    assert_eq!(
+        checker.global_type("n", None),
+        Some(Type::option(Type::String))
+    );
+    match checker.global_type("r", None) {
+        Some(Type::Result(ok, error)) => {
+            assert_eq!(*ok, Type::String);
+            assert!(error.is_var());
+        }
+        other => panic!("expected a Result, got {:?}", other),
+    }
+    assert_eq!(checker.global_type("v", None), Some(Type::Int));
+
+    assert!(type_check_program("let o: Option[Int] = Some(\"a\")").is_err());
+    assert!(type_check_program("let r: Result[Int, String] = Error(1)").is_err());
+    assert!(type_check_program(
+        "let s = Some(\"a\")\nlet n = match s {\n  Some(x) => x + 1,\n  None => 0\n}"
+    )
+    .is_err());
+}
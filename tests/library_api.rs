@@ -0,0 +1,95 @@
+//! Tests for the library entry points used when embedding the compiler.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Helper: execute Python source and capture stdout.
+fn run_python(python_code: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let unique = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let path = std::env::temp_dir().join(format!(
+        "nevermind_library_api_{}_{}.py",
+        std::process::id(),
+        unique
+    ));
+
+    fs::write(&path, python_code)?;
+
+    let result = run_python_file(&path);
+    let _ = fs::remove_file(&path);
+    result
+}
+
+fn run_python_file(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let python_cmds = if cfg!(windows) {
+        vec!["python", "python3", "py"]
+    } else {
+        vec!["python3", "python"]
+    };
+
+    let mut last_err = None;
+
+    for python_cmd in &python_cmds {
+        match Command::new(python_cmd).arg(path).output() {
+            Ok(output) => {
+                if output.status.success() {
+                    return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+                }
+
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                return Err(format!("Python execution failed: {}", stderr).into());
+            }
+            Err(err) => {
+                last_err = Some(err);
+            }
+        }
+    }
+
+    Err(format!(
+        "Could not find Python interpreter. Tried: {}. Error: {}",
+        python_cmds.join(", "),
+        last_err.unwrap()
+    )
+    .into())
+}
+
+#[test]
+fn compile_str_output_runs_under_python() {
+    let source = r#"
+fn square(x: Int) -> Int do
+  x * x
+end
+
+print(square(7))
+"#;
+
+    let python_code = nevermind::compile_str(source).expect("program should compile");
+    let output = run_python(&python_code).expect("generated Python should run");
+    assert_eq!(output.trim(), "49");
+}
+
+#[test]
+fn character_arithmetic_runs_through_ord_and_chr() {
+    let python_code = nevermind::compile_str("print(chr(ord('a') + 1))").expect("compile");
+    let output = run_python(&python_code).expect("generated Python should run");
+    assert_eq!(output.trim(), "b");
+}
+
+#[test]
+fn compile_str_reports_why_it_failed() {
+    let err = nevermind::compile_str("print(missing)").unwrap_err();
+    assert!(!err.diagnostics.is_empty());
+    assert!(err.to_string().contains("missing"), "got: {}", err);
+}
+
+#[test]
+fn check_str_accepts_a_valid_program() {
+    assert!(nevermind::check_str("let x = 1\nprint(x)").is_ok());
+}
+
+#[test]
+fn check_str_returns_type_errors() {
+    let errors = nevermind::check_str("let x: Int = \"text\"\nprint(x)").unwrap_err();
+    assert_eq!(errors.len(), 1);
+}
@@ -5,6 +5,8 @@ use std::path::PathBuf;
 use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use nevermind::CompileOptions;
+
 /// Helper: compile a source string through the full pipeline and return generated Python.
 fn compile_to_python(source: &str) -> Result<String, Box<dyn std::error::Error>> {
     let mut lexer = nevermind_lexer::Lexer::new(source);
@@ -29,8 +31,8 @@ fn compile_to_python(source: &str) -> Result<String, Box<dyn std::error::Error>>
     let mut checker = nevermind_type_checker::TypeChecker::new();
     checker.check(&stmts)?;
 
-    let mir_program = nevermind_mir::lower_program(&stmts)?;
-    let python_code = nevermind_codegen::generate(&mir_program)?;
+    let mir_program = nevermind_mir::lower_program(&stmts, &CompileOptions::default())?;
+    let python_code = nevermind_codegen::generate(&mir_program, &CompileOptions::default())?;
 
     Ok(python_code)
 }
@@ -166,11 +168,11 @@ end
 fn main() do
   let tree = [1, 2, 2, 0, 3, 1, 4, 0]
   let forest = [1, 2, 2, 0, 3, 1, 4, 0, 5, 2, 6, 0, 7, 0]
-  print "TREE_SIZE=" + str(tree_size(tree))
-  print "TREE_HEIGHT=" + str(tree_height(tree))
-  print "FOREST_ROOTS=" + str(forest_root_count(forest))
-  print "FOREST_SIZE=" + str(forest_size(forest))
-  print "FOREST_HEIGHT=" + str(forest_height(forest))
+  println "TREE_SIZE=" + str(tree_size(tree))
+  println "TREE_HEIGHT=" + str(tree_height(tree))
+  println "FOREST_ROOTS=" + str(forest_root_count(forest))
+  println "FOREST_SIZE=" + str(forest_size(forest))
+  println "FOREST_HEIGHT=" + str(forest_height(forest))
 end
 "#;
 
@@ -192,3 +194,403 @@ end
         ]
     );
 }
+
+#[test]
+fn bool_and_null_literals_print_as_python_values() {
+    let source = r#"
+println true
+println false
+println null
+"#;
+
+    let output = run_python(source).expect("literal program should compile and run");
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+
+    assert_eq!(lines, vec!["True", "False", "None"]);
+}
+
+#[test]
+fn compound_index_assignment_calls_index_function_once() {
+    let source = r#"
+fn pick() do
+  println "picked"
+  1
+end
+
+var xs = [10, 20, 30]
+xs[pick()] += 5
+var total = 1
+total *= 3
+println xs[1]
+println total
+"#;
+
+    let output = run_python(source).expect("compound assignment program should compile and run");
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+
+    assert_eq!(lines, vec!["picked", "25", "3"]);
+}
+
+#[test]
+fn overloaded_functions_dispatch_on_argument_count() {
+    let source = r#"
+fn area(r) do
+  r * r * 3
+end
+
+fn area(w, h) do
+  w * h
+end
+
+println area(2)
+println area(2, 5)
+"#;
+
+    let output = run_python(source).expect("overloaded program should compile and run");
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+
+    assert_eq!(lines, vec!["12", "10"]);
+}
+
+#[test]
+fn list_reductions_compute_at_runtime() {
+    let source = r#"
+println sum([1, 2, 3])
+println product([2, 3, 4])
+println min([5, 2, 8])
+println max([5, 2, 8])
+println min(3, 1)
+println max(3, 1)
+"#;
+
+    let output = run_python(source).expect("reduction program should compile and run");
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+
+    assert_eq!(lines, vec!["6", "24", "2", "8", "1", "3"]);
+}
+
+#[test]
+fn match_expressions_pick_the_matching_arm() {
+    let source = r#"
+fn describe(n) do
+  match n { 0 => "z", _ => "o" }
+end
+
+fn bump(n) do
+  match n {
+    0 => 100,
+    k: k > 10 => k * 2,
+    k => k + 1,
+  }
+end
+
+fn pair_sum(xs: List[Int]) -> Int do
+  match xs {
+    [a, b] => a + b,
+    _ => 0,
+  }
+end
+
+println describe(0)
+println describe(7)
+println bump(0)
+println bump(20)
+println bump(3)
+println pair_sum([3, 4])
+println pair_sum([1, 2, 3])
+"#;
+
+    let output = run_python(source).expect("match program should compile and run");
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+
+    assert_eq!(lines, vec!["z", "o", "100", "40", "4", "7", "0"]);
+}
+
+#[test]
+fn higher_order_builtins_take_the_list_first() {
+    let source = r#"
+let doubled = [1, 2, 3] |> map |x| x * 2 |
+let big = doubled |> filter |x| x > 2 |
+println doubled
+println big
+println fold(big, 0, |acc, x| acc + x)
+"#;
+
+    let output = run_python(source).expect("higher-order program should compile and run");
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+
+    assert_eq!(lines, vec!["[2, 4, 6]", "[4, 6]", "10"]);
+}
+
+#[test]
+fn string_interpolation_runs_as_f_strings() {
+    let source = r#"
+let name = "Ada"
+let scores = {"Ada": 3}
+println "hi ${name}!"
+println "${scores["Ada"] * 2} points"
+"#;
+
+    let output = run_python(source).expect("interpolation program should compile and run");
+    let lines: Vec<&str> = output.lines().map(str::trim).collect();
+
+    assert_eq!(lines, vec!["hi Ada!", "6 points"]);
+}
+
+#[test]
+fn string_literals_with_special_characters_round_trip() {
+    let source = r#"
+println "say \"hi\""
+println "C:\\temp\\new"
+println "col\tcol"
+println "two\nlines"
+println "héllo ✓ 日本 \u{1F600}"
+"#;
+
+    let output = run_python(source).expect("escaped strings should compile and run");
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            "say \"hi\"",
+            "C:\\temp\\new",
+            "col\tcol",
+            "two",
+            "lines",
+            "héllo ✓ 日本 😀"
+        ]
+    );
+}
+
+#[test]
+fn integer_division_floors_and_float_division_does_not() {
+    let output = run_python("let n = -7\nprintln 7 / 2\nprintln 7.0 / 2\nprintln n / 2\n")
+        .expect("division should compile and run");
+
+    assert_eq!(output.lines().collect::<Vec<_>>(), vec!["3", "3.5", "-4"]);
+}
+
+#[test]
+fn chained_comparisons_evaluate_each_operand_once() {
+    let source = r#"
+fn mid() do
+  println("mid")
+  2
+end
+
+println(1 < 2 < 3)
+println(1 < mid() <= 2)
+println(3 < mid() < 4)
+"#;
+    let output = run_python(source).expect("chained comparisons should compile and run");
+
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec!["True", "mid", "True", "mid", "False"]
+    );
+}
+
+#[test]
+fn power_is_right_associative_and_binds_tighter_than_negation() {
+    let output = run_python(
+        "let x = 2\nprintln(-2 ** 2)\nprintln(2 ** 3 ** 2)\nprintln((-x) ** 2)\nprintln(-x ** 2)\n",
+    )
+    .expect("powers should compile and run");
+
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec!["-4", "512", "4", "-4"]
+    );
+}
+
+#[test]
+fn option_and_result_values_match_at_runtime() {
+    let source = r#"
+fn double(o) do
+  match o {
+    Some(v) => v * 2,
+    None => 0
+  }
+end
+
+println(double(Some(3)))
+println(double(None))
+let r = Error("bad")
+let msg = match r {
+  Ok(v) => "ok",
+  Error(e) => e
+}
+println(msg)
+println(Ok(1) == Error(1))
+"#;
+    let output = run_python(source).expect("constructors should compile and run");
+
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec!["6", "0", "bad", "False"]
+    );
+}
+
+#[test]
+fn slicing_with_a_range_takes_a_sublist() {
+    let output = run_python("let xs = [1, 2, 3, 4]\nprintln(xs[1..3])\nprintln(xs[1..=3])\n")
+        .expect("slices should compile and run");
+
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec!["[2, 3]", "[2, 3, 4]"]
+    );
+}
+
+#[test]
+fn classes_construct_instances_and_call_methods() {
+    let source = r#"
+class Counter {
+  let name: String
+  var count = 0
+  fn bump(by) do
+    count = count + by
+    count
+  end
+  fn label() do
+    name + ": " + str(count)
+  end
+}
+
+let c = Counter("clicks")
+c.bump(2)
+c.bump(3)
+println(c.label())
+println(c.count)
+"#;
+    let output = run_python(source).expect("classes should compile and run");
+
+    assert_eq!(output.lines().collect::<Vec<_>>(), vec!["clicks: 5", "5"]);
+}
+
+#[test]
+fn subclasses_take_and_read_inherited_fields() {
+    let source = r#"
+class Shape {
+  let name: String
+  fn area() do
+    0
+  end
+  fn describe() do
+    name + " with area " + str(area())
+  end
+}
+
+class Square extends Shape {
+  let side: Int
+  fn area() do
+    if side > 0 do
+      let name = "unused"
+      println(name)
+    end
+    side * side
+  end
+}
+
+let s = Square("square", 3)
+println(s.describe())
+println(s.name)
+"#;
+    let output = run_python(source).expect("subclasses should compile and run");
+
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec!["unused", "square with area 9", "square"]
+    );
+}
+
+#[test]
+fn match_arms_with_block_bodies_yield_their_last_value() {
+    let source = r#"
+fn describe(x) do
+  match x {
+    1 => do
+      println("one")
+      10
+    end,
+    n => do
+      let doubled = n * 2
+      doubled + 1
+    end
+  }
+end
+
+let r = match 2 {
+  2 => do
+    println("two")
+    20
+  end,
+  _ => 0
+}
+println(describe(1))
+println(describe(3))
+println(r)
+"#;
+    let output = run_python(source).expect("block arm bodies should compile and run");
+
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec!["two", "one", "10", "7", "20"]
+    );
+}
+
+#[test]
+fn raised_errors_are_caught_and_finally_always_runs() {
+    let source = r#"
+fn check(n) do
+  if n < 0 do
+    raise "negative: " + str(n)
+  end
+  n
+end
+
+fn safe_check(n) do
+  try do
+    return check(n)
+  catch message do
+    println("caught " + message)
+  end
+  0
+end
+
+println(safe_check(-2))
+try do
+  println(check(5))
+  println(check(-1))
+  println("not reached")
+catch e do
+  println(e)
+finally do
+  println("cleanup")
+end
+"#;
+    let output = run_python(source).expect("try/catch/finally should compile and run");
+
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec!["caught negative: -2", "0", "5", "negative: -1", "cleanup"]
+    );
+}
+
+#[test]
+fn block_bodied_matches_nested_in_expressions_each_get_a_value() {
+    let source = r#"
+let v = 1
+println(match v { 1 => do println("side") 10 end, _ => 0 })
+let total = match v { 1 => do println("a") 2 end, _ => 0 } + match v { 1 => do println("b") 3 end, _ => 0 }
+println(total)
+println(false and match v { 1 => do println("skipped") true end, _ => false })
+"#;
+    let output = run_python(source).expect("nested block arms should compile and run");
+
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec!["side", "10", "a", "b", "5", "False"]
+    );
+}